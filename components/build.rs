@@ -2,7 +2,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_prost_build::configure()
         .build_server(false)
         .build_client(true)
-        .compile_protos(&["proto/lnd/lightning.proto"], &[])?;
+        .compile_protos(
+            &["proto/lnd/lightning.proto", "proto/lnd/invoices.proto"],
+            &["proto/lnd"],
+        )?;
 
     tonic_prost_build::configure()
         .build_server(false)
@@ -30,6 +30,7 @@ pub struct Model {
     pub name: Option<String>,
     pub weight: i32,
     pub enabled: bool,
+    pub region: Option<String>,
     pub implementation: Vec<u8>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
@@ -124,6 +125,7 @@ impl DbDiscoveryBackendStore {
                 partitions: model.partitions.0,
                 weight: model.weight as usize,
                 enabled: model.enabled,
+                region: model.region,
                 implementation: model.implementation,
             },
         })
@@ -204,6 +206,7 @@ impl DiscoveryBackendStore for DbDiscoveryBackendStore {
             name: Set(backend.backend.name),
             weight: Set(backend.backend.weight as i32),
             enabled: Set(backend.backend.enabled),
+            region: Set(backend.backend.region),
             implementation: Set(backend.backend.implementation),
             created_at: Set(now.into()),
             updated_at: Set(now.into()),
@@ -277,6 +280,7 @@ impl DiscoveryBackendStore for DbDiscoveryBackendStore {
             name: Set(backend.backend.name),
             weight: Set(backend.backend.weight as i32),
             enabled: Set(backend.backend.enabled),
+            region: Set(backend.backend.region),
             implementation: Set(backend.backend.implementation),
             created_at: Set(now.into()),
             updated_at: Set(now.into()),
@@ -294,6 +298,7 @@ impl DiscoveryBackendStore for DbDiscoveryBackendStore {
                                         Column::Name,
                                         Column::Weight,
                                         Column::Enabled,
+                                        Column::Region,
                                         Column::Implementation,
                                     ])
                                     .value(Column::UpdatedAt, Expr::val(future_timestamp))
@@ -401,6 +406,9 @@ impl DiscoveryBackendStore for DbDiscoveryBackendStore {
         if let Some(enabled) = backend.backend.enabled {
             update = update.col_expr(Column::Enabled, Expr::value(enabled));
         }
+        if let Some(region) = backend.backend.region {
+            update = update.col_expr(Column::Region, Expr::value(region));
+        }
 
         update = update.col_expr(Column::UpdatedAt, Expr::value(Utc::now()));
 
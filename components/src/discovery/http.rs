@@ -1,7 +1,7 @@
 use crate::discovery::error::DiscoveryBackendStoreError;
 use async_trait::async_trait;
 use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::{Certificate, Client, ClientBuilder, IntoUrl, StatusCode};
+use reqwest::{Certificate, Client, ClientBuilder, IntoUrl, Proxy, StatusCode};
 use rustls::pki_types::CertificateDer;
 use secp256k1::PublicKey;
 use std::time::Duration;
@@ -26,6 +26,7 @@ impl HttpDiscoveryBackendStore {
         connect_timeout: Duration,
         trusted_roots: &[CertificateDer],
         authorization: String,
+        socks5_proxy: Option<&str>,
     ) -> Result<Self, DiscoveryBackendStoreError> {
         let mut headers = HeaderMap::new();
         let mut auth_value =
@@ -52,6 +53,19 @@ impl HttpDiscoveryBackendStore {
             builder = builder.add_root_certificate(root);
         }
 
+        // socks5h has the proxy resolve the store's host name, which onion addresses require
+        if let Some(socks5_proxy) = socks5_proxy {
+            let socks5_proxy = socks5_proxy.replacen("socks5://", "socks5h://", 1);
+            let proxy = Proxy::all(&socks5_proxy).map_err(|e| {
+                DiscoveryBackendStoreError::http_error(
+                    ServiceErrorSource::Internal,
+                    format!("creating SOCKS5 proxy for url: {}", base_url.as_str()),
+                    e,
+                )
+            })?;
+            builder = builder.proxy(proxy);
+        }
+
         let client = builder
             .default_headers(headers)
             .use_rustls_tls()
@@ -98,6 +112,10 @@ impl HttpDiscoveryBackendStore {
         })
     }
 
+    // the discovery service redacts inline credentials unless asked not to, and backends can't
+    // be connected without them
+    const SECRETS_QUERY: &[(&str, &str)] = &[("secrets", "true")];
+
     fn discovery_public_key_url(&self, public_key: &PublicKey) -> String {
         format!("{}/{}", self.discovery_url, public_key)
     }
@@ -131,13 +149,19 @@ impl DiscoveryBackendStore for HttpDiscoveryBackendStore {
     async fn get(&self, public_key: &PublicKey) -> Result<Option<DiscoveryBackend>, Self::Error> {
         let url = self.discovery_public_key_url(public_key);
 
-        let response = self.client.get(&url).send().await.map_err(|e| {
-            DiscoveryBackendStoreError::http_error(
-                ServiceErrorSource::Upstream,
-                format!("get backend {url}"),
-                e,
-            )
-        })?;
+        let response = self
+            .client
+            .get(&url)
+            .query(Self::SECRETS_QUERY)
+            .send()
+            .await
+            .map_err(|e| {
+                DiscoveryBackendStoreError::http_error(
+                    ServiceErrorSource::Upstream,
+                    format!("get backend {url}"),
+                    e,
+                )
+            })?;
 
         match response.status() {
             StatusCode::OK => {
@@ -157,7 +181,7 @@ impl DiscoveryBackendStore for HttpDiscoveryBackendStore {
 
     async fn get_all(&self, requested_etag: Option<u64>) -> Result<DiscoveryBackends, Self::Error> {
         let url = &self.discovery_url;
-        let client = self.client.get(url);
+        let client = self.client.get(url).query(Self::SECRETS_QUERY);
         let client = if let Some(requested_etag) = requested_etag {
             client.header(
                 reqwest::header::IF_NONE_MATCH,
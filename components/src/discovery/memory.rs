@@ -118,6 +118,9 @@ impl DiscoveryBackendStore for MemoryDiscoveryBackendStore {
         if let Some(name) = backend.backend.name {
             entry.backend.backend.name = name;
         }
+        if let Some(region) = backend.backend.region {
+            entry.backend.backend.region = region;
+        }
         self.etag.fetch_add(1, Ordering::Relaxed);
         Ok(true)
     }
@@ -0,0 +1,132 @@
+use crate::discovery::error::DiscoveryBackendStoreError;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Certificate, Client, ClientBuilder, IntoUrl, StatusCode};
+use rustls::pki_types::CertificateDer;
+use std::time::Duration;
+use switchgear_service_api::balance::LnBackendStatus;
+use switchgear_service_api::service::ServiceErrorSource;
+use url::Url;
+
+#[derive(Clone, Debug)]
+pub struct HttpLnBackendStatusClient {
+    client: Client,
+    status_url: String,
+}
+
+impl HttpLnBackendStatusClient {
+    pub fn create<U: IntoUrl>(
+        base_url: U,
+        total_timeout: Duration,
+        connect_timeout: Duration,
+        trusted_roots: &[CertificateDer],
+        authorization: String,
+    ) -> Result<Self, DiscoveryBackendStoreError> {
+        let mut headers = HeaderMap::new();
+        let mut auth_value =
+            HeaderValue::from_str(&format!("Bearer {authorization}")).map_err(|e| {
+                DiscoveryBackendStoreError::internal_error(
+                    ServiceErrorSource::Internal,
+                    format!("creating http client with base url: {}", base_url.as_str()),
+                    e.to_string(),
+                )
+            })?;
+        auth_value.set_sensitive(true);
+        headers.insert(reqwest::header::AUTHORIZATION, auth_value);
+
+        let mut builder = ClientBuilder::new();
+
+        for root in trusted_roots {
+            let root = Certificate::from_der(root).map_err(|e| {
+                DiscoveryBackendStoreError::internal_error(
+                    ServiceErrorSource::Internal,
+                    format!("parsing certificate for url: {}", base_url.as_str()),
+                    e.to_string(),
+                )
+            })?;
+            builder = builder.add_root_certificate(root);
+        }
+
+        let client = builder
+            .default_headers(headers)
+            .use_rustls_tls()
+            .timeout(total_timeout)
+            .connect_timeout(connect_timeout)
+            .build()
+            .map_err(|e| {
+                DiscoveryBackendStoreError::http_error(
+                    ServiceErrorSource::Internal,
+                    format!("creating http client with base url: {}", base_url.as_str()),
+                    e,
+                )
+            })?;
+        Self::with_client(client, base_url)
+    }
+
+    pub fn with_client<U: IntoUrl>(
+        client: Client,
+        base_url: U,
+    ) -> Result<Self, DiscoveryBackendStoreError> {
+        let base_url = base_url.as_str().trim_end_matches('/').to_string();
+        let status_url = format!("{base_url}/status");
+        Url::parse(&status_url).map_err(|e| {
+            DiscoveryBackendStoreError::internal_error(
+                ServiceErrorSource::Upstream,
+                format!("parsing service url {status_url}"),
+                e.to_string(),
+            )
+        })?;
+
+        Ok(Self { client, status_url })
+    }
+
+    pub async fn status(&self) -> Result<Vec<LnBackendStatus>, DiscoveryBackendStoreError> {
+        let url = &self.status_url;
+        let response = self.client.get(url).send().await.map_err(|e| {
+            DiscoveryBackendStoreError::http_error(
+                ServiceErrorSource::Upstream,
+                format!("get backend status {url}"),
+                e,
+            )
+        })?;
+
+        match response.status() {
+            StatusCode::OK => response.json().await.map_err(|e| {
+                DiscoveryBackendStoreError::deserialization_error(
+                    ServiceErrorSource::Upstream,
+                    format!("parse backend status {url}"),
+                    e,
+                )
+            }),
+            status => Err(DiscoveryBackendStoreError::http_status_error(
+                ServiceErrorSource::Upstream,
+                format!("get backend status {url}"),
+                status.as_u16(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::discovery::status::HttpLnBackendStatusClient;
+    use url::Url;
+
+    #[test]
+    fn base_urls() {
+        let client = HttpLnBackendStatusClient::with_client(
+            reqwest::Client::default(),
+            Url::parse("https://base.com").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(&client.status_url, "https://base.com/status");
+
+        let client = HttpLnBackendStatusClient::with_client(
+            reqwest::Client::default(),
+            Url::parse("https://base.com/").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(&client.status_url, "https://base.com/status");
+    }
+}
@@ -1,6 +1,8 @@
 use crate::discovery::db::Column;
 use crate::offer::db_orm::prelude::*;
-use crate::offer::db_orm::{offer_metadata_table, offer_record_table};
+use crate::offer::db_orm::{
+    bolt12_offer_table, hold_invoice_table, offer_metadata_table, offer_record_table,
+};
 use crate::offer::error::OfferStoreError;
 use async_trait::async_trait;
 use chrono::Utc;
@@ -11,7 +13,9 @@ use sea_orm::{
 use switchgear_migration::OnConflict;
 use switchgear_migration::{Expr, MigratorTrait};
 use switchgear_service_api::offer::{
-    OfferMetadata, OfferMetadataStore, OfferRecord, OfferRecordSparse, OfferStore,
+    Bolt12OfferRecord, Bolt12OfferSparse, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceSparse,
+    HoldInvoiceStore, OfferMetadata, OfferMetadataStore, OfferRecord, OfferRecordSparse,
+    OfferStore,
 };
 use switchgear_service_api::service::ServiceErrorSource;
 use uuid::Uuid;
@@ -108,6 +112,7 @@ impl OfferStore for DbOfferStore {
                 metadata: metadata_model,
                 timestamp: offer_model.timestamp.into(),
                 expires: offer_model.expires.map(|dt| dt.into()),
+                hold_invoice: offer_model.hold_invoice,
             },
         }))
     }
@@ -146,6 +151,7 @@ impl OfferStore for DbOfferStore {
                     metadata: None,
                     timestamp: model.timestamp.into(),
                     expires: model.expires.map(|dt| dt.into()),
+                    hold_invoice: model.hold_invoice,
                 },
             });
         }
@@ -163,6 +169,7 @@ impl OfferStore for DbOfferStore {
             metadata_id: Set(offer.offer.metadata_id),
             timestamp: Set(offer.offer.timestamp.into()),
             expires: Set(offer.offer.expires.map(|dt| dt.into())),
+            hold_invoice: Set(offer.offer.hold_invoice),
             created_at: Set(now.into()),
             updated_at: Set(now.into()),
         };
@@ -219,6 +226,7 @@ impl OfferStore for DbOfferStore {
             metadata_id: Set(offer.offer.metadata_id),
             timestamp: Set(offer.offer.timestamp.into()),
             expires: Set(offer.offer.expires.map(|dt| dt.into())),
+            hold_invoice: Set(offer.offer.hold_invoice),
             created_at: Set(now.into()), // Set for initial insert
             updated_at: Set(now.into()),
         };
@@ -235,6 +243,7 @@ impl OfferStore for DbOfferStore {
                     offer_record_table::Column::MetadataId,
                     offer_record_table::Column::Timestamp,
                     offer_record_table::Column::Expires,
+                    offer_record_table::Column::HoldInvoice,
                 ])
                 .value(Column::UpdatedAt, Expr::val(future_timestamp))
                 .to_owned(),
@@ -570,3 +579,306 @@ impl OfferMetadataStore for DbOfferStore {
         Ok(result.rows_affected > 0)
     }
 }
+
+#[async_trait]
+impl Bolt12OfferStore for DbOfferStore {
+    type Error = OfferStoreError;
+
+    async fn get_bolt12_offers(
+        &self,
+        partition: &str,
+        id: &Uuid,
+    ) -> Result<Vec<Bolt12OfferRecord>, Self::Error> {
+        let models = Bolt12OfferTable::find()
+            .filter(bolt12_offer_table::Column::Partition.eq(partition))
+            .filter(bolt12_offer_table::Column::Id.eq(*id))
+            .order_by_asc(bolt12_offer_table::Column::PublicKey)
+            .all(&self.db)
+            .await
+            .map_err(|e| {
+                OfferStoreError::from_db(
+                    ServiceErrorSource::Internal,
+                    format!("getting Bolt12 offers for partition {partition} id {id}"),
+                    e,
+                )
+            })?;
+
+        let mut offers = Vec::new();
+        for model in models {
+            let public_key = model.public_key.parse().map_err(|e| {
+                OfferStoreError::internal_error(
+                    ServiceErrorSource::Internal,
+                    format!("parsing Bolt12 offer public key for partition {partition} id {id}"),
+                    format!("{e}: {}", model.public_key),
+                )
+            })?;
+            offers.push(Bolt12OfferRecord {
+                partition: model.partition,
+                id: model.id,
+                public_key,
+                offer: Bolt12OfferSparse {
+                    offer_id: model.offer_id,
+                    bolt12: model.bolt12,
+                    amount_msat: model.amount_msat as u64,
+                    description: model.description,
+                    expires: model.expires.map(|dt| dt.into()),
+                },
+            });
+        }
+
+        Ok(offers)
+    }
+
+    async fn put_bolt12_offer(&self, offer: Bolt12OfferRecord) -> Result<bool, Self::Error> {
+        let now = Utc::now();
+        let future_timestamp = now + chrono::Duration::seconds(1);
+
+        let active_model = bolt12_offer_table::ActiveModel {
+            partition: Set(offer.partition.clone()),
+            id: Set(offer.id),
+            public_key: Set(offer.public_key.to_string()),
+            offer_id: Set(offer.offer.offer_id.clone()),
+            bolt12: Set(offer.offer.bolt12.clone()),
+            amount_msat: Set(offer.offer.amount_msat as i64),
+            description: Set(offer.offer.description.clone()),
+            expires: Set(offer.offer.expires.map(|dt| dt.into())),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+        };
+
+        match Bolt12OfferTable::insert(active_model)
+            .on_conflict(
+                OnConflict::columns([
+                    bolt12_offer_table::Column::Partition,
+                    bolt12_offer_table::Column::Id,
+                    bolt12_offer_table::Column::PublicKey,
+                ])
+                .update_columns([
+                    bolt12_offer_table::Column::OfferId,
+                    bolt12_offer_table::Column::Bolt12,
+                    bolt12_offer_table::Column::AmountMsat,
+                    bolt12_offer_table::Column::Description,
+                    bolt12_offer_table::Column::Expires,
+                ])
+                .value(Column::UpdatedAt, Expr::val(future_timestamp))
+                .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+        {
+            Ok(_) => {}
+            // Foreign key constraint violation (offer doesn't exist)
+            Err(sea_orm::DbErr::Query(sea_orm::RuntimeErr::SqlxError(sqlx::Error::Database(
+                db_err,
+            ))))
+            | Err(sea_orm::DbErr::Exec(sea_orm::RuntimeErr::SqlxError(sqlx::Error::Database(
+                db_err,
+            )))) if db_err.is_foreign_key_violation() => {
+                return Err(OfferStoreError::invalid_input_error(
+                    format!("put Bolt12 offer {offer:?}"),
+                    format!("offer {} not found", offer.id),
+                ));
+            }
+            Err(e) => {
+                return Err(OfferStoreError::from_db(
+                    ServiceErrorSource::Internal,
+                    format!(
+                        "upserting Bolt12 offer for partition {} id {}",
+                        offer.partition, offer.id
+                    ),
+                    e,
+                ));
+            }
+        };
+
+        let result = Bolt12OfferTable::find_by_id((
+            offer.partition.clone(),
+            offer.id,
+            offer.public_key.to_string(),
+        ))
+        .select_only()
+        .column(bolt12_offer_table::Column::CreatedAt)
+        .column(bolt12_offer_table::Column::UpdatedAt)
+        .into_tuple::<(
+            chrono::DateTime<chrono::FixedOffset>,
+            chrono::DateTime<chrono::FixedOffset>,
+        )>()
+        .one(&self.db)
+        .await
+        .map_err(|e| {
+            OfferStoreError::from_db(
+                ServiceErrorSource::Internal,
+                format!(
+                    "fetching Bolt12 offer after upsert for partition {} id {}",
+                    offer.partition, offer.id
+                ),
+                e,
+            )
+        })?
+        .ok_or_else(|| {
+            OfferStoreError::from_db(
+                ServiceErrorSource::Internal,
+                "upsert succeeded but record not found",
+                sea_orm::DbErr::RecordNotFound(
+                    "Record should exist after successful upsert".to_string(),
+                ),
+            )
+        })?;
+
+        // Compare timestamps to determine if it was insert (true) or update (false)
+        Ok(result.0 == result.1)
+    }
+}
+
+impl DbOfferStore {
+    fn hold_invoice_from_model(
+        model: hold_invoice_table::Model,
+    ) -> Result<HoldInvoiceRecord, OfferStoreError> {
+        let context = format!("parsing hold invoice {}", model.payment_hash);
+        let invalid = |e: String| {
+            OfferStoreError::internal_error(ServiceErrorSource::Internal, context.clone(), e)
+        };
+
+        let mut payment_hash = [0u8; 32];
+        hex::decode_to_slice(&model.payment_hash, &mut payment_hash)
+            .map_err(|e| invalid(format!("payment hash: {e}")))?;
+        let mut preimage = [0u8; 32];
+        hex::decode_to_slice(&model.preimage, &mut preimage)
+            .map_err(|e| invalid(format!("preimage: {e}")))?;
+        let public_key = model
+            .public_key
+            .parse()
+            .map_err(|e| invalid(format!("public key {}: {e}", model.public_key)))?;
+
+        Ok(HoldInvoiceRecord {
+            payment_hash,
+            hold: HoldInvoiceSparse {
+                public_key,
+                preimage,
+                invoice: model.invoice,
+                cancel_at: model.cancel_at.into(),
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl HoldInvoiceStore for DbOfferStore {
+    type Error = OfferStoreError;
+
+    async fn get_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<Option<HoldInvoiceRecord>, Self::Error> {
+        let payment_hash = hex::encode(payment_hash);
+        HoldInvoiceTable::find_by_id(payment_hash.clone())
+            .one(&self.db)
+            .await
+            .map_err(|e| {
+                OfferStoreError::from_db(
+                    ServiceErrorSource::Internal,
+                    format!("getting hold invoice {payment_hash}"),
+                    e,
+                )
+            })?
+            .map(Self::hold_invoice_from_model)
+            .transpose()
+    }
+
+    async fn get_hold_invoices(&self) -> Result<Vec<HoldInvoiceRecord>, Self::Error> {
+        HoldInvoiceTable::find()
+            .order_by_asc(hold_invoice_table::Column::CancelAt)
+            .all(&self.db)
+            .await
+            .map_err(|e| {
+                OfferStoreError::from_db(ServiceErrorSource::Internal, "getting hold invoices", e)
+            })?
+            .into_iter()
+            .map(Self::hold_invoice_from_model)
+            .collect()
+    }
+
+    async fn put_hold_invoice(&self, hold: HoldInvoiceRecord) -> Result<bool, Self::Error> {
+        let now = Utc::now();
+        let future_timestamp = now + chrono::Duration::seconds(1);
+        let payment_hash = hex::encode(hold.payment_hash);
+
+        let active_model = hold_invoice_table::ActiveModel {
+            payment_hash: Set(payment_hash.clone()),
+            public_key: Set(hold.hold.public_key.to_string()),
+            preimage: Set(hex::encode(hold.hold.preimage)),
+            invoice: Set(hold.hold.invoice),
+            cancel_at: Set(hold.hold.cancel_at.into()),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+        };
+
+        HoldInvoiceTable::insert(active_model)
+            .on_conflict(
+                OnConflict::column(hold_invoice_table::Column::PaymentHash)
+                    .update_columns([
+                        hold_invoice_table::Column::PublicKey,
+                        hold_invoice_table::Column::Preimage,
+                        hold_invoice_table::Column::Invoice,
+                        hold_invoice_table::Column::CancelAt,
+                    ])
+                    .value(Column::UpdatedAt, Expr::val(future_timestamp))
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .map_err(|e| {
+                OfferStoreError::from_db(
+                    ServiceErrorSource::Internal,
+                    format!("upserting hold invoice {payment_hash}"),
+                    e,
+                )
+            })?;
+
+        let result = HoldInvoiceTable::find_by_id(payment_hash.clone())
+            .select_only()
+            .column(hold_invoice_table::Column::CreatedAt)
+            .column(hold_invoice_table::Column::UpdatedAt)
+            .into_tuple::<(
+                chrono::DateTime<chrono::FixedOffset>,
+                chrono::DateTime<chrono::FixedOffset>,
+            )>()
+            .one(&self.db)
+            .await
+            .map_err(|e| {
+                OfferStoreError::from_db(
+                    ServiceErrorSource::Internal,
+                    format!("fetching hold invoice {payment_hash} after upsert"),
+                    e,
+                )
+            })?
+            .ok_or_else(|| {
+                OfferStoreError::from_db(
+                    ServiceErrorSource::Internal,
+                    "upsert succeeded but record not found",
+                    sea_orm::DbErr::RecordNotFound(
+                        "Record should exist after successful upsert".to_string(),
+                    ),
+                )
+            })?;
+
+        // Compare timestamps to determine if it was insert (true) or update (false)
+        Ok(result.0 == result.1)
+    }
+
+    async fn delete_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        let payment_hash = hex::encode(payment_hash);
+        let result = HoldInvoiceTable::delete_by_id(payment_hash.clone())
+            .exec(&self.db)
+            .await
+            .map_err(|e| {
+                OfferStoreError::from_db(
+                    ServiceErrorSource::Internal,
+                    format!("deleting hold invoice {payment_hash}"),
+                    e,
+                )
+            })?;
+
+        Ok(result.rows_affected > 0)
+    }
+}
@@ -2,5 +2,7 @@
 
 pub mod prelude;
 
+pub mod bolt12_offer_table;
+pub mod hold_invoice_table;
 pub mod offer_metadata_table;
 pub mod offer_record_table;
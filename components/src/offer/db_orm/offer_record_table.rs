@@ -14,12 +14,15 @@ pub struct Model {
     pub metadata_id: Uuid,
     pub timestamp: DateTimeWithTimeZone,
     pub expires: Option<DateTimeWithTimeZone>,
+    pub hold_invoice: bool,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::bolt12_offer_table::Entity")]
+    Bolt12OfferTable,
     #[sea_orm(
         belongs_to = "super::offer_metadata_table::Entity",
         from = "(Column::Partition, Column::MetadataId)",
@@ -29,6 +32,12 @@ pub enum Relation {
     OfferMetadataTable,
 }
 
+impl Related<super::bolt12_offer_table::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Bolt12OfferTable.def()
+    }
+}
+
 impl Related<super::offer_metadata_table::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::OfferMetadataTable.def()
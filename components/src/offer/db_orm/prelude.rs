@@ -1,4 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.0
 
+pub use super::bolt12_offer_table::Entity as Bolt12OfferTable;
+pub use super::hold_invoice_table::Entity as HoldInvoiceTable;
 pub use super::offer_metadata_table::Entity as OfferMetadataTable;
 pub use super::offer_record_table::Entity as OfferRecordTable;
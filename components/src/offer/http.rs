@@ -1,11 +1,12 @@
 use crate::offer::error::OfferStoreError;
 use async_trait::async_trait;
 use axum::http::{HeaderMap, HeaderValue};
-use reqwest::{Certificate, Client, ClientBuilder, IntoUrl, StatusCode};
+use reqwest::{Certificate, Client, ClientBuilder, IntoUrl, Proxy, StatusCode};
 use rustls::pki_types::CertificateDer;
 use std::time::Duration;
 use switchgear_service_api::offer::{
-    HttpOfferClient, OfferMetadata, OfferMetadataStore, OfferRecord, OfferStore,
+    Bolt12OfferRecord, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceStore, HttpOfferClient,
+    OfferMetadata, OfferMetadataStore, OfferRecord, OfferStore,
 };
 use switchgear_service_api::service::ServiceErrorSource;
 use url::Url;
@@ -16,6 +17,7 @@ pub struct HttpOfferStore {
     client: Client,
     offer_url: String,
     metadata_url: String,
+    hold_url: String,
     health_check_url: String,
 }
 
@@ -26,6 +28,7 @@ impl HttpOfferStore {
         connect_timeout: Duration,
         trusted_roots: &[CertificateDer],
         authorization: String,
+        socks5_proxy: Option<&str>,
     ) -> Result<Self, OfferStoreError> {
         let mut headers = HeaderMap::new();
         let mut auth_value =
@@ -51,6 +54,19 @@ impl HttpOfferStore {
             builder = builder.add_root_certificate(root);
         }
 
+        // socks5h has the proxy resolve the store's host name, which onion addresses require
+        if let Some(socks5_proxy) = socks5_proxy {
+            let socks5_proxy = socks5_proxy.replacen("socks5://", "socks5h://", 1);
+            let proxy = Proxy::all(&socks5_proxy).map_err(|e| {
+                OfferStoreError::http_error(
+                    ServiceErrorSource::Internal,
+                    format!("creating SOCKS5 proxy for url: {}", base_url.as_str()),
+                    e,
+                )
+            })?;
+            builder = builder.proxy(proxy);
+        }
+
         let client = builder
             .default_headers(headers)
             .use_rustls_tls()
@@ -88,6 +104,15 @@ impl HttpOfferStore {
             )
         })?;
 
+        let hold_url = format!("{base_url}/holds");
+        Url::parse(&hold_url).map_err(|e| {
+            OfferStoreError::internal_error(
+                ServiceErrorSource::Upstream,
+                format!("parsing service url {hold_url}"),
+                e.to_string(),
+            )
+        })?;
+
         let health_check_url = format!("{base_url}/health");
         Url::parse(&health_check_url).map_err(|e| {
             OfferStoreError::internal_error(
@@ -101,6 +126,7 @@ impl HttpOfferStore {
             client,
             offer_url,
             metadata_url,
+            hold_url,
             health_check_url,
         })
     }
@@ -113,6 +139,14 @@ impl HttpOfferStore {
         format!("{}/{}", self.offers_partition_url(partition), id)
     }
 
+    fn bolt12_offers_url(&self, partition: &str, id: &Uuid) -> String {
+        format!("{}/bolt12", self.offers_partition_id_url(partition, id))
+    }
+
+    fn hold_payment_hash_url(&self, payment_hash: &[u8; 32]) -> String {
+        format!("{}/{}", self.hold_url, hex::encode(payment_hash))
+    }
+
     fn metadata_partition_url(&self, partition: &str) -> String {
         format!("{}/{}", self.metadata_url, partition)
     }
@@ -276,6 +310,190 @@ impl OfferStore for HttpOfferStore {
     }
 }
 
+#[async_trait]
+impl Bolt12OfferStore for HttpOfferStore {
+    type Error = OfferStoreError;
+
+    async fn get_bolt12_offers(
+        &self,
+        partition: &str,
+        id: &Uuid,
+    ) -> Result<Vec<Bolt12OfferRecord>, Self::Error> {
+        let url = self.bolt12_offers_url(partition, id);
+        let response = self.client.get(&url).send().await.map_err(|e| {
+            OfferStoreError::http_error(
+                ServiceErrorSource::Upstream,
+                format!("get Bolt12 offers {url}"),
+                e,
+            )
+        })?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let offers = response
+                    .json::<Vec<Bolt12OfferRecord>>()
+                    .await
+                    .map_err(|e| {
+                        OfferStoreError::deserialization_error(
+                            ServiceErrorSource::Upstream,
+                            format!("parsing Bolt12 offers for {url}"),
+                            e,
+                        )
+                    })?;
+                Ok(offers)
+            }
+            status => Err(Self::general_error(
+                status,
+                &format!("get Bolt12 offers {url}"),
+            )),
+        }
+    }
+
+    async fn put_bolt12_offer(&self, offer: Bolt12OfferRecord) -> Result<bool, Self::Error> {
+        let url = format!(
+            "{}/{}",
+            self.bolt12_offers_url(&offer.partition, &offer.id),
+            offer.public_key
+        );
+        let response = self
+            .client
+            .put(&url)
+            .json(&offer.offer)
+            .send()
+            .await
+            .map_err(|e| {
+                OfferStoreError::http_error(
+                    ServiceErrorSource::Upstream,
+                    format!("put Bolt12 offer {url}"),
+                    e,
+                )
+            })?;
+
+        match response.status() {
+            StatusCode::CREATED => Ok(true),
+            StatusCode::NO_CONTENT => Ok(false),
+            status => Err(Self::general_error(
+                status,
+                &format!("put Bolt12 offer {url}"),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl HoldInvoiceStore for HttpOfferStore {
+    type Error = OfferStoreError;
+
+    async fn get_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<Option<HoldInvoiceRecord>, Self::Error> {
+        let url = self.hold_payment_hash_url(payment_hash);
+        let response = self.client.get(&url).send().await.map_err(|e| {
+            OfferStoreError::http_error(
+                ServiceErrorSource::Upstream,
+                format!("get hold invoice {url}"),
+                e,
+            )
+        })?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let hold = response.json::<HoldInvoiceRecord>().await.map_err(|e| {
+                    OfferStoreError::deserialization_error(
+                        ServiceErrorSource::Upstream,
+                        format!("parsing hold invoice {url}"),
+                        e,
+                    )
+                })?;
+                Ok(Some(hold))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            status => Err(Self::general_error(
+                status,
+                &format!("get hold invoice {url}"),
+            )),
+        }
+    }
+
+    async fn get_hold_invoices(&self) -> Result<Vec<HoldInvoiceRecord>, Self::Error> {
+        let url = &self.hold_url;
+        let response = self.client.get(url).send().await.map_err(|e| {
+            OfferStoreError::http_error(
+                ServiceErrorSource::Upstream,
+                format!("get hold invoices {url}"),
+                e,
+            )
+        })?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let holds = response
+                    .json::<Vec<HoldInvoiceRecord>>()
+                    .await
+                    .map_err(|e| {
+                        OfferStoreError::deserialization_error(
+                            ServiceErrorSource::Upstream,
+                            format!("parsing hold invoices for {url}"),
+                            e,
+                        )
+                    })?;
+                Ok(holds)
+            }
+            status => Err(Self::general_error(
+                status,
+                &format!("get hold invoices {url}"),
+            )),
+        }
+    }
+
+    async fn put_hold_invoice(&self, hold: HoldInvoiceRecord) -> Result<bool, Self::Error> {
+        let url = self.hold_payment_hash_url(&hold.payment_hash);
+        let response = self
+            .client
+            .put(&url)
+            .json(&hold.hold)
+            .send()
+            .await
+            .map_err(|e| {
+                OfferStoreError::http_error(
+                    ServiceErrorSource::Upstream,
+                    format!("put hold invoice {url}"),
+                    e,
+                )
+            })?;
+
+        match response.status() {
+            StatusCode::CREATED => Ok(true),
+            StatusCode::NO_CONTENT => Ok(false),
+            status => Err(Self::general_error(
+                status,
+                &format!("put hold invoice {url}"),
+            )),
+        }
+    }
+
+    async fn delete_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        let url = self.hold_payment_hash_url(payment_hash);
+        let response = self.client.delete(&url).send().await.map_err(|e| {
+            OfferStoreError::http_error(
+                ServiceErrorSource::Upstream,
+                format!("delete hold invoice {url}"),
+                e,
+            )
+        })?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            status => Err(Self::general_error(
+                status,
+                &format!("delete hold invoice {url}"),
+            )),
+        }
+    }
+}
+
 #[async_trait]
 impl OfferMetadataStore for HttpOfferStore {
     type Error = OfferStoreError;
@@ -1,11 +1,17 @@
 use crate::offer::error::OfferStoreError;
 use async_trait::async_trait;
+use secp256k1::PublicKey;
 use std::collections::HashMap;
 use std::sync::Arc;
-use switchgear_service_api::offer::{OfferMetadata, OfferMetadataStore, OfferRecord, OfferStore};
+use switchgear_service_api::offer::{
+    Bolt12OfferRecord, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceStore, OfferMetadata,
+    OfferMetadataStore, OfferRecord, OfferStore,
+};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+type Bolt12OfferMap = HashMap<(String, Uuid, PublicKey), Bolt12OfferRecord>;
+
 #[derive(Clone, Debug)]
 struct OfferRecordTimestamped {
     created: chrono::DateTime<chrono::Utc>,
@@ -22,6 +28,8 @@ struct OfferMetadataTimestamped {
 pub struct MemoryOfferStore {
     offer: Arc<Mutex<HashMap<(String, Uuid), OfferRecordTimestamped>>>,
     metadata: Arc<Mutex<HashMap<(String, Uuid), OfferMetadataTimestamped>>>,
+    bolt12: Arc<Mutex<Bolt12OfferMap>>,
+    hold: Arc<Mutex<HashMap<[u8; 32], HoldInvoiceRecord>>>,
 }
 
 impl MemoryOfferStore {
@@ -29,6 +37,8 @@ impl MemoryOfferStore {
         Self {
             offer: Arc::new(Mutex::new(HashMap::new())),
             metadata: Arc::new(Mutex::new(HashMap::new())),
+            bolt12: Arc::new(Mutex::new(HashMap::new())),
+            hold: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -152,10 +162,82 @@ impl OfferStore for MemoryOfferStore {
 
     async fn delete_offer(&self, partition: &str, id: &Uuid) -> Result<bool, Self::Error> {
         let mut store = self.offer.lock().await;
+        let mut bolt12_store = self.bolt12.lock().await;
+        bolt12_store.retain(|(p, i, _), _| p != partition || i != id);
         Ok(store.remove(&(partition.to_string(), *id)).is_some())
     }
 }
 
+#[async_trait]
+impl Bolt12OfferStore for MemoryOfferStore {
+    type Error = OfferStoreError;
+
+    async fn get_bolt12_offers(
+        &self,
+        partition: &str,
+        id: &Uuid,
+    ) -> Result<Vec<Bolt12OfferRecord>, Self::Error> {
+        let store = self.bolt12.lock().await;
+        let mut offers: Vec<Bolt12OfferRecord> = store
+            .values()
+            .filter(|o| o.partition == partition && o.id == *id)
+            .cloned()
+            .collect();
+        offers.sort_by_key(|o| o.public_key);
+        Ok(offers)
+    }
+
+    async fn put_bolt12_offer(&self, offer: Bolt12OfferRecord) -> Result<bool, Self::Error> {
+        let offer_store = self.offer.lock().await;
+        let mut store = self.bolt12.lock().await;
+
+        if !offer_store.contains_key(&(offer.partition.to_string(), offer.id)) {
+            return Err(OfferStoreError::invalid_input_error(
+                format!("put Bolt12 offer {offer:?}"),
+                format!("offer {} not found", offer.id),
+            ));
+        }
+
+        let was_new = store
+            .insert(
+                (offer.partition.to_string(), offer.id, offer.public_key),
+                offer,
+            )
+            .is_none();
+        Ok(was_new)
+    }
+}
+
+#[async_trait]
+impl HoldInvoiceStore for MemoryOfferStore {
+    type Error = OfferStoreError;
+
+    async fn get_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<Option<HoldInvoiceRecord>, Self::Error> {
+        let store = self.hold.lock().await;
+        Ok(store.get(payment_hash).cloned())
+    }
+
+    async fn get_hold_invoices(&self) -> Result<Vec<HoldInvoiceRecord>, Self::Error> {
+        let store = self.hold.lock().await;
+        let mut holds: Vec<HoldInvoiceRecord> = store.values().cloned().collect();
+        holds.sort_by_key(|h| h.hold.cancel_at);
+        Ok(holds)
+    }
+
+    async fn put_hold_invoice(&self, hold: HoldInvoiceRecord) -> Result<bool, Self::Error> {
+        let mut store = self.hold.lock().await;
+        Ok(store.insert(hold.payment_hash, hold).is_none())
+    }
+
+    async fn delete_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        let mut store = self.hold.lock().await;
+        Ok(store.remove(payment_hash).is_some())
+    }
+}
+
 #[async_trait]
 impl OfferMetadataStore for MemoryOfferStore {
     type Error = OfferStoreError;
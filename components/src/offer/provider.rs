@@ -2,7 +2,9 @@ use crate::offer::error::OfferStoreError;
 use async_trait::async_trait;
 use sha2::{Digest, Sha256};
 use switchgear_service_api::lnurl::LnUrlOfferMetadata;
-use switchgear_service_api::offer::{Offer, OfferProvider, OfferStore};
+use switchgear_service_api::offer::{
+    Bolt12OfferRecord, Bolt12OfferStore, Offer, OfferProvider, OfferStore,
+};
 use switchgear_service_api::service::ServiceErrorSource;
 use uuid::Uuid;
 
@@ -61,6 +63,7 @@ where
                 metadata_json_hash,
                 timestamp: offer.offer.timestamp,
                 expires: offer.offer.expires,
+                hold_invoice: offer.offer.hold_invoice,
             }))
         } else {
             Ok(None)
@@ -68,6 +71,27 @@ where
     }
 }
 
+// Bolt12 offers are kept next to the offer records they were created for
+#[async_trait]
+impl<S> Bolt12OfferStore for StoreOfferProvider<S>
+where
+    S: Bolt12OfferStore + Send + Sync + 'static,
+{
+    type Error = S::Error;
+
+    async fn get_bolt12_offers(
+        &self,
+        partition: &str,
+        id: &Uuid,
+    ) -> Result<Vec<Bolt12OfferRecord>, Self::Error> {
+        self.store.get_bolt12_offers(partition, id).await
+    }
+
+    async fn put_bolt12_offer(&self, offer: Bolt12OfferRecord) -> Result<bool, Self::Error> {
+        self.store.put_bolt12_offer(offer).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,6 +169,7 @@ mod tests {
                 }),
                 timestamp: Utc::now(),
                 expires: Some(Utc::now() + chrono::Duration::hours(24)),
+                hold_invoice: false,
             },
         }
     }
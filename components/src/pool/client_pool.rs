@@ -1,21 +1,48 @@
 use crate::pool::cln::grpc::client::TonicClnGrpcClient;
-use crate::pool::error::LnPoolError;
+use crate::pool::cln::rest::client::ReqwestClnRestClient;
+use crate::pool::eclair::http::client::ReqwestEclairHttpClient;
+use crate::pool::error::{LnPoolError, LnPoolErrorSourceKind};
+use crate::pool::fake::client::FakeLnClient;
 use crate::pool::lnd::grpc::client::TonicLndGrpcClient;
+use crate::pool::lnd::rest::client::ReqwestLndRestClient;
+use crate::pool::nwc::client::TungsteniteNwcClient;
+use crate::pool::phoenixd::http::client::ReqwestPhoenixdHttpClient;
 use crate::pool::{
-    Bolt11InvoiceDescription, DiscoveryBackendImplementation, LnMetrics, LnRpcClient,
+    Bolt11InvoiceDescription, DiscoveryBackendImplementation, LnBolt12Offer, LnFeatures,
+    LnInvoiceState, LnMetrics, LnRpcClient,
 };
+use bitcoin_hashes::Hash;
+use chrono::DateTime;
+use lightning_invoice::Bolt11Invoice;
+use log::{error, info, warn};
+use secp256k1::PublicKey;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+use switchgear_service_api::balance::IssuedInvoice;
 use switchgear_service_api::discovery::DiscoveryBackend;
 use switchgear_service_api::offer::Offer;
 use switchgear_service_api::service::ServiceErrorSource;
 use tonic::transport::CertificateDer;
+use url::Url;
 
 type LnClientMap<K> =
     HashMap<K, Arc<Box<dyn LnRpcClient<Error = LnPoolError> + Send + Sync + 'static>>>;
 
+// modification time and length of each credential file, None when it can't be read
+type CredentialFingerprint = Vec<(PathBuf, Option<(SystemTime, u64)>)>;
+
+#[derive(Clone)]
+struct CredentialWatch {
+    implementation: DiscoveryBackendImplementation,
+    fingerprint: CredentialFingerprint,
+    error: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct LnClientPool<K>
 where
@@ -24,19 +51,34 @@ where
     timeout: Duration,
     pool: Arc<Mutex<LnClientMap<K>>>,
     metrics_cache: Arc<Mutex<HashMap<K, LnMetrics>>>,
+    node_ids: Arc<Mutex<HashMap<K, PublicKey>>>,
+    credentials: Arc<Mutex<HashMap<K, CredentialWatch>>>,
     trusted_roots: Vec<CertificateDer<'static>>,
+    secrets: Arc<HashMap<String, String>>,
+    socks5_proxy: Option<Url>,
 }
 
 impl<K> LnClientPool<K>
 where
     K: Clone + std::hash::Hash + Eq + Debug,
 {
-    pub fn new(timeout: Duration, trusted_roots: Vec<CertificateDer<'static>>) -> LnClientPool<K> {
+    // secrets are keyed as secret.NAME, matching the {secret.NAME} placeholders they replace, and
+    // the SOCKS5 proxy is used by gRPC backends that don't set their own
+    pub fn new(
+        timeout: Duration,
+        trusted_roots: Vec<CertificateDer<'static>>,
+        secrets: HashMap<String, String>,
+        socks5_proxy: Option<Url>,
+    ) -> LnClientPool<K> {
         Self {
             timeout,
             pool: Default::default(),
             metrics_cache: Default::default(),
+            node_ids: Default::default(),
+            credentials: Default::default(),
             trusted_roots,
+            secrets: Arc::new(secrets),
+            socks5_proxy,
         }
     }
 
@@ -67,59 +109,416 @@ where
         key: &K,
         amount_msat: Option<u64>,
         expiry_secs: Option<u64>,
-    ) -> Result<String, LnPoolError> {
+    ) -> Result<IssuedInvoice, LnPoolError> {
         let client = self.get_client(key).await?;
 
-        let capabilities = client.get_features();
+        let invoice = client
+            .get_invoice(
+                amount_msat,
+                Self::offer_description(client.get_features(), offer),
+                expiry_secs,
+            )
+            .await?;
+        Self::issued_invoice(invoice, format!("decoding invoice issued by key: {key:?}"))
+    }
 
-        let invoice_from_desc_hash =
-            capabilities.map_or_else(|| false, |c| c.invoice_from_desc_hash);
+    pub async fn get_hold_invoice(
+        &self,
+        offer: &Offer,
+        key: &K,
+        amount_msat: Option<u64>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<IssuedInvoice, LnPoolError> {
+        let client = self.get_client(key).await?;
 
-        let description = if invoice_from_desc_hash {
-            Bolt11InvoiceDescription::Hash(&offer.metadata_json_hash)
-        } else {
-            Bolt11InvoiceDescription::DirectIntoHash(offer.metadata_json_string.as_str())
-        };
+        let invoice = client
+            .get_hold_invoice(
+                amount_msat,
+                Self::offer_description(client.get_features(), offer),
+                expiry_secs,
+                payment_hash,
+            )
+            .await?;
+        Self::issued_invoice(
+            invoice,
+            format!("decoding hold invoice issued by key: {key:?}"),
+        )
+    }
 
+    pub async fn settle_hold_invoice(
+        &self,
+        key: &K,
+        preimage: &[u8; 32],
+    ) -> Result<(), LnPoolError> {
+        let client = self.get_client(key).await?;
+        client.settle_hold_invoice(preimage).await
+    }
+
+    pub async fn cancel_hold_invoice(
+        &self,
+        key: &K,
+        payment_hash: &[u8; 32],
+    ) -> Result<(), LnPoolError> {
+        let client = self.get_client(key).await?;
+        client.cancel_hold_invoice(payment_hash).await
+    }
+
+    pub async fn get_bolt12_offer(
+        &self,
+        key: &K,
+        amount_msat: u64,
+        description: &str,
+        absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, LnPoolError> {
+        let client = self.get_client(key).await?;
         client
-            .get_invoice(amount_msat, description, expiry_secs)
+            .get_bolt12_offer(amount_msat, description, absolute_expiry)
             .await
     }
 
+    // None until the client is connected
+    pub fn get_features(&self, key: &K) -> Option<LnFeatures> {
+        match self.pool.lock() {
+            Ok(pool) => pool.get(key).and_then(|c| c.get_features().copied()),
+            Err(_) => None,
+        }
+    }
+
+    // a node returning an invoice it didn't sign correctly is an upstream failure
+    fn issued_invoice(invoice: String, context: String) -> Result<IssuedInvoice, LnPoolError> {
+        let invalid = |e: String| {
+            LnPoolError::from_invoice_error(e, ServiceErrorSource::Upstream, context.clone())
+        };
+
+        let bolt11 = Bolt11Invoice::from_str(&invoice).map_err(|e| invalid(e.to_string()))?;
+        let public_key = PublicKey::from_slice(&bolt11.get_payee_pub_key().serialize())
+            .map_err(|e| invalid(e.to_string()))?;
+        let expires = bolt11
+            .expires_at()
+            .and_then(|expires_at| DateTime::from_timestamp(expires_at.as_secs() as i64, 0))
+            .ok_or_else(|| invalid("invoice expiry out of range".to_string()))?;
+
+        Ok(IssuedInvoice {
+            payment_hash: *bolt11.payment_hash().as_byte_array(),
+            public_key,
+            expires,
+            attempts: 1,
+            invoice,
+        })
+    }
+
+    fn offer_description<'a>(
+        features: Option<&LnFeatures>,
+        offer: &'a Offer,
+    ) -> Bolt11InvoiceDescription<'a> {
+        if features.is_some_and(|f| f.invoice_from_desc_hash) {
+            Bolt11InvoiceDescription::Hash(&offer.metadata_json_hash)
+        } else {
+            Bolt11InvoiceDescription::DirectIntoHash(offer.metadata_json_string.as_str())
+        }
+    }
+
     pub async fn get_metrics(&self, key: &K) -> Result<LnMetrics, LnPoolError> {
+        // metrics are polled by the health check, which makes this the place to pick up rotated
+        // credentials
+        self.reload_credentials(key).await?;
+
         let client = self.get_client(key).await?;
 
+        // checked every poll, the address behind a backend can start pointing at another node
+        self.check_node_id(key, client.as_ref().as_ref()).await?;
+
         let metrics = client.get_metrics().await?;
 
+        let pool = self.pool.lock().map_err(|e| {
+            LnPoolError::from_memory_error(e.to_string(), format!("get node metrics key: {key:?}"))
+        })?;
         let mut cache = self.metrics_cache.lock().map_err(|e| {
             LnPoolError::from_memory_error(e.to_string(), format!("get node metrics key: {key:?}"))
         })?;
 
-        cache.insert(key.clone(), metrics.clone());
+        // the client may have been disconnected while the metrics were in flight
+        if pool.contains_key(key) {
+            cache.insert(key.clone(), metrics.clone());
+        }
         Ok(metrics)
     }
 
+    // the node behind the connection must be the one its discovery public key names, a node that
+    // can't be asked right now is left to the metrics poll, which checks again every time
+    pub async fn verify_node_id(&self, key: &K) -> Result<(), LnPoolError> {
+        let client = self.get_client(key).await?;
+        match self.check_node_id(key, client.as_ref().as_ref()).await {
+            Err(e) if matches!(e.source(), LnPoolErrorSourceKind::NodeIdMismatch(..)) => Err(e),
+            Err(e) => {
+                warn!("could not verify node id of ln client {key:?}: {e}");
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    async fn check_node_id(
+        &self,
+        key: &K,
+        client: &(dyn LnRpcClient<Error = LnPoolError> + Send + Sync),
+    ) -> Result<(), LnPoolError> {
+        let expected = {
+            let node_ids = self.node_ids.lock().map_err(|e| {
+                LnPoolError::from_memory_error(e.to_string(), format!("verify node id {key:?}"))
+            })?;
+            match node_ids.get(key) {
+                Some(expected) => *expected,
+                None => return Ok(()),
+            }
+        };
+
+        match client.get_node_id().await? {
+            Some(actual) if actual != expected => Err(LnPoolError::from_node_id_mismatch(
+                expected.to_string(),
+                actual.to_string(),
+                format!("verify node id {key:?}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn get_invoice_state(
+        &self,
+        key: &K,
+        invoice: &str,
+        payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, LnPoolError> {
+        let client = self.get_client(key).await?;
+        client.get_invoice_state(invoice, payment_hash).await
+    }
+
     pub fn connect(&self, key: K, backend: &DiscoveryBackend) -> Result<(), LnPoolError> {
-        let implementation: DiscoveryBackendImplementation =
+        let mut implementation: serde_json::Value =
             serde_json::from_slice(backend.backend.implementation.as_slice())
                 .map_err(|e| LnPoolError::from_json_error(e, "parsing backend implementation"))?;
-        let client: Box<dyn LnRpcClient<Error = LnPoolError> + Send + Sync> = match implementation {
-            DiscoveryBackendImplementation::ClnGrpc(implementation) => Box::new(
-                TonicClnGrpcClient::create(self.timeout, implementation, &self.trusted_roots)?,
-            ),
-            DiscoveryBackendImplementation::LndGrpc(implementation) => Box::new(
-                TonicLndGrpcClient::create(self.timeout, implementation, &self.trusted_roots)?,
-            ),
-        };
+        expand_placeholders(&mut implementation, &self.secrets).map_err(|e| {
+            LnPoolError::from_invalid_configuration(
+                e,
+                ServiceErrorSource::Internal,
+                format!("expanding backend implementation placeholders for {key:?}"),
+            )
+        })?;
+        let implementation: DiscoveryBackendImplementation = serde_json::from_value(implementation)
+            .map_err(|e| LnPoolError::from_json_error(e, "parsing backend implementation"))?;
+
+        // fingerprint before reading the files so a change racing the read is picked up later
+        let fingerprint = credential_fingerprint(&implementation.credential_paths());
+        let client = self.create_client(implementation.clone())?;
 
         let mut pool = self.pool.lock().map_err(|e| {
             LnPoolError::from_memory_error(e.to_string(), format!("connecting ln client {key:?}"))
         })?;
-        pool.insert(key, Arc::new(client));
+        let mut credentials = self.credentials.lock().map_err(|e| {
+            LnPoolError::from_memory_error(e.to_string(), format!("connecting ln client {key:?}"))
+        })?;
+        let mut metrics_cache = self.metrics_cache.lock().map_err(|e| {
+            LnPoolError::from_memory_error(e.to_string(), format!("connecting ln client {key:?}"))
+        })?;
+        let mut node_ids = self.node_ids.lock().map_err(|e| {
+            LnPoolError::from_memory_error(e.to_string(), format!("connecting ln client {key:?}"))
+        })?;
+        // a replaced client is dropped here, closing its connection once in-flight requests finish
+        pool.insert(key.clone(), Arc::new(client));
+        metrics_cache.remove(&key);
+        node_ids.insert(key.clone(), backend.public_key);
+        credentials.insert(
+            key,
+            CredentialWatch {
+                implementation,
+                fingerprint,
+                error: None,
+            },
+        );
 
         Ok(())
     }
 
+    pub fn disconnect(&self, key: &K) -> Result<(), LnPoolError> {
+        let mut pool = self.pool.lock().map_err(|e| {
+            LnPoolError::from_memory_error(
+                e.to_string(),
+                format!("disconnecting ln client {key:?}"),
+            )
+        })?;
+        let mut credentials = self.credentials.lock().map_err(|e| {
+            LnPoolError::from_memory_error(
+                e.to_string(),
+                format!("disconnecting ln client {key:?}"),
+            )
+        })?;
+        let mut metrics_cache = self.metrics_cache.lock().map_err(|e| {
+            LnPoolError::from_memory_error(
+                e.to_string(),
+                format!("disconnecting ln client {key:?}"),
+            )
+        })?;
+        let mut node_ids = self.node_ids.lock().map_err(|e| {
+            LnPoolError::from_memory_error(
+                e.to_string(),
+                format!("disconnecting ln client {key:?}"),
+            )
+        })?;
+        pool.remove(key);
+        credentials.remove(key);
+        metrics_cache.remove(key);
+        node_ids.remove(key);
+
+        Ok(())
+    }
+
+    // the last failure to rebuild the client after its credential files changed, cleared once a
+    // rebuild succeeds
+    pub fn get_credentials_error(&self, key: &K) -> Option<String> {
+        match self.credentials.lock() {
+            Ok(credentials) => credentials.get(key).and_then(|w| w.error.clone()),
+            Err(_) => None,
+        }
+    }
+
+    async fn reload_credentials(&self, key: &K) -> Result<(), LnPoolError> {
+        let watch = {
+            let credentials = self.credentials.lock().map_err(|e| {
+                LnPoolError::from_memory_error(
+                    e.to_string(),
+                    format!("reloading credentials for key: {key:?}"),
+                )
+            })?;
+            match credentials.get(key) {
+                Some(watch) => watch.clone(),
+                None => return Ok(()),
+            }
+        };
+
+        let fingerprint = credential_fingerprint(&watch.implementation.credential_paths());
+        if fingerprint == watch.fingerprint {
+            return Ok(());
+        }
+
+        // the old client keeps serving until one built from the changed files answers the node
+        let result = match self.create_client(watch.implementation.clone()) {
+            Ok(client) => match self.check_node_id(key, client.as_ref()).await {
+                Ok(()) => client.get_metrics().await.map(|_| client),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+
+        let mut pool = self.pool.lock().map_err(|e| {
+            LnPoolError::from_memory_error(
+                e.to_string(),
+                format!("reloading credentials for key: {key:?}"),
+            )
+        })?;
+        let mut credentials = self.credentials.lock().map_err(|e| {
+            LnPoolError::from_memory_error(
+                e.to_string(),
+                format!("reloading credentials for key: {key:?}"),
+            )
+        })?;
+        let current = match credentials.get_mut(key) {
+            // reconnected with another implementation while the files were being read
+            Some(current) if current.implementation == watch.implementation => current,
+            _ => return Ok(()),
+        };
+
+        // failures keep the old fingerprint so the reload is retried on the next poll, files may
+        // be mid-rotation or the node briefly unreachable
+        match result {
+            Ok(client) => {
+                info!("credential files changed, reconnected ln client {key:?}");
+                pool.insert(key.clone(), Arc::new(client));
+                current.fingerprint = fingerprint;
+                current.error = None;
+            }
+            Err(e) => {
+                error!("credential files changed, keeping previous ln client {key:?}: {e}");
+                current.error = Some(format!("reloading credentials: {e}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_client(
+        &self,
+        implementation: DiscoveryBackendImplementation,
+    ) -> Result<Box<dyn LnRpcClient<Error = LnPoolError> + Send + Sync>, LnPoolError> {
+        let client: Box<dyn LnRpcClient<Error = LnPoolError> + Send + Sync> = match implementation {
+            DiscoveryBackendImplementation::ClnGrpc(mut implementation) => {
+                if implementation.socks5_proxy.is_none() {
+                    implementation.socks5_proxy = self.socks5_proxy.clone();
+                }
+                Box::new(TonicClnGrpcClient::create(
+                    self.timeout,
+                    implementation,
+                    &self.trusted_roots,
+                )?)
+            }
+            DiscoveryBackendImplementation::ClnRest(implementation) => {
+                Box::new(ReqwestClnRestClient::create(
+                    self.timeout,
+                    implementation,
+                    &self.trusted_roots,
+                    self.socks5_proxy.as_ref(),
+                )?)
+            }
+            DiscoveryBackendImplementation::LndGrpc(mut implementation) => {
+                if implementation.socks5_proxy.is_none() {
+                    implementation.socks5_proxy = self.socks5_proxy.clone();
+                }
+                Box::new(TonicLndGrpcClient::create(
+                    self.timeout,
+                    implementation,
+                    &self.trusted_roots,
+                )?)
+            }
+            DiscoveryBackendImplementation::LndRest(implementation) => {
+                Box::new(ReqwestLndRestClient::create(
+                    self.timeout,
+                    implementation,
+                    &self.trusted_roots,
+                    self.socks5_proxy.as_ref(),
+                )?)
+            }
+            DiscoveryBackendImplementation::PhoenixdHttp(implementation) => {
+                Box::new(ReqwestPhoenixdHttpClient::create(
+                    self.timeout,
+                    implementation,
+                    &self.trusted_roots,
+                    self.socks5_proxy.as_ref(),
+                )?)
+            }
+            DiscoveryBackendImplementation::EclairHttp(implementation) => {
+                Box::new(ReqwestEclairHttpClient::create(
+                    self.timeout,
+                    implementation,
+                    &self.trusted_roots,
+                    self.socks5_proxy.as_ref(),
+                )?)
+            }
+            DiscoveryBackendImplementation::Nwc(implementation) => {
+                Box::new(TungsteniteNwcClient::create(
+                    self.timeout,
+                    implementation,
+                    &self.trusted_roots,
+                    self.socks5_proxy.as_ref(),
+                )?)
+            }
+            DiscoveryBackendImplementation::Fake(implementation) => {
+                Box::new(FakeLnClient::create(self.timeout, implementation)?)
+            }
+        };
+
+        Ok(client)
+    }
+
     pub fn get_cached_metrics(&self, key: &K) -> Option<LnMetrics> {
         match self.metrics_cache.lock() {
             Ok(cache) => cache.get(key).cloned(),
@@ -127,3 +526,135 @@ where
         }
     }
 }
+
+// env vars ($NAME or ${NAME}) then secrets ({secret.NAME}) are expanded in the url, domain and
+// *Path strings of the implementation, so one discovery store can serve several environments.
+// inline credentials are taken as is, $$, {{ and }} escape placeholders in the expanded strings
+fn expand_placeholders(
+    value: &mut serde_json::Value,
+    secrets: &HashMap<String, String>,
+) -> Result<(), String> {
+    match value {
+        serde_json::Value::Array(values) => {
+            for v in values {
+                expand_placeholders(v, secrets)?;
+            }
+        }
+        serde_json::Value::Object(values) => {
+            for (k, v) in values.iter_mut() {
+                match v {
+                    serde_json::Value::String(s) if is_placeholder_field(k) => {
+                        expand_placeholder_string(s, secrets)?;
+                    }
+                    _ => expand_placeholders(v, secrets)?,
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn is_placeholder_field(key: &str) -> bool {
+    key == "url" || key == "domain" || key.ends_with("Path")
+}
+
+fn expand_placeholder_string(
+    s: &mut String,
+    secrets: &HashMap<String, String>,
+) -> Result<(), String> {
+    if s.contains('$') {
+        *s = shellexpand::env(s)
+            .map_err(|e| format!("expanding env vars in '{s}': {e}"))?
+            .into_owned();
+    }
+    if s.contains('{') || s.contains('}') {
+        *s = strfmt::strfmt(s, secrets)
+            .map_err(|_| format!("inserting secrets into '{s}'. Missing secrets?"))?;
+    }
+    Ok(())
+}
+
+fn credential_fingerprint(paths: &[&Path]) -> CredentialFingerprint {
+    paths
+        .iter()
+        .map(|path| {
+            let stat = fs::metadata(path)
+                .and_then(|m| Ok((m.modified()?, m.len())))
+                .ok();
+            (path.to_path_buf(), stat)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn expand_placeholders_when_inline_credential_has_placeholder_chars_then_kept() {
+        std::env::set_var("SWITCHGEAR_TEST_LND_DIR", "/var/lnd");
+        let secrets = HashMap::from([("secret.LND_HOST".to_string(), "lnd.local".to_string())]);
+        let mut implementation = json!({
+            "type": "lndGrpc",
+            "url": "https://{secret.LND_HOST}:10009",
+            "domain": "${SWITCHGEAR_TEST_LND_DIR}",
+            "auth": {
+                "type": "inline",
+                "tlsCert": "-----BEGIN CERTIFICATE-----{$HOME}",
+                "macaroon": "pa$$word{secret.LND_HOST}$HOME}"
+            },
+            "ampInvoice": false
+        });
+
+        expand_placeholders(&mut implementation, &secrets).unwrap();
+
+        assert_eq!(implementation["url"], "https://lnd.local:10009");
+        assert_eq!(implementation["domain"], "/var/lnd");
+        assert_eq!(
+            implementation["auth"]["tlsCert"],
+            "-----BEGIN CERTIFICATE-----{$HOME}"
+        );
+        assert_eq!(
+            implementation["auth"]["macaroon"],
+            "pa$$word{secret.LND_HOST}$HOME}"
+        );
+    }
+
+    #[test]
+    fn expand_placeholders_when_path_has_escapes_then_literal_chars() {
+        std::env::set_var("SWITCHGEAR_TEST_PHOENIXD_DIR", "/var/phoenixd");
+        let mut implementation = json!({
+            "type": "phoenixdHttp",
+            "url": "http://localhost:9740",
+            "auth": {
+                "type": "path",
+                "passwordPath": "$SWITCHGEAR_TEST_PHOENIXD_DIR/pa$$word{{1}}"
+            }
+        });
+
+        expand_placeholders(&mut implementation, &HashMap::new()).unwrap();
+
+        assert_eq!(
+            implementation["auth"]["passwordPath"],
+            "/var/phoenixd/pa$word{1}"
+        );
+    }
+
+    #[test]
+    fn expand_placeholders_when_secret_missing_then_error() {
+        let mut implementation = json!({
+            "type": "phoenixdHttp",
+            "url": "http://localhost:9740",
+            "auth": {
+                "type": "path",
+                "passwordPath": "{secret.PHOENIXD_PASSWORD_PATH}"
+            }
+        });
+
+        let error = expand_placeholders(&mut implementation, &HashMap::new()).unwrap_err();
+
+        assert!(error.contains("Missing secrets"), "{error}");
+    }
+}
@@ -1,16 +1,23 @@
-use crate::pool::cln::grpc::config::{ClnGrpcClientAuth, ClnGrpcDiscoveryBackendImplementation};
+use crate::pool::cln::grpc::config::{
+    ClnGrpcClientAuth, ClnGrpcClientAuthPath, ClnGrpcDiscoveryBackendImplementation,
+};
 use crate::pool::error::LnPoolError;
-use crate::pool::{Bolt11InvoiceDescription, LnFeatures, LnMetrics, LnRpcClient};
+use crate::pool::grpc::{create_balanced_channel, GrpcChannel};
+use crate::pool::{
+    Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnGrpcChannels, LnInvoiceState, LnMetrics,
+    LnRouteHints, LnRpcClient,
+};
 use async_trait::async_trait;
 use hex::ToHex;
 use rustls::pki_types::CertificateDer;
+use secp256k1::PublicKey;
 use sha2::Digest;
 use std::fs;
-use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use switchgear_service_api::service::ServiceErrorSource;
-use tokio::sync::Mutex;
+use tokio::sync::OnceCell;
 use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
+use url::Url;
 
 #[allow(clippy::all)]
 pub mod cln {
@@ -19,11 +26,14 @@ pub mod cln {
 
 use cln::node_client::NodeClient;
 
+// PEM encoded CA certificate, client certificate and client key
+type ClnGrpcCredentials = (Option<Vec<u8>>, Vec<u8>, Vec<u8>);
+
 pub struct TonicClnGrpcClient {
     timeout: Duration,
     config: ClnGrpcDiscoveryBackendImplementation,
     features: Option<LnFeatures>,
-    inner: Arc<Mutex<Option<Arc<InnerTonicClnGrpcClient>>>>,
+    inner: OnceCell<InnerTonicClnGrpcClient>,
     ca_certificates: Vec<Certificate>,
     identity: Identity,
 }
@@ -34,8 +44,6 @@ impl TonicClnGrpcClient {
         config: ClnGrpcDiscoveryBackendImplementation,
         trusted_roots: &[CertificateDer],
     ) -> Result<Self, LnPoolError> {
-        let ClnGrpcClientAuth::Path(auth) = &config.auth;
-
         let mut ca_certificates = trusted_roots
             .iter()
             .map(|c| {
@@ -44,20 +52,54 @@ impl TonicClnGrpcClient {
             })
             .collect::<Vec<_>>();
 
-        if let Some(ca_cert_path) = &auth.ca_cert_path {
-            let ca_certificate = fs::read(ca_cert_path).map_err(|e| {
-                LnPoolError::from_invalid_credentials(
-                    e.to_string(),
-                    ServiceErrorSource::Internal,
-                    format!(
-                        "loading CLN credentials and reading CA certificate from path {}",
-                        ca_cert_path.to_string_lossy()
-                    ),
-                )
-            })?;
+        let (ca_certificate, client_cert, client_key) = match &config.auth {
+            ClnGrpcClientAuth::Path(auth) => Self::read_path_credentials(auth)?,
+            ClnGrpcClientAuth::Inline(auth) => (
+                auth.ca_cert.clone().map(String::into_bytes),
+                auth.client_cert.clone().into_bytes(),
+                auth.client_key.clone().into_bytes(),
+            ),
+        };
+        if let Some(ca_certificate) = ca_certificate {
             ca_certificates.push(Certificate::from_pem(&ca_certificate));
         }
 
+        let identity = Identity::from_pem(client_cert, client_key);
+
+        Ok(Self {
+            timeout,
+            config,
+            features: Some(LnFeatures {
+                invoice_from_desc_hash: false,
+                hold_invoice: false,
+                bolt12: true,
+            }),
+            inner: OnceCell::new(),
+            ca_certificates,
+            identity,
+        })
+    }
+
+    fn read_path_credentials(
+        auth: &ClnGrpcClientAuthPath,
+    ) -> Result<ClnGrpcCredentials, LnPoolError> {
+        let ca_certificate = auth
+            .ca_cert_path
+            .as_ref()
+            .map(|ca_cert_path| {
+                fs::read(ca_cert_path).map_err(|e| {
+                    LnPoolError::from_invalid_credentials(
+                        e.to_string(),
+                        ServiceErrorSource::Internal,
+                        format!(
+                            "loading CLN credentials and reading CA certificate from path {}",
+                            ca_cert_path.to_string_lossy()
+                        ),
+                    )
+                })
+            })
+            .transpose()?;
+
         let client_cert = fs::read(&auth.client_cert_path).map_err(|e| {
             LnPoolError::from_invalid_credentials(
                 e.to_string(),
@@ -80,44 +122,25 @@ impl TonicClnGrpcClient {
             )
         })?;
 
-        let identity = Identity::from_pem(client_cert, client_key);
-
-        Ok(Self {
-            timeout,
-            config,
-            features: Some(LnFeatures {
-                invoice_from_desc_hash: false,
-            }),
-            inner: Arc::new(Default::default()),
-            ca_certificates,
-            identity,
-        })
+        Ok((ca_certificate, client_cert, client_key))
     }
 
-    async fn inner_connect(&self) -> Result<Arc<InnerTonicClnGrpcClient>, LnPoolError> {
-        let mut inner = self.inner.lock().await;
-        match inner.as_ref() {
-            None => {
-                let inner_connect = Arc::new(
-                    InnerTonicClnGrpcClient::connect(
-                        self.timeout,
-                        self.ca_certificates.clone(),
-                        self.identity.clone(),
-                        self.config.url.to_string(),
-                        self.config.domain.as_deref(),
-                    )
-                    .await?,
-                );
-                *inner = Some(inner_connect.clone());
-                Ok(inner_connect)
-            }
-            Some(inner) => Ok(inner.clone()),
-        }
-    }
-
-    async fn inner_disconnect(&self) {
-        let mut inner = self.inner.lock().await;
-        *inner = None;
+    // built on first use, the balanced channel spawns its worker on the runtime
+    async fn inner_connect(&self) -> Result<&InnerTonicClnGrpcClient, LnPoolError> {
+        self.inner
+            .get_or_try_init(|| async {
+                InnerTonicClnGrpcClient::connect(
+                    self.timeout,
+                    self.ca_certificates.clone(),
+                    self.identity.clone(),
+                    self.config.url.to_string(),
+                    self.config.domain.as_deref(),
+                    self.config.route_hints.clone(),
+                    &self.config.channels.clone().unwrap_or_default(),
+                    self.config.socks5_proxy.as_ref(),
+                )
+            })
+            .await
     }
 
     fn certificate_der_as_pem(certificate: &CertificateDer) -> String {
@@ -137,27 +160,65 @@ impl LnRpcClient for TonicClnGrpcClient {
         description: Bolt11InvoiceDescription<'a>,
         expiry_secs: Option<u64>,
     ) -> Result<String, Self::Error> {
-        let inner = self.inner_connect().await?;
-
-        let r = inner
+        self.inner_connect()
+            .await?
             .get_invoice(amount_msat, description, expiry_secs)
-            .await;
-
-        if r.is_err() {
-            self.inner_disconnect().await;
-        }
-        r
+            .await
     }
 
     async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
-        let inner = self.inner_connect().await?;
+        self.inner_connect().await?.get_metrics().await
+    }
 
-        let r = inner.get_metrics().await;
+    async fn get_invoice_state(
+        &self,
+        invoice: &str,
+        _payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        self.inner_connect().await?.get_invoice_state(invoice).await
+    }
 
-        if r.is_err() {
-            self.inner_disconnect().await;
-        }
-        r
+    async fn get_hold_invoice<'a>(
+        &self,
+        _amount_msat: Option<u64>,
+        _description: Bolt11InvoiceDescription<'a>,
+        _expiry_secs: Option<u64>,
+        _payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "CLN gRPC does not expose plugin RPCs such as holdinvoice",
+            "CLN gRPC get hold invoice",
+        ))
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "CLN gRPC does not expose plugin RPCs such as holdinvoice",
+            "CLN gRPC settle hold invoice",
+        ))
+    }
+
+    async fn cancel_hold_invoice(&self, _payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "CLN gRPC does not expose plugin RPCs such as holdinvoice",
+            "CLN gRPC cancel hold invoice",
+        ))
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        self.inner_connect()
+            .await?
+            .get_bolt12_offer(amount_msat, description, absolute_expiry)
+            .await
+    }
+
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error> {
+        self.inner_connect().await?.get_node_id().await.map(Some)
     }
 
     fn get_features(&self) -> Option<&LnFeatures> {
@@ -166,17 +227,22 @@ impl LnRpcClient for TonicClnGrpcClient {
 }
 
 struct InnerTonicClnGrpcClient {
-    client: NodeClient<Channel>,
+    client: NodeClient<GrpcChannel>,
     url: String,
+    route_hints: Option<LnRouteHints>,
 }
 
 impl InnerTonicClnGrpcClient {
-    async fn connect(
+    #[allow(clippy::too_many_arguments)]
+    fn connect(
         timeout: Duration,
         ca_certificates: Vec<Certificate>,
         identity: Identity,
         url: String,
         domain: Option<&str>,
+        route_hints: Option<LnRouteHints>,
+        channels: &LnGrpcChannels,
+        socks5_proxy: Option<&Url>,
     ) -> Result<Self, LnPoolError> {
         let endpoint = Channel::from_shared(url.clone()).map_err(|e| {
             LnPoolError::from_invalid_configuration(
@@ -203,21 +269,79 @@ impl InnerTonicClnGrpcClient {
             )
         })?;
 
-        let channel = endpoint
-            .connect_timeout(timeout)
-            .timeout(timeout)
-            .connect()
+        let channel =
+            create_balanced_channel("CLN", &url, endpoint, timeout, channels, socks5_proxy)?;
+
+        let client = NodeClient::new(channel);
+        Ok(Self {
+            client,
+            url,
+            route_hints,
+        })
+    }
+
+    const CHANNELD_NORMAL: i32 = 2;
+
+    async fn get_node_id(&self) -> Result<PublicKey, LnPoolError> {
+        let mut client = self.client.clone();
+        let response = client
+            .getinfo(cln::GetinfoRequest {})
             .await
             .map_err(|e| {
-                LnPoolError::from_transport_error(
-                    e,
-                    ServiceErrorSource::Upstream,
-                    format!("connecting CLN client to {url}"),
+                LnPoolError::from_tonic_error(e, format!("CLN get node id from {}", self.url))
+            })?
+            .into_inner();
+
+        PublicKey::from_slice(&response.id).map_err(|e| {
+            LnPoolError::from_invalid_configuration(
+                e.to_string(),
+                ServiceErrorSource::Upstream,
+                format!("CLN get node id from {}, parsing node id", self.url),
+            )
+        })
+    }
+
+    async fn list_peer_channels(&self) -> Result<Vec<cln::ListpeerchannelsChannels>, LnPoolError> {
+        let channels_request = cln::ListpeerchannelsRequest {
+            id: None,
+            short_channel_id: None,
+        };
+        let mut client = self.client.clone();
+        let channels_response = client
+            .list_peer_channels(channels_request)
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(e, format!("CLN list peer channels for {}", self.url))
+            })?
+            .into_inner();
+
+        Ok(channels_response.channels)
+    }
+
+    fn is_hinted(&self, channel: &cln::ListpeerchannelsChannels) -> bool {
+        channel.private == Some(true)
+            && self.route_hints.as_ref().is_some_and(|h| {
+                h.includes(
+                    channel.short_channel_id.as_deref(),
+                    &channel.peer_id.encode_hex::<String>(),
                 )
-            })?;
+            })
+    }
 
-        let client = NodeClient::new(channel);
-        Ok(Self { client, url })
+    // an empty list leaves CLN to its default of only hinting private channels when there is no
+    // public one
+    async fn expose_private_channels(&self) -> Result<Vec<String>, LnPoolError> {
+        if self.route_hints.is_none() {
+            return Ok(vec![]);
+        }
+
+        Ok(self
+            .list_peer_channels()
+            .await?
+            .into_iter()
+            .filter(|c| c.state == Self::CHANNELD_NORMAL && self.is_hinted(c))
+            .filter_map(|c| c.short_channel_id)
+            .collect())
     }
 
     async fn get_invoice<'a>(
@@ -270,6 +394,7 @@ impl InnerTonicClnGrpcClient {
             label,
             deschashonly,
             expiry: expiry_secs,
+            exposeprivatechannels: self.expose_private_channels().await?,
             ..Default::default()
         };
 
@@ -287,41 +412,98 @@ impl InnerTonicClnGrpcClient {
         Ok(response.bolt11)
     }
 
-    async fn get_metrics(&self) -> Result<LnMetrics, LnPoolError> {
-        let channels_request = cln::ListpeerchannelsRequest {
-            id: None,
-            short_channel_id: None,
-        };
+    async fn get_bolt12_offer(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, LnPoolError> {
         let mut client = self.client.clone();
-        let channels_response = client
-            .list_peer_channels(channels_request)
+        let request = cln::OfferRequest {
+            amount: format!("{amount_msat}msat"),
+            description: Some(description.to_string()),
+            absolute_expiry,
+            ..Default::default()
+        };
+
+        let response = client
+            .offer(request)
             .await
             .map_err(|e| {
                 LnPoolError::from_tonic_error(
                     e,
-                    format!("CLN get metrics for {}, requesting channels", self.url),
+                    format!("CLN get Bolt12 offer from {}, requesting offer", self.url),
                 )
             })?
             .into_inner();
 
-        let mut node_effective_inbound_msat = 0u64;
+        Ok(LnBolt12Offer {
+            offer_id: response.offer_id.encode_hex(),
+            bolt12: response.bolt12,
+        })
+    }
 
-        const CHANNELD_NORMAL: i32 = 2;
+    async fn get_metrics(&self) -> Result<LnMetrics, LnPoolError> {
+        let mut node_effective_inbound_msat = 0u64;
+        let mut node_private_inbound_msat = 0u64;
 
-        for channel in &channels_response.channels {
-            if channel.state == CHANNELD_NORMAL {
+        for channel in &self.list_peer_channels().await? {
+            if channel.state == Self::CHANNELD_NORMAL {
                 let receivable_msat = channel
                     .receivable_msat
                     .as_ref()
                     .map(|a| a.msat)
                     .unwrap_or(0);
-                node_effective_inbound_msat += receivable_msat;
+                if channel.private != Some(true) {
+                    node_effective_inbound_msat += receivable_msat;
+                } else {
+                    node_private_inbound_msat += receivable_msat;
+                    if self.is_hinted(channel) {
+                        node_effective_inbound_msat += receivable_msat;
+                    }
+                }
             }
         }
 
         Ok(LnMetrics {
             healthy: true,
             node_effective_inbound_msat,
+            node_private_inbound_msat,
+        })
+    }
+
+    async fn get_invoice_state(&self, invoice: &str) -> Result<LnInvoiceState, LnPoolError> {
+        let request = cln::ListinvoicesRequest {
+            invstring: Some(invoice.to_string()),
+            ..Default::default()
+        };
+        let mut client = self.client.clone();
+        let response = client
+            .list_invoices(request)
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(
+                    e,
+                    format!("CLN get invoice state from {}, listing invoices", self.url),
+                )
+            })?
+            .into_inner();
+
+        let invoice = response.invoices.first().ok_or_else(|| {
+            LnPoolError::from_invalid_configuration(
+                "invoice not found".to_string(),
+                ServiceErrorSource::Upstream,
+                format!("CLN get invoice state from {}, listing invoices", self.url),
+            )
+        })?;
+
+        const PAID: i32 = 1;
+        const EXPIRED: i32 = 2;
+
+        Ok(match invoice.status {
+            PAID => LnInvoiceState::Settled,
+            EXPIRED => LnInvoiceState::Canceled,
+            _ => LnInvoiceState::Open,
         })
     }
 }
@@ -1,3 +1,4 @@
+use crate::pool::{LnGrpcChannels, LnRouteHints};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use url::Url;
@@ -8,6 +9,14 @@ pub struct ClnGrpcDiscoveryBackendImplementation {
     pub url: Url,
     pub domain: Option<String>,
     pub auth: ClnGrpcClientAuth,
+    #[serde(default)]
+    pub route_hints: Option<LnRouteHints>,
+    // a single channel without keepalive when unset
+    #[serde(default)]
+    pub channels: Option<LnGrpcChannels>,
+    // socks5:// or socks5h:// proxy url, host names are always resolved by the proxy
+    #[serde(default)]
+    pub socks5_proxy: Option<Url>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -15,6 +24,7 @@ pub struct ClnGrpcDiscoveryBackendImplementation {
 #[serde(tag = "type")]
 pub enum ClnGrpcClientAuth {
     Path(ClnGrpcClientAuthPath),
+    Inline(ClnGrpcClientAuthInline),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -24,3 +34,12 @@ pub struct ClnGrpcClientAuthPath {
     pub client_cert_path: PathBuf,
     pub client_key_path: PathBuf,
 }
+
+// PEM encoded credentials carried in the implementation itself
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClnGrpcClientAuthInline {
+    pub ca_cert: Option<String>,
+    pub client_cert: String,
+    pub client_key: String,
+}
@@ -0,0 +1,475 @@
+use crate::pool::cln::rest::config::{ClnRestClientAuth, ClnRestDiscoveryBackendImplementation};
+use crate::pool::error::LnPoolError;
+use crate::pool::http::{create_http_client, parse_http_response};
+use crate::pool::{
+    Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnInvoiceState, LnMetrics, LnRouteHints,
+    LnRpcClient, HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA,
+};
+use async_trait::async_trait;
+use hex::ToHex;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Client;
+use rustls::pki_types::CertificateDer;
+use secp256k1::PublicKey;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::fs;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use switchgear_service_api::service::ServiceErrorSource;
+use url::Url;
+
+const RUNE_HEADER: &str = "Rune";
+
+pub struct ReqwestClnRestClient {
+    client: Client,
+    base_url: String,
+    route_hints: Option<LnRouteHints>,
+    features: Option<LnFeatures>,
+}
+
+impl ReqwestClnRestClient {
+    pub fn create(
+        timeout: Duration,
+        config: ClnRestDiscoveryBackendImplementation,
+        trusted_roots: &[CertificateDer],
+        socks5_proxy: Option<&Url>,
+    ) -> Result<Self, LnPoolError> {
+        let ClnRestClientAuth::Path(auth) = &config.auth;
+        let base_url = config.url.as_str().trim_end_matches('/').to_string();
+
+        let rune = fs::read_to_string(&auth.rune_path).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!("loading CLN rune from {}", auth.rune_path.to_string_lossy()),
+            )
+        })?;
+
+        let mut headers = HeaderMap::new();
+        let mut rune = HeaderValue::from_str(rune.trim()).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!("loading CLN rune into client for {base_url}"),
+            )
+        })?;
+        rune.set_sensitive(true);
+        headers.insert(RUNE_HEADER, rune);
+
+        let client = create_http_client(
+            "CLN",
+            &base_url,
+            timeout,
+            headers,
+            trusted_roots,
+            auth.ca_cert_path.as_deref(),
+            socks5_proxy,
+        )?;
+
+        Ok(Self {
+            client,
+            base_url,
+            route_hints: config.route_hints,
+            features: Some(LnFeatures {
+                invoice_from_desc_hash: false,
+                // served by the holdinvoice plugin, calls fail if the node does not run it
+                hold_invoice: true,
+                bolt12: true,
+            }),
+        })
+    }
+
+    // every clnrest RPC is a POST to /v1/{method} with the JSON params as the body
+    async fn call<Q: Serialize, T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &Q,
+        context: String,
+    ) -> Result<T, LnPoolError> {
+        let url = format!("{}/v1/{method}", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(params)
+            .send()
+            .await
+            .map_err(|e| {
+                LnPoolError::from_http_error(e, ServiceErrorSource::Upstream, context.clone())
+            })?;
+
+        parse_http_response(response, context).await
+    }
+
+    async fn list_peer_channels(&self) -> Result<Vec<ClnRestPeerChannel>, LnPoolError> {
+        let response: ClnRestListPeerChannelsResponse = self
+            .call(
+                "listpeerchannels",
+                &serde_json::json!({}),
+                format!("CLN list peer channels for {}", self.base_url),
+            )
+            .await?;
+        Ok(response.channels)
+    }
+
+    fn is_hinted(&self, channel: &ClnRestPeerChannel) -> bool {
+        channel.private == Some(true)
+            && self
+                .route_hints
+                .as_ref()
+                .is_some_and(|h| h.includes(channel.short_channel_id.as_deref(), &channel.peer_id))
+    }
+
+    async fn expose_private_channels(
+        &self,
+    ) -> Result<Option<ClnRestExposePrivateChannels>, LnPoolError> {
+        match &self.route_hints {
+            None => Ok(None),
+            Some(h) if h.channels.is_empty() && h.peers.is_empty() => {
+                Ok(Some(ClnRestExposePrivateChannels::All(true)))
+            }
+            Some(_) => Ok(Some(ClnRestExposePrivateChannels::Channels(
+                self.list_peer_channels()
+                    .await?
+                    .into_iter()
+                    .filter(|c| c.state == "CHANNELD_NORMAL" && self.is_hinted(c))
+                    .filter_map(|c| c.short_channel_id)
+                    .collect(),
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl LnRpcClient for ReqwestClnRestClient {
+    type Error = LnPoolError;
+
+    async fn get_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+    ) -> Result<String, Self::Error> {
+        let (description, deschashonly, label) = match description {
+            Bolt11InvoiceDescription::Direct(d) => (d.to_string(), false, d.to_string()),
+            Bolt11InvoiceDescription::DirectIntoHash(d) => {
+                let hash = sha2::Sha256::digest(d.as_bytes()).to_vec();
+                (d.to_string(), true, hash.encode_hex())
+            }
+            Bolt11InvoiceDescription::Hash(_) => {
+                return Err(LnPoolError::from_invalid_configuration(
+                    "hash descriptions unsupported".to_string(),
+                    ServiceErrorSource::Internal,
+                    format!(
+                        "CLN get invoice from {}, parsing invoice description",
+                        self.base_url
+                    ),
+                ))
+            }
+        };
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| {
+            LnPoolError::from_invalid_configuration(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!(
+                    "CLN get invoice from {}, getting current time for label",
+                    self.base_url
+                ),
+            )
+        })?;
+
+        let request = ClnRestInvoiceRequest {
+            amount_msat: match amount_msat {
+                Some(msat) => ClnRestAmountOrAny::Amount(msat),
+                None => ClnRestAmountOrAny::Any("any"),
+            },
+            label: format!("{label}:{}", now.as_nanos()),
+            description,
+            expiry: expiry_secs,
+            deschashonly,
+            exposeprivatechannels: self.expose_private_channels().await?,
+        };
+
+        let response: ClnRestInvoiceResponse = self
+            .call(
+                "invoice",
+                &request,
+                format!("CLN get invoice from {}, requesting invoice", self.base_url),
+            )
+            .await?;
+
+        Ok(response.bolt11)
+    }
+
+    async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
+        let mut node_effective_inbound_msat = 0u64;
+        let mut node_private_inbound_msat = 0u64;
+
+        for channel in self
+            .list_peer_channels()
+            .await?
+            .iter()
+            .filter(|channel| channel.state == "CHANNELD_NORMAL")
+        {
+            let receivable_msat = channel.receivable_msat.unwrap_or(0);
+            if channel.private != Some(true) {
+                node_effective_inbound_msat += receivable_msat;
+            } else {
+                node_private_inbound_msat += receivable_msat;
+                if self.is_hinted(channel) {
+                    node_effective_inbound_msat += receivable_msat;
+                }
+            }
+        }
+
+        Ok(LnMetrics {
+            healthy: true,
+            node_effective_inbound_msat,
+            node_private_inbound_msat,
+        })
+    }
+
+    async fn get_invoice_state(
+        &self,
+        invoice: &str,
+        _payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        let context = format!(
+            "CLN get invoice state from {}, listing invoices",
+            self.base_url
+        );
+        let response: ClnRestListInvoicesResponse = self
+            .call(
+                "listinvoices",
+                &serde_json::json!({ "invstring": invoice }),
+                context.clone(),
+            )
+            .await?;
+
+        let invoice = response.invoices.first().ok_or_else(|| {
+            LnPoolError::from_invalid_configuration(
+                "invoice not found".to_string(),
+                ServiceErrorSource::Upstream,
+                context,
+            )
+        })?;
+
+        Ok(match invoice.status.as_str() {
+            "paid" => LnInvoiceState::Settled,
+            "expired" => LnInvoiceState::Canceled,
+            _ => LnInvoiceState::Open,
+        })
+    }
+
+    async fn get_hold_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        let (description, deschashonly) = match description {
+            Bolt11InvoiceDescription::Direct(d) => (d.to_string(), false),
+            Bolt11InvoiceDescription::DirectIntoHash(d) => (d.to_string(), true),
+            Bolt11InvoiceDescription::Hash(_) => {
+                return Err(LnPoolError::from_invalid_configuration(
+                    "hash descriptions unsupported".to_string(),
+                    ServiceErrorSource::Internal,
+                    format!(
+                        "CLN get hold invoice from {}, parsing invoice description",
+                        self.base_url
+                    ),
+                ))
+            }
+        };
+
+        let request = ClnRestHoldInvoiceRequest {
+            amount_msat: match amount_msat {
+                Some(msat) => ClnRestAmountOrAny::Amount(msat),
+                None => ClnRestAmountOrAny::Any("any"),
+            },
+            description,
+            expiry: expiry_secs,
+            payment_hash: payment_hash.encode_hex(),
+            cltv: HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA,
+            deschashonly,
+        };
+
+        let response: ClnRestInvoiceResponse = self
+            .call(
+                "holdinvoice",
+                &request,
+                format!(
+                    "CLN get hold invoice from {}, requesting invoice",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        Ok(response.bolt11)
+    }
+
+    async fn settle_hold_invoice(&self, preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        let _: serde_json::Value = self
+            .call(
+                "holdinvoicesettle",
+                &serde_json::json!({ "preimage": preimage.encode_hex::<String>() }),
+                format!("CLN settle hold invoice on {}", self.base_url),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        let _: serde_json::Value = self
+            .call(
+                "holdinvoicecancel",
+                &serde_json::json!({ "payment_hash": payment_hash.encode_hex::<String>() }),
+                format!("CLN cancel hold invoice on {}", self.base_url),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        let request = ClnRestOfferRequest {
+            amount: format!("{amount_msat}msat"),
+            description: description.to_string(),
+            absolute_expiry,
+        };
+
+        let response: ClnRestOfferResponse = self
+            .call(
+                "offer",
+                &request,
+                format!(
+                    "CLN get Bolt12 offer from {}, requesting offer",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        Ok(LnBolt12Offer {
+            offer_id: response.offer_id,
+            bolt12: response.bolt12,
+        })
+    }
+
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error> {
+        let response: ClnRestGetinfoResponse = self
+            .call(
+                "getinfo",
+                &serde_json::json!({}),
+                format!("CLN get node id from {}", self.base_url),
+            )
+            .await?;
+
+        PublicKey::from_str(&response.id).map(Some).map_err(|e| {
+            LnPoolError::from_invalid_configuration(
+                e.to_string(),
+                ServiceErrorSource::Upstream,
+                format!("CLN get node id from {}, parsing node id", self.base_url),
+            )
+        })
+    }
+
+    fn get_features(&self) -> Option<&LnFeatures> {
+        self.features.as_ref()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ClnRestAmountOrAny {
+    Amount(u64),
+    Any(&'static str),
+}
+
+#[derive(Serialize)]
+struct ClnRestInvoiceRequest {
+    amount_msat: ClnRestAmountOrAny,
+    label: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiry: Option<u64>,
+    deschashonly: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exposeprivatechannels: Option<ClnRestExposePrivateChannels>,
+}
+
+// either every private channel or only the listed short channel ids
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ClnRestExposePrivateChannels {
+    All(bool),
+    Channels(Vec<String>),
+}
+
+#[derive(Serialize)]
+struct ClnRestHoldInvoiceRequest {
+    amount_msat: ClnRestAmountOrAny,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiry: Option<u64>,
+    payment_hash: String,
+    cltv: u64,
+    deschashonly: bool,
+}
+
+#[derive(Serialize)]
+struct ClnRestOfferRequest {
+    amount: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    absolute_expiry: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ClnRestGetinfoResponse {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ClnRestOfferResponse {
+    offer_id: String,
+    bolt12: String,
+}
+
+#[derive(Deserialize)]
+struct ClnRestInvoiceResponse {
+    bolt11: String,
+}
+
+#[derive(Deserialize)]
+struct ClnRestListPeerChannelsResponse {
+    #[serde(default)]
+    channels: Vec<ClnRestPeerChannel>,
+}
+
+#[derive(Deserialize)]
+struct ClnRestPeerChannel {
+    #[serde(default)]
+    peer_id: String,
+    state: String,
+    short_channel_id: Option<String>,
+    private: Option<bool>,
+    receivable_msat: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ClnRestListInvoicesResponse {
+    #[serde(default)]
+    invoices: Vec<ClnRestInvoice>,
+}
+
+#[derive(Deserialize)]
+struct ClnRestInvoice {
+    status: String,
+}
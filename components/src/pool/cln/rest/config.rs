@@ -0,0 +1,27 @@
+use crate::pool::LnRouteHints;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClnRestDiscoveryBackendImplementation {
+    pub url: Url,
+    pub auth: ClnRestClientAuth,
+    #[serde(default)]
+    pub route_hints: Option<LnRouteHints>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum ClnRestClientAuth {
+    Path(ClnRestClientAuthPath),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClnRestClientAuthPath {
+    pub ca_cert_path: Option<PathBuf>,
+    pub rune_path: PathBuf,
+}
@@ -0,0 +1,234 @@
+use crate::pool::eclair::http::config::{
+    EclairHttpClientAuth, EclairHttpDiscoveryBackendImplementation,
+};
+use crate::pool::error::LnPoolError;
+use crate::pool::http::{basic_auth_headers, create_http_client, parse_http_response};
+use crate::pool::{
+    Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnInvoiceState, LnMetrics, LnRpcClient,
+};
+use async_trait::async_trait;
+use hex::ToHex;
+use reqwest::Client;
+use rustls::pki_types::CertificateDer;
+use secp256k1::PublicKey;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::Digest;
+use std::time::Duration;
+use switchgear_service_api::service::ServiceErrorSource;
+use url::Url;
+
+pub struct ReqwestEclairHttpClient {
+    client: Client,
+    base_url: String,
+    features: Option<LnFeatures>,
+}
+
+impl ReqwestEclairHttpClient {
+    pub fn create(
+        timeout: Duration,
+        config: EclairHttpDiscoveryBackendImplementation,
+        trusted_roots: &[CertificateDer],
+        socks5_proxy: Option<&Url>,
+    ) -> Result<Self, LnPoolError> {
+        let EclairHttpClientAuth::Path(auth) = &config.auth;
+        let base_url = config.url.as_str().trim_end_matches('/').to_string();
+
+        let headers = basic_auth_headers("Eclair", &base_url, &auth.password_path)?;
+        let client = create_http_client(
+            "Eclair",
+            &base_url,
+            timeout,
+            headers,
+            trusted_roots,
+            auth.ca_cert_path.as_deref(),
+            socks5_proxy,
+        )?;
+
+        Ok(Self {
+            client,
+            base_url,
+            features: Some(LnFeatures {
+                invoice_from_desc_hash: true,
+                hold_invoice: false,
+                bolt12: false,
+            }),
+        })
+    }
+
+    // every Eclair API call is a form encoded POST to /{method}
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        form: &[(&str, String)],
+        context: String,
+    ) -> Result<T, LnPoolError> {
+        let url = format!("{}/{method}", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| {
+                LnPoolError::from_http_error(e, ServiceErrorSource::Upstream, context.clone())
+            })?;
+        parse_http_response(response, context).await
+    }
+}
+
+#[async_trait]
+impl LnRpcClient for ReqwestEclairHttpClient {
+    type Error = LnPoolError;
+
+    async fn get_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+    ) -> Result<String, Self::Error> {
+        let mut form = match description {
+            Bolt11InvoiceDescription::Direct(d) => vec![("description", d.to_string())],
+            Bolt11InvoiceDescription::DirectIntoHash(d) => vec![(
+                "descriptionHash",
+                sha2::Sha256::digest(d.as_bytes()).encode_hex(),
+            )],
+            Bolt11InvoiceDescription::Hash(h) => vec![("descriptionHash", h.encode_hex())],
+        };
+        if let Some(amount_msat) = amount_msat {
+            form.push(("amountMsat", amount_msat.to_string()));
+        }
+        if let Some(expiry_secs) = expiry_secs {
+            form.push(("expireIn", expiry_secs.to_string()));
+        }
+
+        let response: EclairInvoiceResponse = self
+            .call(
+                "createinvoice",
+                &form,
+                format!(
+                    "Eclair get invoice from {}, requesting invoice",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        Ok(response.serialized)
+    }
+
+    async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
+        // usable balances only cover channels in the NORMAL state
+        let balances: Vec<EclairUsableBalance> = self
+            .call(
+                "usablebalances",
+                &[],
+                format!(
+                    "Eclair get metrics for {}, requesting usable balances",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        let node_effective_inbound_msat = balances.iter().map(|b| b.can_receive).sum();
+
+        Ok(LnMetrics {
+            healthy: true,
+            node_effective_inbound_msat,
+            node_private_inbound_msat: 0,
+        })
+    }
+
+    async fn get_invoice_state(
+        &self,
+        invoice: &str,
+        _payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        let received: EclairReceivedInfo = self
+            .call(
+                "getreceivedinfo",
+                &[("invoice", invoice.to_string())],
+                format!(
+                    "Eclair get invoice state from {}, requesting received info",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        Ok(match received.status.status_type.as_str() {
+            "received" => LnInvoiceState::Settled,
+            "expired" => LnInvoiceState::Canceled,
+            _ => LnInvoiceState::Open,
+        })
+    }
+
+    async fn get_hold_invoice<'a>(
+        &self,
+        _amount_msat: Option<u64>,
+        _description: Bolt11InvoiceDescription<'a>,
+        _expiry_secs: Option<u64>,
+        _payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by Eclair",
+            "Eclair get hold invoice",
+        ))
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by Eclair",
+            "Eclair settle hold invoice",
+        ))
+    }
+
+    async fn cancel_hold_invoice(&self, _payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by Eclair",
+            "Eclair cancel hold invoice",
+        ))
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        _amount_msat: u64,
+        _description: &str,
+        _absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "Bolt12 offers are not supported by Eclair",
+            "Eclair get Bolt12 offer",
+        ))
+    }
+
+    // identity is only checked for CLN and LND
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error> {
+        Ok(None)
+    }
+
+    fn get_features(&self) -> Option<&LnFeatures> {
+        self.features.as_ref()
+    }
+}
+
+#[derive(Deserialize)]
+struct EclairInvoiceResponse {
+    serialized: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EclairUsableBalance {
+    #[serde(default)]
+    can_receive: u64,
+}
+
+#[derive(Deserialize)]
+struct EclairReceivedInfo {
+    status: EclairReceivedStatus,
+}
+
+#[derive(Deserialize)]
+struct EclairReceivedStatus {
+    #[serde(rename = "type")]
+    status_type: String,
+}
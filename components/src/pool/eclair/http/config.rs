@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EclairHttpDiscoveryBackendImplementation {
+    pub url: Url,
+    pub auth: EclairHttpClientAuth,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum EclairHttpClientAuth {
+    Path(EclairHttpClientAuthPath),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EclairHttpClientAuthPath {
+    pub ca_cert_path: Option<PathBuf>,
+    pub password_path: PathBuf,
+}
@@ -1,9 +1,22 @@
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
-use switchgear_service_api::service::{HasServiceErrorSource, ServiceErrorSource};
+use std::time::Duration;
+use switchgear_service_api::service::{
+    HasServiceErrorSource, ServiceErrorRetry, ServiceErrorSource,
+};
 use thiserror::Error;
+use tokio_tungstenite::tungstenite;
 use tonic::{transport, Code, Status};
 
+// node error messages for requests that fail the same way on every node, matched lowercase
+const FATAL_NODE_ERRORS: &[&str] = &[
+    // CLN 900 and 901
+    "duplicate label",
+    "preimage already used",
+    // LND duplicate payment hash on AddInvoice and AddHoldInvoice
+    "invoice with payment hash already exists",
+];
+
 #[derive(Error, Debug)]
 pub enum LnPoolErrorSourceKind {
     #[error("CLN tonic gRPC error: {0}")]
@@ -18,6 +31,26 @@ pub enum LnPoolErrorSourceKind {
     MemoryError(String),
     #[error("json error: {0}")]
     JsonError(serde_json::Error),
+    #[error("HTTP request error: {0}")]
+    HttpError(reqwest::Error),
+    #[error("HTTP status error: {0}: {1}")]
+    HttpStatusError(u16, String),
+    #[error("websocket error: {0}")]
+    WebSocketError(tungstenite::Error),
+    #[error("relay error: {0}")]
+    RelayError(String),
+    #[error("NWC error: {0}: {1}")]
+    NwcError(String, String),
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("invoice error: {0}")]
+    InvoiceError(String),
+    #[error("injected failure")]
+    InjectedFailure,
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+    #[error("node id mismatch, expected {0} but node reports {1}")]
+    NodeIdMismatch(String, String),
 }
 
 #[derive(Error, Debug)]
@@ -112,6 +145,107 @@ impl LnPoolError {
         )
     }
 
+    pub fn from_http_error<C: Into<Cow<'static, str>>>(
+        source: reqwest::Error,
+        esource: ServiceErrorSource,
+        context: C,
+    ) -> Self {
+        Self::new(LnPoolErrorSourceKind::HttpError(source), esource, context)
+    }
+
+    pub fn from_http_status<C: Into<Cow<'static, str>>>(
+        status: u16,
+        message: String,
+        context: C,
+    ) -> Self {
+        let esource = Self::from_http_status_code(status);
+        Self::new(
+            LnPoolErrorSourceKind::HttpStatusError(status, message),
+            esource,
+            context,
+        )
+    }
+
+    pub fn from_websocket_error<C: Into<Cow<'static, str>>>(
+        source: tungstenite::Error,
+        esource: ServiceErrorSource,
+        context: C,
+    ) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::WebSocketError(source),
+            esource,
+            context,
+        )
+    }
+
+    pub fn from_relay_error<C: Into<Cow<'static, str>>>(message: String, context: C) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::RelayError(message),
+            ServiceErrorSource::Upstream,
+            context,
+        )
+    }
+
+    pub fn from_nwc_error<C: Into<Cow<'static, str>>>(
+        code: String,
+        message: String,
+        context: C,
+    ) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::NwcError(code, message),
+            ServiceErrorSource::Upstream,
+            context,
+        )
+    }
+
+    pub fn from_timeout<C: Into<Cow<'static, str>>>(timeout: Duration, context: C) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::Timeout(timeout),
+            ServiceErrorSource::Upstream,
+            context,
+        )
+    }
+
+    pub fn from_invoice_error<C: Into<Cow<'static, str>>>(
+        source: String,
+        esource: ServiceErrorSource,
+        context: C,
+    ) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::InvoiceError(source),
+            esource,
+            context,
+        )
+    }
+
+    pub fn from_injected_failure<C: Into<Cow<'static, str>>>(context: C) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::InjectedFailure,
+            ServiceErrorSource::Upstream,
+            context,
+        )
+    }
+
+    pub fn from_unsupported<C: Into<Cow<'static, str>>>(source: C, context: C) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::Unsupported(source.into().to_string()),
+            ServiceErrorSource::Internal,
+            context,
+        )
+    }
+
+    pub fn from_node_id_mismatch<C: Into<Cow<'static, str>>>(
+        expected: String,
+        actual: String,
+        context: C,
+    ) -> Self {
+        Self::new(
+            LnPoolErrorSourceKind::NodeIdMismatch(expected, actual),
+            ServiceErrorSource::Upstream,
+            context,
+        )
+    }
+
     pub fn context(&self) -> &str {
         self.context.as_ref()
     }
@@ -124,6 +258,45 @@ impl LnPoolError {
         self.esource
     }
 
+    pub fn retry(&self) -> ServiceErrorRetry {
+        if self.esource == ServiceErrorSource::Downstream {
+            return ServiceErrorRetry::Fatal;
+        }
+        match &self.source {
+            LnPoolErrorSourceKind::TonicError(status) => Self::retry_from_message(status.message())
+                .unwrap_or_else(|| match status.code() {
+                    Code::Unavailable | Code::Aborted | Code::ResourceExhausted => {
+                        ServiceErrorRetry::SameNode
+                    }
+                    Code::AlreadyExists => ServiceErrorRetry::Fatal,
+                    _ => ServiceErrorRetry::OtherNode,
+                }),
+            LnPoolErrorSourceKind::HttpStatusError(status, message) => {
+                Self::retry_from_message(message).unwrap_or(match status {
+                    429 | 503 => ServiceErrorRetry::SameNode,
+                    _ => ServiceErrorRetry::OtherNode,
+                })
+            }
+            LnPoolErrorSourceKind::NwcError(code, message) => Self::retry_from_message(message)
+                .unwrap_or(match code.as_str() {
+                    "RATE_LIMITED" => ServiceErrorRetry::SameNode,
+                    _ => ServiceErrorRetry::OtherNode,
+                }),
+            LnPoolErrorSourceKind::InvoiceError(message) => {
+                Self::retry_from_message(message).unwrap_or(ServiceErrorRetry::OtherNode)
+            }
+            _ => ServiceErrorRetry::OtherNode,
+        }
+    }
+
+    fn retry_from_message(message: &str) -> Option<ServiceErrorRetry> {
+        let message = message.to_lowercase();
+        FATAL_NODE_ERRORS
+            .iter()
+            .any(|fatal| message.contains(fatal))
+            .then_some(ServiceErrorRetry::Fatal)
+    }
+
     fn from_tonic_code(code: Code) -> ServiceErrorSource {
         match code {
             Code::InvalidArgument | Code::OutOfRange | Code::AlreadyExists => {
@@ -133,10 +306,112 @@ impl LnPoolError {
             _ => ServiceErrorSource::Upstream,
         }
     }
+
+    // mirrors from_tonic_code for REST gateways, which map InvalidArgument and OutOfRange to 400
+    // and AlreadyExists to 409
+    fn from_http_status_code(status: u16) -> ServiceErrorSource {
+        match status {
+            400 | 409 => ServiceErrorSource::Downstream,
+            _ => ServiceErrorSource::Upstream,
+        }
+    }
 }
 
 impl HasServiceErrorSource for LnPoolError {
     fn get_service_error_source(&self) -> ServiceErrorSource {
         self.esource
     }
+
+    fn get_service_error_retry(&self) -> ServiceErrorRetry {
+        self.retry()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_when_downstream_then_fatal() {
+        let error = LnPoolError::from_tonic_error(
+            Status::invalid_argument("bad amount"),
+            "creating invoice",
+        );
+        assert_eq!(error.retry(), ServiceErrorRetry::Fatal);
+    }
+
+    #[test]
+    fn retry_when_node_rejects_request_then_fatal() {
+        let cln = LnPoolError::from_tonic_error(
+            Status::unknown(
+                "Error calling method Invoice: RpcError { code: Some(900), message: \"Duplicate label 'abc'\" }",
+            ),
+            "creating invoice",
+        );
+        assert_eq!(cln.retry(), ServiceErrorRetry::Fatal);
+
+        let lnd = LnPoolError::from_http_status(
+            500,
+            "invoice with payment hash already exists".to_string(),
+            "creating invoice",
+        );
+        assert_eq!(lnd.retry(), ServiceErrorRetry::Fatal);
+
+        let tonic = LnPoolError::from_tonic_error(
+            Status::already_exists("invoice already exists"),
+            "creating invoice",
+        );
+        assert_eq!(tonic.retry(), ServiceErrorRetry::Fatal);
+    }
+
+    #[test]
+    fn retry_when_node_unavailable_then_same_node() {
+        let tonic = LnPoolError::from_tonic_error(
+            Status::unavailable("connection reset"),
+            "creating invoice",
+        );
+        assert_eq!(tonic.retry(), ServiceErrorRetry::SameNode);
+
+        let nwc = LnPoolError::from_nwc_error(
+            "RATE_LIMITED".to_string(),
+            "slow down".to_string(),
+            "creating invoice",
+        );
+        assert_eq!(nwc.retry(), ServiceErrorRetry::SameNode);
+    }
+
+    #[test]
+    fn retry_when_node_fails_then_other_node() {
+        let tonic = LnPoolError::from_tonic_error(Status::internal("no route"), "creating invoice");
+        assert_eq!(tonic.retry(), ServiceErrorRetry::OtherNode);
+
+        let unsupported = LnPoolError::from_unsupported(
+            "amount 1500 msat is not a whole satoshi amount",
+            "creating invoice",
+        );
+        assert_eq!(unsupported.retry(), ServiceErrorRetry::OtherNode);
+
+        // amount limits differ between nodes
+        let lnd = LnPoolError::from_http_status(
+            500,
+            "invoice amount 5000000000000 is too large, max is 4294967295000".to_string(),
+            "creating invoice",
+        );
+        assert_eq!(lnd.retry(), ServiceErrorRetry::OtherNode);
+
+        // only invoice duplicates are fatal, not any message mentioning existence
+        let exists = LnPoolError::from_http_status(
+            500,
+            "channel already exists".to_string(),
+            "creating invoice",
+        );
+        assert_eq!(exists.retry(), ServiceErrorRetry::OtherNode);
+
+        let timeout = LnPoolError::from_timeout(Duration::from_secs(1), "creating invoice");
+        assert_eq!(timeout.retry(), ServiceErrorRetry::OtherNode);
+        assert_eq!(
+            timeout.get_service_error_retry(),
+            ServiceErrorRetry::OtherNode
+        );
+    }
 }
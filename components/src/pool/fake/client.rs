@@ -0,0 +1,531 @@
+use crate::pool::error::LnPoolError;
+use crate::pool::fake::config::{
+    FakeClientAuth, FakeDiscoveryBackendImplementation, FakeFailureMode, FakeNetwork,
+};
+use crate::pool::{
+    Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnInvoiceState, LnMetrics, LnRpcClient,
+};
+use async_trait::async_trait;
+use bitcoin_hashes::{sha256, Hash};
+use lightning_invoice::{Bolt11Invoice, Currency, InvoiceBuilder, PaymentSecret};
+use rand::Rng;
+use secp256k1_0_29::{PublicKey, Secp256k1, SecretKey};
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+use switchgear_service_api::service::ServiceErrorSource;
+
+const MIN_FINAL_CLTV_EXPIRY_DELTA: u64 = 144;
+
+// signs invoices locally with a configured key, so the LNURL flow can run without a node
+pub struct FakeLnClient {
+    timeout: Duration,
+    config: FakeDiscoveryBackendImplementation,
+    currency: Currency,
+    secret_key: SecretKey,
+    public_key: PublicKey,
+    features: Option<LnFeatures>,
+    // hold invoices by payment hash, these are the only invoices whose state is stored
+    holds: Mutex<HashMap<[u8; 32], LnInvoiceState>>,
+}
+
+impl FakeLnClient {
+    pub fn create(
+        timeout: Duration,
+        config: FakeDiscoveryBackendImplementation,
+    ) -> Result<Self, LnPoolError> {
+        if config.error_rate_percent > 100 {
+            return Err(LnPoolError::from_invalid_configuration(
+                format!(
+                    "error rate {}% is greater than 100%",
+                    config.error_rate_percent
+                ),
+                ServiceErrorSource::Internal,
+                "creating fake client".to_string(),
+            ));
+        }
+
+        let secret_key = match &config.auth {
+            FakeClientAuth::Path(auth) => {
+                fs::read_to_string(&auth.secret_key_path).map_err(|e| {
+                    LnPoolError::from_invalid_credentials(
+                        e.to_string(),
+                        ServiceErrorSource::Internal,
+                        format!(
+                            "loading fake node secret key from {}",
+                            auth.secret_key_path.to_string_lossy()
+                        ),
+                    )
+                })?
+            }
+            FakeClientAuth::Inline(auth) => auth.secret_key.clone(),
+        };
+        let secret_key = SecretKey::from_str(secret_key.trim()).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                "parsing fake node secret key".to_string(),
+            )
+        })?;
+        let public_key = secret_key.public_key(&Secp256k1::new());
+
+        let currency = match config.network {
+            FakeNetwork::Bitcoin => Currency::Bitcoin,
+            FakeNetwork::Testnet => Currency::BitcoinTestnet,
+            FakeNetwork::Signet => Currency::Signet,
+            FakeNetwork::Regtest => Currency::Regtest,
+        };
+
+        Ok(Self {
+            timeout,
+            config,
+            currency,
+            secret_key,
+            public_key,
+            features: Some(LnFeatures {
+                invoice_from_desc_hash: true,
+                hold_invoice: true,
+                bolt12: false,
+            }),
+            holds: Default::default(),
+        })
+    }
+
+    // waits out the configured latency, then decides whether this call fails and how
+    async fn simulate(&self) -> Option<FakeFailureMode> {
+        let (latency, failure) = {
+            let mut rng = rand::thread_rng();
+            let jitter = match self.config.latency_jitter_ms {
+                0 => 0,
+                jitter => rng.gen_range(0..=jitter),
+            };
+            let latency = Duration::from_millis(self.config.latency_ms.saturating_add(jitter));
+            let failure = rng.gen_range(0..100) < self.config.error_rate_percent;
+            let failure = failure.then(|| match self.config.failure_modes.len() {
+                0 => FakeFailureMode::Error,
+                n => self.config.failure_modes[rng.gen_range(0..n)],
+            });
+            (latency, failure)
+        };
+
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        failure
+    }
+
+    fn sign_invoice(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription,
+        expiry_secs: Option<u64>,
+        payment_hash: sha256::Hash,
+        context: &'static str,
+    ) -> Result<String, LnPoolError> {
+        let payment_secret = PaymentSecret(rand::thread_rng().gen());
+
+        let builder = InvoiceBuilder::new(self.currency.clone())
+            .payment_hash(payment_hash)
+            .payment_secret(payment_secret)
+            .current_timestamp()
+            .min_final_cltv_expiry_delta(MIN_FINAL_CLTV_EXPIRY_DELTA);
+        let builder = match description {
+            Bolt11InvoiceDescription::Direct(d) => builder.description(d.to_string()),
+            Bolt11InvoiceDescription::DirectIntoHash(d) => {
+                builder.description_hash(sha256::Hash::hash(d.as_bytes()))
+            }
+            Bolt11InvoiceDescription::Hash(h) => {
+                builder.description_hash(sha256::Hash::from_byte_array(*h))
+            }
+        };
+        let builder = match amount_msat {
+            Some(amount_msat) => builder.amount_milli_satoshis(amount_msat),
+            None => builder,
+        };
+        let builder = match expiry_secs {
+            Some(expiry_secs) => builder.expiry_time(Duration::from_secs(expiry_secs)),
+            None => builder,
+        };
+
+        let secp = Secp256k1::new();
+        let invoice = builder
+            .build_signed(|hash| secp.sign_ecdsa_recoverable(hash, &self.secret_key))
+            .map_err(|e| {
+                LnPoolError::from_invoice_error(
+                    e.to_string(),
+                    ServiceErrorSource::Downstream,
+                    context,
+                )
+            })?;
+
+        Ok(invoice.to_string())
+    }
+
+    fn lock_holds(
+        &self,
+        context: &'static str,
+    ) -> Result<MutexGuard<'_, HashMap<[u8; 32], LnInvoiceState>>, LnPoolError> {
+        self.holds
+            .lock()
+            .map_err(|e| LnPoolError::from_memory_error(e.to_string(), context))
+    }
+
+    // like a node, only open holds can be resolved and resolving twice the same way is a no-op
+    fn resolve_hold(
+        &self,
+        payment_hash: &[u8; 32],
+        resolution: LnInvoiceState,
+        context: &'static str,
+    ) -> Result<(), LnPoolError> {
+        let mut holds = self.lock_holds(context)?;
+        let state = holds.get_mut(payment_hash).ok_or_else(|| {
+            LnPoolError::from_invoice_error(
+                "hold invoice not found".to_string(),
+                ServiceErrorSource::Downstream,
+                context,
+            )
+        })?;
+        match *state {
+            LnInvoiceState::Open => {
+                *state = resolution;
+                Ok(())
+            }
+            state if state == resolution => Ok(()),
+            state => Err(LnPoolError::from_invoice_error(
+                format!("hold invoice is already {state:?}"),
+                ServiceErrorSource::Downstream,
+                context,
+            )),
+        }
+    }
+
+    async fn fail(&self, mode: FakeFailureMode, context: &'static str) -> LnPoolError {
+        match mode {
+            FakeFailureMode::Timeout => {
+                tokio::time::sleep(self.timeout).await;
+                LnPoolError::from_timeout(self.timeout, context)
+            }
+            FakeFailureMode::Error | FakeFailureMode::Unhealthy => {
+                LnPoolError::from_injected_failure(context)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LnRpcClient for FakeLnClient {
+    type Error = LnPoolError;
+
+    async fn get_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+    ) -> Result<String, Self::Error> {
+        let context = "fake get invoice, signing invoice";
+        if let Some(mode) = self.simulate().await {
+            return Err(self.fail(mode, context).await);
+        }
+
+        let preimage = rand::thread_rng().gen::<[u8; 32]>();
+        self.sign_invoice(
+            amount_msat,
+            description,
+            expiry_secs,
+            sha256::Hash::hash(&preimage),
+            context,
+        )
+    }
+
+    async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
+        let healthy = match self.simulate().await {
+            None => true,
+            Some(FakeFailureMode::Unhealthy) => false,
+            Some(mode) => return Err(self.fail(mode, "fake get metrics").await),
+        };
+
+        Ok(LnMetrics {
+            healthy,
+            node_effective_inbound_msat: self.config.inbound_msat,
+            node_private_inbound_msat: 0,
+        })
+    }
+
+    async fn get_invoice_state(
+        &self,
+        invoice: &str,
+        _payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        let context = "fake get invoice state, decoding invoice";
+        if let Some(mode) = self.simulate().await {
+            return Err(self.fail(mode, context).await);
+        }
+
+        let invoice = Bolt11Invoice::from_str(invoice).map_err(|e| {
+            LnPoolError::from_invoice_error(e.to_string(), ServiceErrorSource::Downstream, context)
+        })?;
+        if invoice.get_payee_pub_key() != self.public_key {
+            return Err(LnPoolError::from_invoice_error(
+                "invoice was not signed by this node".to_string(),
+                ServiceErrorSource::Downstream,
+                context,
+            ));
+        }
+
+        let age = SystemTime::now()
+            .duration_since(invoice.timestamp())
+            .unwrap_or_default();
+        let expiry = invoice.expiry_time();
+
+        let hold = self
+            .lock_holds(context)?
+            .get(invoice.payment_hash().as_byte_array())
+            .copied();
+        if let Some(state) = hold {
+            return Ok(match state {
+                LnInvoiceState::Open if age >= expiry => LnInvoiceState::Canceled,
+                state => state,
+            });
+        }
+
+        // other invoices are never stored, their state follows from their age alone
+        Ok(
+            match self.config.settle_after_secs.map(Duration::from_secs) {
+                Some(settle_after) if settle_after < expiry && age >= settle_after => {
+                    LnInvoiceState::Settled
+                }
+                _ if age >= expiry => LnInvoiceState::Canceled,
+                _ => LnInvoiceState::Open,
+            },
+        )
+    }
+
+    async fn get_hold_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        let context = "fake get hold invoice, signing invoice";
+        if let Some(mode) = self.simulate().await {
+            return Err(self.fail(mode, context).await);
+        }
+
+        let invoice = self.sign_invoice(
+            amount_msat,
+            description,
+            expiry_secs,
+            sha256::Hash::from_byte_array(*payment_hash),
+            context,
+        )?;
+        self.lock_holds(context)?
+            .insert(*payment_hash, LnInvoiceState::Open);
+        Ok(invoice)
+    }
+
+    async fn settle_hold_invoice(&self, preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        let context = "fake settle hold invoice";
+        if let Some(mode) = self.simulate().await {
+            return Err(self.fail(mode, context).await);
+        }
+
+        let payment_hash = sha256::Hash::hash(preimage).to_byte_array();
+        self.resolve_hold(&payment_hash, LnInvoiceState::Settled, context)
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        let context = "fake cancel hold invoice";
+        if let Some(mode) = self.simulate().await {
+            return Err(self.fail(mode, context).await);
+        }
+
+        self.resolve_hold(payment_hash, LnInvoiceState::Canceled, context)
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        _amount_msat: u64,
+        _description: &str,
+        _absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "Bolt12 offers are not supported by the fake backend",
+            "fake get Bolt12 offer",
+        ))
+    }
+
+    // the fake key signs invoices but isn't tied to the discovery public key
+    async fn get_node_id(&self) -> Result<Option<secp256k1::PublicKey>, Self::Error> {
+        Ok(None)
+    }
+
+    fn get_features(&self) -> Option<&LnFeatures> {
+        self.features.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::fake::config::FakeClientAuthInline;
+    use lightning_invoice::Bolt11InvoiceDescriptionRef;
+
+    const SECRET_KEY: &str = "e126f68f7eafcc8b74f54d269fe206be715000f94dac067d1c04a8ca3b2db734";
+
+    fn config() -> FakeDiscoveryBackendImplementation {
+        FakeDiscoveryBackendImplementation {
+            network: FakeNetwork::Regtest,
+            auth: FakeClientAuth::Inline(FakeClientAuthInline {
+                secret_key: SECRET_KEY.to_string(),
+            }),
+            latency_ms: 0,
+            latency_jitter_ms: 0,
+            error_rate_percent: 0,
+            failure_modes: vec![],
+            inbound_msat: 42_000,
+            settle_after_secs: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn get_invoice_when_hash_description_then_signed_by_configured_key() {
+        let client = FakeLnClient::create(Duration::from_secs(1), config()).unwrap();
+        let hash = [7u8; 32];
+
+        let invoice = client
+            .get_invoice(
+                Some(21_000),
+                Bolt11InvoiceDescription::Hash(&hash),
+                Some(600),
+            )
+            .await
+            .unwrap();
+
+        let invoice = Bolt11Invoice::from_str(&invoice).unwrap();
+        assert_eq!(invoice.currency(), Currency::Regtest);
+        assert_eq!(invoice.amount_milli_satoshis(), Some(21_000));
+        assert_eq!(invoice.expiry_time(), Duration::from_secs(600));
+        assert_eq!(invoice.get_payee_pub_key(), client.public_key);
+        assert!(matches!(
+            invoice.description(),
+            Bolt11InvoiceDescriptionRef::Hash(h) if h.0 == sha256::Hash::from_byte_array(hash)
+        ));
+        assert_eq!(
+            client
+                .get_invoice_state(
+                    &invoice.to_string(),
+                    &invoice.payment_hash().to_byte_array()
+                )
+                .await
+                .unwrap(),
+            LnInvoiceState::Open
+        );
+    }
+
+    #[tokio::test]
+    async fn get_invoice_state_when_settle_after_elapsed_then_settled() {
+        let client = FakeLnClient::create(
+            Duration::from_secs(1),
+            FakeDiscoveryBackendImplementation {
+                settle_after_secs: Some(0),
+                ..config()
+            },
+        )
+        .unwrap();
+
+        let invoice = client
+            .get_invoice(None, Bolt11InvoiceDescription::Direct("hello"), None)
+            .await
+            .unwrap();
+
+        let payment_hash = Bolt11Invoice::from_str(&invoice)
+            .unwrap()
+            .payment_hash()
+            .to_byte_array();
+
+        assert_eq!(
+            client
+                .get_invoice_state(&invoice, &payment_hash)
+                .await
+                .unwrap(),
+            LnInvoiceState::Settled
+        );
+    }
+
+    #[tokio::test]
+    async fn settle_hold_invoice_when_preimage_matches_then_settled_once() {
+        let client = FakeLnClient::create(Duration::from_secs(1), config()).unwrap();
+        let preimage = [3u8; 32];
+        let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
+
+        let invoice = client
+            .get_hold_invoice(
+                Some(21_000),
+                Bolt11InvoiceDescription::Direct("hold"),
+                Some(600),
+                &payment_hash,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            Bolt11Invoice::from_str(&invoice)
+                .unwrap()
+                .payment_hash()
+                .to_byte_array(),
+            payment_hash
+        );
+        assert_eq!(
+            client
+                .get_invoice_state(&invoice, &payment_hash)
+                .await
+                .unwrap(),
+            LnInvoiceState::Open
+        );
+
+        client.settle_hold_invoice(&preimage).await.unwrap();
+        client.settle_hold_invoice(&preimage).await.unwrap();
+        assert_eq!(
+            client
+                .get_invoice_state(&invoice, &payment_hash)
+                .await
+                .unwrap(),
+            LnInvoiceState::Settled
+        );
+        assert!(client.cancel_hold_invoice(&payment_hash).await.is_err());
+        assert!(client.settle_hold_invoice(&[4u8; 32]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn get_metrics_when_always_failing_then_failure_modes_applied() {
+        let client = FakeLnClient::create(
+            Duration::from_millis(10),
+            FakeDiscoveryBackendImplementation {
+                error_rate_percent: 100,
+                failure_modes: vec![FakeFailureMode::Unhealthy],
+                ..config()
+            },
+        )
+        .unwrap();
+        let metrics = client.get_metrics().await.unwrap();
+        assert!(!metrics.healthy);
+        assert_eq!(metrics.node_effective_inbound_msat, 42_000);
+
+        let client = FakeLnClient::create(
+            Duration::from_millis(10),
+            FakeDiscoveryBackendImplementation {
+                error_rate_percent: 100,
+                failure_modes: vec![FakeFailureMode::Timeout],
+                ..config()
+            },
+        )
+        .unwrap();
+        let error = client.get_metrics().await.unwrap_err();
+        assert!(matches!(
+            error.source(),
+            crate::pool::error::LnPoolErrorSourceKind::Timeout(_)
+        ));
+    }
+}
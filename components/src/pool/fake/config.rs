@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FakeDiscoveryBackendImplementation {
+    pub network: FakeNetwork,
+    pub auth: FakeClientAuth,
+    // added to every call, plus a uniformly random 0..=latencyJitterMs
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub latency_jitter_ms: u64,
+    // share of calls, 0 to 100, failing with one of failureModes picked at random, or with an
+    // error when none are given
+    #[serde(default)]
+    pub error_rate_percent: u8,
+    #[serde(default)]
+    pub failure_modes: Vec<FakeFailureMode>,
+    #[serde(default)]
+    pub inbound_msat: u64,
+    // invoices stay open until they expire unless settled after this many seconds
+    pub settle_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FakeNetwork {
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FakeFailureMode {
+    // the call fails immediately with an upstream error
+    Error,
+    // the call hangs for the pool timeout, then fails with a timeout error
+    Timeout,
+    // metrics report the node unhealthy, other calls fail with an upstream error
+    Unhealthy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum FakeClientAuth {
+    Path(FakeClientAuthPath),
+    Inline(FakeClientAuthInline),
+}
+
+// the hex encoded node secret key invoices are signed with
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FakeClientAuthPath {
+    pub secret_key_path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FakeClientAuthInline {
+    pub secret_key: String,
+}
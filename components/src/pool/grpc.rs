@@ -0,0 +1,288 @@
+use crate::pool::error::LnPoolError;
+use crate::pool::LnGrpcChannels;
+use hyper_util::client::legacy::connect::proxy::SocksV5;
+use hyper_util::client::legacy::connect::HttpConnector;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use switchgear_service_api::service::ServiceErrorSource;
+use tonic::body::Body;
+use tonic::codegen::http::{Request, Response};
+use tonic::transport::{Channel, Endpoint, Uri};
+use tower::balance::p2c::Balance;
+use tower::buffer::Buffer;
+use tower::discover::ServiceList;
+use tower::load::{CompleteOnResponse, PendingRequestsDiscover};
+use tower::util::BoxService;
+use tower::{BoxError, Service};
+use url::Url;
+
+type GrpcResponseFuture =
+    Pin<Box<dyn Future<Output = Result<Response<Body>, BoxError>> + Send + 'static>>;
+
+// requests queued for the balancer, as with a single tonic channel
+const GRPC_BUFFER_SIZE: usize = 1024;
+
+// concrete service for the generated clients, naming the buffer directly in their futures trips
+// higher-ranked lifetime errors in the async trait methods
+#[derive(Clone)]
+pub(crate) struct GrpcChannel {
+    inner: Buffer<Request<Body>, GrpcResponseFuture>,
+}
+
+impl Service<Request<Body>> for GrpcChannel {
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = GrpcResponseFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        Box::pin(self.inner.call(request))
+    }
+}
+
+// shared by the gRPC node clients: the endpoint is opened channels.count times behind tower's p2c
+// balancer, connections are made lazily and a failed one is re-established without touching the
+// others
+pub(crate) fn create_balanced_channel(
+    node: &str,
+    url: &str,
+    endpoint: Endpoint,
+    timeout: Duration,
+    channels: &LnGrpcChannels,
+    socks5_proxy: Option<&Url>,
+) -> Result<GrpcChannel, LnPoolError> {
+    if channels.count == 0 {
+        return Err(LnPoolError::from_invalid_configuration(
+            "channel count must be at least 1".to_string(),
+            ServiceErrorSource::Internal,
+            format!("creating {node} channels for {url}"),
+        ));
+    }
+
+    let mut endpoint = endpoint.connect_timeout(timeout).timeout(timeout);
+    if let Some(interval) = channels.keep_alive_interval_secs {
+        endpoint = endpoint
+            .http2_keep_alive_interval(Duration::from_secs(interval))
+            .keep_alive_while_idle(true);
+    }
+    if let Some(keep_alive_timeout) = channels.keep_alive_timeout_secs {
+        endpoint = endpoint.keep_alive_timeout(Duration::from_secs(keep_alive_timeout));
+    }
+
+    let mut services = Vec::with_capacity(channels.count);
+    for _ in 0..channels.count {
+        let channel: Channel = match socks5_proxy {
+            None => endpoint.connect_lazy(),
+            Some(proxy) => {
+                endpoint.connect_with_connector_lazy(socks5_connector(node, url, proxy)?)
+            }
+        };
+        services.push(channel);
+    }
+
+    let discover =
+        PendingRequestsDiscover::new(ServiceList::new(services), CompleteOnResponse::default());
+    let balance = BoxService::new(Balance::new(discover));
+    Ok(GrpcChannel {
+        inner: Buffer::new(balance, GRPC_BUFFER_SIZE),
+    })
+}
+
+// the node's host name is resolved by the proxy, so onion addresses work and TLS still verifies
+// against the name in the endpoint url
+pub(crate) fn socks5_connector(
+    node: &str,
+    url: &str,
+    proxy: &Url,
+) -> Result<SocksV5<HttpConnector>, LnPoolError> {
+    let invalid = |e: String| {
+        LnPoolError::from_invalid_configuration(
+            e,
+            ServiceErrorSource::Internal,
+            format!("creating {node} SOCKS5 proxy connector for {url}"),
+        )
+    };
+
+    if !matches!(proxy.scheme(), "socks5" | "socks5h") {
+        return Err(invalid(format!(
+            "unsupported proxy scheme '{}', expected socks5 or socks5h",
+            proxy.scheme()
+        )));
+    }
+    let host = proxy
+        .host_str()
+        .ok_or_else(|| invalid("proxy url has no host".to_string()))?;
+    let proxy_uri: Uri = format!("socks5://{host}:{}", proxy.port().unwrap_or(1080))
+        .parse()
+        .map_err(|e: tonic::codegen::http::uri::InvalidUri| invalid(e.to_string()))?;
+
+    let mut connector = HttpConnector::new();
+    connector.enforce_http(false);
+    connector.set_nodelay(true);
+
+    let connector = SocksV5::new(proxy_uri, connector).local_dns(false);
+    Ok(match proxy.password() {
+        Some(password) => connector.with_auth(proxy.username().to_string(), password.to_string()),
+        None if !proxy.username().is_empty() => {
+            connector.with_auth(proxy.username().to_string(), String::new())
+        }
+        None => connector,
+    })
+}
+
+// minimal SOCKS5 stand-in for proxy tests: records the requested destination and refuses it
+#[cfg(test)]
+pub(crate) async fn socks5_refusing_proxy(
+) -> (Url, tokio::sync::oneshot::Receiver<(Option<String>, u16)>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = Url::parse(&format!("socks5://{}", listener.local_addr().unwrap())).unwrap();
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut greeting = [0u8; 2];
+        stream.read_exact(&mut greeting).await.unwrap();
+        let mut methods = vec![0u8; greeting[1] as usize];
+        stream.read_exact(&mut methods).await.unwrap();
+        stream.write_all(&[0x05, 0x00]).await.unwrap();
+
+        let mut request = [0u8; 4];
+        stream.read_exact(&mut request).await.unwrap();
+        let destination = match request[3] {
+            0x03 => {
+                let len = stream.read_u8().await.unwrap();
+                let mut domain = vec![0u8; len as usize];
+                stream.read_exact(&mut domain).await.unwrap();
+                Some(String::from_utf8(domain).unwrap())
+            }
+            _ => None,
+        };
+        let port = stream.read_u16().await.unwrap();
+        stream
+            .write_all(&[0x05, 0x01, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+        let _ = tx.send((destination, port));
+    });
+
+    (proxy, rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::cln::grpc::client::cln;
+    use crate::pool::cln::grpc::client::cln::node_client::NodeClient;
+
+    #[test]
+    fn create_balanced_channel_when_no_channels_then_invalid_configuration() {
+        let endpoint = Channel::from_static("http://127.0.0.1:1");
+        let channels = LnGrpcChannels {
+            count: 0,
+            ..Default::default()
+        };
+
+        let error = create_balanced_channel(
+            "CLN",
+            "http://127.0.0.1:1",
+            endpoint,
+            Duration::from_secs(1),
+            &channels,
+            None,
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(error.esource(), ServiceErrorSource::Internal);
+    }
+
+    #[tokio::test]
+    async fn create_balanced_channel_when_node_unreachable_then_every_request_fails() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = format!("http://127.0.0.1:{port}");
+        let channels = LnGrpcChannels {
+            count: 3,
+            keep_alive_interval_secs: Some(30),
+            keep_alive_timeout_secs: Some(10),
+        };
+        let channel = create_balanced_channel(
+            "CLN",
+            &url,
+            Channel::from_shared(url.clone()).unwrap(),
+            Duration::from_secs(1),
+            &channels,
+            None,
+        )
+        .unwrap();
+        let mut client = NodeClient::new(channel);
+
+        // failed connections stay in the balancer and are retried, requests don't queue forever
+        for _ in 0..4 {
+            let result = tokio::time::timeout(
+                Duration::from_secs(5),
+                client.getinfo(cln::GetinfoRequest {}),
+            )
+            .await
+            .unwrap();
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn create_balanced_channel_when_proxy_not_socks5_then_invalid_configuration() {
+        let proxy = Url::parse("http://127.0.0.1:8080").unwrap();
+
+        let error = create_balanced_channel(
+            "LND",
+            "https://node.onion:10009",
+            Channel::from_static("https://node.onion:10009"),
+            Duration::from_secs(1),
+            &LnGrpcChannels::default(),
+            Some(&proxy),
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(error.esource(), ServiceErrorSource::Internal);
+    }
+
+    #[tokio::test]
+    async fn create_balanced_channel_when_socks5_proxy_then_proxy_resolves_host() {
+        let (proxy, rx) = socks5_refusing_proxy().await;
+
+        let url = "http://node.onion:10009";
+        let channel = create_balanced_channel(
+            "CLN",
+            url,
+            Channel::from_static(url),
+            Duration::from_secs(1),
+            &LnGrpcChannels::default(),
+            Some(&proxy),
+        )
+        .unwrap();
+        let mut client = NodeClient::new(channel);
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.getinfo(cln::GetinfoRequest {}),
+        )
+        .await
+        .unwrap();
+        assert!(result.is_err());
+
+        let (destination, port) = rx.await.unwrap();
+        assert_eq!(destination.as_deref(), Some("node.onion"));
+        assert_eq!(port, 10009);
+    }
+}
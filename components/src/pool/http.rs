@@ -0,0 +1,219 @@
+use crate::pool::error::LnPoolError;
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Certificate, Client, ClientBuilder, Proxy, Response};
+use rustls::pki_types::CertificateDer;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use switchgear_service_api::service::ServiceErrorSource;
+use url::Url;
+
+// shared by the HTTP based node clients: default headers carry the node credentials, and an
+// optional node CA certificate is trusted in addition to the native and configured roots
+pub(crate) fn create_http_client(
+    node: &str,
+    base_url: &str,
+    timeout: Duration,
+    headers: HeaderMap,
+    trusted_roots: &[CertificateDer],
+    ca_cert_path: Option<&Path>,
+    socks5_proxy: Option<&Url>,
+) -> Result<Client, LnPoolError> {
+    let mut builder = ClientBuilder::new();
+
+    // socks5h has the proxy resolve the node's host name, which onion addresses require
+    if let Some(socks5_proxy) = socks5_proxy {
+        let invalid = |e: String| {
+            LnPoolError::from_invalid_configuration(
+                e,
+                ServiceErrorSource::Internal,
+                format!("creating {node} SOCKS5 proxy for {base_url}"),
+            )
+        };
+        if !matches!(socks5_proxy.scheme(), "socks5" | "socks5h") {
+            return Err(invalid(format!(
+                "unsupported proxy scheme '{}', expected socks5 or socks5h",
+                socks5_proxy.scheme()
+            )));
+        }
+        let socks5_proxy = socks5_proxy.as_str().replacen("socks5://", "socks5h://", 1);
+        let proxy = Proxy::all(&socks5_proxy).map_err(|e| invalid(e.to_string()))?;
+        builder = builder.proxy(proxy);
+    }
+
+    for root in trusted_roots {
+        let root = Certificate::from_der(root).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!("parsing trusted root certificate for {node} {base_url}"),
+            )
+        })?;
+        builder = builder.add_root_certificate(root);
+    }
+
+    if let Some(ca_cert_path) = ca_cert_path {
+        let ca_certificate = fs::read(ca_cert_path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| Certificate::from_pem(&c).map_err(|e| e.to_string()))
+            .map_err(|e| {
+                LnPoolError::from_invalid_credentials(
+                    e,
+                    ServiceErrorSource::Internal,
+                    format!(
+                        "loading {node} credentials and reading CA certificate from path {}",
+                        ca_cert_path.to_string_lossy()
+                    ),
+                )
+            })?;
+        builder = builder.add_root_certificate(ca_certificate);
+    }
+
+    builder
+        .default_headers(headers)
+        .use_rustls_tls()
+        .timeout(timeout)
+        .connect_timeout(timeout)
+        .build()
+        .map_err(|e| {
+            LnPoolError::from_http_error(
+                e,
+                ServiceErrorSource::Internal,
+                format!("creating {node} HTTP client for {base_url}"),
+            )
+        })
+}
+
+// phoenixd and Eclair use basic auth with an empty user name and the API password
+pub(crate) fn basic_auth_headers(
+    node: &str,
+    base_url: &str,
+    password_path: &Path,
+) -> Result<HeaderMap, LnPoolError> {
+    let password = fs::read_to_string(password_path).map_err(|e| {
+        LnPoolError::from_invalid_credentials(
+            e.to_string(),
+            ServiceErrorSource::Internal,
+            format!(
+                "loading {node} password from {}",
+                password_path.to_string_lossy()
+            ),
+        )
+    })?;
+    let credentials =
+        base64::engine::general_purpose::STANDARD.encode(format!(":{}", password.trim()));
+
+    let mut authorization =
+        HeaderValue::from_str(&format!("Basic {credentials}")).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!("loading {node} password into client for {base_url}"),
+            )
+        })?;
+    authorization.set_sensitive(true);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(reqwest::header::AUTHORIZATION, authorization);
+    Ok(headers)
+}
+
+pub(crate) async fn parse_http_response<T: DeserializeOwned>(
+    response: Response,
+    context: String,
+) -> Result<T, LnPoolError> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(LnPoolError::from_http_status(
+            status.as_u16(),
+            error_message(body),
+            context,
+        ));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| LnPoolError::from_http_error(e, ServiceErrorSource::Upstream, context))
+}
+
+// node APIs report failures as {"message": ..}, {"error": ..} or plain text
+fn error_message(body: String) -> String {
+    serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| {
+            ["message", "error"]
+                .iter()
+                .find_map(|field| v.get(field)?.as_str().map(String::from))
+        })
+        .unwrap_or(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::grpc::socks5_refusing_proxy;
+
+    #[test]
+    fn error_message_when_json_or_text_then_extracted() {
+        assert_eq!(
+            error_message(r#"{"code": 2, "message": "invoice not found"}"#.to_string()),
+            "invoice not found"
+        );
+        assert_eq!(
+            error_message(r#"{"error": "channel not found"}"#.to_string()),
+            "channel not found"
+        );
+        assert_eq!(
+            error_message("Invalid authentication".to_string()),
+            "Invalid authentication"
+        );
+    }
+
+    #[test]
+    fn create_http_client_when_proxy_not_socks5_then_invalid_configuration() {
+        let proxy = Url::parse("http://127.0.0.1:8080").unwrap();
+
+        let error = create_http_client(
+            "phoenixd",
+            "http://node.onion:9740",
+            Duration::from_secs(1),
+            HeaderMap::new(),
+            &[],
+            None,
+            Some(&proxy),
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(error.esource(), ServiceErrorSource::Internal);
+    }
+
+    #[tokio::test]
+    async fn create_http_client_when_socks5_proxy_then_proxy_resolves_host() {
+        let (proxy, rx) = socks5_refusing_proxy().await;
+
+        let client = create_http_client(
+            "Eclair",
+            "http://node.onion:8080",
+            Duration::from_secs(5),
+            HeaderMap::new(),
+            &[],
+            None,
+            Some(&proxy),
+        )
+        .unwrap();
+
+        assert!(client
+            .post("http://node.onion:8080/getinfo")
+            .send()
+            .await
+            .is_err());
+
+        let (destination, port) = rx.await.unwrap();
+        assert_eq!(destination.as_deref(), Some("node.onion"));
+        assert_eq!(port, 8080);
+    }
+}
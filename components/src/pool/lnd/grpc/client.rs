@@ -1,29 +1,43 @@
 use crate::pool::error::LnPoolError;
-use crate::pool::lnd::grpc::config::{LndGrpcClientAuth, LndGrpcDiscoveryBackendImplementation};
-use crate::pool::{Bolt11InvoiceDescription, LnFeatures, LnMetrics, LnRpcClient};
+use crate::pool::grpc::{create_balanced_channel, GrpcChannel};
+use crate::pool::lnd::grpc::config::{
+    LndGrpcClientAuth, LndGrpcClientAuthPath, LndGrpcDiscoveryBackendImplementation,
+};
+use crate::pool::{
+    short_channel_id_from_u64, Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnGrpcChannels,
+    LnInvoiceState, LnMetrics, LnRouteHints, LnRpcClient, HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA,
+};
 use async_trait::async_trait;
 use rustls::pki_types::CertificateDer;
+use secp256k1::PublicKey;
 use sha2::Digest;
 use std::fs;
-use std::sync::Arc;
+use std::str::FromStr;
 use std::time::Duration;
 use switchgear_service_api::service::ServiceErrorSource;
-use tokio::sync::Mutex;
+use tokio::sync::OnceCell;
 use tonic::service::Interceptor;
 use tonic::transport::{Certificate, Channel, ClientTlsConfig};
+use url::Url;
 
 #[allow(clippy::all)]
 pub mod lnrpc {
     tonic::include_proto!("lnrpc");
 }
 
+#[allow(clippy::all)]
+pub mod invoicesrpc {
+    tonic::include_proto!("invoicesrpc");
+}
+
+use invoicesrpc::invoices_client::InvoicesClient;
 use lnrpc::lightning_client::LightningClient;
 
 pub struct TonicLndGrpcClient {
     timeout: Duration,
     config: LndGrpcDiscoveryBackendImplementation,
     features: Option<LnFeatures>,
-    inner: Arc<Mutex<Option<Arc<InnerTonicLndGrpcClient>>>>,
+    inner: OnceCell<InnerTonicLndGrpcClient>,
     ca_certificates: Vec<Certificate>,
     macaroon: String,
 }
@@ -34,8 +48,6 @@ impl TonicLndGrpcClient {
         config: LndGrpcDiscoveryBackendImplementation,
         trusted_roots: &[CertificateDer],
     ) -> Result<Self, LnPoolError> {
-        let LndGrpcClientAuth::Path(auth) = &config.auth;
-
         let mut ca_certificates = trusted_roots
             .iter()
             .map(|c| {
@@ -44,30 +56,16 @@ impl TonicLndGrpcClient {
             })
             .collect::<Vec<_>>();
 
-        if let Some(tls_cert_path) = &auth.tls_cert_path {
-            let ca_certificate = fs::read(tls_cert_path).map_err(|e| {
-                LnPoolError::from_invalid_credentials(
-                    e.to_string(),
-                    ServiceErrorSource::Internal,
-                    format!(
-                        "loading LND credentials and reading CA certificate from path {}",
-                        tls_cert_path.to_string_lossy()
-                    ),
-                )
-            })?;
+        let (ca_certificate, macaroon) = match &config.auth {
+            LndGrpcClientAuth::Path(auth) => Self::read_path_credentials(auth)?,
+            LndGrpcClientAuth::Inline(auth) => (
+                auth.tls_cert.clone().map(String::into_bytes),
+                Self::decode_inline_macaroon(&auth.macaroon)?,
+            ),
+        };
+        if let Some(ca_certificate) = ca_certificate {
             ca_certificates.push(Certificate::from_pem(&ca_certificate));
         }
-
-        let macaroon = fs::read(&auth.macaroon_path).map_err(|e| {
-            LnPoolError::from_invalid_credentials(
-                e.to_string(),
-                ServiceErrorSource::Internal,
-                format!(
-                    "loading LND macaroon from {}",
-                    auth.macaroon_path.to_string_lossy()
-                ),
-            )
-        })?;
         let macaroon = hex::encode(&macaroon);
 
         Ok(Self {
@@ -75,38 +73,81 @@ impl TonicLndGrpcClient {
             config,
             features: Some(LnFeatures {
                 invoice_from_desc_hash: true,
+                hold_invoice: true,
+                bolt12: false,
             }),
-            inner: Arc::new(Default::default()),
+            inner: OnceCell::new(),
             ca_certificates,
             macaroon,
         })
     }
 
-    async fn inner_connect(&self) -> Result<Arc<InnerTonicLndGrpcClient>, LnPoolError> {
-        let mut inner = self.inner.lock().await;
-        match inner.as_ref() {
-            None => {
-                let inner_connect = Arc::new(
-                    InnerTonicLndGrpcClient::connect(
-                        self.timeout,
-                        self.ca_certificates.clone(),
-                        self.macaroon.clone(),
-                        self.config.url.to_string(),
-                        self.config.domain.as_deref(),
-                        self.config.amp_invoice,
+    fn read_path_credentials(
+        auth: &LndGrpcClientAuthPath,
+    ) -> Result<(Option<Vec<u8>>, Vec<u8>), LnPoolError> {
+        let ca_certificate = auth
+            .tls_cert_path
+            .as_ref()
+            .map(|tls_cert_path| {
+                fs::read(tls_cert_path).map_err(|e| {
+                    LnPoolError::from_invalid_credentials(
+                        e.to_string(),
+                        ServiceErrorSource::Internal,
+                        format!(
+                            "loading LND credentials and reading CA certificate from path {}",
+                            tls_cert_path.to_string_lossy()
+                        ),
                     )
-                    .await?,
-                );
-                *inner = Some(inner_connect.clone());
-                Ok(inner_connect)
-            }
-            Some(inner) => Ok(inner.clone()),
-        }
+                })
+            })
+            .transpose()?;
+
+        let macaroon = fs::read(&auth.macaroon_path).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!(
+                    "loading LND macaroon from {}",
+                    auth.macaroon_path.to_string_lossy()
+                ),
+            )
+        })?;
+
+        Ok((ca_certificate, macaroon))
     }
 
-    async fn inner_disconnect(&self) {
-        let mut inner = self.inner.lock().await;
-        *inner = None;
+    // lncli prints macaroons as hex, most node hosting providers hand them out as base64
+    fn decode_inline_macaroon(macaroon: &str) -> Result<Vec<u8>, LnPoolError> {
+        use base64::Engine;
+        let macaroon = macaroon.trim();
+        hex::decode(macaroon)
+            .or_else(|_| base64::engine::general_purpose::STANDARD.decode(macaroon))
+            .map_err(|_| {
+                LnPoolError::from_invalid_credentials(
+                    "macaroon is neither hex nor base64 encoded".to_string(),
+                    ServiceErrorSource::Internal,
+                    "loading inline LND macaroon".to_string(),
+                )
+            })
+    }
+
+    // built on first use, the balanced channel spawns its worker on the runtime
+    async fn inner_connect(&self) -> Result<&InnerTonicLndGrpcClient, LnPoolError> {
+        self.inner
+            .get_or_try_init(|| async {
+                InnerTonicLndGrpcClient::connect(
+                    self.timeout,
+                    self.ca_certificates.clone(),
+                    self.macaroon.clone(),
+                    self.config.url.to_string(),
+                    self.config.domain.as_deref(),
+                    self.config.amp_invoice,
+                    self.config.route_hints.clone(),
+                    &self.config.channels.clone().unwrap_or_default(),
+                    self.config.socks5_proxy.as_ref(),
+                )
+            })
+            .await
     }
 
     fn certificate_der_as_pem(certificate: &CertificateDer) -> String {
@@ -126,27 +167,68 @@ impl LnRpcClient for TonicLndGrpcClient {
         description: Bolt11InvoiceDescription<'a>,
         expiry_secs: Option<u64>,
     ) -> Result<String, Self::Error> {
-        let inner = self.inner_connect().await?;
-
-        let r = inner
+        self.inner_connect()
+            .await?
             .get_invoice(amount_msat, description, expiry_secs)
-            .await;
-
-        if r.is_err() {
-            self.inner_disconnect().await;
-        }
-        r
+            .await
     }
 
     async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
-        let inner = self.inner_connect().await?;
+        self.inner_connect().await?.get_metrics().await
+    }
 
-        let r = inner.get_metrics().await;
+    async fn get_invoice_state(
+        &self,
+        _invoice: &str,
+        payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        self.inner_connect()
+            .await?
+            .get_invoice_state(payment_hash)
+            .await
+    }
 
-        if r.is_err() {
-            self.inner_disconnect().await;
-        }
-        r
+    async fn get_hold_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        self.inner_connect()
+            .await?
+            .get_hold_invoice(amount_msat, description, expiry_secs, payment_hash)
+            .await
+    }
+
+    async fn settle_hold_invoice(&self, preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        self.inner_connect()
+            .await?
+            .settle_hold_invoice(preimage)
+            .await
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        self.inner_connect()
+            .await?
+            .cancel_hold_invoice(payment_hash)
+            .await
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        _amount_msat: u64,
+        _description: &str,
+        _absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "Bolt12 offers are not supported by LND",
+            "LND get Bolt12 offer",
+        ))
+    }
+
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error> {
+        self.inner_connect().await?.get_node_id().await.map(Some)
     }
 
     fn get_features(&self) -> Option<&LnFeatures> {
@@ -156,20 +238,28 @@ impl LnRpcClient for TonicLndGrpcClient {
 
 struct InnerTonicLndGrpcClient {
     client: LightningClient<
-        tonic::service::interceptor::InterceptedService<Channel, MacaroonInterceptor>,
+        tonic::service::interceptor::InterceptedService<GrpcChannel, MacaroonInterceptor>,
+    >,
+    invoices: InvoicesClient<
+        tonic::service::interceptor::InterceptedService<GrpcChannel, MacaroonInterceptor>,
     >,
     url: String,
     amp_invoice: bool,
+    route_hints: Option<LnRouteHints>,
 }
 
 impl InnerTonicLndGrpcClient {
-    async fn connect(
+    #[allow(clippy::too_many_arguments)]
+    fn connect(
         timeout: Duration,
         ca_certificates: Vec<Certificate>,
         macaroon: String,
         url: String,
         domain: Option<&str>,
         amp_invoice: bool,
+        route_hints: Option<LnRouteHints>,
+        channels: &LnGrpcChannels,
+        socks5_proxy: Option<&Url>,
     ) -> Result<Self, LnPoolError> {
         let endpoint = Channel::from_shared(url.clone()).map_err(|e| {
             LnPoolError::from_invalid_configuration(
@@ -195,26 +285,19 @@ impl InnerTonicLndGrpcClient {
             )
         })?;
 
-        let channel = endpoint
-            .connect_timeout(timeout)
-            .timeout(timeout)
-            .connect()
-            .await
-            .map_err(|e| {
-                LnPoolError::from_transport_error(
-                    e,
-                    ServiceErrorSource::Upstream,
-                    format!("connecting LND client to {url}"),
-                )
-            })?;
+        let channel =
+            create_balanced_channel("LND", &url, endpoint, timeout, channels, socks5_proxy)?;
 
         let interceptor = MacaroonInterceptor { macaroon };
 
-        let client = LightningClient::with_interceptor(channel, interceptor);
+        let client = LightningClient::with_interceptor(channel.clone(), interceptor.clone());
+        let invoices = InvoicesClient::with_interceptor(channel, interceptor);
         Ok(Self {
             client,
+            invoices,
             url,
             amp_invoice,
+            route_hints,
         })
     }
 
@@ -226,13 +309,8 @@ impl InnerTonicLndGrpcClient {
     ) -> Result<String, LnPoolError> {
         let mut client = self.client.clone();
 
-        let (memo, description_hash) = match description {
-            Bolt11InvoiceDescription::Direct(d) => (d.to_string(), vec![]),
-            Bolt11InvoiceDescription::DirectIntoHash(d) => {
-                (String::new(), sha2::Sha256::digest(d.as_bytes()).to_vec())
-            }
-            Bolt11InvoiceDescription::Hash(h) => (String::new(), h.to_vec()),
-        };
+        let (memo, description_hash) = Self::memo_and_description_hash(description);
+        let (private, route_hints) = self.route_hints().await?;
 
         let invoice_request = lnrpc::Invoice {
             memo,
@@ -240,6 +318,8 @@ impl InnerTonicLndGrpcClient {
             description_hash,
             expiry: expiry_secs.unwrap_or(3600) as i64,
             is_amp: self.amp_invoice,
+            private,
+            route_hints,
             ..Default::default()
         };
 
@@ -257,6 +337,190 @@ impl InnerTonicLndGrpcClient {
         Ok(response.payment_request)
     }
 
+    async fn get_hold_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<String, LnPoolError> {
+        let mut invoices = self.invoices.clone();
+
+        let (memo, description_hash) = Self::memo_and_description_hash(description);
+        let (private, route_hints) = self.route_hints().await?;
+
+        let invoice_request = invoicesrpc::AddHoldInvoiceRequest {
+            memo,
+            hash: payment_hash.to_vec(),
+            value_msat: amount_msat.unwrap_or(0) as i64,
+            description_hash,
+            expiry: expiry_secs.unwrap_or(3600) as i64,
+            cltv_expiry: HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA,
+            route_hints,
+            private,
+            ..Default::default()
+        };
+
+        let response = invoices
+            .add_hold_invoice(invoice_request)
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(
+                    e,
+                    format!("LND get hold invoice from {}, requesting invoice", self.url),
+                )
+            })?
+            .into_inner();
+
+        Ok(response.payment_request)
+    }
+
+    async fn settle_hold_invoice(&self, preimage: &[u8; 32]) -> Result<(), LnPoolError> {
+        let mut invoices = self.invoices.clone();
+
+        invoices
+            .settle_invoice(invoicesrpc::SettleInvoiceMsg {
+                preimage: preimage.to_vec(),
+            })
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(e, format!("LND settle hold invoice on {}", self.url))
+            })?;
+
+        Ok(())
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<(), LnPoolError> {
+        let mut invoices = self.invoices.clone();
+
+        invoices
+            .cancel_invoice(invoicesrpc::CancelInvoiceMsg {
+                payment_hash: payment_hash.to_vec(),
+            })
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(e, format!("LND cancel hold invoice on {}", self.url))
+            })?;
+
+        Ok(())
+    }
+
+    // LND picks the hints itself when every private channel is advertised, a selection needs the
+    // peer's forwarding policy for each channel
+    async fn route_hints(&self) -> Result<(bool, Vec<lnrpc::RouteHint>), LnPoolError> {
+        let route_hints = match &self.route_hints {
+            None => return Ok((false, vec![])),
+            Some(h) if h.channels.is_empty() && h.peers.is_empty() => return Ok((true, vec![])),
+            Some(h) => h,
+        };
+
+        let mut client = self.client.clone();
+        let channels = self
+            .list_private_channels(true)
+            .await?
+            .into_iter()
+            .filter(|c| {
+                route_hints.includes(
+                    Some(&short_channel_id_from_u64(c.chan_id)),
+                    &c.remote_pubkey,
+                )
+            });
+
+        let mut hints = vec![];
+        for channel in channels {
+            let edge = client
+                .get_chan_info(lnrpc::ChanInfoRequest {
+                    chan_id: channel.chan_id,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| {
+                    LnPoolError::from_tonic_error(
+                        e,
+                        format!(
+                            "LND get route hints from {}, requesting channel {}",
+                            self.url, channel.chan_id
+                        ),
+                    )
+                })?
+                .into_inner();
+
+            let policy = if edge.node1_pub == channel.remote_pubkey {
+                edge.node1_policy
+            } else {
+                edge.node2_policy
+            };
+            let Some(policy) = policy else {
+                continue;
+            };
+
+            hints.push(lnrpc::RouteHint {
+                hop_hints: vec![lnrpc::HopHint {
+                    node_id: channel.remote_pubkey,
+                    chan_id: channel.chan_id,
+                    fee_base_msat: policy.fee_base_msat as u32,
+                    fee_proportional_millionths: policy.fee_rate_milli_msat as u32,
+                    cltv_expiry_delta: policy.time_lock_delta,
+                }],
+            });
+        }
+
+        Ok((false, hints))
+    }
+
+    async fn get_node_id(&self) -> Result<PublicKey, LnPoolError> {
+        let mut client = self.client.clone();
+
+        let response = client
+            .get_info(lnrpc::GetInfoRequest {})
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(e, format!("LND get node id from {}", self.url))
+            })?
+            .into_inner();
+
+        PublicKey::from_str(&response.identity_pubkey).map_err(|e| {
+            LnPoolError::from_invalid_configuration(
+                e.to_string(),
+                ServiceErrorSource::Upstream,
+                format!("LND get node id from {}, parsing identity pubkey", self.url),
+            )
+        })
+    }
+
+    async fn list_private_channels(
+        &self,
+        active_only: bool,
+    ) -> Result<Vec<lnrpc::Channel>, LnPoolError> {
+        let mut client = self.client.clone();
+        let response = client
+            .list_channels(lnrpc::ListChannelsRequest {
+                active_only,
+                private_only: true,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(
+                    e,
+                    format!("LND list private channels for {}", self.url),
+                )
+            })?
+            .into_inner();
+
+        Ok(response.channels)
+    }
+
+    fn memo_and_description_hash(description: Bolt11InvoiceDescription) -> (String, Vec<u8>) {
+        match description {
+            Bolt11InvoiceDescription::Direct(d) => (d.to_string(), vec![]),
+            Bolt11InvoiceDescription::DirectIntoHash(d) => {
+                (String::new(), sha2::Sha256::digest(d.as_bytes()).to_vec())
+            }
+            Bolt11InvoiceDescription::Hash(h) => (String::new(), h.to_vec()),
+        }
+    }
+
     async fn get_metrics(&self) -> Result<LnMetrics, LnPoolError> {
         let mut client = self.client.clone();
 
@@ -272,14 +536,65 @@ impl InnerTonicLndGrpcClient {
             })?
             .into_inner();
 
-        let node_effective_inbound_msat = channels_balance_response
+        let node_inbound_msat = channels_balance_response
             .remote_balance
             .map(|balance| balance.msat)
             .unwrap_or(0);
 
+        let mut node_private_inbound_msat = 0u64;
+        let mut node_hinted_inbound_msat = 0u64;
+        for channel in self.list_private_channels(false).await? {
+            let inbound_msat = channel.remote_balance.max(0) as u64 * 1000;
+            node_private_inbound_msat += inbound_msat;
+            if self.route_hints.as_ref().is_some_and(|h| {
+                h.includes(
+                    Some(&short_channel_id_from_u64(channel.chan_id)),
+                    &channel.remote_pubkey,
+                )
+            }) {
+                node_hinted_inbound_msat += inbound_msat;
+            }
+        }
+
         Ok(LnMetrics {
             healthy: true,
-            node_effective_inbound_msat,
+            node_effective_inbound_msat: node_inbound_msat
+                .saturating_sub(node_private_inbound_msat)
+                + node_hinted_inbound_msat,
+            node_private_inbound_msat,
+        })
+    }
+
+    async fn get_invoice_state(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, LnPoolError> {
+        let mut client = self.client.clone();
+
+        let response = client
+            .lookup_invoice(lnrpc::PaymentHash {
+                r_hash: payment_hash.to_vec(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| {
+                LnPoolError::from_tonic_error(
+                    e,
+                    format!(
+                        "LND get invoice state from {}, looking up invoice",
+                        self.url
+                    ),
+                )
+            })?
+            .into_inner();
+
+        const SETTLED: i32 = 1;
+        const CANCELED: i32 = 2;
+
+        Ok(match response.state {
+            SETTLED => LnInvoiceState::Settled,
+            CANCELED => LnInvoiceState::Canceled,
+            _ => LnInvoiceState::Open,
         })
     }
 }
@@ -299,3 +614,22 @@ impl Interceptor for MacaroonInterceptor {
         Ok(req)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_inline_macaroon_when_hex_or_base64_then_decoded() {
+        let expected = vec![0x02, 0x01, 0x03, 0x6c, 0x6e, 0x64];
+        assert_eq!(
+            TonicLndGrpcClient::decode_inline_macaroon("0201036c6e64\n").unwrap(),
+            expected
+        );
+        assert_eq!(
+            TonicLndGrpcClient::decode_inline_macaroon("AgEDbG5k").unwrap(),
+            expected
+        );
+        assert!(TonicLndGrpcClient::decode_inline_macaroon("not a macaroon").is_err());
+    }
+}
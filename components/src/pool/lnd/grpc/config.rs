@@ -1,3 +1,4 @@
+use crate::pool::{LnGrpcChannels, LnRouteHints};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use url::Url;
@@ -9,6 +10,14 @@ pub struct LndGrpcDiscoveryBackendImplementation {
     pub domain: Option<String>,
     pub auth: LndGrpcClientAuth,
     pub amp_invoice: bool,
+    #[serde(default)]
+    pub route_hints: Option<LnRouteHints>,
+    // a single channel without keepalive when unset
+    #[serde(default)]
+    pub channels: Option<LnGrpcChannels>,
+    // socks5:// or socks5h:// proxy url, host names are always resolved by the proxy
+    #[serde(default)]
+    pub socks5_proxy: Option<Url>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -16,6 +25,7 @@ pub struct LndGrpcDiscoveryBackendImplementation {
 #[serde(tag = "type")]
 pub enum LndGrpcClientAuth {
     Path(LndGrpcClientAuthPath),
+    Inline(LndGrpcClientAuthInline),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -24,3 +34,11 @@ pub struct LndGrpcClientAuthPath {
     pub tls_cert_path: Option<PathBuf>,
     pub macaroon_path: PathBuf,
 }
+
+// the TLS certificate is PEM encoded, the macaroon hex or base64 encoded
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LndGrpcClientAuthInline {
+    pub tls_cert: Option<String>,
+    pub macaroon: String,
+}
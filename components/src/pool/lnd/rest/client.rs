@@ -0,0 +1,591 @@
+use crate::pool::error::LnPoolError;
+use crate::pool::http::{create_http_client, parse_http_response};
+use crate::pool::lnd::rest::config::{LndRestClientAuth, LndRestDiscoveryBackendImplementation};
+use crate::pool::{
+    short_channel_id_from_u64, Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnInvoiceState,
+    LnMetrics, LnRouteHints, LnRpcClient, HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA,
+};
+use async_trait::async_trait;
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Client;
+use rustls::pki_types::CertificateDer;
+use secp256k1::PublicKey;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
+use switchgear_service_api::service::ServiceErrorSource;
+use url::Url;
+
+const MACAROON_HEADER: &str = "Grpc-Metadata-macaroon";
+
+pub struct ReqwestLndRestClient {
+    client: Client,
+    base_url: String,
+    amp_invoice: bool,
+    route_hints: Option<LnRouteHints>,
+    features: Option<LnFeatures>,
+}
+
+impl ReqwestLndRestClient {
+    pub fn create(
+        timeout: Duration,
+        config: LndRestDiscoveryBackendImplementation,
+        trusted_roots: &[CertificateDer],
+        socks5_proxy: Option<&Url>,
+    ) -> Result<Self, LnPoolError> {
+        let LndRestClientAuth::Path(auth) = &config.auth;
+        let base_url = config.url.as_str().trim_end_matches('/').to_string();
+
+        let macaroon = fs::read(&auth.macaroon_path).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!(
+                    "loading LND macaroon from {}",
+                    auth.macaroon_path.to_string_lossy()
+                ),
+            )
+        })?;
+
+        let mut headers = HeaderMap::new();
+        let mut macaroon = HeaderValue::from_str(&hex::encode(&macaroon)).map_err(|e| {
+            LnPoolError::from_invalid_credentials(
+                e.to_string(),
+                ServiceErrorSource::Internal,
+                format!("loading LND macaroon into client for {base_url}"),
+            )
+        })?;
+        macaroon.set_sensitive(true);
+        headers.insert(MACAROON_HEADER, macaroon);
+
+        let client = create_http_client(
+            "LND",
+            &base_url,
+            timeout,
+            headers,
+            trusted_roots,
+            auth.tls_cert_path.as_deref(),
+            socks5_proxy,
+        )?;
+
+        Ok(Self {
+            client,
+            base_url,
+            amp_invoice: config.amp_invoice,
+            route_hints: config.route_hints,
+            features: Some(LnFeatures {
+                invoice_from_desc_hash: true,
+                hold_invoice: true,
+                bolt12: false,
+            }),
+        })
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        context: String,
+    ) -> Result<T, LnPoolError> {
+        let url = format!("{}{path}", self.base_url);
+        let response = self.client.get(&url).send().await.map_err(|e| {
+            LnPoolError::from_http_error(e, ServiceErrorSource::Upstream, context.clone())
+        })?;
+        parse_http_response(response, context).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+        context: String,
+    ) -> Result<T, LnPoolError> {
+        let url = format!("{}{path}", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| {
+                LnPoolError::from_http_error(e, ServiceErrorSource::Upstream, context.clone())
+            })?;
+        parse_http_response(response, context).await
+    }
+
+    // LND picks the hints itself when every private channel is advertised, a selection needs the
+    // peer's forwarding policy for each channel
+    async fn route_hints(&self) -> Result<(bool, Vec<LndRestRouteHint>), LnPoolError> {
+        let route_hints = match &self.route_hints {
+            None => return Ok((false, vec![])),
+            Some(h) if h.channels.is_empty() && h.peers.is_empty() => return Ok((true, vec![])),
+            Some(h) => h,
+        };
+
+        let mut hints = vec![];
+        for channel in self.list_private_channels(true).await? {
+            let chan_id = self.parse_u64(&channel.chan_id, "channel id")?;
+            if !route_hints.includes(
+                Some(&short_channel_id_from_u64(chan_id)),
+                &channel.remote_pubkey,
+            ) {
+                continue;
+            }
+
+            let edge: LndRestChannelEdge = self
+                .get(
+                    &format!("/v1/graph/edge/{chan_id}"),
+                    format!(
+                        "LND get route hints from {}, requesting channel {chan_id}",
+                        self.base_url
+                    ),
+                )
+                .await?;
+
+            let policy = if edge.node1_pub == channel.remote_pubkey {
+                edge.node1_policy
+            } else {
+                edge.node2_policy
+            };
+            let Some(policy) = policy else {
+                continue;
+            };
+
+            hints.push(LndRestRouteHint {
+                hop_hints: vec![LndRestHopHint {
+                    node_id: channel.remote_pubkey,
+                    chan_id: channel.chan_id,
+                    fee_base_msat: self.parse_u64(&policy.fee_base_msat, "base fee")? as u32,
+                    fee_proportional_millionths: self
+                        .parse_u64(&policy.fee_rate_milli_msat, "fee rate")?
+                        as u32,
+                    cltv_expiry_delta: policy.time_lock_delta,
+                }],
+            });
+        }
+
+        Ok((false, hints))
+    }
+
+    async fn list_private_channels(
+        &self,
+        active_only: bool,
+    ) -> Result<Vec<LndRestChannel>, LnPoolError> {
+        let response: LndRestListChannelsResponse = self
+            .get(
+                &format!("/v1/channels?private_only=true&active_only={active_only}"),
+                format!("LND list private channels for {}", self.base_url),
+            )
+            .await?;
+        Ok(response.channels)
+    }
+
+    fn parse_u64(&self, value: &str, field: &str) -> Result<u64, LnPoolError> {
+        value.parse::<u64>().map_err(|e| {
+            LnPoolError::from_invalid_configuration(
+                e.to_string(),
+                ServiceErrorSource::Upstream,
+                format!("LND parsing {field} from {}", self.base_url),
+            )
+        })
+    }
+
+    fn memo_and_description_hash(
+        description: Bolt11InvoiceDescription,
+    ) -> (String, Option<String>) {
+        let (memo, description_hash) = match description {
+            Bolt11InvoiceDescription::Direct(d) => (d.to_string(), None),
+            Bolt11InvoiceDescription::DirectIntoHash(d) => (
+                String::new(),
+                Some(sha2::Sha256::digest(d.as_bytes()).to_vec()),
+            ),
+            Bolt11InvoiceDescription::Hash(h) => (String::new(), Some(h.to_vec())),
+        };
+        (
+            memo,
+            description_hash.map(|h| base64::engine::general_purpose::STANDARD.encode(h)),
+        )
+    }
+}
+
+#[async_trait]
+impl LnRpcClient for ReqwestLndRestClient {
+    type Error = LnPoolError;
+
+    async fn get_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+    ) -> Result<String, Self::Error> {
+        let (memo, description_hash) = Self::memo_and_description_hash(description);
+        let (private, route_hints) = self.route_hints().await?;
+
+        let request = LndRestInvoiceRequest {
+            memo,
+            value_msat: amount_msat.unwrap_or(0),
+            description_hash,
+            expiry: expiry_secs.unwrap_or(3600),
+            is_amp: self.amp_invoice,
+            private,
+            route_hints,
+        };
+
+        let response: LndRestInvoiceResponse = self
+            .post(
+                "/v1/invoices",
+                &request,
+                format!("LND get invoice from {}, requesting invoice", self.base_url),
+            )
+            .await?;
+        Ok(response.payment_request)
+    }
+
+    async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
+        let response: LndRestChannelBalanceResponse = self
+            .get(
+                "/v1/balance/channels",
+                format!("LND get metrics for {}, requesting channels", self.base_url),
+            )
+            .await?;
+
+        let node_inbound_msat = response
+            .remote_balance
+            .map(|balance| balance.msat.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                LnPoolError::from_invalid_configuration(
+                    e.to_string(),
+                    ServiceErrorSource::Upstream,
+                    format!(
+                        "LND get metrics for {}, parsing remote balance",
+                        self.base_url
+                    ),
+                )
+            })?
+            .unwrap_or(0);
+
+        let mut node_private_inbound_msat = 0u64;
+        let mut node_hinted_inbound_msat = 0u64;
+        for channel in self.list_private_channels(false).await? {
+            let inbound_msat = self.parse_u64(&channel.remote_balance, "remote balance")? * 1000;
+            node_private_inbound_msat += inbound_msat;
+            let chan_id = self.parse_u64(&channel.chan_id, "channel id")?;
+            if self.route_hints.as_ref().is_some_and(|h| {
+                h.includes(
+                    Some(&short_channel_id_from_u64(chan_id)),
+                    &channel.remote_pubkey,
+                )
+            }) {
+                node_hinted_inbound_msat += inbound_msat;
+            }
+        }
+
+        Ok(LnMetrics {
+            healthy: true,
+            node_effective_inbound_msat: node_inbound_msat
+                .saturating_sub(node_private_inbound_msat)
+                + node_hinted_inbound_msat,
+            node_private_inbound_msat,
+        })
+    }
+
+    async fn get_invoice_state(
+        &self,
+        _invoice: &str,
+        payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        let response: LndRestLookupInvoiceResponse = self
+            .get(
+                &format!("/v1/invoice/{}", hex::encode(payment_hash)),
+                format!(
+                    "LND get invoice state from {}, looking up invoice",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        Ok(match response.state.as_str() {
+            "SETTLED" => LnInvoiceState::Settled,
+            "CANCELED" => LnInvoiceState::Canceled,
+            _ => LnInvoiceState::Open,
+        })
+    }
+
+    async fn get_hold_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        let (memo, description_hash) = Self::memo_and_description_hash(description);
+        let (private, route_hints) = self.route_hints().await?;
+
+        let request = LndRestHoldInvoiceRequest {
+            memo,
+            hash: base64::engine::general_purpose::STANDARD.encode(payment_hash),
+            value_msat: amount_msat.unwrap_or(0),
+            description_hash,
+            expiry: expiry_secs.unwrap_or(3600),
+            cltv_expiry: HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA,
+            private,
+            route_hints,
+        };
+
+        let response: LndRestInvoiceResponse = self
+            .post(
+                "/v2/invoices/hodl",
+                &request,
+                format!(
+                    "LND get hold invoice from {}, requesting invoice",
+                    self.base_url
+                ),
+            )
+            .await?;
+        Ok(response.payment_request)
+    }
+
+    async fn settle_hold_invoice(&self, preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        let request = LndRestSettleInvoiceRequest {
+            preimage: base64::engine::general_purpose::STANDARD.encode(preimage),
+        };
+
+        let _: LndRestEmptyResponse = self
+            .post(
+                "/v2/invoices/settle",
+                &request,
+                format!("LND settle hold invoice on {}", self.base_url),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        let request = LndRestCancelInvoiceRequest {
+            payment_hash: base64::engine::general_purpose::STANDARD.encode(payment_hash),
+        };
+
+        let _: LndRestEmptyResponse = self
+            .post(
+                "/v2/invoices/cancel",
+                &request,
+                format!("LND cancel hold invoice on {}", self.base_url),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        _amount_msat: u64,
+        _description: &str,
+        _absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "Bolt12 offers are not supported by LND",
+            "LND get Bolt12 offer",
+        ))
+    }
+
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error> {
+        let response: LndRestGetInfoResponse = self
+            .get(
+                "/v1/getinfo",
+                format!("LND get node id from {}", self.base_url),
+            )
+            .await?;
+
+        PublicKey::from_str(&response.identity_pubkey)
+            .map(Some)
+            .map_err(|e| {
+                LnPoolError::from_invalid_configuration(
+                    e.to_string(),
+                    ServiceErrorSource::Upstream,
+                    format!(
+                        "LND get node id from {}, parsing identity pubkey",
+                        self.base_url
+                    ),
+                )
+            })
+    }
+
+    fn get_features(&self) -> Option<&LnFeatures> {
+        self.features.as_ref()
+    }
+}
+
+// the REST gateway encodes bytes as base64 and 64 bit integers as strings
+
+#[derive(Serialize)]
+struct LndRestInvoiceRequest {
+    memo: String,
+    value_msat: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description_hash: Option<String>,
+    expiry: u64,
+    is_amp: bool,
+    private: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    route_hints: Vec<LndRestRouteHint>,
+}
+
+#[derive(Serialize)]
+struct LndRestHoldInvoiceRequest {
+    memo: String,
+    hash: String,
+    value_msat: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description_hash: Option<String>,
+    expiry: u64,
+    cltv_expiry: u64,
+    private: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    route_hints: Vec<LndRestRouteHint>,
+}
+
+#[derive(Serialize)]
+struct LndRestRouteHint {
+    hop_hints: Vec<LndRestHopHint>,
+}
+
+#[derive(Serialize)]
+struct LndRestHopHint {
+    node_id: String,
+    chan_id: String,
+    fee_base_msat: u32,
+    fee_proportional_millionths: u32,
+    cltv_expiry_delta: u32,
+}
+
+#[derive(Serialize)]
+struct LndRestSettleInvoiceRequest {
+    preimage: String,
+}
+
+#[derive(Serialize)]
+struct LndRestCancelInvoiceRequest {
+    payment_hash: String,
+}
+
+#[derive(Deserialize)]
+struct LndRestEmptyResponse {}
+
+#[derive(Deserialize)]
+struct LndRestInvoiceResponse {
+    payment_request: String,
+}
+
+#[derive(Deserialize)]
+struct LndRestGetInfoResponse {
+    identity_pubkey: String,
+}
+
+#[derive(Deserialize)]
+struct LndRestChannelBalanceResponse {
+    remote_balance: Option<LndRestAmount>,
+}
+
+#[derive(Deserialize)]
+struct LndRestAmount {
+    #[serde(default)]
+    msat: String,
+}
+
+#[derive(Deserialize)]
+struct LndRestListChannelsResponse {
+    #[serde(default)]
+    channels: Vec<LndRestChannel>,
+}
+
+#[derive(Deserialize)]
+struct LndRestChannel {
+    remote_pubkey: String,
+    chan_id: String,
+    #[serde(default = "zero")]
+    remote_balance: String,
+}
+
+#[derive(Deserialize)]
+struct LndRestChannelEdge {
+    node1_pub: String,
+    node1_policy: Option<LndRestRoutingPolicy>,
+    node2_policy: Option<LndRestRoutingPolicy>,
+}
+
+#[derive(Deserialize)]
+struct LndRestRoutingPolicy {
+    #[serde(default)]
+    time_lock_delta: u32,
+    #[serde(default = "zero")]
+    fee_base_msat: String,
+    #[serde(default = "zero")]
+    fee_rate_milli_msat: String,
+}
+
+// the REST gateway omits zero values
+fn zero() -> String {
+    "0".to_string()
+}
+
+#[derive(Deserialize)]
+struct LndRestLookupInvoiceResponse {
+    #[serde(default)]
+    state: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invoice_request_when_hash_description_then_base64_encoded() {
+        let request = LndRestInvoiceRequest {
+            memo: String::new(),
+            value_msat: 1000,
+            description_hash: Some(base64::engine::general_purpose::STANDARD.encode([1u8; 32])),
+            expiry: 3600,
+            is_amp: true,
+            private: false,
+            route_hints: vec![],
+        };
+
+        let request = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(
+            request,
+            serde_json::json!({
+                "memo": "",
+                "value_msat": 1000,
+                "description_hash": "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=",
+                "expiry": 3600,
+                "is_amp": true,
+                "private": false,
+            })
+        );
+    }
+
+    #[test]
+    fn channel_balance_response_when_amounts_are_strings_then_parsed() {
+        let response: LndRestChannelBalanceResponse = serde_json::from_str(
+            r#"{"balance":"0","remote_balance":{"sat":"1500","msat":"1500000"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.remote_balance.unwrap().msat, "1500000");
+    }
+
+    #[test]
+    fn list_channels_response_when_balance_omitted_then_zero() {
+        let response: LndRestListChannelsResponse = serde_json::from_str(
+            r#"{"channels":[{"remote_pubkey":"02aa","chan_id":"879609302286337","private":true}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.channels[0].chan_id, "879609302286337");
+        assert_eq!(response.channels[0].remote_balance, "0");
+    }
+}
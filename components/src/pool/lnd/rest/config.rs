@@ -0,0 +1,28 @@
+use crate::pool::LnRouteHints;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LndRestDiscoveryBackendImplementation {
+    pub url: Url,
+    pub auth: LndRestClientAuth,
+    pub amp_invoice: bool,
+    #[serde(default)]
+    pub route_hints: Option<LnRouteHints>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum LndRestClientAuth {
+    Path(LndRestClientAuthPath),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LndRestClientAuthPath {
+    pub tls_cert_path: Option<PathBuf>,
+    pub macaroon_path: PathBuf,
+}
@@ -1,12 +1,30 @@
 mod client_pool;
 pub mod cln;
+pub mod eclair;
 pub mod error;
+pub mod fake;
+mod grpc;
+mod http;
 pub mod lnd;
+pub mod nwc;
+pub mod phoenixd;
 
-use crate::pool::cln::grpc::config::ClnGrpcDiscoveryBackendImplementation;
-use crate::pool::lnd::grpc::config::LndGrpcDiscoveryBackendImplementation;
+use crate::pool::cln::grpc::config::{ClnGrpcClientAuth, ClnGrpcDiscoveryBackendImplementation};
+use crate::pool::cln::rest::config::{ClnRestClientAuth, ClnRestDiscoveryBackendImplementation};
+use crate::pool::eclair::http::config::{
+    EclairHttpClientAuth, EclairHttpDiscoveryBackendImplementation,
+};
+use crate::pool::fake::config::{FakeClientAuth, FakeDiscoveryBackendImplementation};
+use crate::pool::lnd::grpc::config::{LndGrpcClientAuth, LndGrpcDiscoveryBackendImplementation};
+use crate::pool::lnd::rest::config::{LndRestClientAuth, LndRestDiscoveryBackendImplementation};
+use crate::pool::nwc::config::{NwcClientAuth, NwcDiscoveryBackendImplementation};
+use crate::pool::phoenixd::http::config::{
+    PhoenixdHttpClientAuth, PhoenixdHttpDiscoveryBackendImplementation,
+};
 use async_trait::async_trait;
+use secp256k1::PublicKey;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 pub use client_pool::LnClientPool;
 
@@ -15,7 +33,65 @@ pub use client_pool::LnClientPool;
 #[serde(tag = "type")]
 pub enum DiscoveryBackendImplementation {
     ClnGrpc(ClnGrpcDiscoveryBackendImplementation),
+    ClnRest(ClnRestDiscoveryBackendImplementation),
     LndGrpc(LndGrpcDiscoveryBackendImplementation),
+    LndRest(LndRestDiscoveryBackendImplementation),
+    PhoenixdHttp(PhoenixdHttpDiscoveryBackendImplementation),
+    EclairHttp(EclairHttpDiscoveryBackendImplementation),
+    Nwc(NwcDiscoveryBackendImplementation),
+    Fake(FakeDiscoveryBackendImplementation),
+}
+
+impl DiscoveryBackendImplementation {
+    // files read when the client is created, inline credentials have none
+    pub fn credential_paths(&self) -> Vec<&Path> {
+        let (ca_cert_path, paths): (Option<&Path>, Vec<&Path>) = match self {
+            DiscoveryBackendImplementation::ClnGrpc(i) => match &i.auth {
+                ClnGrpcClientAuth::Path(a) => (
+                    a.ca_cert_path.as_deref(),
+                    vec![a.client_cert_path.as_path(), a.client_key_path.as_path()],
+                ),
+                ClnGrpcClientAuth::Inline(_) => (None, vec![]),
+            },
+            DiscoveryBackendImplementation::ClnRest(i) => match &i.auth {
+                ClnRestClientAuth::Path(a) => {
+                    (a.ca_cert_path.as_deref(), vec![a.rune_path.as_path()])
+                }
+            },
+            DiscoveryBackendImplementation::LndGrpc(i) => match &i.auth {
+                LndGrpcClientAuth::Path(a) => {
+                    (a.tls_cert_path.as_deref(), vec![a.macaroon_path.as_path()])
+                }
+                LndGrpcClientAuth::Inline(_) => (None, vec![]),
+            },
+            DiscoveryBackendImplementation::LndRest(i) => match &i.auth {
+                LndRestClientAuth::Path(a) => {
+                    (a.tls_cert_path.as_deref(), vec![a.macaroon_path.as_path()])
+                }
+            },
+            DiscoveryBackendImplementation::PhoenixdHttp(i) => match &i.auth {
+                PhoenixdHttpClientAuth::Path(a) => {
+                    (a.ca_cert_path.as_deref(), vec![a.password_path.as_path()])
+                }
+            },
+            DiscoveryBackendImplementation::EclairHttp(i) => match &i.auth {
+                EclairHttpClientAuth::Path(a) => {
+                    (a.ca_cert_path.as_deref(), vec![a.password_path.as_path()])
+                }
+            },
+            DiscoveryBackendImplementation::Nwc(i) => match &i.auth {
+                NwcClientAuth::Path(a) => (
+                    a.ca_cert_path.as_deref(),
+                    vec![a.connection_uri_path.as_path()],
+                ),
+            },
+            DiscoveryBackendImplementation::Fake(i) => match &i.auth {
+                FakeClientAuth::Path(a) => (None, vec![a.secret_key_path.as_path()]),
+                FakeClientAuth::Inline(_) => (None, vec![]),
+            },
+        };
+        ca_cert_path.into_iter().chain(paths).collect()
+    }
 }
 
 #[async_trait]
@@ -31,9 +107,46 @@ pub trait LnRpcClient {
 
     async fn get_metrics(&self) -> Result<LnMetrics, Self::Error>;
 
+    // the payment hash is the one decoded when the invoice was issued, nodes that look invoices up
+    // by hash don't need to decode the invoice again
+    async fn get_invoice_state(
+        &self,
+        invoice: &str,
+        payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error>;
+
+    // the node accepts payment of a hold invoice without settling it, the preimage is kept by the
+    // caller and only handed over on settle
+    async fn get_hold_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error>;
+
+    async fn settle_hold_invoice(&self, preimage: &[u8; 32]) -> Result<(), Self::Error>;
+
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<(), Self::Error>;
+
+    // the node returns the existing offer when one was already created with the same parameters
+    async fn get_bolt12_offer(
+        &self,
+        amount_msat: u64,
+        description: &str,
+        absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error>;
+
+    // None when the backend doesn't report the identity of its node
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error>;
+
     fn get_features(&self) -> Option<&LnFeatures>;
 }
 
+// final CLTV delta requested for hold invoices, held payments must be settled or canceled well
+// before this many blocks pass
+pub const HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA: u64 = 144;
+
 #[derive(Eq, PartialEq, Debug, Clone, Ord, PartialOrd)]
 pub enum Bolt11InvoiceDescription<'a> {
     Direct(&'a str),
@@ -44,10 +157,106 @@ pub enum Bolt11InvoiceDescription<'a> {
 #[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct LnFeatures {
     pub invoice_from_desc_hash: bool,
+    pub hold_invoice: bool,
+    pub bolt12: bool,
 }
 
+#[derive(Eq, PartialEq, Debug, Clone, Ord, PartialOrd)]
+pub struct LnBolt12Offer {
+    pub offer_id: String,
+    pub bolt12: String,
+}
+
+// inbound on private channels only counts towards the effective inbound when those channels are
+// advertised as route hints
 #[derive(Eq, PartialEq, Debug, Clone, Ord, PartialOrd)]
 pub struct LnMetrics {
     pub healthy: bool,
     pub node_effective_inbound_msat: u64,
+    pub node_private_inbound_msat: u64,
+}
+
+// private channels advertised as route hints on generated invoices, every private channel is
+// advertised when no channel or peer is selected
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnRouteHints {
+    // short channel ids formatted as BLOCKxTXxOUTPUT
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub peers: Vec<PublicKey>,
+}
+
+impl LnRouteHints {
+    pub fn includes(&self, short_channel_id: Option<&str>, peer: &str) -> bool {
+        if self.channels.is_empty() && self.peers.is_empty() {
+            return true;
+        }
+        short_channel_id.is_some_and(|id| self.channels.iter().any(|c| c == id))
+            || self.peers.iter().any(|p| p.to_string() == peer)
+    }
+}
+
+// HTTP/2 connections opened to a gRPC node, requests are balanced across them and each
+// connection reconnects on its own
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnGrpcChannels {
+    pub count: usize,
+    #[serde(default)]
+    pub keep_alive_interval_secs: Option<u64>,
+    #[serde(default)]
+    pub keep_alive_timeout_secs: Option<u64>,
+}
+
+impl Default for LnGrpcChannels {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            keep_alive_interval_secs: None,
+            keep_alive_timeout_secs: None,
+        }
+    }
+}
+
+// LND packs short channel ids as block height << 40 | tx index << 16 | output index
+pub(crate) fn short_channel_id_from_u64(id: u64) -> String {
+    format!("{}x{}x{}", id >> 40, (id >> 16) & 0xff_ffff, id & 0xffff)
+}
+
+#[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum LnInvoiceState {
+    Open,
+    Settled,
+    Canceled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_hints_includes_when_selected_then_matches_channel_or_peer() {
+        let peer: PublicKey = "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619"
+            .parse()
+            .unwrap();
+
+        let all = LnRouteHints::default();
+        assert!(all.includes(None, "03aa"));
+
+        let selected = LnRouteHints {
+            channels: vec!["800000x12x1".to_string()],
+            peers: vec![peer],
+        };
+        assert!(selected.includes(Some("800000x12x1"), "03aa"));
+        assert!(selected.includes(None, &peer.to_string()));
+        assert!(!selected.includes(Some("800000x12x0"), "03aa"));
+    }
+
+    #[test]
+    fn short_channel_id_when_lnd_channel_id_then_formatted() {
+        let id = (800_000u64 << 40) | (12 << 16) | 1;
+        assert_eq!(short_channel_id_from_u64(id), "800000x12x1");
+    }
 }
@@ -0,0 +1,592 @@
+use crate::pool::error::LnPoolError;
+use crate::pool::grpc::socks5_connector;
+use crate::pool::nwc::config::{
+    NwcClientAuth, NwcConnectionUri, NwcDiscoveryBackendImplementation,
+};
+use crate::pool::nwc::event::{
+    nip04_decrypt, nip04_encrypt, NostrEvent, NWC_REQUEST_KIND, NWC_RESPONSE_KIND,
+};
+use crate::pool::{
+    Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnInvoiceState, LnMetrics, LnRpcClient,
+};
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use hex::ToHex;
+use hyper_util::client::legacy::connect::proxy::SocksV5;
+use hyper_util::client::legacy::connect::HttpConnector;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::CertificateDer;
+use rustls::{ClientConfig, RootCertStore};
+use secp256k1::PublicKey;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Digest;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use switchgear_service_api::service::ServiceErrorSource;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{tungstenite, Connector};
+use tonic::transport::Uri;
+use tower::ServiceExt;
+use url::Url;
+
+type PendingResponses =
+    Arc<std::sync::Mutex<HashMap<String, oneshot::Sender<Result<NostrEvent, String>>>>>;
+
+pub struct TungsteniteNwcClient {
+    timeout: Duration,
+    uri: NwcConnectionUri,
+    connector: Connector,
+    socks5: Option<SocksV5<HttpConnector>>,
+    features: Option<LnFeatures>,
+    inner: Arc<Mutex<Option<Arc<InnerNwcConnection>>>>,
+}
+
+impl TungsteniteNwcClient {
+    pub fn create(
+        timeout: Duration,
+        config: NwcDiscoveryBackendImplementation,
+        trusted_roots: &[CertificateDer],
+        socks5_proxy: Option<&Url>,
+    ) -> Result<Self, LnPoolError> {
+        let NwcClientAuth::Path(auth) = &config.auth;
+
+        let uri = fs::read_to_string(&auth.connection_uri_path)
+            .map_err(|e| e.to_string())
+            .and_then(|uri| uri.parse::<NwcConnectionUri>())
+            .map_err(|e| {
+                LnPoolError::from_invalid_credentials(
+                    e,
+                    ServiceErrorSource::Internal,
+                    format!(
+                        "loading NWC connection URI from {}",
+                        auth.connection_uri_path.to_string_lossy()
+                    ),
+                )
+            })?;
+
+        let mut roots = RootCertStore::empty();
+        roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+        roots.add_parsable_certificates(trusted_roots.iter().map(|c| c.clone().into_owned()));
+
+        if let Some(ca_cert_path) = &auth.ca_cert_path {
+            let ca_certificates = fs::read(ca_cert_path)
+                .map_err(|e| e.to_string())
+                .and_then(|c| {
+                    CertificateDer::pem_slice_iter(&c)
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|e| {
+                    LnPoolError::from_invalid_credentials(
+                        e,
+                        ServiceErrorSource::Internal,
+                        format!(
+                            "loading NWC credentials and reading CA certificate from path {}",
+                            ca_cert_path.to_string_lossy()
+                        ),
+                    )
+                })?;
+            roots.add_parsable_certificates(ca_certificates);
+        }
+
+        let provider = CryptoProvider::get_default().ok_or_else(|| {
+            LnPoolError::from_invalid_configuration(
+                "no default crypto provider installed".to_string(),
+                ServiceErrorSource::Internal,
+                format!("creating NWC TLS configuration for {}", uri.relay),
+            )
+        })?;
+        let tls_config = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| {
+                LnPoolError::from_invalid_configuration(
+                    e.to_string(),
+                    ServiceErrorSource::Internal,
+                    format!("creating NWC TLS configuration for {}", uri.relay),
+                )
+            })?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let socks5 = socks5_proxy
+            .map(|proxy| socks5_connector("NWC", uri.relay.as_str(), proxy))
+            .transpose()?;
+
+        Ok(Self {
+            timeout,
+            uri,
+            connector: Connector::Rustls(Arc::new(tls_config)),
+            socks5,
+            features: Some(LnFeatures {
+                invoice_from_desc_hash: true,
+                hold_invoice: false,
+                bolt12: false,
+            }),
+            inner: Arc::new(Default::default()),
+        })
+    }
+
+    async fn inner_connect(&self) -> Result<Arc<InnerNwcConnection>, LnPoolError> {
+        let mut inner = self.inner.lock().await;
+        match inner.as_ref() {
+            Some(inner) if !inner.is_closed() => Ok(inner.clone()),
+            _ => {
+                let inner_connect = Arc::new(
+                    InnerNwcConnection::connect(
+                        &self.uri,
+                        self.connector.clone(),
+                        self.socks5.clone(),
+                    )
+                    .await?,
+                );
+                *inner = Some(inner_connect.clone());
+                Ok(inner_connect)
+            }
+        }
+    }
+
+    async fn inner_disconnect(&self) {
+        let mut inner = self.inner.lock().await;
+        *inner = None;
+    }
+
+    // one NIP-47 round trip: the request event is published to the relay, and the response is
+    // the wallet event whose e tag references the request event id
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+        context: String,
+    ) -> Result<T, LnPoolError> {
+        let deadline = Instant::now() + self.timeout;
+
+        let inner = tokio::time::timeout_at(deadline, self.inner_connect())
+            .await
+            .map_err(|_| LnPoolError::from_timeout(self.timeout, context.clone()))??;
+
+        let content = nip04_encrypt(
+            &self.uri.keypair.secret_key(),
+            &self.uri.wallet_pubkey,
+            &json!({"method": method, "params": params}).to_string(),
+        );
+        let request = NostrEvent::new(
+            &self.uri.keypair,
+            NWC_REQUEST_KIND,
+            vec![vec!["p".to_string(), self.uri.wallet_pubkey.to_string()]],
+            content,
+        );
+        let request_id = request.id.clone();
+
+        let receiver = match inner.send(request) {
+            Ok(receiver) => receiver,
+            Err(e) => {
+                self.inner_disconnect().await;
+                return Err(LnPoolError::from_relay_error(e, context));
+            }
+        };
+
+        let response = match tokio::time::timeout_at(deadline, receiver).await {
+            Ok(Ok(Ok(response))) => response,
+            Ok(Ok(Err(message))) => return Err(LnPoolError::from_relay_error(message, context)),
+            Ok(Err(_)) => {
+                self.inner_disconnect().await;
+                return Err(LnPoolError::from_relay_error(
+                    "connection closed".to_string(),
+                    context,
+                ));
+            }
+            Err(_) => {
+                inner.forget(&request_id);
+                return Err(LnPoolError::from_timeout(self.timeout, context));
+            }
+        };
+
+        let content = nip04_decrypt(
+            &self.uri.keypair.secret_key(),
+            &self.uri.wallet_pubkey,
+            &response.content,
+        )
+        .map_err(|e| {
+            LnPoolError::from_invalid_credentials(e, ServiceErrorSource::Upstream, context.clone())
+        })?;
+        let response: NwcResponse = serde_json::from_str(&content)
+            .map_err(|e| LnPoolError::from_json_error(e, context.clone()))?;
+
+        if let Some(error) = response.error {
+            return Err(LnPoolError::from_nwc_error(
+                error.code,
+                error.message,
+                context,
+            ));
+        }
+        let result = response.result.ok_or_else(|| {
+            LnPoolError::from_nwc_error(
+                "OTHER".to_string(),
+                format!("missing {method} result"),
+                context.clone(),
+            )
+        })?;
+        serde_json::from_value(result).map_err(|e| LnPoolError::from_json_error(e, context))
+    }
+}
+
+#[async_trait]
+impl LnRpcClient for TungsteniteNwcClient {
+    type Error = LnPoolError;
+
+    async fn get_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+    ) -> Result<String, Self::Error> {
+        let context = format!(
+            "NWC get invoice from {}, requesting invoice",
+            self.uri.relay
+        );
+
+        // make_invoice has no amountless form
+        let amount_msat = amount_msat.ok_or_else(|| {
+            LnPoolError::from_invalid_configuration(
+                "NWC invoices require an amount".to_string(),
+                ServiceErrorSource::Downstream,
+                context.clone(),
+            )
+        })?;
+
+        let mut params = match description {
+            Bolt11InvoiceDescription::Direct(d) => json!({"description": d}),
+            Bolt11InvoiceDescription::DirectIntoHash(d) => json!({
+                "description_hash": sha2::Sha256::digest(d.as_bytes()).encode_hex::<String>(),
+            }),
+            Bolt11InvoiceDescription::Hash(h) => {
+                json!({"description_hash": h.encode_hex::<String>()})
+            }
+        };
+        params["amount"] = json!(amount_msat);
+        if let Some(expiry_secs) = expiry_secs {
+            params["expiry"] = json!(expiry_secs);
+        }
+
+        let transaction: NwcTransaction = self.request("make_invoice", params, context).await?;
+
+        transaction.invoice.ok_or_else(|| {
+            LnPoolError::from_nwc_error(
+                "OTHER".to_string(),
+                "make_invoice result has no invoice".to_string(),
+                format!(
+                    "NWC get invoice from {}, requesting invoice",
+                    self.uri.relay
+                ),
+            )
+        })
+    }
+
+    async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
+        // NWC exposes no inbound liquidity, the wallet balance stands in for it
+        let balance: NwcBalance = self
+            .request(
+                "get_balance",
+                json!({}),
+                format!("NWC get metrics for {}, requesting balance", self.uri.relay),
+            )
+            .await?;
+
+        Ok(LnMetrics {
+            healthy: true,
+            node_effective_inbound_msat: balance.balance,
+            node_private_inbound_msat: 0,
+        })
+    }
+
+    async fn get_invoice_state(
+        &self,
+        invoice: &str,
+        _payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        let transaction: NwcTransaction = self
+            .request(
+                "lookup_invoice",
+                json!({"invoice": invoice}),
+                format!(
+                    "NWC get invoice state from {}, looking up invoice",
+                    self.uri.relay
+                ),
+            )
+            .await?;
+
+        Ok(match transaction.state.as_deref() {
+            Some("settled") => LnInvoiceState::Settled,
+            Some("expired") | Some("failed") => LnInvoiceState::Canceled,
+            // state is optional in NIP-47, settled_at is not
+            _ if transaction.settled_at.is_some() => LnInvoiceState::Settled,
+            _ => LnInvoiceState::Open,
+        })
+    }
+
+    async fn get_hold_invoice<'a>(
+        &self,
+        _amount_msat: Option<u64>,
+        _description: Bolt11InvoiceDescription<'a>,
+        _expiry_secs: Option<u64>,
+        _payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by NWC",
+            "NWC get hold invoice",
+        ))
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by NWC",
+            "NWC settle hold invoice",
+        ))
+    }
+
+    async fn cancel_hold_invoice(&self, _payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by NWC",
+            "NWC cancel hold invoice",
+        ))
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        _amount_msat: u64,
+        _description: &str,
+        _absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "Bolt12 offers are not supported by NWC",
+            "NWC get Bolt12 offer",
+        ))
+    }
+
+    // identity is only checked for CLN and LND
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error> {
+        Ok(None)
+    }
+
+    fn get_features(&self) -> Option<&LnFeatures> {
+        self.features.as_ref()
+    }
+}
+
+// a relay connection with a writer task draining queued messages into the socket and a reader
+// task routing wallet responses to the pending requests
+struct InnerNwcConnection {
+    sender: mpsc::UnboundedSender<Message>,
+    pending: PendingResponses,
+    writer: JoinHandle<()>,
+    reader: JoinHandle<()>,
+}
+
+impl InnerNwcConnection {
+    async fn connect(
+        uri: &NwcConnectionUri,
+        connector: Connector,
+        socks5: Option<SocksV5<HttpConnector>>,
+    ) -> Result<Self, LnPoolError> {
+        let proxy_error = |e: String| tungstenite::Error::Io(io::Error::other(e));
+        let connected = match socks5 {
+            // the relay's host name is resolved by the proxy, TLS is still verified against it
+            Some(socks5) => {
+                async {
+                    let dst = format!(
+                        "https://{}:{}",
+                        uri.relay.host_str().unwrap_or_default(),
+                        uri.relay.port_or_known_default().unwrap_or(443)
+                    )
+                    .parse::<Uri>()
+                    .map_err(|e| proxy_error(e.to_string()))?;
+                    let tcp = socks5
+                        .oneshot(dst)
+                        .await
+                        .map_err(|e| proxy_error(e.to_string()))?
+                        .into_inner();
+                    tokio_tungstenite::client_async_tls_with_config(
+                        uri.relay.as_str(),
+                        tcp,
+                        None,
+                        Some(connector),
+                    )
+                    .await
+                }
+                .await
+            }
+            None => {
+                tokio_tungstenite::connect_async_tls_with_config(
+                    uri.relay.as_str(),
+                    None,
+                    false,
+                    Some(connector),
+                )
+                .await
+            }
+        };
+        let (stream, _) = connected.map_err(|e| {
+            LnPoolError::from_websocket_error(
+                e,
+                ServiceErrorSource::Upstream,
+                format!("connecting to NWC relay {}", uri.relay),
+            )
+        })?;
+        let (mut sink, mut stream) = stream.split();
+
+        let wallet_pubkey = uri.wallet_pubkey.to_string();
+        let client_pubkey = uri.keypair.x_only_public_key().0.to_string();
+
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Message>();
+        let subscription = json!([
+            "REQ",
+            "switchgear",
+            {"kinds": [NWC_RESPONSE_KIND], "authors": [wallet_pubkey], "#p": [client_pubkey]}
+        ]);
+        let _ = sender.send(Message::text(subscription.to_string()));
+
+        let writer = tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let pending = PendingResponses::default();
+        let reader = {
+            let pending = pending.clone();
+            tokio::spawn(async move {
+                while let Some(Ok(message)) = stream.next().await {
+                    if let Message::Text(text) = message {
+                        Self::dispatch(&pending, &wallet_pubkey, text.as_str());
+                    }
+                }
+                // dropping the senders fails every request still waiting on this connection
+                if let Ok(mut pending) = pending.lock() {
+                    pending.clear();
+                }
+            })
+        };
+
+        Ok(Self {
+            sender,
+            pending,
+            writer,
+            reader,
+        })
+    }
+
+    fn is_closed(&self) -> bool {
+        self.reader.is_finished() || self.writer.is_finished()
+    }
+
+    fn send(
+        &self,
+        event: NostrEvent,
+    ) -> Result<oneshot::Receiver<Result<NostrEvent, String>>, String> {
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(event.id.clone(), sender);
+
+        let message = json!(["EVENT", event]).to_string();
+        if self.sender.send(Message::text(message)).is_err() {
+            self.forget(&event.id);
+            return Err("connection closed".to_string());
+        }
+        Ok(receiver)
+    }
+
+    fn forget(&self, request_id: &str) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(request_id);
+        }
+    }
+
+    fn dispatch(pending: &PendingResponses, wallet_pubkey: &str, text: &str) {
+        let Ok(message) = serde_json::from_str::<Vec<Value>>(text) else {
+            return;
+        };
+
+        let (request_id, response) = match message.first().and_then(Value::as_str) {
+            Some("EVENT") => {
+                let Some(event) = message.get(2).and_then(|e| NostrEvent::deserialize(e).ok())
+                else {
+                    return;
+                };
+                if event.kind != NWC_RESPONSE_KIND
+                    || event.pubkey != wallet_pubkey
+                    || !event.verify()
+                {
+                    return;
+                }
+                let Some(request_id) = event.tag("e").map(String::from) else {
+                    return;
+                };
+                (request_id, Ok(event))
+            }
+            // relays acknowledge every published event, only rejections are of interest
+            Some("OK") if message.get(2).and_then(Value::as_bool) == Some(false) => {
+                let Some(request_id) = message.get(1).and_then(Value::as_str) else {
+                    return;
+                };
+                let reason = message
+                    .get(3)
+                    .and_then(Value::as_str)
+                    .unwrap_or("event rejected");
+                (request_id.to_string(), Err(reason.to_string()))
+            }
+            _ => return,
+        };
+
+        let sender = pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&request_id));
+        if let Some(sender) = sender {
+            let _ = sender.send(response);
+        }
+    }
+}
+
+impl Drop for InnerNwcConnection {
+    fn drop(&mut self) {
+        self.writer.abort();
+        self.reader.abort();
+    }
+}
+
+#[derive(Deserialize)]
+struct NwcResponse {
+    error: Option<NwcResponseError>,
+    result: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct NwcResponseError {
+    code: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct NwcTransaction {
+    invoice: Option<String>,
+    state: Option<String>,
+    settled_at: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct NwcBalance {
+    balance: u64,
+}
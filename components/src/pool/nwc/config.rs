@@ -0,0 +1,112 @@
+use secp256k1::{Keypair, Secp256k1, SecretKey, XOnlyPublicKey};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NwcDiscoveryBackendImplementation {
+    pub auth: NwcClientAuth,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "type")]
+pub enum NwcClientAuth {
+    Path(NwcClientAuthPath),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NwcClientAuthPath {
+    pub ca_cert_path: Option<PathBuf>,
+    pub connection_uri_path: PathBuf,
+}
+
+// nostr+walletconnect://<wallet pubkey>?relay=<relay url>&secret=<client secret key>
+#[derive(Debug, Clone)]
+pub struct NwcConnectionUri {
+    pub wallet_pubkey: XOnlyPublicKey,
+    pub relay: Url,
+    pub keypair: Keypair,
+}
+
+impl FromStr for NwcConnectionUri {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let uri = Url::parse(s.trim()).map_err(|e| e.to_string())?;
+        if uri.scheme() != "nostr+walletconnect" {
+            return Err(format!("unsupported scheme: {}", uri.scheme()));
+        }
+
+        let wallet_pubkey = uri
+            .host_str()
+            .ok_or_else(|| "missing wallet public key".to_string())?;
+        let wallet_pubkey =
+            XOnlyPublicKey::from_str(wallet_pubkey).map_err(|e| format!("wallet pubkey: {e}"))?;
+
+        let mut relay = None;
+        let mut secret = None;
+        for (key, value) in uri.query_pairs() {
+            match key.as_ref() {
+                // additional relays are ignored, the first one is used
+                "relay" if relay.is_none() => {
+                    relay = Some(Url::parse(&value).map_err(|e| format!("relay: {e}"))?)
+                }
+                "secret" => {
+                    secret = Some(SecretKey::from_str(&value).map_err(|e| format!("secret: {e}"))?)
+                }
+                _ => {}
+            }
+        }
+
+        let relay = relay.ok_or_else(|| "missing relay".to_string())?;
+        let secret = secret.ok_or_else(|| "missing secret".to_string())?;
+
+        Ok(Self {
+            wallet_pubkey,
+            relay,
+            keypair: Keypair::from_secret_key(&Secp256k1::new(), &secret),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "b889ff5b1513b641e2a139f661a661364979c5beee91842f8f0ef42ab558e9d4";
+    const SECRET: &str = "71a8c14c1407c113601079c4302dab36460f0ccd0ad506f1f2dc73b5100e4f3c";
+
+    #[test]
+    fn parse_when_valid_uri_then_parsed() {
+        let uri: NwcConnectionUri = format!(
+            "nostr+walletconnect://{WALLET}?relay=wss%3A%2F%2Frelay.example.com%2Fv1&secret={SECRET}&lud16=a%40example.com\n"
+        )
+        .parse()
+        .unwrap();
+
+        assert_eq!(uri.wallet_pubkey.to_string(), WALLET);
+        assert_eq!(uri.relay.as_str(), "wss://relay.example.com/v1");
+        assert_eq!(hex::encode(uri.keypair.secret_bytes()), SECRET);
+    }
+
+    #[test]
+    fn parse_when_invalid_uri_then_error() {
+        assert!(format!("nostr+walletconnect://{WALLET}?secret={SECRET}")
+            .parse::<NwcConnectionUri>()
+            .is_err());
+        assert!(
+            format!("nostr+walletconnect://{WALLET}?relay=wss://relay.example.com")
+                .parse::<NwcConnectionUri>()
+                .is_err()
+        );
+        assert!(
+            format!("https://{WALLET}?relay=wss://relay.example.com&secret={SECRET}")
+                .parse::<NwcConnectionUri>()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use base64::Engine;
+use secp256k1::schnorr::Signature;
+use secp256k1::{ecdh, Keypair, Parity, PublicKey, Secp256k1, SecretKey, XOnlyPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const NWC_REQUEST_KIND: u16 = 23194;
+pub const NWC_RESPONSE_KIND: u16 = 23195;
+
+// a signed NIP-01 event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NostrEvent {
+    pub id: String,
+    pub pubkey: String,
+    pub created_at: u64,
+    pub kind: u16,
+    pub tags: Vec<Vec<String>>,
+    pub content: String,
+    pub sig: String,
+}
+
+impl NostrEvent {
+    pub fn new(keypair: &Keypair, kind: u16, tags: Vec<Vec<String>>, content: String) -> Self {
+        let secp = Secp256k1::new();
+        let pubkey = hex::encode(keypair.x_only_public_key().0.serialize());
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let id = Self::compute_id(&pubkey, created_at, kind, &tags, &content);
+        let sig = secp.sign_schnorr_with_aux_rand(&id, keypair, &rand::random());
+
+        Self {
+            id: hex::encode(id),
+            pubkey,
+            created_at,
+            kind,
+            tags,
+            content,
+            sig: hex::encode(sig.to_byte_array()),
+        }
+    }
+
+    pub fn verify(&self) -> bool {
+        let id = Self::compute_id(
+            &self.pubkey,
+            self.created_at,
+            self.kind,
+            &self.tags,
+            &self.content,
+        );
+        if hex::encode(id) != self.id {
+            return false;
+        }
+
+        let Some(pubkey) = hex::decode(&self.pubkey)
+            .ok()
+            .and_then(|p| XOnlyPublicKey::from_byte_array(p.try_into().ok()?).ok())
+        else {
+            return false;
+        };
+        let Some(sig) = hex::decode(&self.sig)
+            .ok()
+            .and_then(|s| Some(Signature::from_byte_array(s.try_into().ok()?)))
+        else {
+            return false;
+        };
+        Secp256k1::verification_only()
+            .verify_schnorr(&sig, &id, &pubkey)
+            .is_ok()
+    }
+
+    // the first value of the first tag with the given name
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|t| t.first().map(String::as_str) == Some(name))
+            .and_then(|t| t.get(1))
+            .map(String::as_str)
+    }
+
+    fn compute_id(
+        pubkey: &str,
+        created_at: u64,
+        kind: u16,
+        tags: &[Vec<String>],
+        content: &str,
+    ) -> [u8; 32] {
+        let serialized = serde_json::json!([0, pubkey, created_at, kind, tags, content]);
+        sha2::Sha256::digest(serialized.to_string().as_bytes()).into()
+    }
+}
+
+type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+
+// NIP-04 shared secret: the unhashed x coordinate of the ECDH point
+fn nip04_shared_secret(secret_key: &SecretKey, pubkey: &XOnlyPublicKey) -> [u8; 32] {
+    let pubkey = PublicKey::from_x_only_public_key(*pubkey, Parity::Even);
+    let point = ecdh::shared_secret_point(&pubkey, secret_key);
+    let mut shared = [0u8; 32];
+    shared.copy_from_slice(&point[..32]);
+    shared
+}
+
+pub fn nip04_encrypt(secret_key: &SecretKey, pubkey: &XOnlyPublicKey, plaintext: &str) -> String {
+    let key = nip04_shared_secret(secret_key, pubkey);
+    let iv: [u8; 16] = rand::random();
+    let ciphertext = Aes256CbcEnc::new(&key.into(), &iv.into())
+        .encrypt_padded_vec_mut::<Pkcs7>(plaintext.as_bytes());
+    let engine = base64::engine::general_purpose::STANDARD;
+    format!("{}?iv={}", engine.encode(ciphertext), engine.encode(iv))
+}
+
+pub fn nip04_decrypt(
+    secret_key: &SecretKey,
+    pubkey: &XOnlyPublicKey,
+    content: &str,
+) -> Result<String, String> {
+    let (ciphertext, iv) = content
+        .split_once("?iv=")
+        .ok_or_else(|| "missing iv".to_string())?;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let ciphertext = engine.decode(ciphertext).map_err(|e| e.to_string())?;
+    let iv: [u8; 16] = engine
+        .decode(iv)
+        .map_err(|e| e.to_string())?
+        .try_into()
+        .map_err(|_| "invalid iv length".to_string())?;
+
+    let key = nip04_shared_secret(secret_key, pubkey);
+    let plaintext = Aes256CbcDec::new(&key.into(), &iv.into())
+        .decrypt_padded_vec_mut::<Pkcs7>(&ciphertext)
+        .map_err(|e| e.to_string())?;
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair(byte: u8) -> Keypair {
+        Keypair::from_seckey_byte_array(&Secp256k1::new(), [byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn nip04_when_encrypted_then_counterparty_decrypts() {
+        let client = keypair(1);
+        let wallet = keypair(2);
+
+        let content = nip04_encrypt(
+            &client.secret_key(),
+            &wallet.x_only_public_key().0,
+            r#"{"method":"get_balance","params":{}}"#,
+        );
+        let plaintext = nip04_decrypt(
+            &wallet.secret_key(),
+            &client.x_only_public_key().0,
+            &content,
+        )
+        .unwrap();
+
+        assert_eq!(plaintext, r#"{"method":"get_balance","params":{}}"#);
+    }
+
+    #[test]
+    fn verify_when_signed_then_valid_and_tamper_detected() {
+        let event = NostrEvent::new(
+            &keypair(1),
+            NWC_REQUEST_KIND,
+            vec![vec!["p".to_string(), "ab".repeat(32)]],
+            "content".to_string(),
+        );
+        assert!(event.verify());
+        assert_eq!(event.tag("p"), Some("ab".repeat(32).as_str()));
+
+        let mut tampered = event.clone();
+        tampered.content = "other".to_string();
+        assert!(!tampered.verify());
+    }
+}
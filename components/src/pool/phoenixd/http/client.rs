@@ -0,0 +1,247 @@
+use crate::pool::error::LnPoolError;
+use crate::pool::http::{basic_auth_headers, create_http_client, parse_http_response};
+use crate::pool::phoenixd::http::config::{
+    PhoenixdHttpClientAuth, PhoenixdHttpDiscoveryBackendImplementation,
+};
+use crate::pool::{
+    Bolt11InvoiceDescription, LnBolt12Offer, LnFeatures, LnInvoiceState, LnMetrics, LnRpcClient,
+};
+use async_trait::async_trait;
+use hex::ToHex;
+use reqwest::{Client, RequestBuilder};
+use rustls::pki_types::CertificateDer;
+use secp256k1::PublicKey;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::Digest;
+use std::time::Duration;
+use switchgear_service_api::service::ServiceErrorSource;
+use url::Url;
+
+pub struct ReqwestPhoenixdHttpClient {
+    client: Client,
+    base_url: String,
+    features: Option<LnFeatures>,
+}
+
+impl ReqwestPhoenixdHttpClient {
+    pub fn create(
+        timeout: Duration,
+        config: PhoenixdHttpDiscoveryBackendImplementation,
+        trusted_roots: &[CertificateDer],
+        socks5_proxy: Option<&Url>,
+    ) -> Result<Self, LnPoolError> {
+        let PhoenixdHttpClientAuth::Path(auth) = &config.auth;
+        let base_url = config.url.as_str().trim_end_matches('/').to_string();
+
+        let headers = basic_auth_headers("phoenixd", &base_url, &auth.password_path)?;
+        let client = create_http_client(
+            "phoenixd",
+            &base_url,
+            timeout,
+            headers,
+            trusted_roots,
+            auth.ca_cert_path.as_deref(),
+            socks5_proxy,
+        )?;
+
+        Ok(Self {
+            client,
+            base_url,
+            features: Some(LnFeatures {
+                invoice_from_desc_hash: true,
+                hold_invoice: false,
+                bolt12: false,
+            }),
+        })
+    }
+
+    // phoenixd only accepts whole satoshi amounts, and the invoice must match the requested
+    // amount exactly. other implementations can serve the amount, so the request moves on to
+    // another node instead of failing
+    pub fn amount_sat(amount_msat: u64, context: String) -> Result<u64, LnPoolError> {
+        if !amount_msat.is_multiple_of(1000) {
+            return Err(LnPoolError::from_unsupported(
+                format!("amount {amount_msat} msat is not a whole satoshi amount"),
+                context,
+            ));
+        }
+        Ok(amount_msat / 1000)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        context: String,
+    ) -> Result<T, LnPoolError> {
+        let response = request.send().await.map_err(|e| {
+            LnPoolError::from_http_error(e, ServiceErrorSource::Upstream, context.clone())
+        })?;
+        parse_http_response(response, context).await
+    }
+}
+
+#[async_trait]
+impl LnRpcClient for ReqwestPhoenixdHttpClient {
+    type Error = LnPoolError;
+
+    async fn get_invoice<'a>(
+        &self,
+        amount_msat: Option<u64>,
+        description: Bolt11InvoiceDescription<'a>,
+        expiry_secs: Option<u64>,
+    ) -> Result<String, Self::Error> {
+        let context = format!(
+            "phoenixd get invoice from {}, requesting invoice",
+            self.base_url
+        );
+
+        let mut form = match description {
+            Bolt11InvoiceDescription::Direct(d) => vec![("description", d.to_string())],
+            Bolt11InvoiceDescription::DirectIntoHash(d) => vec![(
+                "descriptionHash",
+                sha2::Sha256::digest(d.as_bytes()).encode_hex(),
+            )],
+            Bolt11InvoiceDescription::Hash(h) => vec![("descriptionHash", h.encode_hex())],
+        };
+
+        if let Some(amount_msat) = amount_msat {
+            form.push((
+                "amountSat",
+                Self::amount_sat(amount_msat, context.clone())?.to_string(),
+            ));
+        }
+        if let Some(expiry_secs) = expiry_secs {
+            form.push(("expirySeconds", expiry_secs.to_string()));
+        }
+
+        let response: PhoenixdInvoiceResponse = self
+            .send(
+                self.client
+                    .post(format!("{}/createinvoice", self.base_url))
+                    .form(&form),
+                context,
+            )
+            .await?;
+
+        Ok(response.serialized)
+    }
+
+    async fn get_metrics(&self) -> Result<LnMetrics, Self::Error> {
+        let channels: Vec<PhoenixdChannel> = self
+            .send(
+                self.client.get(format!("{}/listchannels", self.base_url)),
+                format!(
+                    "phoenixd get metrics for {}, requesting channels",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        let node_effective_inbound_msat = channels
+            .iter()
+            .filter(|channel| channel.state == "Normal")
+            .map(|channel| channel.inbound_liquidity_sat.saturating_mul(1000))
+            .sum();
+
+        Ok(LnMetrics {
+            healthy: true,
+            node_effective_inbound_msat,
+            node_private_inbound_msat: 0,
+        })
+    }
+
+    async fn get_invoice_state(
+        &self,
+        _invoice: &str,
+        payment_hash: &[u8; 32],
+    ) -> Result<LnInvoiceState, Self::Error> {
+        let payment: PhoenixdIncomingPayment = self
+            .send(
+                self.client.get(format!(
+                    "{}/payments/incoming/{}",
+                    self.base_url,
+                    hex::encode(payment_hash)
+                )),
+                format!(
+                    "phoenixd get invoice state from {}, looking up payment",
+                    self.base_url
+                ),
+            )
+            .await?;
+
+        Ok(if payment.is_paid {
+            LnInvoiceState::Settled
+        } else {
+            LnInvoiceState::Open
+        })
+    }
+
+    async fn get_hold_invoice<'a>(
+        &self,
+        _amount_msat: Option<u64>,
+        _description: Bolt11InvoiceDescription<'a>,
+        _expiry_secs: Option<u64>,
+        _payment_hash: &[u8; 32],
+    ) -> Result<String, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by Phoenixd",
+            "Phoenixd get hold invoice",
+        ))
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by Phoenixd",
+            "Phoenixd settle hold invoice",
+        ))
+    }
+
+    async fn cancel_hold_invoice(&self, _payment_hash: &[u8; 32]) -> Result<(), Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "hold invoices are not supported by Phoenixd",
+            "Phoenixd cancel hold invoice",
+        ))
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        _amount_msat: u64,
+        _description: &str,
+        _absolute_expiry: Option<u64>,
+    ) -> Result<LnBolt12Offer, Self::Error> {
+        Err(LnPoolError::from_unsupported(
+            "Bolt12 offers are not supported by phoenixd",
+            "phoenixd get Bolt12 offer",
+        ))
+    }
+
+    // identity is only checked for CLN and LND
+    async fn get_node_id(&self) -> Result<Option<PublicKey>, Self::Error> {
+        Ok(None)
+    }
+
+    fn get_features(&self) -> Option<&LnFeatures> {
+        self.features.as_ref()
+    }
+}
+
+#[derive(Deserialize)]
+struct PhoenixdInvoiceResponse {
+    serialized: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhoenixdChannel {
+    state: String,
+    #[serde(default)]
+    inbound_liquidity_sat: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhoenixdIncomingPayment {
+    #[serde(default)]
+    is_paid: bool,
+}
@@ -24,6 +24,7 @@ pub fn gen_backends() -> (DiscoveryBackend, DiscoveryBackend, DiscoveryBackend)
             partitions: ["default".to_string()].into(),
             weight: 100,
             enabled: true,
+            region: None,
             implementation: "{}".as_bytes().to_vec(),
         },
     };
@@ -35,6 +36,7 @@ pub fn gen_backends() -> (DiscoveryBackend, DiscoveryBackend, DiscoveryBackend)
             partitions: ["default".to_string()].into(),
             weight: 200,
             enabled: true,
+            region: Some("us-east".to_string()),
             implementation: "{}".as_bytes().to_vec(),
         },
     };
@@ -54,6 +56,7 @@ pub fn gen_backends() -> (DiscoveryBackend, DiscoveryBackend, DiscoveryBackend)
             partitions: ["default".to_string()].into(),
             weight: 10,
             enabled: false,
+            region: Some("eu-central".to_string()),
             implementation: "{}".as_bytes().to_vec(),
         },
     };
@@ -224,6 +227,7 @@ where
             partitions: None,
             weight: Some(modified_backend2.backend.weight),
             enabled: Some(modified_backend2.backend.enabled),
+            region: Some(modified_backend2.backend.region.clone()),
         },
     };
     let patched = store.patch(backend_patch).await.unwrap();
@@ -264,6 +268,7 @@ where
             partitions: None,
             weight: Some(modified_backend2.backend.weight),
             enabled: Some(modified_backend2.backend.enabled),
+            region: None,
         },
     };
     let patched = store.patch(backend_patch).await.unwrap();
@@ -316,6 +321,7 @@ where
             partitions: None,
             weight: Some(150),
             enabled: None,
+            region: None,
         },
     };
     let _ = store.patch(backend_patch).await.unwrap();
@@ -18,8 +18,9 @@ use switchgear_service_api::discovery::{
     DiscoveryBackendStore,
 };
 use switchgear_service_api::offer::{
-    OfferMetadata, OfferMetadataSparse, OfferMetadataStore, OfferRecord, OfferRecordSparse,
-    OfferStore,
+    Bolt12OfferRecord, Bolt12OfferSparse, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceSparse,
+    HoldInvoiceStore, OfferMetadata, OfferMetadataSparse, OfferMetadataStore, OfferRecord,
+    OfferRecordSparse, OfferStore,
 };
 use switchgear_testing::ports::PortAllocator;
 use tokio::net::TcpListener as TokioTcpListener;
@@ -256,6 +257,92 @@ async fn delete_offer(
     }
 }
 
+async fn get_bolt12_offers(
+    State(state): State<OfferState>,
+    AxumPath((partition, id)): AxumPath<(String, Uuid)>,
+) -> Result<Json<Vec<Bolt12OfferRecord>>, StatusCode> {
+    match state.store.get_bolt12_offers(&partition, &id).await {
+        Ok(offers) => Ok(Json(offers)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn put_bolt12_offer(
+    State(state): State<OfferState>,
+    AxumPath((partition, id, public_key)): AxumPath<(String, Uuid, secp256k1::PublicKey)>,
+    Json(offer_sparse): Json<Bolt12OfferSparse>,
+) -> Result<StatusCode, StatusCode> {
+    let offer = Bolt12OfferRecord {
+        partition,
+        id,
+        public_key,
+        offer: offer_sparse,
+    };
+
+    match state.store.put_bolt12_offer(offer).await {
+        Ok(true) => Ok(StatusCode::CREATED),
+        Ok(false) => Ok(StatusCode::NO_CONTENT),
+        Err(_) => Err(StatusCode::BAD_REQUEST),
+    }
+}
+
+fn parse_payment_hash(payment_hash: &str) -> Result<[u8; 32], StatusCode> {
+    hex::decode(payment_hash)
+        .ok()
+        .and_then(|payment_hash| payment_hash.try_into().ok())
+        .ok_or(StatusCode::BAD_REQUEST)
+}
+
+async fn get_hold_invoices(
+    State(state): State<OfferState>,
+) -> Result<Json<Vec<HoldInvoiceRecord>>, StatusCode> {
+    match state.store.get_hold_invoices().await {
+        Ok(holds) => Ok(Json(holds)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_hold_invoice(
+    State(state): State<OfferState>,
+    AxumPath(payment_hash): AxumPath<String>,
+) -> Result<Json<HoldInvoiceRecord>, StatusCode> {
+    let payment_hash = parse_payment_hash(&payment_hash)?;
+    match state.store.get_hold_invoice(&payment_hash).await {
+        Ok(Some(hold)) => Ok(Json(hold)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn put_hold_invoice(
+    State(state): State<OfferState>,
+    AxumPath(payment_hash): AxumPath<String>,
+    Json(hold): Json<HoldInvoiceSparse>,
+) -> Result<StatusCode, StatusCode> {
+    let hold = HoldInvoiceRecord {
+        payment_hash: parse_payment_hash(&payment_hash)?,
+        hold,
+    };
+
+    match state.store.put_hold_invoice(hold).await {
+        Ok(true) => Ok(StatusCode::CREATED),
+        Ok(false) => Ok(StatusCode::NO_CONTENT),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn delete_hold_invoice(
+    State(state): State<OfferState>,
+    AxumPath(payment_hash): AxumPath<String>,
+) -> Result<StatusCode, StatusCode> {
+    let payment_hash = parse_payment_hash(&payment_hash)?;
+    match state.store.delete_hold_invoice(&payment_hash).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
 async fn get_metadata(
     State(state): State<OfferState>,
     AxumPath((partition, id)): AxumPath<(String, Uuid)>,
@@ -385,6 +472,15 @@ impl TestService {
             .route("/offers/{partition}/{id}", get(get_offer))
             .route("/offers/{partition}/{id}", put(put_offer))
             .route("/offers/{partition}/{id}", delete(delete_offer))
+            .route("/offers/{partition}/{id}/bolt12", get(get_bolt12_offers))
+            .route(
+                "/offers/{partition}/{id}/bolt12/{public_key}",
+                put(put_bolt12_offer),
+            )
+            .route("/holds", get(get_hold_invoices))
+            .route("/holds/{payment_hash}", get(get_hold_invoice))
+            .route("/holds/{payment_hash}", put(put_hold_invoice))
+            .route("/holds/{payment_hash}", delete(delete_hold_invoice))
             .route("/offers/{partition}", get(get_offers))
             .route("/offers", post(post_offer))
             .route("/metadata/{partition}/{id}", get(get_metadata))
@@ -1,8 +1,10 @@
 use chrono::{Timelike, Utc};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
 use switchgear_components::offer::error::{OfferStoreError, OfferStoreErrorSourceKind};
 use switchgear_service_api::offer::{
-    OfferMetadata, OfferMetadataIdentifier, OfferMetadataImage, OfferMetadataSparse,
-    OfferMetadataStore, OfferRecord, OfferRecordSparse, OfferStore,
+    Bolt12OfferRecord, Bolt12OfferSparse, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceSparse,
+    HoldInvoiceStore, OfferMetadata, OfferMetadataIdentifier, OfferMetadataImage,
+    OfferMetadataSparse, OfferMetadataStore, OfferRecord, OfferRecordSparse, OfferStore,
 };
 use switchgear_service_api::service::ServiceErrorSource;
 use uuid::Uuid;
@@ -25,6 +27,7 @@ pub fn create_test_offer_with_existing_metadata(id: Uuid, metadata_id: Uuid) ->
             metadata: None,
             timestamp: now,
             expires: Some(expires),
+            hold_invoice: true,
         },
     }
 }
@@ -55,6 +58,7 @@ pub fn create_test_offer_with_metadata_id(offer_id: Uuid, metadata_id: Uuid) ->
             metadata: None,
             timestamp: Utc::now(),
             expires: Some(Utc::now() + chrono::Duration::hours(24)),
+            hold_invoice: false,
         },
     }
 }
@@ -422,6 +426,7 @@ where
             metadata: None,
             timestamp: Utc::now(),
             expires: Some(Utc::now() + chrono::Duration::seconds(3600)),
+            hold_invoice: false,
         },
     };
 
@@ -458,6 +463,7 @@ where
             metadata: None,
             timestamp: Utc::now(),
             expires: Some(Utc::now() + chrono::Duration::seconds(3600)),
+            hold_invoice: false,
         },
     };
 
@@ -515,3 +521,115 @@ where
         .unwrap();
     assert!(second_result);
 }
+
+pub fn create_test_bolt12_offer(offer: &OfferRecord, secret: u8) -> Bolt12OfferRecord {
+    Bolt12OfferRecord {
+        partition: offer.partition.clone(),
+        id: offer.id,
+        public_key: PublicKey::from_secret_key(
+            &Secp256k1::new(),
+            &SecretKey::from_byte_array([secret; 32]).unwrap(),
+        ),
+        offer: Bolt12OfferSparse {
+            offer_id: format!("offer-{secret}"),
+            bolt12: format!("lno1{secret}"),
+            amount_msat: 1000,
+            description: "test metadata".to_string(),
+            expires: offer.offer.expires,
+        },
+    }
+}
+
+pub async fn test_put_and_get_bolt12_offers<S>(store: S)
+where
+    S: OfferStore + OfferMetadataStore + Bolt12OfferStore,
+    <S as OfferStore>::Error: std::fmt::Debug,
+    <S as OfferMetadataStore>::Error: std::fmt::Debug,
+    <S as Bolt12OfferStore>::Error: std::fmt::Debug,
+{
+    let offer_id = Uuid::new_v4();
+    let (offer, _metadata) = create_test_offer_with_metadata(&store, offer_id).await;
+    store.put_offer(offer.clone()).await.unwrap();
+
+    let offers = store.get_bolt12_offers("default", &offer_id).await.unwrap();
+    assert!(offers.is_empty());
+
+    let first = create_test_bolt12_offer(&offer, 1);
+    let second = create_test_bolt12_offer(&offer, 2);
+    assert!(store.put_bolt12_offer(first.clone()).await.unwrap());
+    assert!(store.put_bolt12_offer(second.clone()).await.unwrap());
+
+    let mut replaced = first.clone();
+    replaced.offer.bolt12 = "lno1replaced".to_string();
+    assert!(!store.put_bolt12_offer(replaced.clone()).await.unwrap());
+
+    let mut expected = vec![replaced, second];
+    expected.sort_by_key(|o| o.public_key);
+    let offers = store.get_bolt12_offers("default", &offer_id).await.unwrap();
+    assert_eq!(offers, expected);
+
+    // deleting the offer drops its Bolt12 offers
+    assert!(store.delete_offer("default", &offer_id).await.unwrap());
+    let offers = store.get_bolt12_offers("default", &offer_id).await.unwrap();
+    assert!(offers.is_empty());
+}
+
+pub async fn test_put_bolt12_offer_with_missing_offer<S>(store: S)
+where
+    S: Bolt12OfferStore,
+    <S as Bolt12OfferStore>::Error: std::fmt::Debug,
+{
+    let offer = create_test_offer_with_metadata_id(Uuid::new_v4(), Uuid::new_v4());
+    let result = store
+        .put_bolt12_offer(create_test_bolt12_offer(&offer, 1))
+        .await;
+    assert!(result.is_err());
+}
+
+pub fn create_test_hold_invoice(secret: u8, cancel_after_secs: i64) -> HoldInvoiceRecord {
+    HoldInvoiceRecord {
+        payment_hash: [secret; 32],
+        hold: HoldInvoiceSparse {
+            public_key: PublicKey::from_secret_key(
+                &Secp256k1::new(),
+                &SecretKey::from_byte_array([secret; 32]).unwrap(),
+            ),
+            preimage: [secret.wrapping_add(1); 32],
+            invoice: format!("lnbc{secret}"),
+            cancel_at: (Utc::now() + chrono::Duration::seconds(cancel_after_secs))
+                .with_nanosecond(0)
+                .unwrap(),
+        },
+    }
+}
+
+pub async fn test_put_get_and_delete_hold_invoices<S>(store: S)
+where
+    S: HoldInvoiceStore,
+    <S as HoldInvoiceStore>::Error: std::fmt::Debug,
+{
+    assert!(store.get_hold_invoices().await.unwrap().is_empty());
+    assert!(store.get_hold_invoice(&[1; 32]).await.unwrap().is_none());
+
+    let later = create_test_hold_invoice(1, 7200);
+    let sooner = create_test_hold_invoice(2, 3600);
+    assert!(store.put_hold_invoice(later.clone()).await.unwrap());
+    assert!(store.put_hold_invoice(sooner.clone()).await.unwrap());
+
+    let mut replaced = later.clone();
+    replaced.hold.invoice = "lnbcreplaced".to_string();
+    assert!(!store.put_hold_invoice(replaced.clone()).await.unwrap());
+
+    let hold = store.get_hold_invoice(&[1; 32]).await.unwrap();
+    assert_eq!(hold, Some(replaced.clone()));
+
+    // ordered by cancel deadline
+    let holds = store.get_hold_invoices().await.unwrap();
+    assert_eq!(holds, vec![sooner, replaced]);
+
+    // only the first delete claims the hold
+    assert!(store.delete_hold_invoice(&[1; 32]).await.unwrap());
+    assert!(!store.delete_hold_invoice(&[1; 32]).await.unwrap());
+    assert!(store.get_hold_invoice(&[1; 32]).await.unwrap().is_none());
+    assert_eq!(store.get_hold_invoices().await.unwrap().len(), 1);
+}
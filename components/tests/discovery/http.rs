@@ -20,6 +20,7 @@ async fn create_http_store() -> (HttpDiscoveryBackendStore, mock_service::TestSe
         Duration::from_secs(10),
         &[],
         test_service.discovery_authorization.clone(),
+        None,
     )
     .unwrap();
     (store, test_service)
@@ -0,0 +1,368 @@
+use axum::extract::{Path as AxumPath, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use switchgear_components::pool::cln::rest::client::ReqwestClnRestClient;
+use switchgear_components::pool::cln::rest::config::{
+    ClnRestClientAuth, ClnRestClientAuthPath, ClnRestDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::error::LnPoolErrorSourceKind;
+use switchgear_components::pool::{
+    Bolt11InvoiceDescription, DiscoveryBackendImplementation, LnClientPool, LnInvoiceState,
+    LnRouteHints, LnRpcClient,
+};
+use switchgear_service_api::discovery::{DiscoveryBackend, DiscoveryBackendSparse};
+use switchgear_service_api::service::{HasServiceErrorSource, ServiceErrorSource};
+use switchgear_testing::ports::PortAllocator;
+use tempfile::NamedTempFile;
+use tokio::net::TcpListener;
+use url::Url;
+
+const RUNE: &str = "Bc5rJ1dGJfmUFEVlqhrXgBtSuxhxXeB8AYFnyHoJm549MA==";
+const PEER_A: &str = "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619";
+const PEER_B: &str = "0324653eac434488002cc06bbfb7f10fe18991e35f9fe4302dbea6d2353dc0ab1c";
+const NODE_ID: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+#[derive(Clone, Default)]
+struct ClnRestStubState {
+    requests: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+async fn rpc(
+    State(state): State<ClnRestStubState>,
+    AxumPath(method): AxumPath<String>,
+    headers: HeaderMap,
+    Json(params): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    if headers.get("rune").and_then(|r| r.to_str().ok()) != Some(RUNE) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"code": 1501, "message": "Not authorized: Not derived from master"})),
+        );
+    }
+
+    state
+        .requests
+        .lock()
+        .unwrap()
+        .push((method.clone(), params.clone()));
+
+    match method.as_str() {
+        "getinfo" => (
+            StatusCode::OK,
+            Json(json!({"id": NODE_ID, "alias": "stub"})),
+        ),
+        "invoice" => (
+            StatusCode::CREATED,
+            Json(json!({
+                "bolt11": "lnbcrt10n1stub",
+                "payment_hash": "00".repeat(32),
+                "expires_at": 1_700_000_000u64,
+            })),
+        ),
+        "listpeerchannels" => (
+            StatusCode::OK,
+            Json(json!({
+                "channels": [
+                    {"peer_id": PEER_A, "state": "CHANNELD_NORMAL", "short_channel_id": "101x1x0", "private": false, "receivable_msat": 1_000_000u64},
+                    {"peer_id": PEER_A, "state": "CHANNELD_NORMAL", "short_channel_id": "102x1x0", "private": false, "receivable_msat": 2_500_000u64},
+                    {"peer_id": PEER_B, "state": "CHANNELD_NORMAL", "short_channel_id": "103x1x0", "private": true, "receivable_msat": 4_000_000u64},
+                    {"peer_id": PEER_B, "state": "CHANNELD_AWAITING_LOCKIN", "private": true, "receivable_msat": 9_000_000u64},
+                ]
+            })),
+        ),
+        "listinvoices" => {
+            let status = match params["invstring"].as_str() {
+                Some("lnbcrt-paid") => "paid",
+                Some("lnbcrt-expired") => "expired",
+                _ => "unpaid",
+            };
+            (
+                StatusCode::OK,
+                Json(json!({"invoices": [{"label": "stub", "status": status}]})),
+            )
+        }
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"code": -32601, "message": format!("Unknown command '{method}'")})),
+        ),
+    }
+}
+
+struct ClnRestStub {
+    state: ClnRestStubState,
+    url: Url,
+    _rune_file: NamedTempFile,
+    implementation: ClnRestDiscoveryBackendImplementation,
+    client: ReqwestClnRestClient,
+}
+
+impl ClnRestStub {
+    async fn start(rune: &str) -> Self {
+        Self::start_with_route_hints(rune, None).await
+    }
+
+    async fn start_with_route_hints(rune: &str, route_hints: Option<LnRouteHints>) -> Self {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let ports_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+        let port = PortAllocator::find_available_port(&ports_path).unwrap();
+        let listener = TcpListener::bind(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port))
+            .await
+            .unwrap();
+
+        let state = ClnRestStubState::default();
+        let router = Router::new()
+            .route("/v1/{method}", post(rpc))
+            .with_state(state.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let mut rune_file = NamedTempFile::new().unwrap();
+        writeln!(rune_file, "{rune}").unwrap();
+
+        let url = Url::parse(&format!("http://127.0.0.1:{port}/")).unwrap();
+        let implementation = ClnRestDiscoveryBackendImplementation {
+            url: url.clone(),
+            auth: ClnRestClientAuth::Path(ClnRestClientAuthPath {
+                ca_cert_path: None,
+                rune_path: rune_file.path().to_path_buf(),
+            }),
+            route_hints,
+        };
+        let client =
+            ReqwestClnRestClient::create(Duration::from_secs(1), implementation.clone(), &[], None)
+                .unwrap();
+
+        Self {
+            state,
+            url,
+            _rune_file: rune_file,
+            implementation,
+            client,
+        }
+    }
+
+    fn discovery_backend(&self, public_key: &str) -> DiscoveryBackend {
+        DiscoveryBackend {
+            public_key: public_key.parse().unwrap(),
+            backend: DiscoveryBackendSparse {
+                name: None,
+                partitions: ["default".to_string()].into(),
+                weight: 1,
+                enabled: true,
+                region: None,
+                implementation: serde_json::to_vec(&DiscoveryBackendImplementation::ClnRest(
+                    self.implementation.clone(),
+                ))
+                .unwrap(),
+            },
+        }
+    }
+
+    fn requests(&self) -> Vec<(String, Value)> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn test_cln_rest_invoice_with_direct_into_hash_description() {
+    let stub = ClnRestStub::start(RUNE).await;
+
+    let invoice = stub
+        .client
+        .get_invoice(
+            Some(1_000),
+            Bolt11InvoiceDescription::DirectIntoHash("[[\"text/plain\",\"hello\"]]"),
+            Some(600),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(invoice, "lnbcrt10n1stub");
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    let (method, params) = &requests[0];
+    assert_eq!(method, "invoice");
+    assert_eq!(params["amount_msat"], json!(1_000));
+    assert_eq!(params["description"], json!("[[\"text/plain\",\"hello\"]]"));
+    assert_eq!(params["deschashonly"], json!(true));
+    assert_eq!(params["expiry"], json!(600));
+    assert!(params["label"].as_str().unwrap().contains(':'));
+}
+
+#[tokio::test]
+async fn test_cln_rest_invoice_with_none_amount() {
+    let stub = ClnRestStub::start(RUNE).await;
+
+    stub.client
+        .get_invoice(None, Bolt11InvoiceDescription::Direct("hello"), None)
+        .await
+        .unwrap();
+
+    let (_, params) = &stub.requests()[0];
+    assert_eq!(params["amount_msat"], json!("any"));
+    assert_eq!(params["deschashonly"], json!(false));
+    assert!(params.get("expiry").is_none());
+}
+
+#[tokio::test]
+async fn test_cln_rest_invoice_with_hash_description_unsupported() {
+    let stub = ClnRestStub::start(RUNE).await;
+
+    let result = stub
+        .client
+        .get_invoice(
+            Some(1_000),
+            Bolt11InvoiceDescription::Hash(&[0u8; 32]),
+            None,
+        )
+        .await;
+
+    assert!(result.is_err());
+    assert!(stub.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_cln_rest_metrics_sums_normal_channels() {
+    let stub = ClnRestStub::start(RUNE).await;
+
+    let metrics = stub.client.get_metrics().await.unwrap();
+
+    assert!(metrics.healthy);
+    assert_eq!(metrics.node_effective_inbound_msat, 3_500_000);
+    assert_eq!(metrics.node_private_inbound_msat, 4_000_000);
+    assert_eq!(stub.requests()[0].0, "listpeerchannels");
+}
+
+#[tokio::test]
+async fn test_cln_rest_route_hints_expose_selected_private_channels() {
+    let stub = ClnRestStub::start_with_route_hints(
+        RUNE,
+        Some(LnRouteHints {
+            channels: vec![],
+            peers: vec![PEER_B.parse().unwrap()],
+        }),
+    )
+    .await;
+
+    stub.client
+        .get_invoice(Some(1_000), Bolt11InvoiceDescription::Direct("hello"), None)
+        .await
+        .unwrap();
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].0, "listpeerchannels");
+    assert_eq!(requests[1].0, "invoice");
+    assert_eq!(requests[1].1["exposeprivatechannels"], json!(["103x1x0"]));
+
+    let metrics = stub.client.get_metrics().await.unwrap();
+    assert_eq!(metrics.node_effective_inbound_msat, 7_500_000);
+    assert_eq!(metrics.node_private_inbound_msat, 4_000_000);
+}
+
+#[tokio::test]
+async fn test_cln_rest_route_hints_expose_all_private_channels() {
+    let stub = ClnRestStub::start_with_route_hints(RUNE, Some(LnRouteHints::default())).await;
+
+    stub.client
+        .get_invoice(Some(1_000), Bolt11InvoiceDescription::Direct("hello"), None)
+        .await
+        .unwrap();
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].1["exposeprivatechannels"], json!(true));
+}
+
+#[tokio::test]
+async fn test_cln_rest_invoice_state() {
+    let stub = ClnRestStub::start(RUNE).await;
+
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-unpaid", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Open
+    );
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-paid", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Settled
+    );
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-expired", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Canceled
+    );
+}
+
+#[tokio::test]
+async fn test_cln_rest_when_rune_rejected_then_upstream_error() {
+    let stub = ClnRestStub::start("not-the-rune").await;
+
+    let error = stub.client.get_metrics().await.unwrap_err();
+
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Upstream
+    );
+    assert!(error.to_string().contains("401"));
+    assert!(error.to_string().contains(stub.url.host_str().unwrap()));
+}
+
+#[tokio::test]
+async fn test_cln_rest_node_id() {
+    let stub = ClnRestStub::start(RUNE).await;
+
+    let node_id = stub.client.get_node_id().await.unwrap();
+
+    assert_eq!(node_id.unwrap().to_string(), NODE_ID);
+    assert_eq!(stub.requests()[0].0, "getinfo");
+}
+
+#[tokio::test]
+async fn test_cln_rest_pool_when_node_id_matches_then_verified() {
+    let stub = ClnRestStub::start(RUNE).await;
+    let pool = LnClientPool::new(Duration::from_secs(1), vec![], HashMap::new(), None);
+    pool.connect(0, &stub.discovery_backend(NODE_ID)).unwrap();
+
+    pool.verify_node_id(&0).await.unwrap();
+    let metrics = pool.get_metrics(&0).await.unwrap();
+
+    assert_eq!(metrics.node_effective_inbound_msat, 3_500_000);
+}
+
+#[tokio::test]
+async fn test_cln_rest_pool_when_node_id_mismatch_then_refused() {
+    let stub = ClnRestStub::start(RUNE).await;
+    let pool = LnClientPool::new(Duration::from_secs(1), vec![], HashMap::new(), None);
+    pool.connect(0, &stub.discovery_backend(PEER_A)).unwrap();
+
+    let error = pool.verify_node_id(&0).await.unwrap_err();
+    assert!(matches!(
+        error.source(),
+        LnPoolErrorSourceKind::NodeIdMismatch(expected, actual)
+            if expected == PEER_A && actual == NODE_ID
+    ));
+
+    // every metrics poll checks again, so the health check keeps the backend unhealthy
+    let error = pool.get_metrics(&0).await.unwrap_err();
+    assert!(matches!(
+        error.source(),
+        LnPoolErrorSourceKind::NodeIdMismatch(..)
+    ));
+    assert!(pool.get_cached_metrics(&0).is_none());
+}
@@ -0,0 +1,238 @@
+use axum::extract::{Path as AxumPath, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Form, Json, Router};
+use base64::Engine;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use switchgear_components::pool::eclair::http::client::ReqwestEclairHttpClient;
+use switchgear_components::pool::eclair::http::config::{
+    EclairHttpClientAuth, EclairHttpClientAuthPath, EclairHttpDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::{Bolt11InvoiceDescription, LnInvoiceState, LnRpcClient};
+use switchgear_service_api::service::{HasServiceErrorSource, ServiceErrorSource};
+use switchgear_testing::ports::PortAllocator;
+use tempfile::NamedTempFile;
+use tokio::net::TcpListener;
+use url::Url;
+
+const PASSWORD: &str = "9f3a7c1e5b2d8046";
+
+type EclairRequest = (String, HashMap<String, String>);
+
+#[derive(Clone, Default)]
+struct EclairStubState {
+    requests: Arc<Mutex<Vec<EclairRequest>>>,
+}
+
+async fn api(
+    State(state): State<EclairStubState>,
+    AxumPath(method): AxumPath<String>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let expected = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(format!(":{PASSWORD}"))
+    );
+    if headers.get("authorization").and_then(|a| a.to_str().ok()) != Some(expected.as_str()) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            "The supplied authentication is invalid",
+        )
+            .into_response();
+    }
+
+    state
+        .requests
+        .lock()
+        .unwrap()
+        .push((method.clone(), form.clone()));
+
+    match method.as_str() {
+        "createinvoice" => Json(json!({
+            "prefix": "lnbcrt",
+            "paymentHash": "00".repeat(32),
+            "serialized": "lnbcrt10n1eclair",
+        }))
+        .into_response(),
+        "usablebalances" => Json(json!([
+            {"remoteNodeId": "a", "canSend": 10, "canReceive": 1_000_000u64, "isPublic": true},
+            {"remoteNodeId": "b", "canSend": 10, "canReceive": 2_500_000u64, "isPublic": false},
+        ]))
+        .into_response(),
+        "getreceivedinfo" => match form.get("invoice").map(String::as_str) {
+            Some("lnbcrt-unknown") => {
+                (StatusCode::NOT_FOUND, Json(json!({"error": "Not found"}))).into_response()
+            }
+            invoice => {
+                let status = match invoice {
+                    Some("lnbcrt-received") => "received",
+                    Some("lnbcrt-expired") => "expired",
+                    _ => "pending",
+                };
+                Json(json!({"paymentRequest": {}, "status": {"type": status}})).into_response()
+            }
+        },
+        _ => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("unknown method {method}")})),
+        )
+            .into_response(),
+    }
+}
+
+struct EclairStub {
+    state: EclairStubState,
+    _password_file: NamedTempFile,
+    client: ReqwestEclairHttpClient,
+}
+
+impl EclairStub {
+    async fn start(password: &str) -> Self {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let ports_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+        let port = PortAllocator::find_available_port(&ports_path).unwrap();
+        let listener = TcpListener::bind(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port))
+            .await
+            .unwrap();
+
+        let state = EclairStubState::default();
+        let router = Router::new()
+            .route("/{method}", post(api))
+            .with_state(state.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let mut password_file = NamedTempFile::new().unwrap();
+        writeln!(password_file, "{password}").unwrap();
+
+        let client = ReqwestEclairHttpClient::create(
+            Duration::from_secs(1),
+            EclairHttpDiscoveryBackendImplementation {
+                url: Url::parse(&format!("http://127.0.0.1:{port}")).unwrap(),
+                auth: EclairHttpClientAuth::Path(EclairHttpClientAuthPath {
+                    ca_cert_path: None,
+                    password_path: password_file.path().to_path_buf(),
+                }),
+            },
+            &[],
+            None,
+        )
+        .unwrap();
+
+        Self {
+            state,
+            _password_file: password_file,
+            client,
+        }
+    }
+
+    fn requests(&self) -> Vec<EclairRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn test_eclair_http_invoice_with_direct_into_hash_description() {
+    let stub = EclairStub::start(PASSWORD).await;
+
+    let invoice = stub
+        .client
+        .get_invoice(
+            Some(1_500),
+            Bolt11InvoiceDescription::DirectIntoHash("hello"),
+            Some(600),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(invoice, "lnbcrt10n1eclair");
+    let (method, form) = &stub.requests()[0];
+    assert_eq!(method, "createinvoice");
+    assert_eq!(
+        form["descriptionHash"],
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+    assert_eq!(form["amountMsat"], "1500");
+    assert_eq!(form["expireIn"], "600");
+}
+
+#[tokio::test]
+async fn test_eclair_http_invoice_with_direct_description_and_none_amount() {
+    let stub = EclairStub::start(PASSWORD).await;
+
+    stub.client
+        .get_invoice(None, Bolt11InvoiceDescription::Direct("hello"), None)
+        .await
+        .unwrap();
+
+    let (_, form) = &stub.requests()[0];
+    assert_eq!(form["description"], "hello");
+    assert!(!form.contains_key("amountMsat"));
+    assert!(!form.contains_key("expireIn"));
+}
+
+#[tokio::test]
+async fn test_eclair_http_metrics_sums_usable_balances() {
+    let stub = EclairStub::start(PASSWORD).await;
+
+    let metrics = stub.client.get_metrics().await.unwrap();
+
+    assert!(metrics.healthy);
+    assert_eq!(metrics.node_effective_inbound_msat, 3_500_000);
+    assert_eq!(stub.requests()[0].0, "usablebalances");
+}
+
+#[tokio::test]
+async fn test_eclair_http_invoice_state() {
+    let stub = EclairStub::start(PASSWORD).await;
+
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-pending", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Open
+    );
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-received", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Settled
+    );
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-expired", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Canceled
+    );
+
+    let error = stub
+        .client
+        .get_invoice_state("lnbcrt-unknown", &[0; 32])
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Not found"));
+}
+
+#[tokio::test]
+async fn test_eclair_http_when_password_rejected_then_upstream_error() {
+    let stub = EclairStub::start("wrong-password").await;
+
+    let error = stub.client.get_metrics().await.unwrap_err();
+
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Upstream
+    );
+    assert!(error.to_string().contains("401"));
+}
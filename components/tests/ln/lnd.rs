@@ -7,7 +7,7 @@ use sha2::Digest;
 use std::str::FromStr;
 use std::time::Duration;
 use switchgear_components::pool::lnd::grpc::client::TonicLndGrpcClient;
-use switchgear_components::pool::{Bolt11InvoiceDescription, LnRpcClient};
+use switchgear_components::pool::{Bolt11InvoiceDescription, LnInvoiceState, LnRpcClient};
 use switchgear_testing::credentials::lightning::LnCredentials;
 
 type LnClientBox = Box<
@@ -226,3 +226,35 @@ async fn test_lnd_tonic_metrics() {
         "Expected metrics response (proving LND connectivity) but got None"
     );
 }
+
+#[tokio::test]
+async fn test_lnd_tonic_invoice_state_when_unpaid_then_open() {
+    let credentials = LnCredentials::create().unwrap();
+    let client = create_lnd_tonic_client(&credentials).await.unwrap();
+
+    let random_string: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(10)
+        .map(char::from)
+        .collect();
+
+    let invoice = client
+        .get_invoice(
+            Some(1_000),
+            Bolt11InvoiceDescription::Direct(&random_string),
+            Some(3600),
+        )
+        .await
+        .expect("Failed to generate invoice");
+
+    let payment_hash = Bolt11Invoice::from_str(&invoice)
+        .expect("Failed to parse generated invoice")
+        .payment_hash()
+        .to_byte_array();
+    let state = client
+        .get_invoice_state(&invoice, &payment_hash)
+        .await
+        .expect("Failed to retrieve LND invoice state");
+
+    assert_eq!(state, LnInvoiceState::Open);
+}
@@ -0,0 +1,163 @@
+use crate::try_create_lnd_rest_backend_implementation;
+use anyhow::anyhow;
+use bitcoin_hashes::Hash;
+use lightning_invoice::Bolt11Invoice;
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::Digest;
+use std::str::FromStr;
+use std::time::Duration;
+use switchgear_components::pool::lnd::rest::client::ReqwestLndRestClient;
+use switchgear_components::pool::{Bolt11InvoiceDescription, LnInvoiceState, LnRpcClient};
+use switchgear_testing::credentials::lightning::LnCredentials;
+
+type LnClientBox = Box<
+    dyn LnRpcClient<Error = switchgear_components::pool::error::LnPoolError>
+        + Send
+        + Sync
+        + 'static,
+>;
+
+async fn create_lnd_rest_client(
+    credentials: &LnCredentials,
+    amp_invoice: bool,
+) -> anyhow::Result<LnClientBox> {
+    let _ = rustls::crypto::aws_lc_rs::default_provider()
+        .install_default()
+        .map_err(|_| anyhow!("failed to stand up rustls encryption platform"));
+
+    let mut backend = try_create_lnd_rest_backend_implementation(credentials)?;
+    backend.amp_invoice = amp_invoice;
+
+    let client = ReqwestLndRestClient::create(Duration::from_secs(1), backend, &[], None)?;
+
+    Ok(Box::new(client))
+}
+
+fn random_description() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(10)
+        .map(char::from)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_lnd_rest_invoice_with_direct_description() {
+    let credentials = LnCredentials::create().unwrap();
+    let client = create_lnd_rest_client(&credentials, false).await.unwrap();
+
+    let random_string = random_description();
+    let expected_amount_msat = 2_000_000;
+    let expected_expiry_secs = 7200;
+
+    let invoice_str = client
+        .get_invoice(
+            Some(expected_amount_msat),
+            Bolt11InvoiceDescription::Direct(&random_string),
+            Some(expected_expiry_secs),
+        )
+        .await
+        .expect("Failed to generate LND REST invoice with direct description");
+
+    let invoice = Bolt11Invoice::from_str(&invoice_str).expect("Failed to parse generated invoice");
+
+    assert_eq!(
+        invoice.amount_milli_satoshis().unwrap(),
+        expected_amount_msat
+    );
+    match invoice.description() {
+        lightning_invoice::Bolt11InvoiceDescriptionRef::Direct(desc) => {
+            assert_eq!(desc.to_string(), random_string);
+        }
+        lightning_invoice::Bolt11InvoiceDescriptionRef::Hash(_) => {
+            panic!("Expected Direct description but got Hash description");
+        }
+    }
+    assert_eq!(invoice.expiry_time().as_secs(), expected_expiry_secs);
+}
+
+#[tokio::test]
+async fn test_lnd_rest_invoice_with_hash_description() {
+    let credentials = LnCredentials::create().unwrap();
+    let client = create_lnd_rest_client(&credentials, false).await.unwrap();
+
+    let hash: [u8; 32] = sha2::Sha256::digest(random_description().as_bytes()).into();
+
+    let invoice_str = client
+        .get_invoice(
+            Some(1_500_000),
+            Bolt11InvoiceDescription::Hash(&hash),
+            Some(3600),
+        )
+        .await
+        .expect("Failed to generate LND REST invoice with hash description");
+
+    let invoice = Bolt11Invoice::from_str(&invoice_str).expect("Failed to parse generated invoice");
+
+    match invoice.description() {
+        lightning_invoice::Bolt11InvoiceDescriptionRef::Hash(invoice_hash) => {
+            assert_eq!(hash, invoice_hash.0.to_byte_array());
+        }
+        lightning_invoice::Bolt11InvoiceDescriptionRef::Direct(_) => {
+            panic!("Expected Hash description but got Direct description");
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_lnd_rest_amp_invoice_with_none_amount() {
+    let credentials = LnCredentials::create().unwrap();
+    let client = create_lnd_rest_client(&credentials, true).await.unwrap();
+
+    let invoice_str = client
+        .get_invoice(
+            None,
+            Bolt11InvoiceDescription::Direct(&random_description()),
+            Some(3600),
+        )
+        .await
+        .expect("Failed to generate LND REST AMP invoice with no amount");
+
+    let invoice = Bolt11Invoice::from_str(&invoice_str).expect("Failed to parse generated invoice");
+
+    assert!(invoice.amount_milli_satoshis().is_none());
+}
+
+#[tokio::test]
+async fn test_lnd_rest_metrics() {
+    let credentials = LnCredentials::create().unwrap();
+    let client = create_lnd_rest_client(&credentials, false).await.unwrap();
+
+    let metrics = client
+        .get_metrics()
+        .await
+        .expect("Failed to connect to LND REST gateway and retrieve metrics");
+
+    assert!(metrics.healthy);
+}
+
+#[tokio::test]
+async fn test_lnd_rest_invoice_state_when_unpaid_then_open() {
+    let credentials = LnCredentials::create().unwrap();
+    let client = create_lnd_rest_client(&credentials, false).await.unwrap();
+
+    let invoice = client
+        .get_invoice(
+            Some(1_000),
+            Bolt11InvoiceDescription::Direct(&random_description()),
+            Some(3600),
+        )
+        .await
+        .expect("Failed to generate invoice");
+
+    let payment_hash = Bolt11Invoice::from_str(&invoice)
+        .expect("Failed to parse generated invoice")
+        .payment_hash()
+        .to_byte_array();
+    let state = client
+        .get_invoice_state(&invoice, &payment_hash)
+        .await
+        .expect("Failed to retrieve LND REST invoice state");
+
+    assert_eq!(state, LnInvoiceState::Open);
+}
@@ -4,6 +4,9 @@ use switchgear_components::pool::cln::grpc::config::{
 use switchgear_components::pool::lnd::grpc::config::{
     LndGrpcClientAuth, LndGrpcClientAuthPath, LndGrpcDiscoveryBackendImplementation,
 };
+use switchgear_components::pool::lnd::rest::config::{
+    LndRestClientAuth, LndRestClientAuthPath, LndRestDiscoveryBackendImplementation,
+};
 use switchgear_testing::credentials::lightning::LnCredentials;
 use url::Url;
 
@@ -11,7 +14,12 @@ use url::Url;
 pub mod common;
 
 mod cln;
+mod cln_rest;
+mod eclair_http;
 mod lnd;
+mod lnd_rest;
+mod nwc;
+mod phoenixd_http;
 
 pub fn try_create_cln_backend_implementation(
     credentials: &LnCredentials,
@@ -30,6 +38,9 @@ pub fn try_create_cln_backend_implementation(
             client_key_path: cln_node.client_key_path,
         }),
         domain: None,
+        route_hints: None,
+        channels: None,
+        socks5_proxy: None,
     };
 
     Ok(implementation)
@@ -52,6 +63,31 @@ pub fn try_create_lnd_backend_implementation(
         }),
         amp_invoice: false,
         domain: None,
+        route_hints: None,
+        channels: None,
+        socks5_proxy: None,
+    };
+
+    Ok(implementation)
+}
+
+pub fn try_create_lnd_rest_backend_implementation(
+    credentials: &LnCredentials,
+) -> anyhow::Result<LndRestDiscoveryBackendImplementation> {
+    let backends = credentials.get_backends()?;
+
+    let lnd_node = backends.lnd.clone();
+
+    let url = Url::parse(&format!("https://{}", lnd_node.rest_address))?;
+
+    let implementation = LndRestDiscoveryBackendImplementation {
+        url,
+        auth: LndRestClientAuth::Path(LndRestClientAuthPath {
+            tls_cert_path: lnd_node.tls_cert_path.into(),
+            macaroon_path: lnd_node.macaroon_path,
+        }),
+        amp_invoice: false,
+        route_hints: None,
     };
 
     Ok(implementation)
@@ -0,0 +1,452 @@
+use futures_util::{SinkExt, StreamExt};
+use secp256k1::{Keypair, Secp256k1};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use switchgear_components::pool::error::LnPoolErrorSourceKind;
+use switchgear_components::pool::nwc::client::TungsteniteNwcClient;
+use switchgear_components::pool::nwc::config::{
+    NwcClientAuth, NwcClientAuthPath, NwcDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::nwc::event::{
+    nip04_decrypt, nip04_encrypt, NostrEvent, NWC_REQUEST_KIND, NWC_RESPONSE_KIND,
+};
+use switchgear_components::pool::{Bolt11InvoiceDescription, LnInvoiceState, LnRpcClient};
+use switchgear_service_api::service::{HasServiceErrorSource, ServiceErrorSource};
+use switchgear_testing::ports::PortAllocator;
+use tempfile::NamedTempFile;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use url::Url;
+
+#[derive(Clone, Copy, PartialEq)]
+enum RelayMode {
+    // answer every request as it arrives
+    Respond,
+    // hold responses until this many requests arrived, then answer them in reverse order
+    Reverse(usize),
+    // never answer get_balance
+    SilentBalance,
+    // reject every published event
+    Reject,
+}
+
+fn keypair(byte: u8) -> Keypair {
+    Keypair::from_seckey_byte_array(&Secp256k1::new(), [byte; 32]).unwrap()
+}
+
+// a relay stand-in that also plays the wallet service
+struct NwcRelayStandIn {
+    mode: RelayMode,
+    wallet: Keypair,
+    client: Keypair,
+    requests: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl NwcRelayStandIn {
+    fn respond(&self, request: &NostrEvent) -> NostrEvent {
+        let content = nip04_decrypt(
+            &self.wallet.secret_key(),
+            &self.client.x_only_public_key().0,
+            &request.content,
+        )
+        .unwrap();
+        let content: Value = serde_json::from_str(&content).unwrap();
+        let method = content["method"].as_str().unwrap().to_string();
+        let params = content["params"].clone();
+        self.requests
+            .lock()
+            .unwrap()
+            .push((method.clone(), params.clone()));
+
+        let response = match method.as_str() {
+            "make_invoice" => json!({
+                "result_type": method,
+                "result": {
+                    "type": "incoming",
+                    "invoice": format!("lnbcrt-{}", params["amount"]),
+                    "amount": params["amount"],
+                },
+            }),
+            "get_balance" => json!({"result_type": method, "result": {"balance": 21_000}}),
+            "lookup_invoice" => {
+                let state = match params["invoice"].as_str() {
+                    Some("lnbcrt-settled") => "settled",
+                    Some("lnbcrt-expired") => "expired",
+                    _ => "pending",
+                };
+                json!({"result_type": method, "result": {"type": "incoming", "state": state}})
+            }
+            _ => json!({
+                "result_type": method,
+                "error": {"code": "NOT_IMPLEMENTED", "message": format!("{method} unsupported")},
+            }),
+        };
+
+        self.response_event(&self.wallet, &request.id, &response)
+    }
+
+    fn response_event(&self, signer: &Keypair, request_id: &str, response: &Value) -> NostrEvent {
+        let client_pubkey = self.client.x_only_public_key().0;
+        NostrEvent::new(
+            signer,
+            NWC_RESPONSE_KIND,
+            vec![
+                vec!["p".to_string(), client_pubkey.to_string()],
+                vec!["e".to_string(), request_id.to_string()],
+            ],
+            nip04_encrypt(&signer.secret_key(), &client_pubkey, &response.to_string()),
+        )
+    }
+
+    async fn serve(self: Arc<Self>, listener: TcpListener) {
+        while let Ok((stream, _)) = listener.accept().await {
+            let relay = self.clone();
+            tokio::spawn(async move {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let mut subscription = String::new();
+                let mut held = Vec::new();
+
+                while let Some(Ok(Message::Text(text))) = ws.next().await {
+                    let message: Vec<Value> = serde_json::from_str(text.as_str()).unwrap();
+                    match message[0].as_str().unwrap() {
+                        "REQ" => {
+                            subscription = message[1].as_str().unwrap().to_string();
+                            let eose = json!(["EOSE", subscription]).to_string();
+                            ws.send(Message::text(eose)).await.unwrap();
+                        }
+                        "EVENT" => {
+                            let request: NostrEvent =
+                                serde_json::from_value(message[1].clone()).unwrap();
+                            assert!(request.verify());
+                            assert_eq!(request.kind, NWC_REQUEST_KIND);
+
+                            if relay.mode == RelayMode::Reject {
+                                let ok = json!(["OK", request.id, false, "blocked: not allowed"]);
+                                ws.send(Message::text(ok.to_string())).await.unwrap();
+                                continue;
+                            }
+                            let ok = json!(["OK", request.id, true, ""]);
+                            ws.send(Message::text(ok.to_string())).await.unwrap();
+
+                            let response = relay.respond(&request);
+                            let silent = relay.mode == RelayMode::SilentBalance
+                                && relay.requests.lock().unwrap().last().unwrap().0
+                                    == "get_balance";
+                            if silent {
+                                continue;
+                            }
+                            held.push(response);
+
+                            if let RelayMode::Reverse(count) = relay.mode {
+                                if held.len() < count {
+                                    continue;
+                                }
+                                // noise first: an unknown request id, and a forged wallet
+                                // response to a pending request
+                                let unknown = relay.response_event(
+                                    &relay.wallet,
+                                    &"00".repeat(32),
+                                    &json!({"result": {"balance": 1}}),
+                                );
+                                let forged = relay.response_event(
+                                    &keypair(3),
+                                    held[0].tag("e").unwrap(),
+                                    &json!({"result": {"invoice": "forged"}}),
+                                );
+                                for event in [unknown, forged] {
+                                    let event = json!(["EVENT", subscription, event]);
+                                    ws.send(Message::text(event.to_string())).await.unwrap();
+                                }
+                                held.reverse();
+                            }
+
+                            for response in held.drain(..) {
+                                let event = json!(["EVENT", subscription, response]);
+                                ws.send(Message::text(event.to_string())).await.unwrap();
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            });
+        }
+    }
+}
+
+// SOCKS5 stand-in that records each requested destination and forwards the connection to it
+async fn socks5_forwarding_proxy() -> (Url, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = Url::parse(&format!("socks5://{}", listener.local_addr().unwrap())).unwrap();
+    let destinations = Arc::new(Mutex::new(vec![]));
+
+    let recorded = destinations.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let recorded = recorded.clone();
+            tokio::spawn(async move {
+                let mut greeting = [0u8; 2];
+                stream.read_exact(&mut greeting).await.unwrap();
+                let mut methods = vec![0u8; greeting[1] as usize];
+                stream.read_exact(&mut methods).await.unwrap();
+                stream.write_all(&[0x05, 0x00]).await.unwrap();
+
+                let mut request = [0u8; 4];
+                stream.read_exact(&mut request).await.unwrap();
+                let host = match request[3] {
+                    0x01 => {
+                        let mut ip = [0u8; 4];
+                        stream.read_exact(&mut ip).await.unwrap();
+                        Ipv4Addr::from(ip).to_string()
+                    }
+                    _ => {
+                        let len = stream.read_u8().await.unwrap();
+                        let mut domain = vec![0u8; len as usize];
+                        stream.read_exact(&mut domain).await.unwrap();
+                        String::from_utf8(domain).unwrap()
+                    }
+                };
+                let port = stream.read_u16().await.unwrap();
+                let destination = format!("{host}:{port}");
+                recorded.lock().unwrap().push(destination.clone());
+
+                let mut upstream = TcpStream::connect(destination).await.unwrap();
+                stream
+                    .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+                    .await
+                    .unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+            });
+        }
+    });
+
+    (proxy, destinations)
+}
+
+struct NwcStub {
+    relay: Arc<NwcRelayStandIn>,
+    _uri_file: NamedTempFile,
+    client: Arc<TungsteniteNwcClient>,
+}
+
+impl NwcStub {
+    async fn start(mode: RelayMode) -> Self {
+        Self::start_with_proxy(mode, None).await
+    }
+
+    async fn start_with_proxy(mode: RelayMode, socks5_proxy: Option<&Url>) -> Self {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let ports_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+        let port = PortAllocator::find_available_port(&ports_path).unwrap();
+        let listener = TcpListener::bind(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port))
+            .await
+            .unwrap();
+
+        let relay = Arc::new(NwcRelayStandIn {
+            mode,
+            wallet: keypair(2),
+            client: keypair(1),
+            requests: Default::default(),
+        });
+        tokio::spawn(relay.clone().serve(listener));
+
+        let mut uri_file = NamedTempFile::new().unwrap();
+        writeln!(
+            uri_file,
+            "nostr+walletconnect://{}?relay=ws%3A%2F%2F127.0.0.1%3A{port}&secret={}",
+            relay.wallet.x_only_public_key().0,
+            hex::encode(relay.client.secret_bytes()),
+        )
+        .unwrap();
+
+        let client = TungsteniteNwcClient::create(
+            Duration::from_secs(1),
+            NwcDiscoveryBackendImplementation {
+                auth: NwcClientAuth::Path(NwcClientAuthPath {
+                    ca_cert_path: None,
+                    connection_uri_path: uri_file.path().to_path_buf(),
+                }),
+            },
+            &[],
+            socks5_proxy,
+        )
+        .unwrap();
+
+        Self {
+            relay,
+            _uri_file: uri_file,
+            client: Arc::new(client),
+        }
+    }
+
+    fn requests(&self) -> Vec<(String, Value)> {
+        self.relay.requests.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn test_nwc_make_invoice_with_direct_into_hash_description() {
+    let stub = NwcStub::start(RelayMode::Respond).await;
+
+    let invoice = stub
+        .client
+        .get_invoice(
+            Some(1_000),
+            Bolt11InvoiceDescription::DirectIntoHash("[[\"text/plain\",\"hello\"]]"),
+            Some(600),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(invoice, "lnbcrt-1000");
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    let (method, params) = &requests[0];
+    assert_eq!(method, "make_invoice");
+    assert_eq!(params["amount"], json!(1_000));
+    assert_eq!(
+        params["description_hash"],
+        json!("f83358e5d63756be415a710bc68464504869ce79af486927e1d3e074459f25fb")
+    );
+    assert!(params.get("description").is_none());
+    assert_eq!(params["expiry"], json!(600));
+}
+
+#[tokio::test]
+async fn test_nwc_make_invoice_with_none_amount_unsupported() {
+    let stub = NwcStub::start(RelayMode::Respond).await;
+
+    let error = stub
+        .client
+        .get_invoice(None, Bolt11InvoiceDescription::Direct("hello"), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Downstream
+    );
+    assert!(stub.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_nwc_metrics_from_balance() {
+    let stub = NwcStub::start(RelayMode::Respond).await;
+
+    let metrics = stub.client.get_metrics().await.unwrap();
+
+    assert!(metrics.healthy);
+    assert_eq!(metrics.node_effective_inbound_msat, 21_000);
+    assert_eq!(stub.requests()[0].0, "get_balance");
+}
+
+#[tokio::test]
+async fn test_nwc_invoice_state() {
+    let stub = NwcStub::start(RelayMode::Respond).await;
+
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-pending", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Open
+    );
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-settled", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Settled
+    );
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-expired", &[0; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Canceled
+    );
+}
+
+#[tokio::test]
+async fn test_nwc_responses_correlated_by_request_event_id() {
+    let stub = NwcStub::start(RelayMode::Reverse(2)).await;
+
+    let first = {
+        let client = stub.client.clone();
+        tokio::spawn(async move {
+            client
+                .get_invoice(Some(1_000), Bolt11InvoiceDescription::Direct("a"), None)
+                .await
+        })
+    };
+    let second = {
+        let client = stub.client.clone();
+        tokio::spawn(async move {
+            client
+                .get_invoice(Some(2_000), Bolt11InvoiceDescription::Direct("b"), None)
+                .await
+        })
+    };
+
+    assert_eq!(first.await.unwrap().unwrap(), "lnbcrt-1000");
+    assert_eq!(second.await.unwrap().unwrap(), "lnbcrt-2000");
+}
+
+#[tokio::test]
+async fn test_nwc_when_wallet_silent_then_timeout() {
+    let stub = NwcStub::start(RelayMode::SilentBalance).await;
+
+    let start = Instant::now();
+    let error = stub.client.get_metrics().await.unwrap_err();
+
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert!(matches!(error.source(), LnPoolErrorSourceKind::Timeout(_)));
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Upstream
+    );
+
+    // the connection outlives the timed out request
+    let invoice = stub
+        .client
+        .get_invoice(Some(3_000), Bolt11InvoiceDescription::Direct("c"), None)
+        .await
+        .unwrap();
+    assert_eq!(invoice, "lnbcrt-3000");
+}
+
+#[tokio::test]
+async fn test_nwc_when_relay_rejects_then_upstream_error() {
+    let stub = NwcStub::start(RelayMode::Reject).await;
+
+    let error = stub.client.get_metrics().await.unwrap_err();
+
+    assert!(matches!(
+        error.source(),
+        LnPoolErrorSourceKind::RelayError(_)
+    ));
+    assert!(error.to_string().contains("blocked: not allowed"));
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Upstream
+    );
+}
+
+#[tokio::test]
+async fn test_nwc_when_socks5_proxy_then_relay_connected_through_proxy() {
+    let (proxy, destinations) = socks5_forwarding_proxy().await;
+    let stub = NwcStub::start_with_proxy(RelayMode::Respond, Some(&proxy)).await;
+
+    let metrics = stub.client.get_metrics().await.unwrap();
+
+    assert!(metrics.healthy);
+    let destinations = destinations.lock().unwrap().clone();
+    assert_eq!(destinations.len(), 1);
+    assert!(destinations[0].starts_with("127.0.0.1:"));
+}
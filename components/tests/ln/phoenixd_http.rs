@@ -0,0 +1,341 @@
+use axum::extract::{Path as AxumPath, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Form, Json, Router};
+use base64::Engine;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use switchgear_components::pool::phoenixd::http::client::ReqwestPhoenixdHttpClient;
+use switchgear_components::pool::phoenixd::http::config::{
+    PhoenixdHttpClientAuth, PhoenixdHttpClientAuthPath, PhoenixdHttpDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::{
+    Bolt11InvoiceDescription, DiscoveryBackendImplementation, LnClientPool, LnInvoiceState,
+    LnRpcClient,
+};
+use switchgear_service_api::discovery::{DiscoveryBackend, DiscoveryBackendSparse};
+use switchgear_service_api::service::{HasServiceErrorSource, ServiceErrorSource};
+use switchgear_testing::ports::PortAllocator;
+use tempfile::NamedTempFile;
+use tokio::net::TcpListener;
+use url::Url;
+
+const PASSWORD: &str = "c2b1e9a3f7d54e6b8a0f";
+
+#[derive(Clone)]
+struct PhoenixdStubState {
+    password: Arc<Mutex<String>>,
+    invoice_requests: Arc<Mutex<Vec<HashMap<String, String>>>>,
+}
+
+fn authorized(state: &PhoenixdStubState, headers: &HeaderMap) -> bool {
+    let password = state.password.lock().unwrap().clone();
+    let expected = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(format!(":{password}"))
+    );
+    headers.get("authorization").and_then(|a| a.to_str().ok()) == Some(expected.as_str())
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        "Invalid authentication (use basic auth with the http password set in phoenix.conf)",
+    )
+        .into_response()
+}
+
+async fn create_invoice(
+    State(state): State<PhoenixdStubState>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    if !authorized(&state, &headers) {
+        return unauthorized();
+    }
+    state.invoice_requests.lock().unwrap().push(form);
+    Json(json!({
+        "amountSat": 1,
+        "paymentHash": "00".repeat(32),
+        "serialized": "lnbcrt10n1phoenixd",
+    }))
+    .into_response()
+}
+
+async fn list_channels(State(state): State<PhoenixdStubState>, headers: HeaderMap) -> Response {
+    if !authorized(&state, &headers) {
+        return unauthorized();
+    }
+    Json(json!([
+        {"state": "Normal", "channelId": "a", "balanceSat": 10, "inboundLiquiditySat": 1000},
+        {"state": "Normal", "channelId": "b", "balanceSat": 10, "inboundLiquiditySat": 2500},
+        {"state": "Offline", "channelId": "c", "balanceSat": 10, "inboundLiquiditySat": 9000},
+    ]))
+    .into_response()
+}
+
+async fn incoming_payment(
+    State(state): State<PhoenixdStubState>,
+    headers: HeaderMap,
+    AxumPath(hash): AxumPath<String>,
+) -> Response {
+    if !authorized(&state, &headers) {
+        return unauthorized();
+    }
+    Json(json!({"paymentHash": hash, "isPaid": hash == "11".repeat(32)})).into_response()
+}
+
+struct PhoenixdStub {
+    state: PhoenixdStubState,
+    password_file: NamedTempFile,
+    implementation: PhoenixdHttpDiscoveryBackendImplementation,
+    client: ReqwestPhoenixdHttpClient,
+}
+
+impl PhoenixdStub {
+    async fn start(password: &str) -> Self {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let ports_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+        let port = PortAllocator::find_available_port(&ports_path).unwrap();
+        let listener = TcpListener::bind(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port))
+            .await
+            .unwrap();
+
+        let state = PhoenixdStubState {
+            password: Arc::new(Mutex::new(PASSWORD.to_string())),
+            invoice_requests: Default::default(),
+        };
+        let router = Router::new()
+            .route("/createinvoice", post(create_invoice))
+            .route("/listchannels", get(list_channels))
+            .route("/payments/incoming/{hash}", get(incoming_payment))
+            .with_state(state.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let mut password_file = NamedTempFile::new().unwrap();
+        writeln!(password_file, "{password}").unwrap();
+
+        let implementation = PhoenixdHttpDiscoveryBackendImplementation {
+            url: Url::parse(&format!("http://127.0.0.1:{port}")).unwrap(),
+            auth: PhoenixdHttpClientAuth::Path(PhoenixdHttpClientAuthPath {
+                ca_cert_path: None,
+                password_path: password_file.path().to_path_buf(),
+            }),
+        };
+        let client = ReqwestPhoenixdHttpClient::create(
+            Duration::from_secs(1),
+            implementation.clone(),
+            &[],
+            None,
+        )
+        .unwrap();
+
+        Self {
+            state,
+            password_file,
+            implementation,
+            client,
+        }
+    }
+
+    fn discovery_backend(&self) -> DiscoveryBackend {
+        DiscoveryBackend {
+            public_key: "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619"
+                .parse()
+                .unwrap(),
+            backend: DiscoveryBackendSparse {
+                name: None,
+                partitions: ["default".to_string()].into(),
+                weight: 1,
+                enabled: true,
+                region: None,
+                implementation: serde_json::to_vec(&DiscoveryBackendImplementation::PhoenixdHttp(
+                    self.implementation.clone(),
+                ))
+                .unwrap(),
+            },
+        }
+    }
+
+    fn rotate_server_password(&self, password: &str) {
+        *self.state.password.lock().unwrap() = password.to_string();
+    }
+
+    fn write_password_file(&self, password: &str) {
+        fs::write(self.password_file.path(), format!("{password}\n")).unwrap();
+    }
+
+    fn invoice_requests(&self) -> Vec<HashMap<String, String>> {
+        self.state.invoice_requests.lock().unwrap().clone()
+    }
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_invoice_with_hash_description() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+    let hash = [7u8; 32];
+
+    let invoice = stub
+        .client
+        .get_invoice(
+            Some(21_000),
+            Bolt11InvoiceDescription::Hash(&hash),
+            Some(600),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(invoice, "lnbcrt10n1phoenixd");
+    let request = &stub.invoice_requests()[0];
+    assert_eq!(request["descriptionHash"], hex::encode(hash));
+    assert_eq!(request["amountSat"], "21");
+    assert_eq!(request["expirySeconds"], "600");
+    assert!(!request.contains_key("description"));
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_invoice_with_direct_description_and_none_amount() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+
+    stub.client
+        .get_invoice(None, Bolt11InvoiceDescription::Direct("hello"), None)
+        .await
+        .unwrap();
+
+    let request = &stub.invoice_requests()[0];
+    assert_eq!(request["description"], "hello");
+    assert!(!request.contains_key("amountSat"));
+    assert!(!request.contains_key("expirySeconds"));
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_invoice_when_fractional_sats_then_internal_error() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+
+    let error = stub
+        .client
+        .get_invoice(Some(1_500), Bolt11InvoiceDescription::Direct("hello"), None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Internal
+    );
+    assert!(stub.invoice_requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_metrics_sums_normal_channels() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+
+    let metrics = stub.client.get_metrics().await.unwrap();
+
+    assert!(metrics.healthy);
+    assert_eq!(metrics.node_effective_inbound_msat, 3_500_000);
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_invoice_state() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-open", &[0x22; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Open
+    );
+    assert_eq!(
+        stub.client
+            .get_invoice_state("lnbcrt-paid", &[0x11; 32])
+            .await
+            .unwrap(),
+        LnInvoiceState::Settled
+    );
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_when_password_rejected_then_upstream_error() {
+    let stub = PhoenixdStub::start("wrong-password").await;
+
+    let error = stub.client.get_metrics().await.unwrap_err();
+
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Upstream
+    );
+    assert!(error.to_string().contains("Invalid authentication"));
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_pool_when_password_file_rotated_then_client_rebuilt() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+    let pool = LnClientPool::new(Duration::from_secs(1), vec![], HashMap::new(), None);
+    pool.connect(0, &stub.discovery_backend()).unwrap();
+    pool.get_metrics(&0).await.unwrap();
+
+    // a half finished rotation keeps the previous client and reports why
+    stub.write_password_file("not-yet-rotated-password");
+    pool.get_metrics(&0).await.unwrap();
+    let error = pool.get_credentials_error(&0).unwrap();
+    assert!(error.contains("Invalid authentication"), "{error}");
+
+    stub.rotate_server_password("rotated-password");
+    pool.get_metrics(&0).await.unwrap_err();
+
+    stub.write_password_file("rotated-password");
+    let metrics = pool.get_metrics(&0).await.unwrap();
+    assert_eq!(metrics.node_effective_inbound_msat, 3_500_000);
+    assert!(pool.get_credentials_error(&0).is_none());
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_pool_when_disconnected_then_client_and_metrics_evicted() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+    let pool = LnClientPool::new(Duration::from_secs(1), vec![], HashMap::new(), None);
+    pool.connect(0, &stub.discovery_backend()).unwrap();
+    pool.get_metrics(&0).await.unwrap();
+    assert!(pool.get_cached_metrics(&0).is_some());
+
+    pool.disconnect(&0).unwrap();
+
+    assert!(pool.get_cached_metrics(&0).is_none());
+    let error = pool.get_metrics(&0).await.unwrap_err();
+    assert_eq!(
+        error.get_service_error_source(),
+        ServiceErrorSource::Internal
+    );
+}
+
+#[tokio::test]
+async fn test_phoenixd_http_pool_when_implementation_has_secret_placeholders_then_expanded() {
+    let stub = PhoenixdStub::start(PASSWORD).await;
+    let mut backend = stub.discovery_backend();
+    let mut implementation: serde_json::Value =
+        serde_json::from_slice(&backend.backend.implementation).unwrap();
+    let password_path = implementation["auth"]["passwordPath"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    implementation["auth"]["passwordPath"] = json!("{secret.PHOENIXD_PASSWORD_PATH}");
+    backend.backend.implementation = serde_json::to_vec(&implementation).unwrap();
+
+    let pool = LnClientPool::new(Duration::from_secs(1), vec![], HashMap::new(), None);
+    let error = pool.connect(0, &backend).unwrap_err();
+    assert!(error.to_string().contains("Missing secrets"), "{error}");
+
+    let secrets = HashMap::from([("secret.PHOENIXD_PASSWORD_PATH".to_string(), password_path)]);
+    let pool = LnClientPool::new(Duration::from_secs(1), vec![], secrets, None);
+    pool.connect(0, &backend).unwrap();
+    let metrics = pool.get_metrics(&0).await.unwrap();
+    assert_eq!(metrics.node_effective_inbound_msat, 3_500_000);
+}
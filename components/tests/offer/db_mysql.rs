@@ -136,3 +136,21 @@ async fn test_mysql_delete_metadata_with_referencing_offers() {
     let (store, _guard) = create_mysql_store().await;
     offer::test_delete_metadata_with_referencing_offers(store).await;
 }
+
+#[tokio::test]
+async fn test_mysql_put_and_get_bolt12_offers() {
+    let (store, _guard) = create_mysql_store().await;
+    offer::test_put_and_get_bolt12_offers(store).await;
+}
+
+#[tokio::test]
+async fn test_mysql_put_bolt12_offer_with_missing_offer() {
+    let (store, _guard) = create_mysql_store().await;
+    offer::test_put_bolt12_offer_with_missing_offer(store).await;
+}
+
+#[tokio::test]
+async fn test_mysql_put_get_and_delete_hold_invoices() {
+    let (store, _guard) = create_mysql_store().await;
+    offer::test_put_get_and_delete_hold_invoices(store).await;
+}
@@ -145,3 +145,24 @@ async fn test_sqlite_delete_metadata_with_referencing_offers() {
     let store = create_sqlite_store(t.path()).await;
     offer::test_delete_metadata_with_referencing_offers(store).await;
 }
+
+#[tokio::test]
+async fn test_sqlite_put_and_get_bolt12_offers() {
+    let t = TempDir::new().unwrap();
+    let store = create_sqlite_store(t.path()).await;
+    offer::test_put_and_get_bolt12_offers(store).await;
+}
+
+#[tokio::test]
+async fn test_sqlite_put_bolt12_offer_with_missing_offer() {
+    let t = TempDir::new().unwrap();
+    let store = create_sqlite_store(t.path()).await;
+    offer::test_put_bolt12_offer_with_missing_offer(store).await;
+}
+
+#[tokio::test]
+async fn test_sqlite_put_get_and_delete_hold_invoices() {
+    let t = TempDir::new().unwrap();
+    let store = create_sqlite_store(t.path()).await;
+    offer::test_put_get_and_delete_hold_invoices(store).await;
+}
@@ -20,6 +20,7 @@ async fn create_http_store() -> (HttpOfferStore, mock_service::TestService) {
         Duration::from_secs(10),
         &[],
         test_service.offer_authorization.clone(),
+        None,
     )
     .unwrap();
 
@@ -165,3 +166,24 @@ async fn test_http_health() {
     store.health().await.unwrap();
     service.shutdown().await;
 }
+
+#[tokio::test]
+async fn test_http_put_and_get_bolt12_offers() {
+    let (store, service) = create_http_store().await;
+    offer::test_put_and_get_bolt12_offers(store).await;
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_http_put_bolt12_offer_with_missing_offer() {
+    let (store, service) = create_http_store().await;
+    offer::test_put_bolt12_offer_with_missing_offer(store).await;
+    service.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_http_put_get_and_delete_hold_invoices() {
+    let (store, service) = create_http_store().await;
+    offer::test_put_get_and_delete_hold_invoices(store).await;
+    service.shutdown().await;
+}
@@ -4,15 +4,20 @@ pub const DISCOVERY_BACKEND_GET_ALL_ETAG_ID: i32 = 1;
 
 mod m20220101_000001_create_table;
 mod m20250724_182058_create_table;
+mod m20261018_093000_add_region;
+mod m20261018_120000_add_hold_invoice;
+mod m20261018_150000_create_bolt12_offer_table;
+mod m20261018_190000_create_hold_invoice_table;
 
 pub struct DiscoveryBackendMigrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for DiscoveryBackendMigrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(
-            m20220101_000001_create_table::DiscoveryBackendMigration,
-        )]
+        vec![
+            Box::new(m20220101_000001_create_table::DiscoveryBackendMigration),
+            Box::new(m20261018_093000_add_region::DiscoveryBackendRegionMigration),
+        ]
     }
 }
 
@@ -21,6 +26,11 @@ pub struct OfferMigrator;
 #[async_trait::async_trait]
 impl MigratorTrait for OfferMigrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20250724_182058_create_table::OfferMigration)]
+        vec![
+            Box::new(m20250724_182058_create_table::OfferMigration),
+            Box::new(m20261018_120000_add_hold_invoice::OfferHoldInvoiceMigration),
+            Box::new(m20261018_150000_create_bolt12_offer_table::OfferBolt12Migration),
+            Box::new(m20261018_190000_create_hold_invoice_table::OfferHoldInvoiceTableMigration),
+        ]
     }
 }
@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct DiscoveryBackendRegionMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for DiscoveryBackendRegionMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DiscoveryBackend::Table)
+                    .add_column(string_null(DiscoveryBackend::Region))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DiscoveryBackend::Table)
+                    .drop_column(DiscoveryBackend::Region)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DiscoveryBackend {
+    Table,
+    Region,
+}
@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct OfferHoldInvoiceMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for OfferHoldInvoiceMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OfferRecordTable::Table)
+                    .add_column(boolean(OfferRecordTable::HoldInvoice).default(false))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OfferRecordTable::Table)
+                    .drop_column(OfferRecordTable::HoldInvoice)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OfferRecordTable {
+    Table,
+    HoldInvoice,
+}
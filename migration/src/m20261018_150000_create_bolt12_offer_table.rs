@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct OfferBolt12Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for OfferBolt12Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Bolt12OfferTable::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::Partition)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Bolt12OfferTable::Id).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::PublicKey)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::OfferId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Bolt12OfferTable::Bolt12).text().not_null())
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::AmountMsat)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::Description)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::Expires)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Bolt12OfferTable::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(Bolt12OfferTable::Partition)
+                            .col(Bolt12OfferTable::Id)
+                            .col(Bolt12OfferTable::PublicKey),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                Bolt12OfferTable::Table,
+                                (Bolt12OfferTable::Partition, Bolt12OfferTable::Id),
+                            )
+                            .to(
+                                OfferRecordTable::Table,
+                                (OfferRecordTable::Partition, OfferRecordTable::Id),
+                            )
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Bolt12OfferTable::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Bolt12OfferTable {
+    Table,
+    Partition,
+    Id,
+    PublicKey,
+    OfferId,
+    Bolt12,
+    AmountMsat,
+    Description,
+    Expires,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OfferRecordTable {
+    Table,
+    Partition,
+    Id,
+}
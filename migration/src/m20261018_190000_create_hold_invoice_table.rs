@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct OfferHoldInvoiceTableMigration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for OfferHoldInvoiceTableMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(HoldInvoiceTable::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(HoldInvoiceTable::PaymentHash)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(HoldInvoiceTable::PublicKey)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(HoldInvoiceTable::Preimage)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(HoldInvoiceTable::Invoice).text().not_null())
+                    .col(
+                        ColumnDef::new(HoldInvoiceTable::CancelAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(HoldInvoiceTable::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(HoldInvoiceTable::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(HoldInvoiceTable::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum HoldInvoiceTable {
+    Table,
+    PaymentHash,
+    PublicKey,
+    Preimage,
+    Invoice,
+    CancelAt,
+    CreatedAt,
+    UpdatedAt,
+}
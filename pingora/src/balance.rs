@@ -1,19 +1,38 @@
 use crate::error::PingoraLnError;
+use crate::hold::PingoraLnHoldInvoices;
+use crate::outlier::PingoraLnOutlierDetection;
+use crate::refresh::PingoraLnForcedRefresh;
+use crate::reservation::PingoraLnInvoiceReservations;
+use crate::status::PingoraLnBackendStatusRegistry;
 use crate::PingoraBackoffProvider;
 use crate::{PingoraLnBackendExtension, PingoraLnClientPool, PingoraLnMetricsCache};
 use async_trait::async_trait;
 use backoff::backoff::Backoff;
-use log::{error, warn};
+use log::{debug, error, info, warn};
+use pingora_core::protocols::l4::socket::SocketAddr;
 use pingora_core::services::background::BackgroundService;
 use pingora_load_balancing::selection::{BackendIter, BackendSelection};
 use pingora_load_balancing::{Backend, LoadBalancer};
+use rand::Rng;
+use secp256k1::PublicKey;
+use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::sync::Arc;
-use switchgear_service_api::balance::{LnBalancer, LnBalancerBackgroundServices};
-use switchgear_service_api::offer::Offer;
-use switchgear_service_api::service::{HasServiceErrorSource, ServiceErrorSource};
+use std::time::{Duration, Instant};
+use switchgear_service_api::balance::{
+    IssuedInvoice, LnBackendMetrics, LnBackendStatus, LnBalancer, LnBalancerBackgroundServices,
+};
+use switchgear_service_api::offer::{
+    Bolt12OfferRecord, Bolt12OfferSparse, HoldInvoiceRecord, HoldInvoiceStore, Offer,
+};
+use switchgear_service_api::service::{
+    HasServiceErrorSource, ServiceErrorRetry, ServiceErrorSource,
+};
 use tokio::sync::watch::Receiver;
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout_at};
+
+// how often hold invoices are checked against their cancel deadline
+const HOLD_INVOICE_SWEEP_INTERVAL: Duration = Duration::from_secs(10);
 
 pub trait MaxIterations: Clone + Send + Sync {
     fn max_iterations(&self, backends: usize) -> usize;
@@ -59,12 +78,13 @@ impl MaxIterations for ConsistentMaxIterations {
     }
 }
 
-pub struct PingoraLnBalancer<S, P, M, B, X>
+pub struct PingoraLnBalancer<S, P, M, B, X, H>
 where
     P: Clone,
     M: Clone,
     B: PingoraBackoffProvider,
     X: MaxIterations,
+    H: Clone,
 {
     load_balancer: Arc<LoadBalancer<S>>,
     backoff_provider: B,
@@ -73,14 +93,20 @@ where
     select_max_iterations: X,
     parallel_health_check: bool,
     selection_capacity_bias: Option<f64>,
+    status: PingoraLnBackendStatusRegistry,
+    reservations: PingoraLnInvoiceReservations,
+    holds: Option<PingoraLnHoldInvoices<H>>,
+    outlier_detection: Option<PingoraLnOutlierDetection>,
+    forced_refresh: PingoraLnForcedRefresh,
 }
 
-impl<S, P, M, B, X> Clone for PingoraLnBalancer<S, P, M, B, X>
+impl<S, P, M, B, X, H> Clone for PingoraLnBalancer<S, P, M, B, X, H>
 where
     P: Clone,
     M: Clone,
     B: PingoraBackoffProvider,
     X: MaxIterations,
+    H: Clone,
 {
     fn clone(&self) -> Self {
         Self {
@@ -91,11 +117,16 @@ where
             metrics: self.metrics.clone(),
             parallel_health_check: self.parallel_health_check,
             selection_capacity_bias: self.selection_capacity_bias,
+            status: self.status.clone(),
+            reservations: self.reservations.clone(),
+            holds: self.holds.clone(),
+            outlier_detection: self.outlier_detection.clone(),
+            forced_refresh: self.forced_refresh.clone(),
         }
     }
 }
 
-impl<S, P, M, B, X> PingoraLnBalancer<S, P, M, B, X>
+impl<S, P, M, B, X, H> PingoraLnBalancer<S, P, M, B, X, H>
 where
     S: BackendSelection + 'static,
     S::Iter: BackendIter,
@@ -104,7 +135,9 @@ where
     M: PingoraLnMetricsCache<Key = Backend> + Clone,
     B: PingoraBackoffProvider,
     X: MaxIterations,
+    H: HoldInvoiceStore + Clone,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         load_balancer: Arc<LoadBalancer<S>>,
         pool: P,
@@ -113,6 +146,11 @@ where
         select_max_iterations: X,
         parallel_health_check: bool,
         selection_capacity_bias: Option<f64>,
+        status: PingoraLnBackendStatusRegistry,
+        reservations: PingoraLnInvoiceReservations,
+        holds: Option<PingoraLnHoldInvoices<H>>,
+        outlier_detection: Option<PingoraLnOutlierDetection>,
+        forced_refresh: PingoraLnForcedRefresh,
     ) -> Self {
         Self {
             load_balancer,
@@ -122,7 +160,44 @@ where
             select_max_iterations,
             parallel_health_check,
             selection_capacity_bias,
+            status,
+            reservations,
+            holds,
+            outlier_detection,
+            forced_refresh,
+        }
+    }
+
+    fn select_preferred_backend(
+        &self,
+        offer: &Offer,
+        amount_msat: u64,
+        key: &[u8],
+        current_selection_capacity_bias: Option<f64>,
+        preferred_regions: &[String],
+        excluded: &[SocketAddr],
+    ) -> Option<Backend> {
+        if !preferred_regions.is_empty() {
+            let backend = self.select_backend(
+                offer,
+                amount_msat,
+                key,
+                current_selection_capacity_bias,
+                Some(preferred_regions),
+                excluded,
+            );
+            if backend.is_some() {
+                return backend;
+            }
         }
+        self.select_backend(
+            offer,
+            amount_msat,
+            key,
+            current_selection_capacity_bias,
+            None,
+            excluded,
+        )
     }
 
     fn select_backend(
@@ -131,23 +206,47 @@ where
         amount_msat: u64,
         key: &[u8],
         current_selection_capacity_bias: Option<f64>,
+        regions: Option<&[String]>,
+        excluded: &[SocketAddr],
     ) -> Option<Backend> {
         let select_max_iterations = self
             .select_max_iterations
             .max_iterations(self.load_balancer.backends().get_backend().len());
         self.load_balancer
             .select_with(key, select_max_iterations, |backend, health| {
-                if !health {
+                if !health || excluded.contains(&backend.addr) {
+                    return false;
+                }
+                if offer.hold_invoice && !self.pool.supports_hold_invoice(backend) {
+                    return false;
+                }
+                if self
+                    .outlier_detection
+                    .as_ref()
+                    .is_some_and(|outlier_detection| outlier_detection.is_ejected(backend))
+                {
                     return false;
                 }
                 if let Some(extension) = backend.ext.get::<PingoraLnBackendExtension>() {
+                    if let Some(regions) = regions {
+                        if !extension
+                            .region
+                            .as_ref()
+                            .is_some_and(|region| regions.contains(region))
+                        {
+                            return false;
+                        }
+                    }
                     if extension.partitions.contains(&offer.partition) {
                         if let Some(metrics) = self.metrics.get_cached_metrics(backend) {
                             if let Some(current_selection_capacity_bias) =
                                 current_selection_capacity_bias
                             {
+                                let available_inbound_msat = metrics
+                                    .node_effective_inbound_msat
+                                    .saturating_sub(self.reservations.reserved_msat(backend));
                                 if amount_msat as f64
-                                    <= metrics.node_effective_inbound_msat as f64
+                                    <= available_inbound_msat as f64
                                         * (1.0 + current_selection_capacity_bias)
                                 {
                                     return true;
@@ -162,100 +261,251 @@ where
             })
     }
 
+    // Bolt12 offers carry no invoice amount, so capacity is not considered
+    fn select_bolt12_backend(
+        &self,
+        offer: &Offer,
+        public_keys: Option<&[PublicKey]>,
+    ) -> Option<Backend> {
+        let select_max_iterations = self
+            .select_max_iterations
+            .max_iterations(self.load_balancer.backends().get_backend().len());
+        self.load_balancer.select_with(
+            offer.id.as_bytes(),
+            select_max_iterations,
+            |backend, health| {
+                health
+                    && self.pool.supports_bolt12(backend)
+                    && !self
+                        .outlier_detection
+                        .as_ref()
+                        .is_some_and(|outlier_detection| outlier_detection.is_ejected(backend))
+                    && backend
+                        .ext
+                        .get::<PingoraLnBackendExtension>()
+                        .is_some_and(|extension| extension.partitions.contains(&offer.partition))
+                    && public_keys.is_none_or(|public_keys| {
+                        self.status
+                            .get(backend)
+                            .is_some_and(|entry| public_keys.contains(&entry.backend.public_key))
+                    })
+            },
+        )
+    }
+
     async fn get_invoice_from_backend(
         &self,
         offer: &Offer,
         amount_msat: u64,
         expiry_secs: u64,
         backend: &Backend,
-    ) -> Result<String, PingoraLnError> {
-        let invoice = self
-            .pool
-            .get_invoice(offer, backend, amount_msat.into(), expiry_secs.into())
-            .await
-            .map_err(|e| {
-                PingoraLnError::from_service_error(
-                    format!("get invoice for offer {}/{}", offer.partition, offer.id),
-                    e,
-                )
-            })?;
+    ) -> Result<IssuedInvoice, PingoraLnError> {
+        // the preimage only leaves Switchgear when the hold invoice is settled
+        let holds = self.holds.as_ref().filter(|_| offer.hold_invoice);
+        let preimage = holds.map(|_| rand::thread_rng().gen::<[u8; 32]>());
+
+        let start = Instant::now();
+        let invoice = match &preimage {
+            Some(preimage) => {
+                self.pool
+                    .get_hold_invoice(
+                        offer,
+                        backend,
+                        amount_msat.into(),
+                        expiry_secs.into(),
+                        &Sha256::digest(preimage).into(),
+                    )
+                    .await
+            }
+            None => {
+                self.pool
+                    .get_invoice(offer, backend, amount_msat.into(), expiry_secs.into())
+                    .await
+            }
+        };
+        self.status.observe_invoice(
+            backend,
+            start.elapsed(),
+            invoice.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+        );
+
+        if let (Some(outlier_detection), Some(extension)) = (
+            &self.outlier_detection,
+            backend.ext.get::<PingoraLnBackendExtension>(),
+        ) {
+            // fatal errors are caused by the request, not the backend
+            let success = invoice.as_ref().map_or_else(
+                |e| e.get_service_error_retry() == ServiceErrorRetry::Fatal,
+                |_| true,
+            );
+            outlier_detection.observe(
+                backend,
+                &extension.partitions,
+                success,
+                &self.load_balancer.backends().get_backend(),
+            );
+        }
+
+        let invoice = invoice.map_err(|e| {
+            PingoraLnError::from_service_error(
+                format!("get invoice for offer {}/{}", offer.partition, offer.id),
+                e,
+            )
+        })?;
+
+        if let (Some(holds), Some(preimage)) = (holds, preimage) {
+            let public_key = self
+                .status
+                .get(backend)
+                .map(|entry| entry.backend.public_key)
+                .ok_or_else(|| {
+                    PingoraLnError::general_error(
+                        ServiceErrorSource::Internal,
+                        format!(
+                            "get hold invoice for offer {}/{}",
+                            offer.partition, offer.id
+                        ),
+                        format!("backend {} has no status", backend.addr),
+                    )
+                })?;
+            let payment_hash: [u8; 32] = Sha256::digest(preimage).into();
+            if let Err(e) = holds
+                .hold(payment_hash, public_key, preimage, invoice.invoice.clone())
+                .await
+            {
+                // without the stored preimage the hold could never be settled, so it is not handed out
+                if let Err(err) = self.pool.cancel_hold_invoice(backend, &payment_hash).await {
+                    error!(
+                        "canceling hold invoice {} that could not be stored: {err}",
+                        hex::encode(payment_hash)
+                    );
+                }
+                return Err(e);
+            }
+        }
+
+        self.reservations.reserve(backend, &invoice, amount_msat);
 
         Ok(invoice)
     }
-}
-
-#[async_trait]
-impl<S, P, M, B, X> LnBalancer for PingoraLnBalancer<S, P, M, B, X>
-where
-    S: BackendSelection + Send + Sync + 'static,
-    S::Iter: BackendIter,
-    P: PingoraLnClientPool<Key = Backend> + Send + Sync + Clone + 'static,
-    P::Error: Error + Send + Sync + 'static + HasServiceErrorSource,
-    M: PingoraLnMetricsCache<Key = Backend> + Send + Sync + Clone + 'static,
-    B: PingoraBackoffProvider + Send + Sync + 'static,
-    X: MaxIterations,
-{
-    type Error = PingoraLnError;
 
-    async fn get_invoice(
+    async fn get_invoice_with_retries(
         &self,
         offer: &Offer,
         amount_msat: u64,
         expiry_secs: u64,
         key: &[u8],
-    ) -> Result<String, Self::Error> {
+        preferred_regions: &[String],
+    ) -> Result<IssuedInvoice, PingoraLnError>
+    where
+        S: Send + Sync,
+    {
         let mut backoff = self.backoff_provider.get_backoff();
+        let mut attempts = 0;
         let mut current_selection_capacity_bias = self.selection_capacity_bias;
+        // addresses of backends that failed this request, skipped while others are available.
+        // keyed by address since a backend's weight can change between discovery updates
+        let mut excluded: Vec<SocketAddr> = Vec::new();
+        // a backend with a transient failure is retried once before it is excluded
+        let mut retry_backend: Option<Backend> = None;
 
         loop {
-            let invoice =
-                self.select_backend(offer, amount_msat, key, current_selection_capacity_bias);
-            if current_selection_capacity_bias.is_some() {
-                current_selection_capacity_bias = None;
-                if invoice.is_none() {
-                    continue;
+            let invoice = match retry_backend.clone() {
+                Some(backend) => Some(backend),
+                None => {
+                    let invoice = self.select_preferred_backend(
+                        offer,
+                        amount_msat,
+                        key,
+                        current_selection_capacity_bias,
+                        preferred_regions,
+                        &excluded,
+                    );
+                    if current_selection_capacity_bias.is_some() {
+                        current_selection_capacity_bias = None;
+                        if invoice.is_none() {
+                            continue;
+                        }
+                    }
+                    if invoice.is_none() && !excluded.is_empty() {
+                        excluded.clear();
+                        continue;
+                    }
+                    invoice
                 }
-            }
+            };
 
-            let invoice = match invoice.ok_or_else(|| {
+            let (invoice, backend) = match invoice.ok_or_else(|| {
                 PingoraLnError::no_available_nodes(
                     ServiceErrorSource::Upstream,
                     format!("load balancing invoice request for offer {offer:?}"),
                 )
             }) {
                 Ok(backend) => {
-                    self.get_invoice_from_backend(offer, amount_msat, expiry_secs, &backend)
-                        .await
+                    attempts += 1;
+                    (
+                        self.get_invoice_from_backend(offer, amount_msat, expiry_secs, &backend)
+                            .await,
+                        Some(backend),
+                    )
                 }
-                Err(e) => Err(e),
+                Err(e) => (Err(e), None),
             };
 
             match invoice {
-                Ok(invoice) => return Ok(invoice),
+                Ok(invoice) => {
+                    debug!(
+                        "issued invoice {} for offer {}/{} from {} after {attempts} attempts",
+                        hex::encode(invoice.payment_hash),
+                        offer.partition,
+                        offer.id,
+                        invoice.public_key
+                    );
+                    return Ok(IssuedInvoice {
+                        attempts,
+                        ..invoice
+                    });
+                }
                 Err(e) => {
-                    if ServiceErrorSource::Downstream == e.esource() {
-                        return Err(e);
-                    } else {
-                        match backoff.next_backoff() {
-                            Some(duration) => {
-                                warn!(
-                                    "error retrieving invoice: {e}, retrying in {}s",
-                                    duration.as_secs()
-                                );
-                                tokio::join!(sleep(duration), async {
-                                    if let Err(e) = self.load_balancer.update().await {
-                                        error!("Error updating load balancer discovery: {e}");
+                    let retried_backend = retry_backend.take();
+                    match (e.retry(), backend) {
+                        (ServiceErrorRetry::Fatal, _) => return Err(e),
+                        (ServiceErrorRetry::SameNode, Some(backend))
+                            if retried_backend
+                                .as_ref()
+                                .is_none_or(|retried| retried.addr != backend.addr) =>
+                        {
+                            retry_backend = Some(backend);
+                        }
+                        (_, Some(backend)) => excluded.push(backend.addr),
+                        (_, None) => {}
+                    }
+                    match backoff.next_backoff() {
+                        Some(duration) => {
+                            warn!(
+                                "error retrieving invoice: {e}, retrying in {}s",
+                                duration.as_secs()
+                            );
+                            tokio::join!(
+                                sleep(duration),
+                                self.forced_refresh.refresh(|| {
+                                    let load_balancer = self.load_balancer.clone();
+                                    let parallel_health_check = self.parallel_health_check;
+                                    async move {
+                                        if let Err(e) = load_balancer.update().await {
+                                            error!("Error updating load balancer discovery: {e}");
+                                        }
+                                        load_balancer
+                                            .backends()
+                                            .run_health_check(parallel_health_check)
+                                            .await
                                     }
-                                    self.load_balancer
-                                        .backends()
-                                        .run_health_check(self.parallel_health_check)
-                                        .await
-                                });
-                            }
-                            None => {
-                                error!("Too many retries, giving up: {e}");
-                                return Err(e);
-                            }
+                                })
+                            );
+                        }
+                        None => {
+                            error!("Too many retries, giving up: {e}");
+                            return Err(e);
                         }
                     }
                 }
@@ -263,6 +513,259 @@ where
         }
     }
 
+    fn find_backend(&self, public_key: &PublicKey) -> Option<Backend> {
+        self.load_balancer
+            .backends()
+            .get_backend()
+            .iter()
+            .find(|backend| {
+                self.status
+                    .get(backend)
+                    .is_some_and(|entry| &entry.backend.public_key == public_key)
+            })
+            .cloned()
+    }
+
+    async fn resolve_hold_invoice(
+        &self,
+        hold: HoldInvoiceRecord,
+        settle: bool,
+    ) -> Result<(), PingoraLnError> {
+        let context = format!(
+            "{} hold invoice {}",
+            if settle { "settle" } else { "cancel" },
+            hex::encode(hold.payment_hash)
+        );
+
+        let r = match self.find_backend(&hold.hold.public_key) {
+            Some(backend) => {
+                let r = if settle {
+                    self.pool
+                        .settle_hold_invoice(&backend, &hold.hold.preimage)
+                        .await
+                } else {
+                    self.pool
+                        .cancel_hold_invoice(&backend, &hold.payment_hash)
+                        .await
+                };
+                r.map(|_| backend)
+                    .map_err(|e| PingoraLnError::from_service_error(context, e))
+            }
+            None => Err(PingoraLnError::no_available_nodes(
+                ServiceErrorSource::Upstream,
+                context,
+            )),
+        };
+
+        match r {
+            Ok(backend) => {
+                self.reservations.release(&backend, &hold.payment_hash);
+                Ok(())
+            }
+            Err(e) => {
+                // kept so the request can be retried and the sweep still cancels it eventually
+                let payment_hash = hold.payment_hash;
+                let restored = match &self.holds {
+                    Some(holds) => holds.restore(hold).await,
+                    None => Ok(()),
+                };
+                if let Err(err) = restored {
+                    error!(
+                        "restoring hold invoice {}: {err}",
+                        hex::encode(payment_hash)
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn cancel_due_hold_invoices(&self) {
+        let Some(holds) = &self.holds else {
+            return;
+        };
+        let holds = match holds.take_due().await {
+            Ok(holds) => holds,
+            Err(e) => {
+                error!("loading unclaimed hold invoices: {e}");
+                return;
+            }
+        };
+        for hold in holds {
+            let payment_hash = hold.payment_hash;
+            match self.resolve_hold_invoice(hold, false).await {
+                Ok(()) => info!(
+                    "canceled unclaimed hold invoice {}",
+                    hex::encode(payment_hash)
+                ),
+                Err(e) => error!("canceling unclaimed hold invoice: {e}"),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S, P, M, B, X, H> LnBalancer for PingoraLnBalancer<S, P, M, B, X, H>
+where
+    S: BackendSelection + Send + Sync + 'static,
+    S::Iter: BackendIter,
+    P: PingoraLnClientPool<Key = Backend> + Send + Sync + Clone + 'static,
+    P::Error: Error + Send + Sync + 'static + HasServiceErrorSource,
+    M: PingoraLnMetricsCache<Key = Backend> + Send + Sync + Clone + 'static,
+    B: PingoraBackoffProvider + Send + Sync + 'static,
+    X: MaxIterations,
+    H: HoldInvoiceStore + Clone + Send + Sync + 'static,
+{
+    type Error = PingoraLnError;
+
+    async fn get_invoice(
+        &self,
+        offer: &Offer,
+        amount_msat: u64,
+        expiry_secs: u64,
+        key: &[u8],
+        preferred_regions: &[String],
+        deadline: std::time::Instant,
+    ) -> Result<IssuedInvoice, Self::Error> {
+        if offer.hold_invoice && self.holds.is_none() {
+            return Err(PingoraLnError::general_error(
+                ServiceErrorSource::Internal,
+                format!(
+                    "load balancing invoice request for offer {}/{}",
+                    offer.partition, offer.id
+                ),
+                "hold invoices are not configured".to_string(),
+            ));
+        }
+
+        // dropping the retries also cancels the node request in flight, as does the client
+        // disconnecting, which drops this future
+        timeout_at(
+            deadline.into(),
+            self.get_invoice_with_retries(offer, amount_msat, expiry_secs, key, preferred_regions),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(PingoraLnError::deadline_exceeded(
+                ServiceErrorSource::Upstream,
+                format!(
+                    "load balancing invoice request for offer {}/{}",
+                    offer.partition, offer.id
+                ),
+            ))
+        })
+    }
+
+    async fn settle_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        let Some(holds) = &self.holds else {
+            return Ok(false);
+        };
+        match holds.take(payment_hash).await? {
+            Some(hold) => self.resolve_hold_invoice(hold, true).await.map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        let Some(holds) = &self.holds else {
+            return Ok(false);
+        };
+        match holds.take(payment_hash).await? {
+            Some(hold) => self.resolve_hold_invoice(hold, false).await.map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        offer: &Offer,
+        bolt12_offers: &[Bolt12OfferRecord],
+    ) -> Result<Bolt12OfferRecord, Self::Error> {
+        let context = format!(
+            "get Bolt12 offer for offer {}/{}",
+            offer.partition, offer.id
+        );
+        // a Bolt12 offer can't enforce an amount range, only fixed amount offers are offered
+        let amount_msat = offer.bolt12_amount_msat().ok_or_else(|| {
+            PingoraLnError::general_error(
+                ServiceErrorSource::Downstream,
+                context.clone(),
+                format!(
+                    "Bolt12 offers require a fixed amount, offer has range [{}, {}]",
+                    offer.min_sendable, offer.max_sendable
+                ),
+            )
+        })?;
+        let description = offer.bolt12_description().map_err(|e| {
+            PingoraLnError::general_error(
+                ServiceErrorSource::Internal,
+                context.clone(),
+                format!("parsing offer metadata: {e}"),
+            )
+        })?;
+
+        // Bolt12 offers created before the offer record changed are replaced
+        let reusable = bolt12_offers
+            .iter()
+            .filter(|bolt12_offer| {
+                bolt12_offer.offer.amount_msat == amount_msat
+                    && bolt12_offer.offer.description == description
+                    && bolt12_offer.offer.expires == offer.expires
+            })
+            .collect::<Vec<_>>();
+        let public_keys = reusable
+            .iter()
+            .map(|bolt12_offer| bolt12_offer.public_key)
+            .collect::<Vec<_>>();
+
+        let backend = self
+            .select_bolt12_backend(offer, Some(&public_keys))
+            .or_else(|| self.select_bolt12_backend(offer, None))
+            .ok_or_else(|| {
+                PingoraLnError::no_available_nodes(ServiceErrorSource::Upstream, context.clone())
+            })?;
+        let public_key = self
+            .status
+            .get(&backend)
+            .map(|entry| entry.backend.public_key)
+            .ok_or_else(|| {
+                PingoraLnError::general_error(
+                    ServiceErrorSource::Internal,
+                    context.clone(),
+                    format!("backend {} has no status", backend.addr),
+                )
+            })?;
+
+        if let Some(bolt12_offer) = reusable
+            .into_iter()
+            .find(|bolt12_offer| bolt12_offer.public_key == public_key)
+        {
+            return Ok(bolt12_offer.clone());
+        }
+
+        let absolute_expiry = offer
+            .expires
+            .map(|expires| expires.timestamp().max(0) as u64);
+        let bolt12_offer = self
+            .pool
+            .get_bolt12_offer(&backend, amount_msat, &description, absolute_expiry)
+            .await
+            .map_err(|e| PingoraLnError::from_service_error(context, e))?;
+
+        Ok(Bolt12OfferRecord {
+            partition: offer.partition.clone(),
+            id: offer.id,
+            public_key,
+            offer: Bolt12OfferSparse {
+                offer_id: bolt12_offer.offer_id,
+                bolt12: bolt12_offer.bolt12,
+                amount_msat,
+                description,
+                expires: offer.expires,
+            },
+        })
+    }
+
     async fn health(&self) -> Result<(), Self::Error> {
         let select_max_iterations = self
             .select_max_iterations
@@ -275,10 +778,44 @@ where
 
         Ok(())
     }
+
+    async fn status(&self) -> Result<Vec<LnBackendStatus>, Self::Error> {
+        let mut statuses = self
+            .load_balancer
+            .backends()
+            .get_backend()
+            .iter()
+            .filter_map(|backend| {
+                let entry = self.status.get(backend)?;
+                Some(LnBackendStatus {
+                    public_key: entry.backend.public_key,
+                    name: entry.backend.backend.name,
+                    partitions: entry.backend.backend.partitions,
+                    enabled: entry.backend.backend.enabled,
+                    healthy: entry.healthy,
+                    consecutive_success: entry.consecutive_success,
+                    consecutive_failure: entry.consecutive_failure,
+                    metrics: self.metrics.get_cached_metrics(backend).map(|metrics| {
+                        LnBackendMetrics {
+                            healthy: metrics.healthy,
+                            node_effective_inbound_msat: metrics.node_effective_inbound_msat,
+                        }
+                    }),
+                    last_error: entry.last_error,
+                    last_invoice_latency_ms: entry
+                        .last_invoice_latency
+                        .map(|latency| latency.as_millis() as u64),
+                })
+            })
+            .collect::<Vec<_>>();
+        statuses.sort();
+
+        Ok(statuses)
+    }
 }
 
 #[async_trait]
-impl<S, P, M, B, X> LnBalancerBackgroundServices for PingoraLnBalancer<S, P, M, B, X>
+impl<S, P, M, B, X, H> LnBalancerBackgroundServices for PingoraLnBalancer<S, P, M, B, X, H>
 where
     S: BackendSelection + Send + Sync + 'static,
     S::Iter: BackendIter,
@@ -287,9 +824,20 @@ where
     M: PingoraLnMetricsCache<Key = Backend> + Send + Sync + Clone + 'static,
     B: PingoraBackoffProvider + Send + Sync + 'static,
     X: MaxIterations,
+    H: HoldInvoiceStore + Clone + Send + Sync + 'static,
 {
     async fn start(&self, shutdown_rx: Receiver<bool>) {
-        self.load_balancer.start(shutdown_rx).await
+        let mut sweep_shutdown_rx = shutdown_rx.clone();
+        let sweep = async {
+            let mut interval = tokio::time::interval(HOLD_INVOICE_SWEEP_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => self.cancel_due_hold_invoices().await,
+                    _ = sweep_shutdown_rx.changed() => break,
+                }
+            }
+        };
+        tokio::join!(self.load_balancer.start(shutdown_rx), sweep);
     }
 }
 
@@ -297,25 +845,88 @@ where
 mod tests {
     use super::*;
     use crate::backoff::StopBackoffProvider;
-    use crate::{PingoraLnBackendExtension, PingoraLnMetrics};
+    use crate::error::PingoraLnErrorSourceKind;
+    use crate::{
+        PingoraLnBackendExtension, PingoraLnBolt12Offer, PingoraLnInvoiceState, PingoraLnMetrics,
+    };
     use async_trait::async_trait;
     use pingora_error::Result as PingoraResult;
     use pingora_load_balancing::discovery::ServiceDiscovery;
     use pingora_load_balancing::health_check::HealthCheck;
     use pingora_load_balancing::selection::RoundRobin;
     use pingora_load_balancing::{Backends, LoadBalancer};
-    use std::collections::{BTreeSet, HashMap};
+    use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
     use std::hash::{DefaultHasher, Hash, Hasher};
     use std::sync::{Arc, Mutex};
+    use switchgear_components::offer::memory::MemoryOfferStore;
+    use switchgear_components::pool::phoenixd::http::client::ReqwestPhoenixdHttpClient;
     use switchgear_service_api::balance::LnBalancer;
-    use switchgear_service_api::discovery::DiscoveryBackend;
-    use switchgear_service_api::service::ServiceErrorSource;
+    use switchgear_service_api::discovery::{DiscoveryBackend, DiscoveryBackendSparse};
+    use thiserror::Error;
     use uuid::Uuid;
 
     #[derive(Clone)]
     struct MockLnClientPool {
         should_succeed: bool,
         backend_specific_response: bool,
+        hold_calls: Arc<Mutex<Vec<String>>>,
+        // failures returned by get_invoice per backend address, in order
+        invoice_failures: Arc<Mutex<HashMap<String, VecDeque<ServiceErrorRetry>>>>,
+        invoice_calls: Arc<Mutex<Vec<String>>>,
+        // backend addresses without hold invoice support, like CLN gRPC
+        hold_unsupported: Arc<Mutex<Vec<String>>>,
+        // backend addresses checking amounts like phoenixd, which only accepts whole satoshis
+        phoenixd: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[derive(Error, Debug)]
+    #[error("mock node error")]
+    struct MockNodeError(ServiceErrorRetry);
+
+    impl HasServiceErrorSource for MockNodeError {
+        fn get_service_error_source(&self) -> ServiceErrorSource {
+            ServiceErrorSource::Upstream
+        }
+
+        fn get_service_error_retry(&self) -> ServiceErrorRetry {
+            self.0
+        }
+    }
+
+    #[derive(Clone)]
+    struct RetriesBackoffProvider(usize, Duration);
+
+    struct RetriesBackoff(usize, Duration);
+
+    impl Backoff for RetriesBackoff {
+        fn next_backoff(&mut self) -> Option<Duration> {
+            self.0 = self.0.checked_sub(1)?;
+            Some(self.1)
+        }
+    }
+
+    impl PingoraBackoffProvider for RetriesBackoffProvider {
+        type Item = RetriesBackoff;
+
+        fn get_backoff(&self) -> Self::Item {
+            RetriesBackoff(self.0, self.1)
+        }
+    }
+
+    fn mock_issued_invoice(invoice: String) -> IssuedInvoice {
+        let secp = secp256k1::Secp256k1::new();
+        let secret_key = secp256k1::SecretKey::from_byte_array([2; 32]).unwrap();
+        IssuedInvoice {
+            payment_hash: Sha256::digest(&invoice).into(),
+            invoice,
+            public_key: secp256k1::PublicKey::from_secret_key(&secp, &secret_key),
+            expires: chrono::Utc::now() + chrono::Duration::hours(1),
+            attempts: 1,
+        }
+    }
+
+    fn test_deadline() -> std::time::Instant {
+        std::time::Instant::now() + Duration::from_secs(60)
     }
 
     #[async_trait]
@@ -327,14 +938,44 @@ mod tests {
             &self,
             _offer: &Offer,
             key: &Self::Key,
-            _amount_msat: Option<u64>,
+            amount_msat: Option<u64>,
             _expiry_secs: Option<u64>,
-        ) -> Result<String, Self::Error> {
+        ) -> Result<IssuedInvoice, Self::Error> {
+            self.invoice_calls
+                .lock()
+                .unwrap()
+                .push(key.addr.to_string());
+            if let Some(amount_msat) = amount_msat {
+                if self
+                    .phoenixd
+                    .lock()
+                    .unwrap()
+                    .contains(&key.addr.to_string())
+                {
+                    ReqwestPhoenixdHttpClient::amount_sat(
+                        amount_msat,
+                        "mock get_invoice".to_string(),
+                    )
+                    .map_err(|e| PingoraLnError::from_service_error("mock get_invoice", e))?;
+                }
+            }
+            let failure = self
+                .invoice_failures
+                .lock()
+                .unwrap()
+                .get_mut(&key.addr.to_string())
+                .and_then(|failures| failures.pop_front());
+            if let Some(retry) = failure {
+                return Err(PingoraLnError::from_service_error(
+                    "mock get_invoice",
+                    MockNodeError(retry),
+                ));
+            }
             if self.should_succeed {
                 if self.backend_specific_response {
-                    Ok(format!("invoice_from_{}", key.addr))
+                    Ok(mock_issued_invoice(format!("invoice_from_{}", key.addr)))
                 } else {
-                    Ok("mock_invoice".to_string())
+                    Ok(mock_issued_invoice("mock_invoice".to_string()))
                 }
             } else {
                 Err(PingoraLnError::general_error(
@@ -349,9 +990,91 @@ mod tests {
             unimplemented!("get_metrics not needed for these tests")
         }
 
+        async fn get_hold_invoice(
+            &self,
+            offer: &Offer,
+            key: &Self::Key,
+            amount_msat: Option<u64>,
+            expiry_secs: Option<u64>,
+            payment_hash: &[u8; 32],
+        ) -> Result<IssuedInvoice, Self::Error> {
+            self.hold_calls
+                .lock()
+                .unwrap()
+                .push(format!("hold:{}", hex::encode(payment_hash)));
+            self.get_invoice(offer, key, amount_msat, expiry_secs)
+                .await
+                .map(|invoice| IssuedInvoice {
+                    payment_hash: *payment_hash,
+                    ..invoice
+                })
+        }
+
+        async fn settle_hold_invoice(
+            &self,
+            _key: &Self::Key,
+            preimage: &[u8; 32],
+        ) -> Result<(), Self::Error> {
+            self.hold_calls
+                .lock()
+                .unwrap()
+                .push(format!("settle:{}", hex::encode(preimage)));
+            Ok(())
+        }
+
+        async fn cancel_hold_invoice(
+            &self,
+            _key: &Self::Key,
+            payment_hash: &[u8; 32],
+        ) -> Result<(), Self::Error> {
+            self.hold_calls
+                .lock()
+                .unwrap()
+                .push(format!("cancel:{}", hex::encode(payment_hash)));
+            Ok(())
+        }
+
+        fn supports_hold_invoice(&self, key: &Self::Key) -> bool {
+            !self
+                .hold_unsupported
+                .lock()
+                .unwrap()
+                .contains(&key.addr.to_string())
+        }
+
+        async fn get_bolt12_offer(
+            &self,
+            key: &Self::Key,
+            _amount_msat: u64,
+            _description: &str,
+            _absolute_expiry: Option<u64>,
+        ) -> Result<PingoraLnBolt12Offer, Self::Error> {
+            Ok(PingoraLnBolt12Offer {
+                offer_id: format!("offer_from_{}", key.addr),
+                bolt12: format!("lno_from_{}", key.addr),
+            })
+        }
+
+        fn supports_bolt12(&self, _key: &Self::Key) -> bool {
+            true
+        }
+
+        async fn get_invoice_state(
+            &self,
+            _key: &Self::Key,
+            _invoice: &str,
+            _payment_hash: &[u8; 32],
+        ) -> Result<PingoraLnInvoiceState, Self::Error> {
+            unimplemented!("get_invoice_state not needed for these tests")
+        }
+
         fn connect(&self, _key: Self::Key, _backend: &DiscoveryBackend) -> Result<(), Self::Error> {
             unimplemented!("connect not needed for these tests")
         }
+
+        fn disconnect(&self, _key: &Self::Key) -> Result<(), Self::Error> {
+            unimplemented!("disconnect not needed for these tests")
+        }
     }
 
     #[derive(Clone, Default)]
@@ -404,6 +1127,7 @@ mod tests {
         let mut backend = Backend::new(addr).unwrap();
         backend.ext.insert(PingoraLnBackendExtension {
             partitions: partitions.into_iter().map(|s| s.to_string()).collect(),
+            region: None,
         });
         backend
     }
@@ -422,9 +1146,43 @@ mod tests {
             metadata_json_hash: [0; 32],
             timestamp: chrono::Utc::now() - chrono::Duration::hours(1),
             expires: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+            hold_invoice: false,
         }
     }
 
+    type MockPingoraLnBalancer = PingoraLnBalancer<
+        RoundRobin,
+        MockLnClientPool,
+        MockLnMetricsCache,
+        StopBackoffProvider,
+        RoundRobinMaxIterations,
+        MemoryOfferStore,
+    >;
+
+    fn observe_discovered_backend(
+        balancer: &MockPingoraLnBalancer,
+        backend: &Backend,
+    ) -> secp256k1::PublicKey {
+        let secp = secp256k1::Secp256k1::new();
+        let secret_key = secp256k1::SecretKey::from_byte_array([1; 32]).unwrap();
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
+        balancer.status.observe_discovery(&BTreeMap::from([(
+            backend.clone(),
+            DiscoveryBackend {
+                public_key,
+                backend: DiscoveryBackendSparse {
+                    name: Some("node".to_string()),
+                    partitions: ["default".to_string()].into(),
+                    weight: 1,
+                    enabled: true,
+                    region: None,
+                    implementation: "{}".as_bytes().to_vec(),
+                },
+            },
+        )]));
+        public_key
+    }
+
     async fn setup_balancer_with_backends_and_optional_bias(
         should_succeed: bool,
         backend_configs: Vec<(Backend, bool)>, // (backend, enabled)
@@ -436,10 +1194,16 @@ mod tests {
         MockLnMetricsCache,
         StopBackoffProvider,
         RoundRobinMaxIterations,
+        MemoryOfferStore,
     > {
         let pool = MockLnClientPool {
             should_succeed,
             backend_specific_response,
+            hold_calls: Default::default(),
+            invoice_failures: Default::default(),
+            invoice_calls: Default::default(),
+            hold_unsupported: Default::default(),
+            phoenixd: Default::default(),
         };
         let metrics_cache = MockLnMetricsCache::default();
 
@@ -475,6 +1239,14 @@ mod tests {
             RoundRobinMaxIterations,
             true,
             selection_capacity_bias,
+            PingoraLnBackendStatusRegistry::new(),
+            PingoraLnInvoiceReservations::new(),
+            Some(PingoraLnHoldInvoices::new(
+                MemoryOfferStore::new(),
+                Duration::from_secs(60),
+            )),
+            None,
+            PingoraLnForcedRefresh::new(Duration::ZERO),
         )
     }
 
@@ -489,6 +1261,7 @@ mod tests {
         MockLnMetricsCache,
         StopBackoffProvider,
         RoundRobinMaxIterations,
+        MemoryOfferStore,
     > {
         setup_balancer_with_backends_and_optional_bias(
             should_succeed,
@@ -508,6 +1281,7 @@ mod tests {
         MockLnMetricsCache,
         StopBackoffProvider,
         RoundRobinMaxIterations,
+        MemoryOfferStore,
     > {
         setup_balancer_with_backends_and_bias(should_succeed, backend_configs, 10.0, false).await
     }
@@ -520,6 +1294,7 @@ mod tests {
         MockLnMetricsCache,
         StopBackoffProvider,
         RoundRobinMaxIterations,
+        MemoryOfferStore,
     > {
         let backend = create_mock_backend("127.0.0.1:8080", "default");
         setup_balancer_with_backends(should_succeed, vec![(backend, true)]).await
@@ -540,9 +1315,11 @@ mod tests {
             },
         );
 
-        let result = balancer.get_invoice(&offer, 50000, 3600, &[]).await;
+        let result = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "mock_invoice");
+        assert_eq!(result.unwrap().invoice, "mock_invoice");
     }
 
     #[tokio::test]
@@ -560,30 +1337,258 @@ mod tests {
             },
         );
 
-        let result = balancer.get_invoice(&offer, 50000, 3600, &[]).await;
+        let result = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await;
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert_eq!(err.esource(), ServiceErrorSource::Upstream);
     }
 
-    #[tokio::test]
-    async fn test_get_invoice_no_backends() {
+    async fn setup_retrying_balancer(
+        backends: &[Backend],
+        failures: Vec<ServiceErrorRetry>,
+        backoff_provider: RetriesBackoffProvider,
+    ) -> (
+        PingoraLnBalancer<
+            RoundRobin,
+            MockLnClientPool,
+            MockLnMetricsCache,
+            RetriesBackoffProvider,
+            RoundRobinMaxIterations,
+            MemoryOfferStore,
+        >,
+        Arc<Mutex<Vec<String>>>,
+    ) {
         let pool = MockLnClientPool {
             should_succeed: true,
-            backend_specific_response: false,
+            backend_specific_response: true,
+            hold_calls: Default::default(),
+            invoice_failures: Arc::new(Mutex::new(
+                backends
+                    .iter()
+                    .map(|backend| (backend.addr.to_string(), failures.clone().into()))
+                    .collect(),
+            )),
+            invoice_calls: Default::default(),
+            hold_unsupported: Default::default(),
+            phoenixd: Default::default(),
         };
+        let invoice_calls = pool.invoice_calls.clone();
         let metrics_cache = MockLnMetricsCache::default();
+        for backend in backends {
+            metrics_cache.set_metrics_for_backend(
+                backend,
+                PingoraLnMetrics {
+                    healthy: true,
+                    node_effective_inbound_msat: 100000,
+                },
+            );
+        }
 
-        // Create empty discovery
+        let enablement = backends
+            .iter()
+            .map(|backend| {
+                let mut hasher = DefaultHasher::new();
+                backend.hash(&mut hasher);
+                (hasher.finish(), true)
+            })
+            .collect();
         let discovery = Box::new(MockServiceDiscovery {
-            backends: BTreeSet::new(),
-            enablement: HashMap::new(),
+            backends: backends.iter().cloned().collect(),
+            enablement,
         });
+        let mut load_balancer = LoadBalancer::<RoundRobin>::from_backends(Backends::new(discovery));
+        load_balancer.set_health_check(Box::new(NoOpHealthCheck));
+        let load_balancer = Arc::new(load_balancer);
+        load_balancer.update().await.unwrap();
 
-        let load_balancer = LoadBalancer::<RoundRobin>::from_backends(Backends::new(discovery));
-        let load_balancer_arc = Arc::new(load_balancer);
-
-        let backoff_provider = StopBackoffProvider;
+        let balancer = PingoraLnBalancer::new(
+            load_balancer,
+            pool,
+            metrics_cache,
+            backoff_provider,
+            RoundRobinMaxIterations,
+            true,
+            None,
+            PingoraLnBackendStatusRegistry::new(),
+            PingoraLnInvoiceReservations::new(),
+            Some(PingoraLnHoldInvoices::new(
+                MemoryOfferStore::new(),
+                Duration::from_secs(60),
+            )),
+            None,
+            PingoraLnForcedRefresh::new(Duration::ZERO),
+        );
+        (balancer, invoice_calls)
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_fatal_error_is_not_retried() {
+        let offer = create_test_offer();
+        let backends = [
+            create_mock_backend("127.0.0.1:8080", &offer.partition),
+            create_mock_backend("127.0.0.1:8081", &offer.partition),
+        ];
+        let (balancer, invoice_calls) = setup_retrying_balancer(
+            &backends,
+            vec![ServiceErrorRetry::Fatal],
+            RetriesBackoffProvider(3, Duration::ZERO),
+        )
+        .await;
+
+        let err = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.retry(), ServiceErrorRetry::Fatal);
+        assert_eq!(invoice_calls.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_same_node_error_is_retried_on_same_node() {
+        let offer = create_test_offer();
+        let backends = [
+            create_mock_backend("127.0.0.1:8080", &offer.partition),
+            create_mock_backend("127.0.0.1:8081", &offer.partition),
+        ];
+        let (balancer, invoice_calls) = setup_retrying_balancer(
+            &backends,
+            vec![ServiceErrorRetry::SameNode],
+            RetriesBackoffProvider(3, Duration::ZERO),
+        )
+        .await;
+
+        let invoice = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await
+            .unwrap();
+
+        let invoice_calls = invoice_calls.lock().unwrap();
+        assert_eq!(invoice_calls.len(), 2);
+        assert_eq!(invoice_calls[0], invoice_calls[1]);
+        assert_eq!(
+            invoice.invoice,
+            format!("invoice_from_{}", invoice_calls[0])
+        );
+        assert_eq!(invoice.attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_other_node_error_is_retried_on_other_node() {
+        let offer = create_test_offer();
+        let backends = [
+            create_mock_backend("127.0.0.1:8080", &offer.partition),
+            create_mock_backend("127.0.0.1:8081", &offer.partition),
+        ];
+        let (balancer, invoice_calls) = setup_retrying_balancer(
+            &backends,
+            vec![ServiceErrorRetry::OtherNode],
+            RetriesBackoffProvider(3, Duration::ZERO),
+        )
+        .await;
+
+        let invoice = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await
+            .unwrap();
+
+        let invoice_calls = invoice_calls.lock().unwrap();
+        assert_eq!(invoice_calls.len(), 2);
+        assert_ne!(invoice_calls[0], invoice_calls[1]);
+        assert_eq!(
+            invoice.invoice,
+            format!("invoice_from_{}", invoice_calls[1])
+        );
+        assert_eq!(invoice.attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_msat_amount_on_phoenixd_is_retried_on_other_node() {
+        let offer = create_test_offer();
+        let backends = [
+            create_mock_backend("127.0.0.1:8080", &offer.partition),
+            create_mock_backend("127.0.0.1:8081", &offer.partition),
+        ];
+        let (balancer, invoice_calls) =
+            setup_retrying_balancer(&backends, vec![], RetriesBackoffProvider(3, Duration::ZERO))
+                .await;
+        balancer
+            .pool
+            .phoenixd
+            .lock()
+            .unwrap()
+            .push("127.0.0.1:8080".to_string());
+
+        // round robin hands at least one of the requests to the phoenixd node first
+        for _ in 0..2 {
+            let invoice = balancer
+                .get_invoice(&offer, 50500, 3600, &[], &[], test_deadline())
+                .await
+                .unwrap();
+            assert_eq!(invoice.invoice, "invoice_from_127.0.0.1:8081");
+        }
+
+        assert!(invoice_calls
+            .lock()
+            .unwrap()
+            .contains(&"127.0.0.1:8080".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_stops_retrying_at_deadline() {
+        let offer = create_test_offer();
+        let backends = [create_mock_backend("127.0.0.1:8080", &offer.partition)];
+        let (balancer, invoice_calls) = setup_retrying_balancer(
+            &backends,
+            vec![ServiceErrorRetry::OtherNode; 10],
+            RetriesBackoffProvider(10, Duration::from_secs(1)),
+        )
+        .await;
+
+        let err = balancer
+            .get_invoice(
+                &offer,
+                50000,
+                3600,
+                &[],
+                &[],
+                std::time::Instant::now() + Duration::from_millis(100),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.source(),
+            PingoraLnErrorSourceKind::DeadlineExceeded
+        ));
+        assert_eq!(invoice_calls.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_no_backends() {
+        let pool = MockLnClientPool {
+            should_succeed: true,
+            backend_specific_response: false,
+            hold_calls: Default::default(),
+            invoice_failures: Default::default(),
+            invoice_calls: Default::default(),
+            hold_unsupported: Default::default(),
+            phoenixd: Default::default(),
+        };
+        let metrics_cache = MockLnMetricsCache::default();
+
+        // Create empty discovery
+        let discovery = Box::new(MockServiceDiscovery {
+            backends: BTreeSet::new(),
+            enablement: HashMap::new(),
+        });
+
+        let load_balancer = LoadBalancer::<RoundRobin>::from_backends(Backends::new(discovery));
+        let load_balancer_arc = Arc::new(load_balancer);
+
+        let backoff_provider = StopBackoffProvider;
 
         let balancer = PingoraLnBalancer::new(
             load_balancer_arc,
@@ -593,11 +1598,21 @@ mod tests {
             RoundRobinMaxIterations,
             true,
             None,
+            PingoraLnBackendStatusRegistry::new(),
+            PingoraLnInvoiceReservations::new(),
+            Some(PingoraLnHoldInvoices::new(
+                MemoryOfferStore::new(),
+                Duration::from_secs(60),
+            )),
+            None,
+            PingoraLnForcedRefresh::new(Duration::ZERO),
         );
 
         let offer = create_test_offer();
 
-        let result = balancer.get_invoice(&offer, 50000, 3600, &[]).await;
+        let result = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await;
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert_eq!(err.esource(), ServiceErrorSource::Upstream);
@@ -624,9 +1639,11 @@ mod tests {
         for partition in ["default", "partition1", "partition2"] {
             let mut offer = create_test_offer();
             offer.partition = partition.to_string();
-            let result = balancer.get_invoice(&offer, 50000, 3600, &[]).await;
+            let result = balancer
+                .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+                .await;
             assert!(result.is_ok());
-            assert_eq!(result.unwrap(), "mock_invoice");
+            assert_eq!(result.unwrap().invoice, "mock_invoice");
         }
     }
 
@@ -657,7 +1674,9 @@ mod tests {
         offer_foreign.partition = "foreign_partition".to_string();
 
         // This should fail because no backend has the "foreign_partition"
-        let result = balancer.get_invoice(&offer_foreign, 50000, 3600, &[]).await;
+        let result = balancer
+            .get_invoice(&offer_foreign, 50000, 3600, &[], &[], test_deadline())
+            .await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().esource(), ServiceErrorSource::Upstream);
 
@@ -665,11 +1684,143 @@ mod tests {
         for partition in ["partition1", "partition2"] {
             let mut offer = create_test_offer();
             offer.partition = partition.to_string();
-            let result = balancer.get_invoice(&offer, 50000, 3600, &[]).await;
+            let result = balancer
+                .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+                .await;
             assert!(result.is_ok());
         }
     }
 
+    #[tokio::test]
+    async fn test_ejected_outlier_is_skipped_until_ejection_ends() {
+        let backends = (0..3)
+            .map(|i| create_mock_backend(&format!("127.0.0.1:{}", 8080 + i), "default"))
+            .collect::<Vec<_>>();
+        let mut balancer = setup_balancer_with_backends_and_bias(
+            true,
+            backends.iter().map(|b| (b.clone(), true)).collect(),
+            10.0,
+            true,
+        )
+        .await;
+        for backend in &backends {
+            balancer.metrics.set_metrics_for_backend(
+                backend,
+                PingoraLnMetrics {
+                    healthy: true,
+                    node_effective_inbound_msat: 100000,
+                },
+            );
+        }
+        let outlier_detection = PingoraLnOutlierDetection::new(
+            Duration::from_secs(60),
+            5,
+            3,
+            1.0,
+            Duration::from_millis(200),
+            Duration::from_millis(200),
+            50.0,
+        );
+        balancer.outlier_detection = Some(outlier_detection.clone());
+
+        let live = backends.iter().cloned().collect::<BTreeSet<_>>();
+        let partitions = BTreeSet::from(["default".to_string()]);
+        for _ in 0..5 {
+            outlier_detection.observe(&backends[0], &partitions, false, &live);
+            outlier_detection.observe(&backends[1], &partitions, true, &live);
+            outlier_detection.observe(&backends[2], &partitions, true, &live);
+        }
+        assert!(outlier_detection.is_ejected(&backends[0]));
+
+        let offer = create_test_offer();
+        let ejected_invoice = format!("invoice_from_{}", backends[0].addr);
+        for _ in 0..6 {
+            let invoice = balancer
+                .get_invoice(&offer, 1000, 3600, &[], &[], test_deadline())
+                .await
+                .unwrap();
+            assert_ne!(invoice.invoice, ejected_invoice);
+        }
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(!outlier_detection.is_ejected(&backends[0]));
+
+        let mut invoices = vec![];
+        for _ in 0..6 {
+            let invoice = balancer
+                .get_invoice(&offer, 1000, 3600, &[], &[], test_deadline())
+                .await
+                .unwrap();
+            invoices.push(invoice.invoice);
+        }
+        assert!(invoices.contains(&ejected_invoice));
+    }
+
+    #[tokio::test]
+    async fn test_preferred_region_is_favored_with_fallback_to_any_region() {
+        let mut backend_eu = create_mock_backend("127.0.0.1:8080", "default");
+        backend_eu.ext.insert(PingoraLnBackendExtension {
+            partitions: ["default".to_string()].into(),
+            region: Some("eu".to_string()),
+        });
+        let mut backend_us = create_mock_backend("127.0.0.1:8081", "default");
+        backend_us.ext.insert(PingoraLnBackendExtension {
+            partitions: ["default".to_string()].into(),
+            region: Some("us".to_string()),
+        });
+
+        let balancer = setup_balancer_with_backends_and_optional_bias(
+            true,
+            vec![(backend_eu.clone(), true), (backend_us.clone(), true)],
+            None,
+            true,
+        )
+        .await;
+        for backend in [&backend_eu, &backend_us] {
+            balancer.metrics.set_metrics_for_backend(
+                backend,
+                PingoraLnMetrics {
+                    healthy: true,
+                    node_effective_inbound_msat: 100000,
+                },
+            );
+        }
+        let offer = create_test_offer();
+
+        for _ in 0..10 {
+            let result = balancer
+                .get_invoice(
+                    &offer,
+                    50000,
+                    3600,
+                    &[],
+                    &["eu".to_string()],
+                    test_deadline(),
+                )
+                .await;
+            assert_eq!(
+                result.unwrap().invoice,
+                format!("invoice_from_{}", backend_eu.addr)
+            );
+        }
+
+        let mut selected = BTreeSet::new();
+        for _ in 0..10 {
+            let result = balancer
+                .get_invoice(
+                    &offer,
+                    50000,
+                    3600,
+                    &[],
+                    &["ap".to_string()],
+                    test_deadline(),
+                )
+                .await;
+            selected.insert(result.unwrap().invoice);
+        }
+        assert_eq!(selected.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_selection_capacity_bias_negative_properly_enforces_capacity_over_weight() {
         // This test demonstrates that capacity constraints override weight preferences
@@ -722,11 +1873,15 @@ mod tests {
         let mut low_weight_count = 0;
         let mut high_weight_count = 0;
 
+        // Zero expiry releases each invoice reservation immediately, so every request
+        // is measured against the raw node capacity
         for _ in 0..20 {
             let offer = create_test_offer();
-            let result = balancer.get_invoice(&offer, 75000, 3600, &[]).await;
+            let result = balancer
+                .get_invoice(&offer, 75000, 0, &[], &[], test_deadline())
+                .await;
             assert!(result.is_ok());
-            let invoice = result.unwrap();
+            let invoice = result.unwrap().invoice;
 
             if invoice == "invoice_from_127.0.0.1:8080" {
                 low_weight_count += 1;
@@ -753,9 +1908,11 @@ mod tests {
 
         for _ in 0..20 {
             let offer = create_test_offer();
-            let result = balancer.get_invoice(&offer, 50000, 3600, &[]).await; // 50k is within all backends' capacity
+            let result = balancer
+                .get_invoice(&offer, 50000, 0, &[], &[], test_deadline())
+                .await; // 50k is within all backends' capacity
             assert!(result.is_ok());
-            let invoice = result.unwrap();
+            let invoice = result.unwrap().invoice;
 
             if invoice == "invoice_from_127.0.0.1:8080" {
                 low_weight_count += 1;
@@ -804,21 +1961,25 @@ mod tests {
         // First pass: no backend meets capacity (75k > 64k effective)
         // Second pass: capacity ignored, backend is selected for service availability
         let offer = create_test_offer();
-        let result = balancer.get_invoice(&offer, 75000, 3600, &[]).await;
+        let result = balancer
+            .get_invoice(&offer, 75000, 3600, &[], &[], test_deadline())
+            .await;
 
         // Should succeed despite insufficient capacity due to fallback mechanism
         assert!(
             result.is_ok(),
             "Invoice generation should succeed via fallback mechanism"
         );
-        assert_eq!(result.unwrap(), "invoice_from_127.0.0.1:8080");
+        assert_eq!(result.unwrap().invoice, "invoice_from_127.0.0.1:8080");
 
         // Test multiple requests to ensure consistent behavior
         for _ in 0..5 {
             let offer = create_test_offer();
-            let result = balancer.get_invoice(&offer, 75000, 3600, &[]).await;
+            let result = balancer
+                .get_invoice(&offer, 75000, 3600, &[], &[], test_deadline())
+                .await;
             assert!(result.is_ok(), "All requests should succeed via fallback");
-            assert_eq!(result.unwrap(), "invoice_from_127.0.0.1:8080");
+            assert_eq!(result.unwrap().invoice, "invoice_from_127.0.0.1:8080");
         }
     }
 
@@ -878,9 +2039,11 @@ mod tests {
 
         for _ in 0..20 {
             let offer = create_test_offer();
-            let result = balancer.get_invoice(&offer, 90000, 3600, &[]).await; // 90k >> 10k capacity
+            let result = balancer
+                .get_invoice(&offer, 90000, 3600, &[], &[], test_deadline())
+                .await; // 90k >> 10k capacity
             assert!(result.is_ok());
-            let invoice = result.unwrap();
+            let invoice = result.unwrap().invoice;
 
             if invoice == "invoice_from_127.0.0.1:8080" {
                 low_weight_count += 1;
@@ -917,4 +2080,377 @@ mod tests {
             "High weight backends should be selected significantly more often (ratio: {ratio})",
         );
     }
+
+    #[tokio::test]
+    async fn test_status_reports_discovered_backends_with_invoice_latency() {
+        let balancer = setup_balancer(true).await;
+        let offer = create_test_offer();
+
+        let backend = create_mock_backend("127.0.0.1:8080", &offer.partition);
+        balancer.metrics.set_metrics_for_backend(
+            &backend,
+            PingoraLnMetrics {
+                healthy: true,
+                node_effective_inbound_msat: 100000,
+            },
+        );
+
+        let public_key = observe_discovered_backend(&balancer, &backend);
+
+        let status = balancer.status().await.unwrap();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].public_key, public_key);
+        assert!(status[0].healthy);
+        assert!(status[0].last_invoice_latency_ms.is_none());
+        assert_eq!(
+            status[0].metrics,
+            Some(LnBackendMetrics {
+                healthy: true,
+                node_effective_inbound_msat: 100000,
+            })
+        );
+
+        balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await
+            .unwrap();
+
+        let status = balancer.status().await.unwrap();
+        assert!(status[0].last_invoice_latency_ms.is_some());
+        assert!(status[0].last_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_selection_capacity_bias_subtracts_reserved_invoice_amounts() {
+        let backend_reserved = create_mock_backend("127.0.0.1:8080", "default");
+        let backend_free = create_mock_backend("127.0.0.1:8081", "default");
+
+        let balancer = setup_balancer_with_backends_and_bias(
+            true,
+            vec![
+                (backend_reserved.clone(), true),
+                (backend_free.clone(), true),
+            ],
+            -0.2,
+            true,
+        )
+        .await;
+
+        for backend in [&backend_reserved, &backend_free] {
+            balancer.metrics.set_metrics_for_backend(
+                backend,
+                PingoraLnMetrics {
+                    healthy: true,
+                    node_effective_inbound_msat: 100000, // 100k * 0.8 = 80k effective
+                },
+            );
+        }
+
+        // 60k outstanding leaves 40k * 0.8 = 32k effective, insufficient for 60k
+        let outstanding = mock_issued_invoice("lnbc_outstanding".to_string());
+        balancer
+            .reservations
+            .reserve(&backend_reserved, &outstanding, 60000);
+
+        let offer = create_test_offer();
+        for _ in 0..10 {
+            let selected = balancer
+                .select_backend(&offer, 60000, &[], Some(-0.2), None, &[])
+                .unwrap();
+            assert_eq!(selected, backend_free);
+        }
+
+        balancer
+            .reservations
+            .release(&backend_reserved, &outstanding.payment_hash);
+
+        let selected = (0..10)
+            .filter_map(|_| balancer.select_backend(&offer, 60000, &[], Some(-0.2), None, &[]))
+            .collect::<Vec<_>>();
+        assert!(selected.contains(&backend_reserved));
+    }
+
+    #[tokio::test]
+    async fn test_select_backend_excludes_by_address_regardless_of_weight() {
+        let backend_excluded = create_mock_backend("127.0.0.1:8080", "default");
+        let backend_other = create_mock_backend("127.0.0.1:8081", "default");
+
+        let balancer = setup_balancer_with_backends(
+            true,
+            vec![
+                (backend_excluded.clone(), true),
+                (backend_other.clone(), true),
+            ],
+        )
+        .await;
+
+        // the same node after discovery changed its weight
+        let mut reweighted = backend_excluded.clone();
+        reweighted.weight += 10;
+
+        let offer = create_test_offer();
+        for _ in 0..10 {
+            let selected = balancer
+                .select_backend(&offer, 1000, &[], None, None, &[reweighted.addr.clone()])
+                .unwrap();
+            assert_eq!(selected, backend_other);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_invoice_reserves_invoice_amount() {
+        let balancer = setup_balancer(true).await;
+        let offer = create_test_offer();
+
+        let backend = create_mock_backend("127.0.0.1:8080", &offer.partition);
+        balancer.metrics.set_metrics_for_backend(
+            &backend,
+            PingoraLnMetrics {
+                healthy: true,
+                node_effective_inbound_msat: 100000,
+            },
+        );
+
+        let invoice = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await
+            .unwrap();
+
+        assert_eq!(balancer.reservations.reserved_msat(&backend), 50000);
+        assert_eq!(
+            balancer.reservations.outstanding_invoices(&backend),
+            vec![(invoice.invoice, invoice.payment_hash)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hold_invoice_refused_when_hold_invoices_not_configured() {
+        let mut balancer = setup_balancer(true).await;
+        balancer.holds = None;
+        let offer = Offer {
+            hold_invoice: true,
+            ..create_test_offer()
+        };
+
+        let backend = create_mock_backend("127.0.0.1:8080", &offer.partition);
+        balancer.metrics.set_metrics_for_backend(
+            &backend,
+            PingoraLnMetrics {
+                healthy: true,
+                node_effective_inbound_msat: 100000,
+            },
+        );
+
+        let result = balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await;
+
+        assert!(result.is_err());
+        assert!(balancer.pool.hold_calls.lock().unwrap().is_empty());
+        assert!(!balancer.settle_hold_invoice(&[0u8; 32]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_hold_invoice_preimage_kept_until_settled() {
+        let balancer = setup_balancer(true).await;
+        let offer = Offer {
+            hold_invoice: true,
+            ..create_test_offer()
+        };
+
+        let backend = create_mock_backend("127.0.0.1:8080", &offer.partition);
+        balancer.metrics.set_metrics_for_backend(
+            &backend,
+            PingoraLnMetrics {
+                healthy: true,
+                node_effective_inbound_msat: 100000,
+            },
+        );
+        observe_discovered_backend(&balancer, &backend);
+
+        balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await
+            .unwrap();
+        let calls = balancer.pool.hold_calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 1);
+        let mut payment_hash = [0u8; 32];
+        hex::decode_to_slice(calls[0].strip_prefix("hold:").unwrap(), &mut payment_hash).unwrap();
+
+        assert!(!balancer.settle_hold_invoice(&[0u8; 32]).await.unwrap());
+        assert!(balancer.settle_hold_invoice(&payment_hash).await.unwrap());
+
+        let calls = balancer.pool.hold_calls.lock().unwrap().clone();
+        let preimage = hex::decode(calls[1].strip_prefix("settle:").unwrap()).unwrap();
+        assert_eq!(Sha256::digest(&preimage).as_slice(), payment_hash);
+        assert_eq!(balancer.reservations.reserved_msat(&backend), 0);
+
+        assert!(!balancer.cancel_hold_invoice(&payment_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_hold_invoice_skips_backends_without_hold_support() {
+        let unsupported = create_mock_backend("127.0.0.1:8080", "default");
+        let supported = create_mock_backend("127.0.0.1:8081", "default");
+        let balancer = setup_balancer_with_backends_and_bias(
+            true,
+            vec![(unsupported.clone(), true), (supported.clone(), true)],
+            10.0,
+            true,
+        )
+        .await;
+        balancer
+            .pool
+            .hold_unsupported
+            .lock()
+            .unwrap()
+            .push(unsupported.addr.to_string());
+        for backend in [&unsupported, &supported] {
+            balancer.metrics.set_metrics_for_backend(
+                backend,
+                PingoraLnMetrics {
+                    healthy: true,
+                    node_effective_inbound_msat: 100000,
+                },
+            );
+        }
+        observe_discovered_backend(&balancer, &supported);
+
+        let hold_offer = Offer {
+            hold_invoice: true,
+            ..create_test_offer()
+        };
+        for _ in 0..4 {
+            let invoice = balancer
+                .get_invoice(&hold_offer, 1000, 3600, &[], &[], test_deadline())
+                .await
+                .unwrap();
+            assert_eq!(invoice.invoice, format!("invoice_from_{}", supported.addr));
+        }
+        assert_eq!(balancer.pool.hold_calls.lock().unwrap().len(), 4);
+
+        // offers without hold invoices still use every backend
+        balancer.pool.invoice_calls.lock().unwrap().clear();
+        for _ in 0..4 {
+            balancer
+                .get_invoice(&create_test_offer(), 1000, 3600, &[], &[], test_deadline())
+                .await
+                .unwrap();
+        }
+        assert!(balancer
+            .pool
+            .invoice_calls
+            .lock()
+            .unwrap()
+            .contains(&unsupported.addr.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_bolt12_offer_reused_until_offer_changes() {
+        let balancer = setup_balancer(true).await;
+        let offer = Offer {
+            metadata_json_string: r#"[["text/plain","coffee"]]"#.to_string(),
+            min_sendable: 5000,
+            max_sendable: 5000,
+            ..create_test_offer()
+        };
+
+        let backend = create_mock_backend("127.0.0.1:8080", &offer.partition);
+        let public_key = observe_discovered_backend(&balancer, &backend);
+
+        let created = balancer.get_bolt12_offer(&offer, &[]).await.unwrap();
+        assert_eq!(created.public_key, public_key);
+        assert_eq!(created.offer.offer_id, "offer_from_127.0.0.1:8080");
+        assert_eq!(created.offer.amount_msat, 5000);
+        assert_eq!(created.offer.description, "coffee");
+
+        let existing = Bolt12OfferRecord {
+            offer: Bolt12OfferSparse {
+                offer_id: "existing".to_string(),
+                ..created.offer.clone()
+            },
+            ..created.clone()
+        };
+        let reused = balancer
+            .get_bolt12_offer(&offer, std::slice::from_ref(&existing))
+            .await
+            .unwrap();
+        assert_eq!(reused, existing);
+
+        let changed_amount = Offer {
+            min_sendable: 6000,
+            max_sendable: 6000,
+            ..offer
+        };
+        let replaced = balancer
+            .get_bolt12_offer(&changed_amount, &[existing])
+            .await
+            .unwrap();
+        assert_eq!(replaced.offer.offer_id, "offer_from_127.0.0.1:8080");
+        assert_eq!(replaced.offer.amount_msat, 6000);
+    }
+
+    #[tokio::test]
+    async fn test_bolt12_offer_refused_for_amount_range() {
+        let balancer = setup_balancer(true).await;
+        let offer = Offer {
+            min_sendable: 1000,
+            max_sendable: 5000,
+            ..create_test_offer()
+        };
+
+        let backend = create_mock_backend("127.0.0.1:8080", &offer.partition);
+        observe_discovered_backend(&balancer, &backend);
+
+        let err = balancer.get_bolt12_offer(&offer, &[]).await.unwrap_err();
+
+        assert_eq!(
+            err.get_service_error_source(),
+            ServiceErrorSource::Downstream
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hold_invoice_canceled_when_unclaimed_past_deadline() {
+        let mut balancer = setup_balancer(true).await;
+        balancer.holds = Some(PingoraLnHoldInvoices::new(
+            MemoryOfferStore::new(),
+            Duration::ZERO,
+        ));
+        let offer = Offer {
+            hold_invoice: true,
+            ..create_test_offer()
+        };
+
+        let backend = create_mock_backend("127.0.0.1:8080", &offer.partition);
+        balancer.metrics.set_metrics_for_backend(
+            &backend,
+            PingoraLnMetrics {
+                healthy: true,
+                node_effective_inbound_msat: 100000,
+            },
+        );
+        observe_discovered_backend(&balancer, &backend);
+
+        balancer
+            .get_invoice(&offer, 50000, 3600, &[], &[], test_deadline())
+            .await
+            .unwrap();
+        balancer.cancel_due_hold_invoices().await;
+
+        let calls = balancer.pool.hold_calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0].strip_prefix("hold:"),
+            calls[1].strip_prefix("cancel:")
+        );
+        assert!(balancer
+            .holds
+            .as_ref()
+            .unwrap()
+            .take_due()
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
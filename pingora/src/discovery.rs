@@ -1,4 +1,8 @@
-use crate::{PingoraBackendProvider, PingoraLnBackendExtension, PingoraLnClientPool};
+use crate::status::PingoraLnBackendStatusRegistry;
+use crate::weight::{PingoraLnDynamicWeights, PingoraLnSlowStart};
+use crate::{
+    PingoraBackendProvider, PingoraLnBackendExtension, PingoraLnClientPool, PingoraLnMetricsCache,
+};
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use axum::http::Extensions;
@@ -6,57 +10,79 @@ use log::error;
 use pingora_core::protocols::l4::socket::SocketAddr;
 use pingora_load_balancing::discovery::ServiceDiscovery;
 use pingora_load_balancing::Backend;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::net::{Ipv6Addr, SocketAddrV6};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use switchgear_service_api::discovery::{DiscoveryBackendStore, DiscoveryBackends};
+use switchgear_service_api::discovery::{
+    DiscoveryBackend, DiscoveryBackendStore, DiscoveryBackends,
+};
 
 pub struct LnServiceDiscovery<B, P> {
     backend_provider: B,
     pool: P,
     partitions: BTreeSet<String>,
+    status: PingoraLnBackendStatusRegistry,
+    dynamic_weights: Option<PingoraLnDynamicWeights>,
+    slow_start: Option<PingoraLnSlowStart>,
+    // connected backends with their static weights and enablement
+    discovered_backend_cache: ArcSwap<BTreeMap<Backend, bool>>,
+    // connected backends with the definitions their clients were built from
+    connected_backend_cache: ArcSwap<BTreeMap<Backend, DiscoveryBackend>>,
     pingora_backend_cache: ArcSwap<BTreeSet<Backend>>,
     last_etag: AtomicU64,
 }
 
 impl<B, P> LnServiceDiscovery<B, P> {
-    pub fn new(backend_provider: B, pool: P, partitions: HashSet<String>) -> Self {
+    pub fn new(
+        backend_provider: B,
+        pool: P,
+        partitions: HashSet<String>,
+        status: PingoraLnBackendStatusRegistry,
+        dynamic_weights: Option<PingoraLnDynamicWeights>,
+        slow_start: Option<PingoraLnSlowStart>,
+    ) -> Self {
         LnServiceDiscovery {
             backend_provider,
             pool,
             partitions: partitions.into_iter().collect(),
+            status,
+            dynamic_weights,
+            slow_start,
+            discovered_backend_cache: ArcSwap::new(Arc::new(BTreeMap::new())),
+            connected_backend_cache: ArcSwap::new(Arc::new(BTreeMap::new())),
             pingora_backend_cache: ArcSwap::new(Arc::new(BTreeSet::new())),
             last_etag: AtomicU64::new(0),
         }
     }
 }
 
-#[async_trait]
-impl<B, P> ServiceDiscovery for LnServiceDiscovery<B, P>
+impl<B, P> LnServiceDiscovery<B, P>
 where
     B: PingoraBackendProvider + Send + Sync,
-    P: PingoraLnClientPool<Key = Backend> + Send + Sync,
+    P: PingoraLnClientPool<Key = Backend> + PingoraLnMetricsCache<Key = Backend> + Send + Sync,
 {
-    async fn discover(&self) -> pingora_error::Result<(BTreeSet<Backend>, HashMap<u64, bool>)> {
+    async fn discover_backends(&self) -> pingora_error::Result<Arc<BTreeMap<Backend, bool>>> {
         let etag = self.last_etag.load(Ordering::Relaxed);
         let backends = self.backend_provider.backends(Some(etag)).await?;
 
         let discovery_backends = match backends.backends {
-            None => {
-                return Ok((
-                    (**self.pingora_backend_cache.load()).clone(),
-                    HashMap::new(),
-                ))
-            }
+            None => return Ok(self.discovered_backend_cache.load_full()),
             Some(backends) => BTreeSet::from_iter(backends),
         };
 
-        self.last_etag.store(backends.etag, Ordering::Relaxed);
+        let connected_backends = self.connected_backend_cache.load_full();
+        let connected_implementations = connected_backends
+            .iter()
+            .map(|(backend, discovery_backend)| {
+                (&backend.addr, &discovery_backend.backend.implementation)
+            })
+            .collect::<HashMap<_, _>>();
 
-        let mut enablement = HashMap::new();
-        let mut pingora_backends = BTreeSet::new();
+        let mut discovered_backends = BTreeMap::new();
+        let mut status_backends = BTreeMap::new();
+        let mut refused = false;
         for discovery_backend in discovery_backends {
             if discovery_backend
                 .backend
@@ -68,6 +94,7 @@ where
             let mut ext = Extensions::new();
             ext.insert(PingoraLnBackendExtension {
                 partitions: discovery_backend.backend.partitions.clone(),
+                region: discovery_backend.backend.region.clone(),
             });
 
             let addr = discovery_backend.public_key.serialize();
@@ -83,18 +110,139 @@ where
                 weight: discovery_backend.backend.weight,
                 ext,
             };
-            if let Err(e) = self
-                .pool
-                .connect(pingora_backend.clone(), &discovery_backend)
-            {
-                error!("Failed to connect to backend {discovery_backend:?}: {e}");
+            // weight, partition and enablement changes reuse the existing client
+            let connected = connected_implementations.get(&pingora_backend.addr)
+                == Some(&&discovery_backend.backend.implementation);
+            if !connected {
+                if let Err(e) = self
+                    .pool
+                    .connect(pingora_backend.clone(), &discovery_backend)
+                {
+                    error!("Failed to connect to backend {discovery_backend:?}: {e}");
+                    continue;
+                }
+                if let Err(e) = self.pool.verify_node_id(&pingora_backend).await {
+                    error!("Refusing backend {discovery_backend:?}: {e}");
+                    refused = true;
+                    if let Err(e) = self.pool.disconnect(&pingora_backend) {
+                        error!("Failed to disconnect from backend {discovery_backend:?}: {e}");
+                    }
+                    continue;
+                }
+            }
+            discovered_backends.insert(pingora_backend.clone(), discovery_backend.backend.enabled);
+            status_backends.insert(pingora_backend, discovery_backend);
+        }
+
+        let addrs = status_backends
+            .keys()
+            .map(|backend| &backend.addr)
+            .collect::<HashSet<_>>();
+        for backend in connected_backends.keys() {
+            if addrs.contains(&backend.addr) {
                 continue;
             }
+            if let Err(e) = self.pool.disconnect(backend) {
+                error!("Failed to disconnect from backend {backend:?}: {e}");
+            }
+        }
+
+        // refused backends are retried on the next poll, even when the store hasn't changed
+        if !refused {
+            self.last_etag.store(backends.etag, Ordering::Relaxed);
+        }
+
+        self.status.observe_discovery(&status_backends);
+        self.connected_backend_cache
+            .store(Arc::new(status_backends));
+
+        let discovered_backends = Arc::new(discovered_backends);
+        self.discovered_backend_cache
+            .store(discovered_backends.clone());
+        Ok(discovered_backends)
+    }
+
+    fn weigh_backends(
+        &self,
+        discovered_backends: &BTreeMap<Backend, bool>,
+    ) -> BTreeMap<Backend, bool> {
+        let weights = match &self.dynamic_weights {
+            Some(dynamic_weights) => {
+                let no_partitions = BTreeSet::new();
+                let inbound = discovered_backends
+                    .keys()
+                    .map(|backend| {
+                        (
+                            backend.weight,
+                            backend
+                                .ext
+                                .get::<PingoraLnBackendExtension>()
+                                .map_or(&no_partitions, |extension| &extension.partitions),
+                            self.pool
+                                .get_cached_metrics(backend)
+                                .map(|m| m.node_effective_inbound_msat),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                dynamic_weights.weights(&inbound)
+            }
+            None => discovered_backends.keys().map(|b| b.weight).collect(),
+        };
+
+        let previous_weights = self
+            .pingora_backend_cache
+            .load()
+            .iter()
+            .map(|backend| (backend.addr.clone(), backend.weight))
+            .collect::<HashMap<_, _>>();
+
+        discovered_backends
+            .iter()
+            .zip(weights)
+            .map(|((backend, enabled), weight)| {
+                let mut backend = backend.clone();
+                let entry = self.status.get(&backend);
+                let healthy = entry.as_ref().is_none_or(|entry| entry.healthy);
+                let weight = match (&self.slow_start, entry.and_then(|e| e.healthy_since)) {
+                    (Some(slow_start), Some(healthy_since)) => {
+                        slow_start.weight(weight, healthy_since.elapsed())
+                    }
+                    _ => weight,
+                };
+                // pingora keys health by address and weight, so reweighing an unhealthy backend
+                // would reset it to healthy; keep its previous weight until it recovers
+                backend.weight = match previous_weights.get(&backend.addr) {
+                    Some(previous_weight) if !healthy => *previous_weight,
+                    _ => weight,
+                };
+                (backend, *enabled)
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl<B, P> ServiceDiscovery for LnServiceDiscovery<B, P>
+where
+    B: PingoraBackendProvider + Send + Sync,
+    P: PingoraLnClientPool<Key = Backend> + PingoraLnMetricsCache<Key = Backend> + Send + Sync,
+{
+    async fn discover(&self) -> pingora_error::Result<(BTreeSet<Backend>, HashMap<u64, bool>)> {
+        let discovered_backends = self.discover_backends().await?;
+
+        let backends = if self.dynamic_weights.is_none() && self.slow_start.is_none() {
+            (*discovered_backends).clone()
+        } else {
+            self.weigh_backends(&discovered_backends)
+        };
+
+        let mut enablement = HashMap::new();
+        let mut pingora_backends = BTreeSet::new();
+        for (backend, enabled) in backends {
             let mut hasher = DefaultHasher::new();
-            pingora_backend.hash(&mut hasher);
-            let hash = hasher.finish();
-            enablement.insert(hash, discovery_backend.backend.enabled);
-            pingora_backends.insert(pingora_backend);
+            backend.hash(&mut hasher);
+            enablement.insert(hasher.finish(), enabled);
+            pingora_backends.insert(backend);
         }
 
         self.pingora_backend_cache
@@ -136,15 +284,23 @@ where
 mod tests {
     use crate::discovery::LnServiceDiscovery;
     use crate::error::PingoraLnError;
-    use crate::{PingoraBackendProvider, PingoraLnClientPool, PingoraLnMetrics};
+    use crate::status::PingoraLnBackendStatusRegistry;
+    use crate::weight::{PingoraLnDynamicWeights, PingoraLnSlowStart};
+    use crate::{
+        PingoraBackendProvider, PingoraLnBolt12Offer, PingoraLnClientPool, PingoraLnInvoiceState,
+        PingoraLnMetrics, PingoraLnMetricsCache,
+    };
     use async_trait::async_trait;
+    use pingora_core::protocols::l4::socket::SocketAddr;
     use pingora_load_balancing::discovery::ServiceDiscovery;
     use pingora_load_balancing::Backend;
     use rand::Rng;
     use secp256k1::{PublicKey, Secp256k1, SecretKey};
-    use std::collections::{BTreeSet, HashSet};
+    use std::collections::{BTreeSet, HashMap, HashSet};
     use std::hash::{DefaultHasher, Hash, Hasher};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex as StdMutex};
+    use std::time::Duration;
+    use switchgear_service_api::balance::IssuedInvoice;
     use switchgear_service_api::discovery::{
         DiscoveryBackend, DiscoveryBackendSparse, DiscoveryBackends,
     };
@@ -182,8 +338,14 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
     struct MockLnClientPool {
         should_fail_connect: bool,
+        should_fail_verify: bool,
+        // cached inbound liquidity keyed by the backend's static weight
+        inbound_msat: HashMap<usize, u64>,
+        connected: Arc<StdMutex<Vec<SocketAddr>>>,
+        disconnected: Arc<StdMutex<Vec<SocketAddr>>>,
     }
 
     #[async_trait]
@@ -197,7 +359,7 @@ mod tests {
             _key: &Self::Key,
             _amount_msat: Option<u64>,
             _expiry_secs: Option<u64>,
-        ) -> Result<String, Self::Error> {
+        ) -> Result<IssuedInvoice, Self::Error> {
             unimplemented!("get_invoice not implemented for MockLnClientPool")
         }
 
@@ -205,7 +367,53 @@ mod tests {
             unimplemented!("get_metrics not implemented for MockLnClientPool")
         }
 
-        fn connect(&self, _key: Self::Key, _backend: &DiscoveryBackend) -> Result<(), Self::Error> {
+        async fn get_invoice_state(
+            &self,
+            _key: &Self::Key,
+            _invoice: &str,
+            _payment_hash: &[u8; 32],
+        ) -> Result<PingoraLnInvoiceState, Self::Error> {
+            unimplemented!("get_invoice_state not implemented for MockLnClientPool")
+        }
+
+        async fn get_hold_invoice(
+            &self,
+            _offer: &Offer,
+            _key: &Self::Key,
+            _amount_msat: Option<u64>,
+            _expiry_secs: Option<u64>,
+            _payment_hash: &[u8; 32],
+        ) -> Result<IssuedInvoice, Self::Error> {
+            unimplemented!("get_hold_invoice not implemented for MockLnClientPool")
+        }
+
+        async fn settle_hold_invoice(
+            &self,
+            _key: &Self::Key,
+            _preimage: &[u8; 32],
+        ) -> Result<(), Self::Error> {
+            unimplemented!("settle_hold_invoice not implemented for MockLnClientPool")
+        }
+
+        async fn cancel_hold_invoice(
+            &self,
+            _key: &Self::Key,
+            _payment_hash: &[u8; 32],
+        ) -> Result<(), Self::Error> {
+            unimplemented!("cancel_hold_invoice not implemented for MockLnClientPool")
+        }
+
+        async fn get_bolt12_offer(
+            &self,
+            _key: &Self::Key,
+            _amount_msat: u64,
+            _description: &str,
+            _absolute_expiry: Option<u64>,
+        ) -> Result<PingoraLnBolt12Offer, Self::Error> {
+            unimplemented!("get_bolt12_offer not implemented for MockLnClientPool")
+        }
+
+        fn connect(&self, key: Self::Key, _backend: &DiscoveryBackend) -> Result<(), Self::Error> {
             if self.should_fail_connect {
                 Err(PingoraLnError::general_error(
                     ServiceErrorSource::Upstream,
@@ -213,9 +421,40 @@ mod tests {
                     "forced error".to_string(),
                 ))
             } else {
+                self.connected.lock().unwrap().push(key.addr);
                 Ok(())
             }
         }
+
+        fn disconnect(&self, key: &Self::Key) -> Result<(), Self::Error> {
+            self.disconnected.lock().unwrap().push(key.addr.clone());
+            Ok(())
+        }
+
+        async fn verify_node_id(&self, _key: &Self::Key) -> Result<(), Self::Error> {
+            if self.should_fail_verify {
+                Err(PingoraLnError::general_error(
+                    ServiceErrorSource::Upstream,
+                    "Mock LnClientPool forced node id mismatch",
+                    "forced error".to_string(),
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl PingoraLnMetricsCache for MockLnClientPool {
+        type Key = Backend;
+
+        fn get_cached_metrics(&self, key: &Self::Key) -> Option<PingoraLnMetrics> {
+            self.inbound_msat
+                .get(&key.weight)
+                .map(|inbound_msat| PingoraLnMetrics {
+                    healthy: true,
+                    node_effective_inbound_msat: *inbound_msat,
+                })
+        }
     }
 
     fn create_discovery_backend(partition: &str, weight: usize, enabled: bool) -> DiscoveryBackend {
@@ -232,6 +471,7 @@ mod tests {
                 partitions: [partition.to_string()].into(),
                 weight,
                 enabled,
+                region: None,
                 implementation: "{}".as_bytes().to_vec(),
             },
         }
@@ -242,13 +482,14 @@ mod tests {
         let mock_backend_provider = MockBackendProvider {
             backends_to_return: Arc::new(Mutex::new(Some(BTreeSet::new()))),
         };
-        let mock_ln_client_pool = MockLnClientPool {
-            should_fail_connect: false,
-        };
+        let mock_ln_client_pool = MockLnClientPool::default();
         let discovery = LnServiceDiscovery::new(
             mock_backend_provider,
             mock_ln_client_pool,
             HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            None,
+            None,
         );
         let (backends, enablement) = discovery.discover().await.unwrap();
 
@@ -268,13 +509,14 @@ mod tests {
             ])))),
         };
 
-        let mock_ln_client_pool = MockLnClientPool {
-            should_fail_connect: false,
-        };
+        let mock_ln_client_pool = MockLnClientPool::default();
         let discovery = LnServiceDiscovery::new(
             mock_backend_provider,
             mock_ln_client_pool,
             HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            None,
+            None,
         );
 
         let (pingora_backends, enablement) = discovery.discover().await.unwrap();
@@ -308,13 +550,14 @@ mod tests {
         let mock_backend_provider = MockBackendProvider {
             backends_to_return: Arc::new(Mutex::new(None)),
         };
-        let mock_ln_client_pool = MockLnClientPool {
-            should_fail_connect: false,
-        };
+        let mock_ln_client_pool = MockLnClientPool::default();
         let discovery = LnServiceDiscovery::new(
             mock_backend_provider,
             mock_ln_client_pool,
             HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            None,
+            None,
         );
 
         let result = discovery.discover().await;
@@ -337,11 +580,15 @@ mod tests {
 
         let mock_ln_client_pool = MockLnClientPool {
             should_fail_connect: true,
+            ..Default::default()
         };
         let discovery = LnServiceDiscovery::new(
             mock_backend_provider,
             mock_ln_client_pool,
             HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            None,
+            None,
         );
 
         let (backends, enablement) = discovery.discover().await.unwrap();
@@ -351,6 +598,36 @@ mod tests {
         assert!(enablement.is_empty());
     }
 
+    #[tokio::test]
+    async fn discover_when_node_id_verification_fails_then_backend_refused_and_disconnected() {
+        let backend1 = create_discovery_backend("default", 100, true);
+        let mock_backend_provider = MockBackendProvider {
+            backends_to_return: Arc::new(Mutex::new(Some(BTreeSet::from([backend1.clone()])))),
+        };
+
+        let mock_ln_client_pool = MockLnClientPool {
+            should_fail_verify: true,
+            ..Default::default()
+        };
+        let connected = mock_ln_client_pool.connected.clone();
+        let disconnected = mock_ln_client_pool.disconnected.clone();
+        let discovery = LnServiceDiscovery::new(
+            mock_backend_provider,
+            mock_ln_client_pool,
+            HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            None,
+            None,
+        );
+
+        let (backends, enablement) = discovery.discover().await.unwrap();
+
+        assert!(backends.is_empty());
+        assert!(enablement.is_empty());
+        assert_eq!(connected.lock().unwrap().len(), 1);
+        assert_eq!(*connected.lock().unwrap(), *disconnected.lock().unwrap());
+    }
+
     #[tokio::test]
     async fn discover_when_some_backends_fail_then_returns_successful_backends() {
         let backend1 = create_discovery_backend("default", 100, true);
@@ -381,7 +658,7 @@ mod tests {
                 _key: &Self::Key,
                 _amount_msat: Option<u64>,
                 _expiry_secs: Option<u64>,
-            ) -> Result<String, Self::Error> {
+            ) -> Result<IssuedInvoice, Self::Error> {
                 unimplemented!("get_invoice not implemented for SelectiveMockLnClientPool")
             }
 
@@ -389,6 +666,52 @@ mod tests {
                 unimplemented!("get_metrics not implemented for SelectiveMockLnClientPool")
             }
 
+            async fn get_invoice_state(
+                &self,
+                _key: &Self::Key,
+                _invoice: &str,
+                _payment_hash: &[u8; 32],
+            ) -> Result<PingoraLnInvoiceState, Self::Error> {
+                unimplemented!("get_invoice_state not implemented for SelectiveMockLnClientPool")
+            }
+
+            async fn get_hold_invoice(
+                &self,
+                _offer: &Offer,
+                _key: &Self::Key,
+                _amount_msat: Option<u64>,
+                _expiry_secs: Option<u64>,
+                _payment_hash: &[u8; 32],
+            ) -> Result<IssuedInvoice, Self::Error> {
+                unimplemented!("get_hold_invoice not implemented for SelectiveMockLnClientPool")
+            }
+
+            async fn settle_hold_invoice(
+                &self,
+                _key: &Self::Key,
+                _preimage: &[u8; 32],
+            ) -> Result<(), Self::Error> {
+                unimplemented!("settle_hold_invoice not implemented for SelectiveMockLnClientPool")
+            }
+
+            async fn cancel_hold_invoice(
+                &self,
+                _key: &Self::Key,
+                _payment_hash: &[u8; 32],
+            ) -> Result<(), Self::Error> {
+                unimplemented!("cancel_hold_invoice not implemented for SelectiveMockLnClientPool")
+            }
+
+            async fn get_bolt12_offer(
+                &self,
+                _key: &Self::Key,
+                _amount_msat: u64,
+                _description: &str,
+                _absolute_expiry: Option<u64>,
+            ) -> Result<PingoraLnBolt12Offer, Self::Error> {
+                unimplemented!("get_bolt12_offer not implemented for SelectiveMockLnClientPool")
+            }
+
             fn connect(
                 &self,
                 _key: Self::Key,
@@ -409,6 +732,18 @@ mod tests {
                     Ok(())
                 }
             }
+
+            fn disconnect(&self, _key: &Self::Key) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+
+        impl PingoraLnMetricsCache for SelectiveMockLnClientPool {
+            type Key = Backend;
+
+            fn get_cached_metrics(&self, _key: &Self::Key) -> Option<PingoraLnMetrics> {
+                None
+            }
         }
 
         let mock_ln_client_pool = SelectiveMockLnClientPool {
@@ -419,6 +754,9 @@ mod tests {
             mock_backend_provider,
             mock_ln_client_pool,
             HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            None,
+            None,
         );
 
         let (backends, enablement) = discovery.discover().await.unwrap();
@@ -433,4 +771,165 @@ mod tests {
         assert!(backend_weights.contains(&300)); // Third backend
         assert!(!backend_weights.contains(&200)); // Second backend should be missing
     }
+
+    #[tokio::test]
+    async fn discover_when_backends_exist_then_status_registry_tracks_them() {
+        let backend1 = create_discovery_backend("default", 100, true);
+        let backend2 = create_discovery_backend("other", 200, true);
+
+        let mock_backend_provider = MockBackendProvider {
+            backends_to_return: Arc::new(Mutex::new(Some(BTreeSet::from([
+                backend1.clone(),
+                backend2.clone(),
+            ])))),
+        };
+        let mock_ln_client_pool = MockLnClientPool::default();
+        let status = PingoraLnBackendStatusRegistry::new();
+        let discovery = LnServiceDiscovery::new(
+            mock_backend_provider,
+            mock_ln_client_pool,
+            HashSet::from(["default".to_string()]),
+            status.clone(),
+            None,
+            None,
+        );
+
+        let (backends, _) = discovery.discover().await.unwrap();
+
+        assert_eq!(backends.len(), 1);
+        let entry = status.get(backends.first().unwrap()).unwrap();
+        assert_eq!(entry.backend, backend1);
+        assert!(entry.healthy);
+    }
+
+    #[tokio::test]
+    async fn discover_when_dynamic_weights_then_weights_follow_inbound_liquidity() {
+        let backend1 = create_discovery_backend("default", 100, true);
+        let backend2 = create_discovery_backend("default", 200, true);
+        let backend3 = create_discovery_backend("default", 300, false);
+
+        let mock_backend_provider = MockBackendProvider {
+            backends_to_return: Arc::new(Mutex::new(Some(BTreeSet::from([
+                backend1.clone(),
+                backend2.clone(),
+                backend3.clone(),
+            ])))),
+        };
+        let mock_ln_client_pool = MockLnClientPool {
+            inbound_msat: HashMap::from([(100, 0), (200, 5_000)]),
+            ..Default::default()
+        };
+        let discovery = LnServiceDiscovery::new(
+            mock_backend_provider,
+            mock_ln_client_pool,
+            HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            Some(PingoraLnDynamicWeights::new(0.5, 2.0)),
+            None,
+        );
+
+        let (backends, enablement) = discovery.discover().await.unwrap();
+
+        let mut weights = backends.iter().map(|b| b.weight).collect::<Vec<_>>();
+        weights.sort();
+        // mean inbound is 2500: backend 1 is clamped to 0.5x, backend 2 to 2x and backend 3
+        // keeps its static weight without cached metrics
+        assert_eq!(weights, vec![50, 300, 400]);
+
+        let disabled = backends.iter().find(|b| b.weight == 300).unwrap();
+        let mut hasher = DefaultHasher::new();
+        disabled.hash(&mut hasher);
+        assert!(!enablement.get(&hasher.finish()).unwrap());
+    }
+
+    #[tokio::test]
+    async fn discover_when_slow_start_then_newly_healthy_backend_ramps_up() {
+        let backend1 = create_discovery_backend("default", 100, true);
+
+        let mock_backend_provider = MockBackendProvider {
+            backends_to_return: Arc::new(Mutex::new(Some(BTreeSet::from([backend1.clone()])))),
+        };
+        let status = PingoraLnBackendStatusRegistry::new();
+        let discovery = LnServiceDiscovery::new(
+            mock_backend_provider,
+            MockLnClientPool::default(),
+            HashSet::from(["default".to_string()]),
+            status.clone(),
+            None,
+            Some(PingoraLnSlowStart::new(Duration::from_secs(3600), 0.1)),
+        );
+
+        let (backends, _) = discovery.discover().await.unwrap();
+        let backend = backends.first().unwrap().clone();
+        assert_eq!(backend.weight, 10);
+
+        // an unhealthy backend keeps its weight so pingora keeps its health state
+        status.observe_health_status_change(&backend, false);
+        let (backends, _) = discovery.discover().await.unwrap();
+        assert_eq!(backends.first().unwrap().weight, 10);
+    }
+
+    #[tokio::test]
+    async fn discover_when_backends_change_then_only_changed_connect_and_removed_disconnect() {
+        let backend1 = create_discovery_backend("default", 100, true);
+        let backend2 = create_discovery_backend("default", 200, true);
+        let backend3 = create_discovery_backend("default", 300, true);
+
+        let backends_to_return = Arc::new(Mutex::new(Some(BTreeSet::from([
+            backend1.clone(),
+            backend2.clone(),
+        ]))));
+        let mock_backend_provider = MockBackendProvider {
+            backends_to_return: backends_to_return.clone(),
+        };
+        let mock_ln_client_pool = MockLnClientPool::default();
+        let connected = mock_ln_client_pool.connected.clone();
+        let disconnected = mock_ln_client_pool.disconnected.clone();
+        let discovery = LnServiceDiscovery::new(
+            mock_backend_provider,
+            mock_ln_client_pool,
+            HashSet::from(["default".to_string()]),
+            PingoraLnBackendStatusRegistry::new(),
+            None,
+            None,
+        );
+
+        let (backends, _) = discovery.discover().await.unwrap();
+        let addr = |weight: usize| {
+            backends
+                .iter()
+                .find(|b| b.weight == weight)
+                .unwrap()
+                .addr
+                .clone()
+        };
+        let (addr1, addr2) = (addr(100), addr(200));
+        assert_eq!(connected.lock().unwrap().len(), 2);
+
+        // reweighed backend 1 keeps its client, backend 2 moves to another partition
+        let mut reweighed1 = backend1.clone();
+        reweighed1.backend.weight = 150;
+        let mut repartitioned2 = backend2.clone();
+        repartitioned2.backend.partitions = ["other".to_string()].into();
+        *backends_to_return.lock().await = Some(BTreeSet::from([
+            reweighed1.clone(),
+            repartitioned2,
+            backend3.clone(),
+        ]));
+        let (backends, _) = discovery.discover().await.unwrap();
+
+        assert_eq!(backends.len(), 2);
+        assert_eq!(connected.lock().unwrap().len(), 3);
+        assert_eq!(*disconnected.lock().unwrap(), vec![addr2]);
+
+        let mut reimplemented1 = reweighed1;
+        reimplemented1.backend.implementation = r#"{"type":"other"}"#.as_bytes().to_vec();
+        *backends_to_return.lock().await = Some(BTreeSet::from([reimplemented1, backend3]));
+        discovery.discover().await.unwrap();
+
+        let connected = connected.lock().unwrap();
+        assert_eq!(connected.len(), 4);
+        assert_eq!(connected.last(), Some(&addr1));
+        assert_eq!(disconnected.lock().unwrap().len(), 1);
+    }
 }
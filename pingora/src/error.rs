@@ -1,7 +1,9 @@
 use std::borrow::Cow;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
-use switchgear_service_api::service::{HasServiceErrorSource, ServiceErrorSource};
+use switchgear_service_api::service::{
+    HasServiceErrorSource, ServiceErrorRetry, ServiceErrorSource,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +12,8 @@ pub enum PingoraLnErrorSourceKind {
     Error(String),
     #[error("no available lightning nodes")]
     NoAvailableNodes,
+    #[error("request deadline exceeded")]
+    DeadlineExceeded,
     #[error("{0}")]
     ServiceError(Box<dyn std::error::Error + Send + Sync + 'static>),
 }
@@ -20,6 +24,7 @@ pub struct PingoraLnError {
     #[source]
     source: PingoraLnErrorSourceKind,
     esource: ServiceErrorSource,
+    retry: ServiceErrorRetry,
 }
 
 impl Display for PingoraLnError {
@@ -43,6 +48,7 @@ impl PingoraLnError {
             context: context.into(),
             source,
             esource,
+            retry: esource.into(),
         }
     }
 
@@ -54,6 +60,19 @@ impl PingoraLnError {
             context: context.into(),
             source: PingoraLnErrorSourceKind::NoAvailableNodes,
             esource,
+            retry: esource.into(),
+        }
+    }
+
+    pub fn deadline_exceeded<C: Into<Cow<'static, str>>>(
+        esource: ServiceErrorSource,
+        context: C,
+    ) -> Self {
+        Self {
+            context: context.into(),
+            source: PingoraLnErrorSourceKind::DeadlineExceeded,
+            esource,
+            retry: esource.into(),
         }
     }
 
@@ -67,6 +86,7 @@ impl PingoraLnError {
         Self {
             context: context.into(),
             esource: source.get_service_error_source(),
+            retry: source.get_service_error_retry(),
             source: PingoraLnErrorSourceKind::ServiceError(source.into()),
         }
     }
@@ -80,6 +100,7 @@ impl PingoraLnError {
             context: context.into(),
             source: PingoraLnErrorSourceKind::Error(error),
             esource,
+            retry: esource.into(),
         }
     }
 
@@ -97,10 +118,19 @@ impl PingoraLnError {
     pub fn esource(&self) -> ServiceErrorSource {
         self.esource
     }
+
+    /// Get how the failed request may be retried
+    pub fn retry(&self) -> ServiceErrorRetry {
+        self.retry
+    }
 }
 
 impl HasServiceErrorSource for PingoraLnError {
     fn get_service_error_source(&self) -> ServiceErrorSource {
         self.esource
     }
+
+    fn get_service_error_retry(&self) -> ServiceErrorRetry {
+        self.retry
+    }
 }
@@ -1,25 +1,68 @@
-use crate::PingoraLnClientPool;
+use crate::reservation::PingoraLnInvoiceReservations;
+use crate::status::PingoraLnBackendStatusRegistry;
+use crate::{PingoraLnClientPool, PingoraLnInvoiceState};
 use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
+use log::warn;
 use pingora_error::ErrorType;
 use pingora_load_balancing::health_check::HealthCheck;
 use pingora_load_balancing::Backend;
 
+// invoice state lookups run concurrently per health check, so a node with many outstanding
+// invoices doesn't stretch the sweep by one round trip per invoice
+const INVOICE_STATE_CONCURRENCY: usize = 8;
+
 pub struct PingoraLnHealthCheck<P> {
     pool: P,
     consecutive_success: usize,
     consecutive_failure: usize,
+    status: PingoraLnBackendStatusRegistry,
+    reservations: PingoraLnInvoiceReservations,
 }
 
 impl<P> PingoraLnHealthCheck<P> {
-    pub fn new(pool: P, consecutive_success: usize, consecutive_failure: usize) -> Self {
+    pub fn new(
+        pool: P,
+        consecutive_success: usize,
+        consecutive_failure: usize,
+        status: PingoraLnBackendStatusRegistry,
+        reservations: PingoraLnInvoiceReservations,
+    ) -> Self {
         Self {
             pool,
             consecutive_success,
             consecutive_failure,
+            status,
+            reservations,
         }
     }
 }
 
+impl<P> PingoraLnHealthCheck<P>
+where
+    P: PingoraLnClientPool<Key = Backend> + Send + Sync,
+{
+    async fn release_closed_invoices(&self, target: &Backend) {
+        stream::iter(self.reservations.outstanding_invoices(target))
+            .map(|(invoice, payment_hash)| async move {
+                let state = self
+                    .pool
+                    .get_invoice_state(target, &invoice, &payment_hash)
+                    .await;
+                (payment_hash, state)
+            })
+            .buffer_unordered(INVOICE_STATE_CONCURRENCY)
+            .for_each(|(payment_hash, state)| async move {
+                match state {
+                    Ok(PingoraLnInvoiceState::Open) => {}
+                    Ok(_) => self.reservations.release(target, &payment_hash),
+                    Err(e) => warn!("checking invoice state for backend {target:?}: {e}"),
+                }
+            })
+            .await;
+    }
+}
+
 #[async_trait]
 impl<P> HealthCheck for PingoraLnHealthCheck<P>
 where
@@ -27,19 +70,33 @@ where
     P::Error: switchgear_service_api::service::HasServiceErrorSource,
 {
     async fn check(&self, target: &Backend) -> pingora_error::Result<()> {
-        let metrics = self.pool.get_metrics(target).await.map_err(|e| {
-            pingora_error::Error::because(
+        let result = match self.pool.get_metrics(target).await {
+            Ok(metrics) if metrics.healthy => Ok(()),
+            Ok(_) => Err(pingora_error::Error::new(ErrorType::ConnectError)),
+            Err(e) => Err(pingora_error::Error::because(
                 ErrorType::InternalError,
                 format!("health health for backend {target:?}"),
                 e,
-            )
-        })?;
+            )),
+        };
 
-        if metrics.healthy {
-            Ok(())
-        } else {
-            Err(pingora_error::Error::new(ErrorType::ConnectError))
+        self.status
+            .observe_health_check(target, result.as_ref().copied().map_err(|e| e.to_string()));
+
+        // the pool keeps serving with the previous client, so this doesn't fail the check
+        if let Some(e) = self.pool.get_credentials_error(target) {
+            self.status.observe_credentials_error(target, e);
         }
+
+        if result.is_ok() {
+            self.release_closed_invoices(target).await;
+        }
+
+        result
+    }
+
+    async fn health_status_change(&self, target: &Backend, healthy: bool) {
+        self.status.observe_health_status_change(target, healthy);
     }
 
     fn health_threshold(&self, success: bool) -> usize {
@@ -55,16 +112,24 @@ where
 mod tests {
     use super::*;
     use crate::error::PingoraLnError;
-    use crate::PingoraLnMetrics;
+    use crate::{PingoraLnBolt12Offer, PingoraLnMetrics};
     use pingora_core::protocols::l4::socket::SocketAddr;
     use std::net::SocketAddr as StdSocketAddr;
-    use switchgear_service_api::discovery::DiscoveryBackend;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use switchgear_service_api::balance::IssuedInvoice;
+    use switchgear_service_api::discovery::{DiscoveryBackend, DiscoveryBackendSparse};
     use switchgear_service_api::offer::Offer;
     use switchgear_service_api::service::ServiceErrorSource;
 
+    #[derive(Default)]
     struct MockPingoraLnClientPool {
         should_be_healthy: bool,
         return_error: bool,
+        credentials_error: Option<String>,
+        // concurrent invoice state lookups
+        lookups_in_flight: Arc<AtomicUsize>,
+        max_lookups_in_flight: Arc<AtomicUsize>,
     }
 
     #[async_trait]
@@ -78,7 +143,7 @@ mod tests {
             _key: &Self::Key,
             _amount_msat: Option<u64>,
             _expiry_secs: Option<u64>,
-        ) -> Result<String, Self::Error> {
+        ) -> Result<IssuedInvoice, Self::Error> {
             unimplemented!("get_invoice is not used in health check tests")
         }
 
@@ -97,9 +162,74 @@ mod tests {
             }
         }
 
+        async fn get_invoice_state(
+            &self,
+            _key: &Self::Key,
+            _invoice: &str,
+            payment_hash: &[u8; 32],
+        ) -> Result<PingoraLnInvoiceState, Self::Error> {
+            let in_flight = self.lookups_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_lookups_in_flight
+                .fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            self.lookups_in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            // invoices with an odd first hash byte are still open
+            Ok(if payment_hash[0].is_multiple_of(2) {
+                PingoraLnInvoiceState::Settled
+            } else {
+                PingoraLnInvoiceState::Open
+            })
+        }
+
+        async fn get_hold_invoice(
+            &self,
+            _offer: &Offer,
+            _key: &Self::Key,
+            _amount_msat: Option<u64>,
+            _expiry_secs: Option<u64>,
+            _payment_hash: &[u8; 32],
+        ) -> Result<IssuedInvoice, Self::Error> {
+            unimplemented!("get_hold_invoice is not used in health check tests")
+        }
+
+        async fn settle_hold_invoice(
+            &self,
+            _key: &Self::Key,
+            _preimage: &[u8; 32],
+        ) -> Result<(), Self::Error> {
+            unimplemented!("settle_hold_invoice is not used in health check tests")
+        }
+
+        async fn cancel_hold_invoice(
+            &self,
+            _key: &Self::Key,
+            _payment_hash: &[u8; 32],
+        ) -> Result<(), Self::Error> {
+            unimplemented!("cancel_hold_invoice is not used in health check tests")
+        }
+
+        async fn get_bolt12_offer(
+            &self,
+            _key: &Self::Key,
+            _amount_msat: u64,
+            _description: &str,
+            _absolute_expiry: Option<u64>,
+        ) -> Result<PingoraLnBolt12Offer, Self::Error> {
+            unimplemented!("get_bolt12_offer is not used in health check tests")
+        }
+
         fn connect(&self, _key: Self::Key, _backend: &DiscoveryBackend) -> Result<(), Self::Error> {
             unimplemented!("connect is not used in health check tests")
         }
+
+        fn disconnect(&self, _key: &Self::Key) -> Result<(), Self::Error> {
+            unimplemented!("disconnect is not used in health check tests")
+        }
+
+        fn get_credentials_error(&self, _key: &Self::Key) -> Option<String> {
+            self.credentials_error.clone()
+        }
     }
 
     fn create_mock_backend() -> Backend {
@@ -111,14 +241,35 @@ mod tests {
         }
     }
 
+    fn issued_invoice(payment_hash: u8) -> IssuedInvoice {
+        IssuedInvoice {
+            invoice: format!("lnbc{payment_hash}"),
+            payment_hash: [payment_hash; 32],
+            public_key: secp256k1::PublicKey::from_secret_key(
+                &secp256k1::Secp256k1::new(),
+                &secp256k1::SecretKey::from_byte_array([1; 32]).unwrap(),
+            ),
+            expires: chrono::Utc::now() + chrono::Duration::hours(1),
+            attempts: 1,
+        }
+    }
+
     #[tokio::test]
     async fn check_when_node_healthy_then_returns_ok() {
         let mock_pool = MockPingoraLnClientPool {
             should_be_healthy: true,
             return_error: false,
+            credentials_error: None,
+            ..Default::default()
         };
 
-        let health_check = PingoraLnHealthCheck::new(mock_pool, 5, 5);
+        let health_check = PingoraLnHealthCheck::new(
+            mock_pool,
+            5,
+            5,
+            PingoraLnBackendStatusRegistry::new(),
+            PingoraLnInvoiceReservations::new(),
+        );
         let backend = create_mock_backend();
         let result = health_check.check(&backend).await;
         assert!(result.is_ok());
@@ -129,8 +280,16 @@ mod tests {
         let mock_pool = MockPingoraLnClientPool {
             should_be_healthy: false,
             return_error: false,
+            credentials_error: None,
+            ..Default::default()
         };
-        let health_check = PingoraLnHealthCheck::new(mock_pool, 5, 5);
+        let health_check = PingoraLnHealthCheck::new(
+            mock_pool,
+            5,
+            5,
+            PingoraLnBackendStatusRegistry::new(),
+            PingoraLnInvoiceReservations::new(),
+        );
         let backend = create_mock_backend();
 
         let result = health_check.check(&backend).await;
@@ -146,8 +305,16 @@ mod tests {
         let mock_pool = MockPingoraLnClientPool {
             should_be_healthy: true,
             return_error: true,
+            credentials_error: None,
+            ..Default::default()
         };
-        let health_check = PingoraLnHealthCheck::new(mock_pool, 5, 5);
+        let health_check = PingoraLnHealthCheck::new(
+            mock_pool,
+            5,
+            5,
+            PingoraLnBackendStatusRegistry::new(),
+            PingoraLnInvoiceReservations::new(),
+        );
         let backend = create_mock_backend();
 
         let result = health_check.check(&backend).await;
@@ -158,13 +325,116 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn check_when_node_healthy_then_releases_settled_invoices() {
+        let mock_pool = MockPingoraLnClientPool {
+            should_be_healthy: true,
+            return_error: false,
+            credentials_error: None,
+            ..Default::default()
+        };
+        let reservations = PingoraLnInvoiceReservations::new();
+        let health_check = PingoraLnHealthCheck::new(
+            mock_pool,
+            5,
+            5,
+            PingoraLnBackendStatusRegistry::new(),
+            reservations.clone(),
+        );
+        let backend = create_mock_backend();
+        reservations.reserve(&backend, &issued_invoice(0), 1000);
+        reservations.reserve(&backend, &issued_invoice(1), 2000);
+
+        health_check.check(&backend).await.unwrap();
+
+        assert_eq!(reservations.reserved_msat(&backend), 2000);
+    }
+
+    #[tokio::test]
+    async fn check_when_many_invoices_outstanding_then_lookups_bounded_and_concurrent() {
+        let mock_pool = MockPingoraLnClientPool {
+            should_be_healthy: true,
+            ..Default::default()
+        };
+        let max_lookups_in_flight = mock_pool.max_lookups_in_flight.clone();
+        let reservations = PingoraLnInvoiceReservations::new();
+        let health_check = PingoraLnHealthCheck::new(
+            mock_pool,
+            5,
+            5,
+            PingoraLnBackendStatusRegistry::new(),
+            reservations.clone(),
+        );
+        let backend = create_mock_backend();
+        for i in 0..32u8 {
+            reservations.reserve(&backend, &issued_invoice(i), 1000);
+        }
+
+        health_check.check(&backend).await.unwrap();
+
+        let max_lookups_in_flight = max_lookups_in_flight.load(Ordering::SeqCst);
+        assert!(max_lookups_in_flight > 1);
+        assert!(max_lookups_in_flight <= INVOICE_STATE_CONCURRENCY);
+        assert_eq!(reservations.reserved_msat(&backend), 16000);
+    }
+
+    #[tokio::test]
+    async fn check_when_credentials_reload_failed_then_passes_and_records_error() {
+        let mock_pool = MockPingoraLnClientPool {
+            should_be_healthy: true,
+            return_error: false,
+            credentials_error: Some("reloading credentials: bad macaroon".to_string()),
+            ..Default::default()
+        };
+        let status = PingoraLnBackendStatusRegistry::new();
+        let health_check = PingoraLnHealthCheck::new(
+            mock_pool,
+            5,
+            5,
+            status.clone(),
+            PingoraLnInvoiceReservations::new(),
+        );
+        let backend = create_mock_backend();
+        let discovery_backend = DiscoveryBackend {
+            public_key: "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619"
+                .parse()
+                .unwrap(),
+            backend: DiscoveryBackendSparse {
+                name: None,
+                partitions: ["default".to_string()].into(),
+                weight: 1,
+                enabled: true,
+                region: None,
+                implementation: vec![],
+            },
+        };
+        status.observe_discovery(&[(backend.clone(), discovery_backend)].into());
+
+        health_check.check(&backend).await.unwrap();
+
+        let entry = status.get(&backend).unwrap();
+        assert_eq!(entry.consecutive_success, 1);
+        assert_eq!(
+            entry.last_error.as_deref(),
+            Some("reloading credentials: bad macaroon")
+        );
+    }
+
     #[test]
     fn health_threshold_when_called_then_returns_five() {
         let mock_pool = MockPingoraLnClientPool {
             should_be_healthy: true,
             return_error: true,
+            credentials_error: None,
+            ..Default::default()
         };
-        let health_check = PingoraLnHealthCheck::new(mock_pool, 5, 5);
+        let health_check = PingoraLnHealthCheck::new(
+            mock_pool,
+            5,
+            5,
+            PingoraLnBackendStatusRegistry::new(),
+            PingoraLnInvoiceReservations::new(),
+        );
 
         assert_eq!(health_check.health_threshold(true), 5);
         assert_eq!(health_check.health_threshold(false), 5);
@@ -0,0 +1,162 @@
+use crate::error::PingoraLnError;
+use chrono::Utc;
+use secp256k1::PublicKey;
+use std::time::Duration;
+use switchgear_service_api::offer::{HoldInvoiceRecord, HoldInvoiceSparse, HoldInvoiceStore};
+
+// preimages of hold invoices that are neither settled nor canceled yet, kept in the hold invoice
+// store so they survive restarts and can be resolved by any balancer sharing the store
+#[derive(Clone)]
+pub struct PingoraLnHoldInvoices<H> {
+    store: H,
+    cancel_after: Duration,
+}
+
+impl<H> PingoraLnHoldInvoices<H>
+where
+    H: HoldInvoiceStore,
+{
+    pub fn new(store: H, cancel_after: Duration) -> Self {
+        Self {
+            store,
+            cancel_after,
+        }
+    }
+
+    pub async fn hold(
+        &self,
+        payment_hash: [u8; 32],
+        public_key: PublicKey,
+        preimage: [u8; 32],
+        invoice: String,
+    ) -> Result<(), PingoraLnError> {
+        self.restore(HoldInvoiceRecord {
+            payment_hash,
+            hold: HoldInvoiceSparse {
+                public_key,
+                preimage,
+                invoice,
+                cancel_at: Utc::now() + self.cancel_after,
+            },
+        })
+        .await
+    }
+
+    // removes the hold so only one caller settles or cancels it, restore puts it back on failure
+    pub async fn take(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<Option<HoldInvoiceRecord>, PingoraLnError> {
+        let context = || format!("taking hold invoice {}", hex::encode(payment_hash));
+
+        let hold = self
+            .store
+            .get_hold_invoice(payment_hash)
+            .await
+            .map_err(|e| PingoraLnError::from_service_error(context(), e))?;
+        let Some(hold) = hold else {
+            return Ok(None);
+        };
+
+        let taken = self
+            .store
+            .delete_hold_invoice(payment_hash)
+            .await
+            .map_err(|e| PingoraLnError::from_service_error(context(), e))?;
+        Ok(taken.then_some(hold))
+    }
+
+    pub async fn restore(&self, hold: HoldInvoiceRecord) -> Result<(), PingoraLnError> {
+        let context = format!("storing hold invoice {}", hex::encode(hold.payment_hash));
+        self.store
+            .put_hold_invoice(hold)
+            .await
+            .map(|_| ())
+            .map_err(|e| PingoraLnError::from_service_error(context, e))
+    }
+
+    // takes every hold past its cancel deadline, including those stored before a restart
+    pub async fn take_due(&self) -> Result<Vec<HoldInvoiceRecord>, PingoraLnError> {
+        let now = Utc::now();
+        let holds = self
+            .store
+            .get_hold_invoices()
+            .await
+            .map_err(|e| PingoraLnError::from_service_error("taking due hold invoices", e))?;
+
+        let mut due = Vec::new();
+        for hold in holds.into_iter().filter(|hold| hold.hold.cancel_at <= now) {
+            if let Some(hold) = self.take(&hold.payment_hash).await? {
+                due.push(hold);
+            }
+        }
+        Ok(due)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Secp256k1, SecretKey};
+    use switchgear_components::offer::memory::MemoryOfferStore;
+
+    fn public_key() -> PublicKey {
+        SecretKey::from_byte_array([1u8; 32])
+            .unwrap()
+            .public_key(&Secp256k1::new())
+    }
+
+    #[tokio::test]
+    async fn take_when_taken_twice_then_only_first_gets_hold() {
+        let holds = PingoraLnHoldInvoices::new(MemoryOfferStore::new(), Duration::from_secs(60));
+        holds
+            .hold([1u8; 32], public_key(), [2u8; 32], "lnbc1".to_string())
+            .await
+            .unwrap();
+
+        let hold = holds.take(&[1u8; 32]).await.unwrap().unwrap();
+        assert_eq!(hold.hold.preimage, [2u8; 32]);
+        assert_eq!(hold.hold.invoice, "lnbc1");
+        assert!(holds.take(&[1u8; 32]).await.unwrap().is_none());
+
+        holds.restore(hold).await.unwrap();
+        assert!(holds.take(&[1u8; 32]).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn take_due_when_cancel_deadline_passed_then_only_due_holds_taken() {
+        let store = MemoryOfferStore::new();
+        let holds = PingoraLnHoldInvoices::new(store.clone(), Duration::ZERO);
+        holds
+            .hold([1u8; 32], public_key(), [2u8; 32], "lnbc1".to_string())
+            .await
+            .unwrap();
+        let holds_later = PingoraLnHoldInvoices::new(store, Duration::from_secs(60));
+        holds_later
+            .hold([3u8; 32], public_key(), [4u8; 32], "lnbc2".to_string())
+            .await
+            .unwrap();
+
+        let due = holds.take_due().await.unwrap();
+
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].payment_hash, [1u8; 32]);
+        assert!(holds.take_due().await.unwrap().is_empty());
+        assert!(holds.take(&[3u8; 32]).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn take_when_held_by_another_balancer_then_hold_survives_restart() {
+        let store = MemoryOfferStore::new();
+        PingoraLnHoldInvoices::new(store.clone(), Duration::from_secs(60))
+            .hold([1u8; 32], public_key(), [2u8; 32], "lnbc1".to_string())
+            .await
+            .unwrap();
+
+        let restarted = PingoraLnHoldInvoices::new(store, Duration::from_secs(60));
+        let hold = restarted.take(&[1u8; 32]).await.unwrap().unwrap();
+
+        assert_eq!(hold.hold.public_key, public_key());
+        assert_eq!(hold.hold.preimage, [2u8; 32]);
+    }
+}
@@ -3,12 +3,19 @@ pub mod balance;
 pub mod discovery;
 pub mod error;
 pub mod health;
+pub mod hold;
+pub mod outlier;
 pub mod pool;
+pub mod refresh;
+pub mod reservation;
+pub mod status;
+pub mod weight;
 
 use ::backoff::backoff::Backoff;
 use async_trait::async_trait;
 use std::collections::BTreeSet;
 use std::error::Error;
+use switchgear_service_api::balance::IssuedInvoice;
 use switchgear_service_api::discovery::{DiscoveryBackend, DiscoveryBackends};
 use switchgear_service_api::offer::Offer;
 use switchgear_service_api::service::HasServiceErrorSource;
@@ -16,6 +23,7 @@ use switchgear_service_api::service::HasServiceErrorSource;
 #[derive(Debug, Clone)]
 pub struct PingoraLnBackendExtension {
     pub partitions: BTreeSet<String>,
+    pub region: Option<String>,
 }
 
 #[async_trait]
@@ -35,11 +43,69 @@ pub trait PingoraLnClientPool {
         key: &Self::Key,
         amount_msat: Option<u64>,
         expiry_secs: Option<u64>,
-    ) -> Result<String, Self::Error>;
+    ) -> Result<IssuedInvoice, Self::Error>;
 
     async fn get_metrics(&self, key: &Self::Key) -> Result<PingoraLnMetrics, Self::Error>;
 
+    async fn get_invoice_state(
+        &self,
+        key: &Self::Key,
+        invoice: &str,
+        payment_hash: &[u8; 32],
+    ) -> Result<PingoraLnInvoiceState, Self::Error>;
+
+    async fn get_hold_invoice(
+        &self,
+        offer: &Offer,
+        key: &Self::Key,
+        amount_msat: Option<u64>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<IssuedInvoice, Self::Error>;
+
+    async fn settle_hold_invoice(
+        &self,
+        key: &Self::Key,
+        preimage: &[u8; 32],
+    ) -> Result<(), Self::Error>;
+
+    async fn cancel_hold_invoice(
+        &self,
+        key: &Self::Key,
+        payment_hash: &[u8; 32],
+    ) -> Result<(), Self::Error>;
+
+    // whether the connected client can create hold invoices
+    fn supports_hold_invoice(&self, _key: &Self::Key) -> bool {
+        false
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        key: &Self::Key,
+        amount_msat: u64,
+        description: &str,
+        absolute_expiry: Option<u64>,
+    ) -> Result<PingoraLnBolt12Offer, Self::Error>;
+
+    // whether the connected client can create Bolt12 offers
+    fn supports_bolt12(&self, _key: &Self::Key) -> bool {
+        false
+    }
+
     fn connect(&self, key: Self::Key, backend: &DiscoveryBackend) -> Result<(), Self::Error>;
+
+    fn disconnect(&self, key: &Self::Key) -> Result<(), Self::Error>;
+
+    // fails when the connected node reports another id than the discovery public key
+    async fn verify_node_id(&self, _key: &Self::Key) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    // why the client could not be rebuilt after its credentials changed, if it could not
+    fn get_credentials_error(&self, _key: &Self::Key) -> Option<String> {
+        None
+    }
 }
 
 pub trait PingoraLnMetricsCache {
@@ -54,6 +120,19 @@ pub struct PingoraLnMetrics {
     pub node_effective_inbound_msat: u64,
 }
 
+#[derive(Eq, PartialEq, Debug, Clone, Ord, PartialOrd)]
+pub struct PingoraLnBolt12Offer {
+    pub offer_id: String,
+    pub bolt12: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum PingoraLnInvoiceState {
+    Open,
+    Settled,
+    Canceled,
+}
+
 pub trait PingoraBackoffProvider: Clone + Send + Sync {
     type Item: Backoff + Send;
 
@@ -0,0 +1,387 @@
+use crate::PingoraLnBackendExtension;
+use log::{error, warn};
+use pingora_core::protocols::l4::socket::SocketAddr;
+use pingora_load_balancing::Backend;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct PingoraLnOutlierEntry {
+    partitions: BTreeSet<String>,
+    outcomes: VecDeque<(Instant, bool)>,
+    ejections: u32,
+    ejected_until: Option<Instant>,
+}
+
+impl PingoraLnOutlierEntry {
+    fn is_ejected(&self, now: Instant) -> bool {
+        self.ejected_until.is_some_and(|until| until > now)
+    }
+
+    fn success_rate(&self) -> f64 {
+        let successes = self.outcomes.iter().filter(|(_, success)| *success).count();
+        successes as f64 / self.outcomes.len() as f64
+    }
+}
+
+// success rate outlier detection, modeled after Envoy's: backends whose success rate over the
+// window falls below the partition mean by more than stdev_factor standard deviations are ejected
+// for base_ejection, doubling with each consecutive ejection up to max_ejection
+#[derive(Clone)]
+pub struct PingoraLnOutlierDetection {
+    window: Duration,
+    minimum_requests: usize,
+    minimum_hosts: usize,
+    stdev_factor: f64,
+    base_ejection: Duration,
+    max_ejection: Duration,
+    max_ejection_percent: f64,
+    // entries are keyed by backend address, which is stable across weight changes
+    entries: Arc<Mutex<HashMap<SocketAddr, PingoraLnOutlierEntry>>>,
+}
+
+impl PingoraLnOutlierDetection {
+    pub fn new(
+        window: Duration,
+        minimum_requests: usize,
+        minimum_hosts: usize,
+        stdev_factor: f64,
+        base_ejection: Duration,
+        max_ejection: Duration,
+        max_ejection_percent: f64,
+    ) -> Self {
+        assert!(
+            Self::valid_limits(minimum_requests, max_ejection_percent),
+            "invalid outlier detection minimum requests {minimum_requests} or max ejection percent {max_ejection_percent}"
+        );
+        Self {
+            window,
+            minimum_requests,
+            minimum_hosts,
+            stdev_factor,
+            base_ejection,
+            max_ejection,
+            max_ejection_percent,
+            entries: Default::default(),
+        }
+    }
+
+    // a success rate needs at least one request, and no more than every backend can be ejected
+    pub fn valid_limits(minimum_requests: usize, max_ejection_percent: f64) -> bool {
+        minimum_requests > 0 && (0.0..=100.0).contains(&max_ejection_percent)
+    }
+
+    // backends are the ones currently discovered, entries of removed backends are dropped so they
+    // don't count as partition members
+    pub fn observe(
+        &self,
+        backend: &Backend,
+        partitions: &BTreeSet<String>,
+        success: bool,
+        backends: &BTreeSet<Backend>,
+    ) {
+        let mut entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("updating outlier detection for backend {backend:?}: {e}");
+                return;
+            }
+        };
+
+        let addrs = backends.iter().map(|b| &b.addr).collect::<HashSet<_>>();
+        entries.retain(|addr, _| addrs.contains(addr));
+
+        let now = Instant::now();
+        let entry = entries.entry(backend.addr.clone()).or_default();
+        entry.partitions = partitions.clone();
+        entry.outcomes.push_back((now, success));
+
+        for partition in partitions {
+            self.evaluate(&mut entries, partition, now, backends);
+        }
+    }
+
+    pub fn is_ejected(&self, backend: &Backend) -> bool {
+        match self.entries.lock() {
+            Ok(entries) => entries
+                .get(&backend.addr)
+                .is_some_and(|entry| entry.is_ejected(Instant::now())),
+            Err(e) => {
+                error!("reading outlier detection for backend {backend:?}: {e}");
+                false
+            }
+        }
+    }
+
+    fn evaluate(
+        &self,
+        entries: &mut HashMap<SocketAddr, PingoraLnOutlierEntry>,
+        partition: &str,
+        now: Instant,
+        backends: &BTreeSet<Backend>,
+    ) {
+        // every discovered member of the partition counts towards max_ejection_percent, not only
+        // the ones that have served requests
+        let mut members = backends
+            .iter()
+            .filter(|backend| {
+                backend
+                    .ext
+                    .get::<PingoraLnBackendExtension>()
+                    .is_some_and(|extension| extension.partitions.contains(partition))
+            })
+            .map(|backend| backend.addr.clone())
+            .collect::<HashSet<_>>();
+        let mut ejected = 0usize;
+        let mut candidates = vec![];
+        for (addr, entry) in entries.iter_mut() {
+            if !entry.partitions.contains(partition) {
+                continue;
+            }
+            members.insert(addr.clone());
+            while entry
+                .outcomes
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > self.window)
+            {
+                entry.outcomes.pop_front();
+            }
+            if entry.is_ejected(now) {
+                ejected += 1;
+            } else if entry.outcomes.len() >= self.minimum_requests {
+                candidates.push((addr.clone(), entry.success_rate()));
+            }
+        }
+
+        if candidates.is_empty() || candidates.len() < self.minimum_hosts {
+            return;
+        }
+
+        let n = candidates.len() as f64;
+        let mean = candidates.iter().map(|(_, rate)| rate).sum::<f64>() / n;
+        let variance = candidates
+            .iter()
+            .map(|(_, rate)| (rate - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        let threshold = mean - self.stdev_factor * variance.sqrt();
+
+        let max_ejected =
+            (members.len() as f64 * self.max_ejection_percent / 100.0).floor() as usize;
+        candidates.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        for (addr, rate) in candidates {
+            let Some(entry) = entries.get_mut(&addr) else {
+                continue;
+            };
+            if rate < threshold && ejected < max_ejected {
+                entry.ejections += 1;
+                let ejection = self
+                    .base_ejection
+                    .saturating_mul(2u32.saturating_pow(entry.ejections - 1))
+                    .min(self.max_ejection);
+                entry.ejected_until = Some(now + ejection);
+                entry.outcomes.clear();
+                ejected += 1;
+                warn!(
+                    "ejecting outlier backend {addr:?} in partition {partition} for {}s, success rate {rate:.2} below {threshold:.2}",
+                    ejection.as_secs()
+                );
+            } else if entry
+                .ejected_until
+                .is_some_and(|until| now.duration_since(until) > self.max_ejection)
+            {
+                // well behaved for long enough after its last ejection
+                entry.ejections = 0;
+                entry.ejected_until = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_detection(max_ejection_percent: f64) -> PingoraLnOutlierDetection {
+        PingoraLnOutlierDetection::new(
+            Duration::from_secs(60),
+            10,
+            3,
+            1.0,
+            Duration::from_secs(30),
+            Duration::from_secs(100),
+            max_ejection_percent,
+        )
+    }
+
+    fn create_backends(count: u16) -> Vec<Backend> {
+        (0..count)
+            .map(|i| Backend::new(&format!("127.0.0.1:{}", 8080 + i)).unwrap())
+            .collect()
+    }
+
+    fn create_partition_backends(count: u16) -> Vec<Backend> {
+        create_backends(count)
+            .into_iter()
+            .map(|mut backend| {
+                backend.ext.insert(PingoraLnBackendExtension {
+                    partitions: BTreeSet::from(["default".to_string()]),
+                    region: None,
+                });
+                backend
+            })
+            .collect()
+    }
+
+    fn observe_many(
+        detection: &PingoraLnOutlierDetection,
+        backends: &[Backend],
+        backend: &Backend,
+        successes: usize,
+        failures: usize,
+    ) {
+        let partitions = BTreeSet::from(["default".to_string()]);
+        let backends = backends.iter().cloned().collect::<BTreeSet<_>>();
+        for _ in 0..successes {
+            detection.observe(backend, &partitions, true, &backends);
+        }
+        for _ in 0..failures {
+            detection.observe(backend, &partitions, false, &backends);
+        }
+    }
+
+    fn ejection(detection: &PingoraLnOutlierDetection, backend: &Backend) -> Option<Duration> {
+        let entries = detection.entries.lock().unwrap();
+        let entry = entries.get(&backend.addr)?;
+        entry
+            .ejected_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+    }
+
+    #[test]
+    fn observe_when_success_rate_far_below_peers_then_ejected() {
+        let detection = create_detection(50.0);
+        let backends = create_backends(4);
+
+        for backend in &backends[1..] {
+            observe_many(&detection, &backends, backend, 10, 0);
+        }
+        observe_many(&detection, &backends, &backends[0], 2, 8);
+
+        assert!(detection.is_ejected(&backends[0]));
+        for backend in &backends[1..] {
+            assert!(!detection.is_ejected(backend));
+        }
+    }
+
+    #[test]
+    fn observe_when_too_few_hosts_then_not_ejected() {
+        let detection = create_detection(50.0);
+        let backends = create_backends(2);
+
+        observe_many(&detection, &backends, &backends[1], 10, 0);
+        observe_many(&detection, &backends, &backends[0], 0, 10);
+
+        assert!(!detection.is_ejected(&backends[0]));
+    }
+
+    #[test]
+    fn observe_when_max_ejection_percent_reached_then_not_ejected() {
+        let detection = create_detection(20.0);
+        let backends = create_backends(4);
+
+        for backend in &backends[1..] {
+            observe_many(&detection, &backends, backend, 10, 0);
+        }
+        observe_many(&detection, &backends, &backends[0], 0, 10);
+
+        // 20% of 4 backends rounds down to no ejections
+        assert!(!detection.is_ejected(&backends[0]));
+    }
+
+    #[test]
+    fn observe_when_backend_removed_then_entry_dropped_and_not_counted() {
+        let detection = create_detection(20.0);
+        let backends = create_backends(5);
+        for backend in &backends[1..] {
+            observe_many(&detection, &backends, backend, 10, 0);
+        }
+
+        // 20% of the 4 remaining backends rounds down to no ejections, the removed one
+        // would have allowed one
+        let remaining = &backends[..4];
+        observe_many(&detection, remaining, &backends[0], 0, 10);
+
+        assert!(!detection.is_ejected(&backends[0]));
+        assert!(!detection
+            .entries
+            .lock()
+            .unwrap()
+            .contains_key(&backends[4].addr));
+    }
+
+    #[test]
+    fn observe_when_ejected_repeatedly_then_ejection_doubles_up_to_max() {
+        let detection = create_detection(50.0);
+        let backends = create_backends(4);
+        for backend in &backends[1..] {
+            observe_many(&detection, &backends, backend, 10, 0);
+        }
+
+        let mut ejections = vec![];
+        for _ in 0..3 {
+            observe_many(&detection, &backends, &backends[0], 0, 10);
+            ejections.push(ejection(&detection, &backends[0]).unwrap().as_secs() + 1);
+            // end the ejection early
+            detection
+                .entries
+                .lock()
+                .unwrap()
+                .get_mut(&backends[0].addr)
+                .unwrap()
+                .ejected_until = Some(Instant::now());
+        }
+
+        assert_eq!(ejections, vec![30, 60, 100]);
+    }
+
+    #[test]
+    fn observe_when_partition_members_without_requests_then_counted() {
+        let detection = create_detection(20.0);
+        let backends = create_partition_backends(5);
+
+        // only 4 of the 5 members serve requests, 20% of 4 alone would allow no ejections
+        for backend in &backends[1..4] {
+            observe_many(&detection, &backends, backend, 10, 0);
+        }
+        observe_many(&detection, &backends, &backends[0], 0, 10);
+
+        assert!(detection.is_ejected(&backends[0]));
+    }
+
+    #[test]
+    fn valid_limits_when_no_minimum_requests_or_percent_out_of_range_then_invalid() {
+        assert!(PingoraLnOutlierDetection::valid_limits(1, 0.0));
+        assert!(PingoraLnOutlierDetection::valid_limits(10, 100.0));
+        assert!(!PingoraLnOutlierDetection::valid_limits(0, 50.0));
+        assert!(!PingoraLnOutlierDetection::valid_limits(10, -1.0));
+        assert!(!PingoraLnOutlierDetection::valid_limits(10, 100.5));
+        assert!(!PingoraLnOutlierDetection::valid_limits(10, f64::NAN));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid outlier detection")]
+    fn new_when_minimum_requests_zero_then_panics() {
+        PingoraLnOutlierDetection::new(
+            Duration::from_secs(60),
+            0,
+            3,
+            1.0,
+            Duration::from_secs(30),
+            Duration::from_secs(100),
+            50.0,
+        );
+    }
+}
@@ -1,18 +1,24 @@
-use crate::{PingoraLnClientPool, PingoraLnMetrics, PingoraLnMetricsCache};
+use crate::{
+    PingoraLnBolt12Offer, PingoraLnClientPool, PingoraLnInvoiceState, PingoraLnMetrics,
+    PingoraLnMetricsCache,
+};
 use async_trait::async_trait;
+use pingora_core::protocols::l4::socket::SocketAddr;
 use pingora_load_balancing::Backend;
 use switchgear_components::pool::error::LnPoolError;
-use switchgear_components::pool::LnClientPool;
+use switchgear_components::pool::{LnClientPool, LnInvoiceState};
+use switchgear_service_api::balance::IssuedInvoice;
 use switchgear_service_api::discovery::DiscoveryBackend;
 use switchgear_service_api::offer::Offer;
 
+// clients and metrics are keyed by backend address, which is stable across weight changes
 #[derive(Clone)]
 pub struct DefaultPingoraLnClientPool {
-    pool: LnClientPool<Backend>,
+    pool: LnClientPool<SocketAddr>,
 }
 
 impl DefaultPingoraLnClientPool {
-    pub fn new(pool: LnClientPool<Backend>) -> Self {
+    pub fn new(pool: LnClientPool<SocketAddr>) -> Self {
         Self { pool }
     }
 }
@@ -28,22 +34,109 @@ impl PingoraLnClientPool for DefaultPingoraLnClientPool {
         key: &Self::Key,
         amount_msat: Option<u64>,
         expiry_secs: Option<u64>,
-    ) -> Result<String, Self::Error> {
+    ) -> Result<IssuedInvoice, Self::Error> {
         self.pool
-            .get_invoice(offer, key, amount_msat, expiry_secs)
+            .get_invoice(offer, &key.addr, amount_msat, expiry_secs)
             .await
     }
 
     async fn get_metrics(&self, key: &Self::Key) -> Result<PingoraLnMetrics, Self::Error> {
-        let metrics = self.pool.get_metrics(key).await?;
+        let metrics = self.pool.get_metrics(&key.addr).await?;
         Ok(PingoraLnMetrics {
             healthy: metrics.healthy,
             node_effective_inbound_msat: metrics.node_effective_inbound_msat,
         })
     }
 
+    async fn get_invoice_state(
+        &self,
+        key: &Self::Key,
+        invoice: &str,
+        payment_hash: &[u8; 32],
+    ) -> Result<PingoraLnInvoiceState, Self::Error> {
+        let state = self
+            .pool
+            .get_invoice_state(&key.addr, invoice, payment_hash)
+            .await?;
+        Ok(match state {
+            LnInvoiceState::Open => PingoraLnInvoiceState::Open,
+            LnInvoiceState::Settled => PingoraLnInvoiceState::Settled,
+            LnInvoiceState::Canceled => PingoraLnInvoiceState::Canceled,
+        })
+    }
+
+    async fn get_hold_invoice(
+        &self,
+        offer: &Offer,
+        key: &Self::Key,
+        amount_msat: Option<u64>,
+        expiry_secs: Option<u64>,
+        payment_hash: &[u8; 32],
+    ) -> Result<IssuedInvoice, Self::Error> {
+        self.pool
+            .get_hold_invoice(offer, &key.addr, amount_msat, expiry_secs, payment_hash)
+            .await
+    }
+
+    async fn settle_hold_invoice(
+        &self,
+        key: &Self::Key,
+        preimage: &[u8; 32],
+    ) -> Result<(), Self::Error> {
+        self.pool.settle_hold_invoice(&key.addr, preimage).await
+    }
+
+    async fn cancel_hold_invoice(
+        &self,
+        key: &Self::Key,
+        payment_hash: &[u8; 32],
+    ) -> Result<(), Self::Error> {
+        self.pool.cancel_hold_invoice(&key.addr, payment_hash).await
+    }
+
+    fn supports_hold_invoice(&self, key: &Self::Key) -> bool {
+        self.pool
+            .get_features(&key.addr)
+            .is_some_and(|features| features.hold_invoice)
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        key: &Self::Key,
+        amount_msat: u64,
+        description: &str,
+        absolute_expiry: Option<u64>,
+    ) -> Result<PingoraLnBolt12Offer, Self::Error> {
+        let offer = self
+            .pool
+            .get_bolt12_offer(&key.addr, amount_msat, description, absolute_expiry)
+            .await?;
+        Ok(PingoraLnBolt12Offer {
+            offer_id: offer.offer_id,
+            bolt12: offer.bolt12,
+        })
+    }
+
+    fn supports_bolt12(&self, key: &Self::Key) -> bool {
+        self.pool
+            .get_features(&key.addr)
+            .is_some_and(|features| features.bolt12)
+    }
+
     fn connect(&self, key: Self::Key, backend: &DiscoveryBackend) -> Result<(), Self::Error> {
-        self.pool.connect(key, backend)
+        self.pool.connect(key.addr, backend)
+    }
+
+    fn disconnect(&self, key: &Self::Key) -> Result<(), Self::Error> {
+        self.pool.disconnect(&key.addr)
+    }
+
+    async fn verify_node_id(&self, key: &Self::Key) -> Result<(), Self::Error> {
+        self.pool.verify_node_id(&key.addr).await
+    }
+
+    fn get_credentials_error(&self, key: &Self::Key) -> Option<String> {
+        self.pool.get_credentials_error(&key.addr)
     }
 }
 
@@ -51,7 +144,7 @@ impl PingoraLnMetricsCache for DefaultPingoraLnClientPool {
     type Key = Backend;
 
     fn get_cached_metrics(&self, key: &Self::Key) -> Option<PingoraLnMetrics> {
-        let metrics = self.pool.get_cached_metrics(key);
+        let metrics = self.pool.get_cached_metrics(&key.addr);
         metrics.map(|m| PingoraLnMetrics {
             healthy: m.healthy,
             node_effective_inbound_msat: m.node_effective_inbound_msat,
@@ -0,0 +1,181 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+#[derive(Default)]
+struct PingoraLnRefreshState {
+    started: Option<Instant>,
+    finished: Option<Instant>,
+    in_flight: Option<watch::Receiver<bool>>,
+}
+
+// discovery updates and health sweeps forced by failed requests, run by one request at a time
+#[derive(Clone)]
+pub struct PingoraLnForcedRefresh {
+    state: Arc<Mutex<PingoraLnRefreshState>>,
+    min_interval: Duration,
+}
+
+impl PingoraLnForcedRefresh {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            state: Default::default(),
+            min_interval,
+        }
+    }
+
+    // callers arriving while a refresh is in flight wait for it instead of starting their own,
+    // and refreshes closer together than min_interval are skipped. the refresh runs in its own
+    // task so it completes even if the caller that started it is cancelled
+    pub async fn refresh<F, Fut>(&self, refresh: F)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let requested = Instant::now();
+        let mut done = {
+            let mut state = self.state.lock().expect("refresh state lock poisoned");
+
+            // a refresh task that panicked drops its sender without clearing in_flight
+            match state
+                .in_flight
+                .clone()
+                .filter(|done| done.has_changed().is_ok())
+            {
+                Some(done) => done,
+                None => {
+                    if state.finished.is_some_and(|finished| finished >= requested)
+                        || state
+                            .started
+                            .is_some_and(|started| started.elapsed() < self.min_interval)
+                    {
+                        return;
+                    }
+
+                    let (done_tx, done) = watch::channel(false);
+                    state.started = Some(Instant::now());
+                    state.in_flight = Some(done.clone());
+
+                    let refresh = refresh();
+                    let task_state = self.state.clone();
+                    tokio::spawn(async move {
+                        refresh.await;
+                        {
+                            let mut state = task_state.lock().expect("refresh state lock poisoned");
+                            state.finished = Some(Instant::now());
+                            state.in_flight = None;
+                        }
+                        let _ = done_tx.send(true);
+                    });
+                    done
+                }
+            }
+        };
+
+        let _ = done.wait_for(|done| *done).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_concurrent_refreshes_share_one_in_flight_refresh() {
+        let forced_refresh = PingoraLnForcedRefresh::new(Duration::ZERO);
+        let refreshes = Arc::new(AtomicUsize::new(0));
+
+        let waiters = (0..100).map(|_| {
+            let forced_refresh = forced_refresh.clone();
+            let refreshes = refreshes.clone();
+            tokio::spawn(async move {
+                forced_refresh
+                    .refresh(|| async move {
+                        refreshes.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    })
+                    .await
+            })
+        });
+        for waiter in waiters.collect::<Vec<_>>() {
+            waiter.await.unwrap();
+        }
+
+        // the first refresh runs, everyone queued behind it shares it
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_skipped_within_min_interval() {
+        let forced_refresh = PingoraLnForcedRefresh::new(Duration::from_secs(60));
+        let refreshes = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..3 {
+            let refreshes = refreshes.clone();
+            forced_refresh
+                .refresh(|| async move {
+                    refreshes.fetch_add(1, Ordering::SeqCst);
+                })
+                .await;
+        }
+
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_runs_again_after_min_interval() {
+        let forced_refresh = PingoraLnForcedRefresh::new(Duration::from_millis(10));
+        let refreshes = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..2 {
+            let refreshes = refreshes.clone();
+            forced_refresh
+                .refresh(|| async move {
+                    refreshes.fetch_add(1, Ordering::SeqCst);
+                })
+                .await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_completes_when_starting_caller_cancelled() {
+        let forced_refresh = PingoraLnForcedRefresh::new(Duration::ZERO);
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        let caller = {
+            let forced_refresh = forced_refresh.clone();
+            let refreshes = refreshes.clone();
+            let completed = completed.clone();
+            tokio::spawn(async move {
+                forced_refresh
+                    .refresh(|| async move {
+                        refreshes.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        completed.fetch_add(1, Ordering::SeqCst);
+                    })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        caller.abort();
+
+        // a later caller waits for the abandoned refresh rather than starting another
+        {
+            let refreshes = refreshes.clone();
+            forced_refresh
+                .refresh(|| async move {
+                    refreshes.fetch_add(1, Ordering::SeqCst);
+                })
+                .await;
+        }
+
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+    }
+}
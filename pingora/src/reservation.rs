@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use log::error;
+use pingora_core::protocols::l4::socket::SocketAddr;
+use pingora_load_balancing::Backend;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use switchgear_service_api::balance::IssuedInvoice;
+
+#[derive(Debug, Clone)]
+struct PingoraLnInvoiceReservation {
+    invoice: String,
+    payment_hash: [u8; 32],
+    amount_msat: u64,
+    expires: DateTime<Utc>,
+}
+
+// reservations are keyed by backend address, which is stable across weight changes
+#[derive(Clone, Default)]
+pub struct PingoraLnInvoiceReservations {
+    reservations: Arc<Mutex<HashMap<SocketAddr, Vec<PingoraLnInvoiceReservation>>>>,
+}
+
+impl PingoraLnInvoiceReservations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // reserved until the invoice expires, released earlier once the node reports it closed
+    pub fn reserve(&self, backend: &Backend, invoice: &IssuedInvoice, amount_msat: u64) {
+        self.update(backend, |reservations| {
+            reservations.push(PingoraLnInvoiceReservation {
+                invoice: invoice.invoice.clone(),
+                payment_hash: invoice.payment_hash,
+                amount_msat,
+                expires: invoice.expires,
+            });
+        });
+    }
+
+    pub fn release(&self, backend: &Backend, payment_hash: &[u8; 32]) {
+        self.update(backend, |reservations| {
+            reservations.retain(|r| &r.payment_hash != payment_hash);
+        });
+    }
+
+    pub fn reserved_msat(&self, backend: &Backend) -> u64 {
+        let mut reserved_msat = 0u64;
+        self.update(backend, |reservations| {
+            reserved_msat = reservations
+                .iter()
+                .fold(0u64, |sum, r| sum.saturating_add(r.amount_msat));
+        });
+        reserved_msat
+    }
+
+    // (invoice, payment hash) pairs, the hash is kept so nodes don't decode the invoice again
+    pub fn outstanding_invoices(&self, backend: &Backend) -> Vec<(String, [u8; 32])> {
+        let mut invoices = vec![];
+        self.update(backend, |reservations| {
+            invoices = reservations
+                .iter()
+                .map(|r| (r.invoice.clone(), r.payment_hash))
+                .collect();
+        });
+        invoices
+    }
+
+    fn update<F>(&self, backend: &Backend, f: F)
+    where
+        F: FnOnce(&mut Vec<PingoraLnInvoiceReservation>),
+    {
+        let mut reservations = match self.reservations.lock() {
+            Ok(reservations) => reservations,
+            Err(e) => {
+                error!("updating invoice reservations for backend {backend:?}: {e}");
+                return;
+            }
+        };
+
+        let now = Utc::now();
+        let backend_reservations = reservations.entry(backend.addr.clone()).or_default();
+        backend_reservations.retain(|r| r.expires > now);
+        f(backend_reservations);
+        if backend_reservations.is_empty() {
+            reservations.remove(&backend.addr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+    fn issued_invoice(invoice: &str, payment_hash: u8, expires_in: Duration) -> IssuedInvoice {
+        IssuedInvoice {
+            invoice: invoice.to_string(),
+            payment_hash: [payment_hash; 32],
+            public_key: PublicKey::from_secret_key(
+                &Secp256k1::new(),
+                &SecretKey::from_byte_array([1; 32]).unwrap(),
+            ),
+            expires: Utc::now() + expires_in,
+            attempts: 1,
+        }
+    }
+
+    #[test]
+    fn reserved_msat_when_invoices_reserved_then_sums_amounts() {
+        let reservations = PingoraLnInvoiceReservations::new();
+        let backend1 = Backend::new("127.0.0.1:8080").unwrap();
+        let backend2 = Backend::new("127.0.0.1:8081").unwrap();
+
+        reservations.reserve(
+            &backend1,
+            &issued_invoice("lnbc1", 1, Duration::seconds(60)),
+            1000,
+        );
+        reservations.reserve(
+            &backend1,
+            &issued_invoice("lnbc2", 2, Duration::seconds(60)),
+            2000,
+        );
+        reservations.reserve(
+            &backend2,
+            &issued_invoice("lnbc3", 3, Duration::seconds(60)),
+            5000,
+        );
+
+        assert_eq!(reservations.reserved_msat(&backend1), 3000);
+        assert_eq!(reservations.reserved_msat(&backend2), 5000);
+    }
+
+    #[test]
+    fn release_when_invoice_settled_then_amount_no_longer_reserved() {
+        let reservations = PingoraLnInvoiceReservations::new();
+        let backend = Backend::new("127.0.0.1:8080").unwrap();
+
+        reservations.reserve(
+            &backend,
+            &issued_invoice("lnbc1", 1, Duration::seconds(60)),
+            1000,
+        );
+        reservations.reserve(
+            &backend,
+            &issued_invoice("lnbc2", 2, Duration::seconds(60)),
+            2000,
+        );
+        reservations.release(&backend, &[1; 32]);
+
+        assert_eq!(reservations.reserved_msat(&backend), 2000);
+        assert_eq!(
+            reservations.outstanding_invoices(&backend),
+            vec![("lnbc2".to_string(), [2; 32])]
+        );
+    }
+
+    #[test]
+    fn reserved_msat_when_invoice_expired_then_released() {
+        let reservations = PingoraLnInvoiceReservations::new();
+        let backend = Backend::new("127.0.0.1:8080").unwrap();
+
+        reservations.reserve(
+            &backend,
+            &issued_invoice("lnbc1", 1, Duration::zero()),
+            1000,
+        );
+        reservations.reserve(
+            &backend,
+            &issued_invoice("lnbc2", 2, Duration::seconds(60)),
+            2000,
+        );
+
+        assert_eq!(reservations.reserved_msat(&backend), 2000);
+        assert_eq!(
+            reservations.outstanding_invoices(&backend),
+            vec![("lnbc2".to_string(), [2; 32])]
+        );
+    }
+}
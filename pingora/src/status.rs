@@ -0,0 +1,236 @@
+use log::error;
+use pingora_core::protocols::l4::socket::SocketAddr;
+use pingora_load_balancing::Backend;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use switchgear_service_api::discovery::DiscoveryBackend;
+
+#[derive(Debug, Clone)]
+pub struct PingoraLnBackendStatusEntry {
+    pub backend: DiscoveryBackend,
+    pub healthy: bool,
+    pub healthy_since: Option<Instant>,
+    pub consecutive_success: usize,
+    pub consecutive_failure: usize,
+    pub last_error: Option<String>,
+    pub last_invoice_latency: Option<Duration>,
+}
+
+impl PingoraLnBackendStatusEntry {
+    fn new(backend: DiscoveryBackend) -> Self {
+        Self {
+            backend,
+            // pingora treats newly discovered backends as healthy until proven otherwise
+            healthy: true,
+            healthy_since: Some(Instant::now()),
+            consecutive_success: 0,
+            consecutive_failure: 0,
+            last_error: None,
+            last_invoice_latency: None,
+        }
+    }
+}
+
+// entries are keyed by backend address, which is stable across weight changes
+#[derive(Clone, Default)]
+pub struct PingoraLnBackendStatusRegistry {
+    entries: Arc<Mutex<HashMap<SocketAddr, PingoraLnBackendStatusEntry>>>,
+}
+
+impl PingoraLnBackendStatusRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe_discovery(&self, backends: &BTreeMap<Backend, DiscoveryBackend>) {
+        let addrs = backends.keys().map(|b| &b.addr).collect::<HashSet<_>>();
+        self.update(|entries| {
+            entries.retain(|addr, _| addrs.contains(addr));
+            for (backend, discovery_backend) in backends {
+                match entries.get_mut(&backend.addr) {
+                    Some(entry) => entry.backend = discovery_backend.clone(),
+                    None => {
+                        entries.insert(
+                            backend.addr.clone(),
+                            PingoraLnBackendStatusEntry::new(discovery_backend.clone()),
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn observe_health_check(&self, backend: &Backend, result: Result<(), String>) {
+        self.update_entry(backend, |entry| match result {
+            Ok(()) => {
+                entry.consecutive_success += 1;
+                entry.consecutive_failure = 0;
+            }
+            Err(e) => {
+                entry.consecutive_success = 0;
+                entry.consecutive_failure += 1;
+                entry.last_error = Some(e);
+            }
+        });
+    }
+
+    pub fn observe_health_status_change(&self, backend: &Backend, healthy: bool) {
+        self.update_entry(backend, |entry| {
+            if healthy && !entry.healthy {
+                entry.healthy_since = Some(Instant::now());
+            } else if !healthy {
+                entry.healthy_since = None;
+            }
+            entry.healthy = healthy;
+        });
+    }
+
+    pub fn observe_invoice(
+        &self,
+        backend: &Backend,
+        latency: Duration,
+        result: Result<(), String>,
+    ) {
+        self.update_entry(backend, |entry| {
+            entry.last_invoice_latency = Some(latency);
+            if let Err(e) = result {
+                entry.last_error = Some(e);
+            }
+        });
+    }
+
+    pub fn observe_credentials_error(&self, backend: &Backend, error: String) {
+        self.update_entry(backend, |entry| entry.last_error = Some(error));
+    }
+
+    pub fn get(&self, backend: &Backend) -> Option<PingoraLnBackendStatusEntry> {
+        match self.entries.lock() {
+            Ok(entries) => entries.get(&backend.addr).cloned(),
+            Err(e) => {
+                error!("reading backend status registry: {e}");
+                None
+            }
+        }
+    }
+
+    fn update_entry<F>(&self, backend: &Backend, f: F)
+    where
+        F: FnOnce(&mut PingoraLnBackendStatusEntry),
+    {
+        self.update(|entries| {
+            if let Some(entry) = entries.get_mut(&backend.addr) {
+                f(entry);
+            }
+        });
+    }
+
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut HashMap<SocketAddr, PingoraLnBackendStatusEntry>),
+    {
+        match self.entries.lock() {
+            Ok(mut entries) => f(&mut entries),
+            Err(e) => error!("updating backend status registry: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
+    use switchgear_service_api::discovery::DiscoveryBackendSparse;
+
+    fn create_discovery_backend() -> DiscoveryBackend {
+        let secp = Secp256k1::new();
+        let mut rng = rand::thread_rng();
+        let secret_key = SecretKey::from_byte_array(rng.gen::<[u8; 32]>()).unwrap();
+
+        DiscoveryBackend {
+            public_key: PublicKey::from_secret_key(&secp, &secret_key),
+            backend: DiscoveryBackendSparse {
+                name: Some("node".to_string()),
+                partitions: ["default".to_string()].into(),
+                weight: 1,
+                enabled: true,
+                region: None,
+                implementation: "{}".as_bytes().to_vec(),
+            },
+        }
+    }
+
+    #[test]
+    fn observe_health_check_when_alternating_then_counts_reset() {
+        let registry = PingoraLnBackendStatusRegistry::new();
+        let backend = Backend::new("127.0.0.1:8080").unwrap();
+        registry.observe_discovery(&BTreeMap::from([(
+            backend.clone(),
+            create_discovery_backend(),
+        )]));
+
+        registry.observe_health_check(&backend, Ok(()));
+        registry.observe_health_check(&backend, Ok(()));
+        let entry = registry.get(&backend).unwrap();
+        assert_eq!(entry.consecutive_success, 2);
+        assert_eq!(entry.consecutive_failure, 0);
+        assert!(entry.last_error.is_none());
+
+        registry.observe_health_check(&backend, Err("unreachable".to_string()));
+        let entry = registry.get(&backend).unwrap();
+        assert_eq!(entry.consecutive_success, 0);
+        assert_eq!(entry.consecutive_failure, 1);
+        assert_eq!(entry.last_error.as_deref(), Some("unreachable"));
+    }
+
+    #[test]
+    fn observe_discovery_when_backend_removed_then_entry_dropped() {
+        let registry = PingoraLnBackendStatusRegistry::new();
+        let backend1 = Backend::new("127.0.0.1:8080").unwrap();
+        let backend2 = Backend::new("127.0.0.1:8081").unwrap();
+        registry.observe_discovery(&BTreeMap::from([
+            (backend1.clone(), create_discovery_backend()),
+            (backend2.clone(), create_discovery_backend()),
+        ]));
+        registry.observe_invoice(&backend1, Duration::from_millis(12), Ok(()));
+
+        registry.observe_discovery(&BTreeMap::from([(
+            backend1.clone(),
+            create_discovery_backend(),
+        )]));
+
+        let entry = registry.get(&backend1).unwrap();
+        assert_eq!(entry.last_invoice_latency, Some(Duration::from_millis(12)));
+        assert!(registry.get(&backend2).is_none());
+    }
+
+    #[test]
+    fn observe_health_status_change_when_recovered_then_healthy_since_reset() {
+        let registry = PingoraLnBackendStatusRegistry::new();
+        let backend = Backend::new("127.0.0.1:8080").unwrap();
+        registry.observe_discovery(&BTreeMap::from([(
+            backend.clone(),
+            create_discovery_backend(),
+        )]));
+        let discovered_since = registry.get(&backend).unwrap().healthy_since.unwrap();
+
+        registry.observe_health_status_change(&backend, false);
+        assert!(registry.get(&backend).unwrap().healthy_since.is_none());
+
+        registry.observe_health_status_change(&backend, true);
+        let recovered_since = registry.get(&backend).unwrap().healthy_since.unwrap();
+        assert!(recovered_since >= discovered_since);
+    }
+
+    #[test]
+    fn observe_health_check_when_backend_unknown_then_ignored() {
+        let registry = PingoraLnBackendStatusRegistry::new();
+        let backend = Backend::new("127.0.0.1:8080").unwrap();
+
+        registry.observe_health_check(&backend, Ok(()));
+        registry.observe_health_status_change(&backend, false);
+
+        assert!(registry.get(&backend).is_none());
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PingoraLnDynamicWeights {
+    min_multiplier: f64,
+    max_multiplier: f64,
+}
+
+impl PingoraLnDynamicWeights {
+    pub fn new(min_multiplier: f64, max_multiplier: f64) -> Self {
+        assert!(
+            Self::valid_multipliers(min_multiplier, max_multiplier),
+            "invalid dynamic weight multipliers [{min_multiplier}, {max_multiplier}]"
+        );
+        Self {
+            min_multiplier,
+            max_multiplier,
+        }
+    }
+
+    // multipliers must be finite with 0 < min <= max
+    pub fn valid_multipliers(min_multiplier: f64, max_multiplier: f64) -> bool {
+        min_multiplier.is_finite()
+            && max_multiplier.is_finite()
+            && min_multiplier > 0.0
+            && min_multiplier <= max_multiplier
+    }
+
+    // each backend is a (static weight, partitions, cached inbound liquidity) tuple; backends
+    // without cached metrics keep their static weight. inbound is compared to the mean of the
+    // backend's partitions, averaged over its partitions when it serves more than one
+    pub fn weights(&self, backends: &[(usize, &BTreeSet<String>, Option<u64>)]) -> Vec<usize> {
+        let mut partition_inbound: HashMap<&str, (f64, usize)> = HashMap::new();
+        for (_, partitions, inbound_msat) in backends {
+            let Some(inbound_msat) = inbound_msat else {
+                continue;
+            };
+            for partition in partitions.iter() {
+                let (sum, count) = partition_inbound.entry(partition).or_default();
+                *sum += *inbound_msat as f64;
+                *count += 1;
+            }
+        }
+
+        backends
+            .iter()
+            .map(|(weight, partitions, inbound_msat)| {
+                let means = partitions
+                    .iter()
+                    .filter_map(|partition| partition_inbound.get(partition.as_str()))
+                    .map(|(sum, count)| sum / *count as f64)
+                    .collect::<Vec<_>>();
+                let mean_inbound_msat = if means.is_empty() {
+                    0.0
+                } else {
+                    means.iter().sum::<f64>() / means.len() as f64
+                };
+                let multiplier = match inbound_msat {
+                    Some(inbound_msat) if mean_inbound_msat > 0.0 => (*inbound_msat as f64
+                        / mean_inbound_msat)
+                        .clamp(self.min_multiplier, self.max_multiplier),
+                    _ => 1.0,
+                };
+                ((*weight as f64 * multiplier).round() as usize).max(1)
+            })
+            .collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PingoraLnSlowStart {
+    window: Duration,
+    initial_fraction: f64,
+}
+
+impl PingoraLnSlowStart {
+    pub fn new(window: Duration, initial_fraction: f64) -> Self {
+        Self {
+            window,
+            initial_fraction,
+        }
+    }
+
+    // ramps linearly from initial_fraction of the weight to the full weight over the window
+    pub fn weight(&self, weight: usize, healthy_for: Duration) -> usize {
+        if self.window.is_zero() || healthy_for >= self.window {
+            return weight;
+        }
+        let progress = healthy_for.as_secs_f64() / self.window.as_secs_f64();
+        let fraction = self.initial_fraction + (1.0 - self.initial_fraction) * progress;
+        ((weight as f64 * fraction).round() as usize).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_partition() -> BTreeSet<String> {
+        BTreeSet::from(["default".to_string()])
+    }
+
+    #[test]
+    fn weights_when_inbound_differs_then_proportional_to_inbound() {
+        let weights = PingoraLnDynamicWeights::new(0.1, 10.0);
+        let partitions = default_partition();
+
+        let result = weights.weights(&[
+            (100, &partitions, Some(1_000)),
+            (100, &partitions, Some(3_000)),
+        ]);
+
+        assert_eq!(result, vec![50, 150]);
+    }
+
+    #[test]
+    fn weights_when_inbound_extreme_then_clamped_to_multipliers() {
+        let weights = PingoraLnDynamicWeights::new(0.5, 2.0);
+        let partitions = default_partition();
+
+        let result = weights.weights(&[
+            (100, &partitions, Some(0)),
+            (100, &partitions, Some(0)),
+            (100, &partitions, Some(9_000)),
+        ]);
+
+        assert_eq!(result, vec![50, 50, 200]);
+    }
+
+    #[test]
+    fn weights_when_metrics_missing_then_static_weight() {
+        let weights = PingoraLnDynamicWeights::new(0.5, 2.0);
+        let partitions = default_partition();
+
+        assert_eq!(
+            weights.weights(&[(100, &partitions, None), (7, &partitions, Some(0))]),
+            vec![100, 7]
+        );
+        assert_eq!(
+            weights.weights(&[(100, &partitions, None), (7, &partitions, Some(10))]),
+            vec![100, 7]
+        );
+    }
+
+    #[test]
+    fn weights_when_rounded_to_zero_then_minimum_one() {
+        let weights = PingoraLnDynamicWeights::new(0.01, 2.0);
+        let partitions = default_partition();
+
+        let result = weights.weights(&[(1, &partitions, Some(1)), (1, &partitions, Some(1_000))]);
+
+        assert_eq!(result, vec![1, 2]);
+    }
+
+    #[test]
+    fn weights_when_partitions_differ_then_compared_within_partition() {
+        let weights = PingoraLnDynamicWeights::new(0.1, 10.0);
+        let large = BTreeSet::from(["large".to_string()]);
+        let small = BTreeSet::from(["small".to_string()]);
+        let both = BTreeSet::from(["large".to_string(), "small".to_string()]);
+
+        let result = weights.weights(&[
+            (100, &large, Some(1_000_000)),
+            (100, &large, Some(3_000_000)),
+            (100, &small, Some(1_000)),
+            (100, &small, Some(3_000)),
+        ]);
+        assert_eq!(result, vec![50, 150, 50, 150]);
+
+        // a backend in both partitions is compared to the average of both partition means
+        let result = weights.weights(&[
+            (100, &large, Some(4_000)),
+            (100, &small, Some(1_000)),
+            (100, &both, Some(2_500)),
+        ]);
+        assert_eq!(result, vec![123, 57, 100]);
+    }
+
+    #[test]
+    fn valid_multipliers_when_invalid_then_false() {
+        assert!(PingoraLnDynamicWeights::valid_multipliers(0.5, 2.0));
+        assert!(PingoraLnDynamicWeights::valid_multipliers(1.0, 1.0));
+        assert!(!PingoraLnDynamicWeights::valid_multipliers(2.0, 0.5));
+        assert!(!PingoraLnDynamicWeights::valid_multipliers(0.0, 2.0));
+        assert!(!PingoraLnDynamicWeights::valid_multipliers(f64::NAN, 2.0));
+        assert!(!PingoraLnDynamicWeights::valid_multipliers(
+            0.5,
+            f64::INFINITY
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid dynamic weight multipliers")]
+    fn new_when_min_above_max_then_panics() {
+        PingoraLnDynamicWeights::new(2.0, 0.5);
+    }
+
+    #[test]
+    fn slow_start_weight_when_in_window_then_ramps_linearly() {
+        let slow_start = PingoraLnSlowStart::new(Duration::from_secs(100), 0.1);
+
+        assert_eq!(slow_start.weight(100, Duration::ZERO), 10);
+        assert_eq!(slow_start.weight(100, Duration::from_secs(50)), 55);
+        assert_eq!(slow_start.weight(100, Duration::from_secs(100)), 100);
+        assert_eq!(slow_start.weight(100, Duration::from_secs(500)), 100);
+    }
+
+    #[test]
+    fn slow_start_weight_when_rounded_to_zero_then_minimum_one() {
+        let slow_start = PingoraLnSlowStart::new(Duration::from_secs(100), 0.0);
+
+        assert_eq!(slow_start.weight(3, Duration::ZERO), 1);
+    }
+}
@@ -9,12 +9,31 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::{env, fs};
 use switchgear_components::discovery::http::HttpDiscoveryBackendStore;
+use switchgear_components::discovery::status::HttpLnBackendStatusClient;
 use switchgear_components::pool::cln::grpc::config::{
     ClnGrpcClientAuth, ClnGrpcClientAuthPath, ClnGrpcDiscoveryBackendImplementation,
 };
+use switchgear_components::pool::cln::rest::config::{
+    ClnRestClientAuth, ClnRestClientAuthPath, ClnRestDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::eclair::http::config::{
+    EclairHttpClientAuth, EclairHttpClientAuthPath, EclairHttpDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::fake::config::{
+    FakeClientAuth, FakeClientAuthPath, FakeDiscoveryBackendImplementation, FakeNetwork,
+};
 use switchgear_components::pool::lnd::grpc::config::{
     LndGrpcClientAuth, LndGrpcClientAuthPath, LndGrpcDiscoveryBackendImplementation,
 };
+use switchgear_components::pool::lnd::rest::config::{
+    LndRestClientAuth, LndRestClientAuthPath, LndRestDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::nwc::config::{
+    NwcClientAuth, NwcClientAuthPath, NwcDiscoveryBackendImplementation,
+};
+use switchgear_components::pool::phoenixd::http::config::{
+    PhoenixdHttpClientAuth, PhoenixdHttpClientAuthPath, PhoenixdHttpDiscoveryBackendImplementation,
+};
 use switchgear_components::pool::DiscoveryBackendImplementation;
 use switchgear_service_api::discovery::{
     DiscoveryBackend, DiscoveryBackendPatch, DiscoveryBackendPatchSparse, DiscoveryBackendSparse,
@@ -41,6 +60,10 @@ pub enum DiscoveryBackendManagementCommands {
         #[arg(short, long)]
         name: Option<String>,
 
+        /// Optional backend region, preferred for clients mapped to it by geo headers
+        #[arg(short, long)]
+        region: Option<String>,
+
         /// Optional output path, defaults to stdout
         #[arg(short, long)]
         output: Option<PathBuf>,
@@ -61,6 +84,9 @@ pub enum DiscoveryBackendManagementCommands {
         /// Optional output path, defaults to stdout
         #[arg(short, long)]
         output: Option<PathBuf>,
+        /// Output inline credentials instead of redacting them
+        #[arg(long)]
+        show_secrets: bool,
         #[clap(flatten)]
         client: DiscoveryBackendManagementClientConfig,
     },
@@ -125,6 +151,13 @@ pub enum DiscoveryBackendManagementCommands {
         #[clap(flatten)]
         client: DiscoveryBackendManagementClientConfig,
     },
+
+    /// Show balancer status of all backends from a running LNURL Service
+    #[command(name = "status")]
+    Status {
+        #[clap(flatten)]
+        client: DiscoveryBackendStatusClientConfig,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -140,19 +173,50 @@ pub struct DiscoveryBackendManagementClientConfig {
     trusted_roots: Option<PathBuf>,
 }
 
+#[derive(Parser, Debug)]
+pub struct DiscoveryBackendStatusClientConfig {
+    /// Optional LNURL Service base Url. Also set with LNURL_SERVICE_HTTP_BASE_URL env var
+    #[clap(short, long)]
+    base_url: Option<Url>,
+    /// Optional Discovery Service authorization token file path. Also set with DISCOVERY_STORE_HTTP_AUTHORIZATION env var
+    #[clap(short, long)]
+    authorization_path: Option<PathBuf>,
+    /// Optional LNURL Service trusted roots file path, in pem format. Also set with DISCOVERY_STORE_HTTP_TRUSTED_ROOTS env var
+    #[clap(short, long)]
+    trusted_roots: Option<PathBuf>,
+}
+
 #[derive(ValueEnum, Debug, Clone)]
 pub enum LnNodeCommandType {
     #[value(name = "cln-grpc")]
     ClnGrpc,
+    #[value(name = "cln-rest")]
+    ClnRest,
     #[value(name = "lnd-grpc")]
     LndGrpc,
+    #[value(name = "lnd-rest")]
+    LndRest,
+    #[value(name = "phoenixd-http")]
+    PhoenixdHttp,
+    #[value(name = "eclair-http")]
+    EclairHttp,
+    #[value(name = "nwc")]
+    Nwc,
+    #[value(name = "fake")]
+    Fake,
 }
 
 impl Display for LnNodeCommandType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             LnNodeCommandType::ClnGrpc => write!(f, "CLN gRPC"),
+            LnNodeCommandType::ClnRest => write!(f, "CLN REST"),
             LnNodeCommandType::LndGrpc => write!(f, "LND gRPC"),
+            LnNodeCommandType::LndRest => write!(f, "LND REST"),
+            LnNodeCommandType::PhoenixdHttp => write!(f, "phoenixd HTTP"),
+            LnNodeCommandType::EclairHttp => write!(f, "Eclair HTTP"),
+            LnNodeCommandType::Nwc => write!(f, "Nostr Wallet Connect"),
+            LnNodeCommandType::Fake => write!(f, "fake"),
         }
     }
 }
@@ -162,6 +226,7 @@ pub fn new_backend(
     public_key: &str,
     name: Option<&str>,
     partition: &str,
+    region: Option<&str>,
     output: Option<&Path>,
 ) -> anyhow::Result<()> {
     let implementation = match ln_node_type {
@@ -174,6 +239,19 @@ pub fn new_backend(
                     client_cert_path: PathBuf::from("/path/to/client.pem"),
                     client_key_path: PathBuf::from("/path/to/client-key.pem"),
                 }),
+                route_hints: None,
+                channels: None,
+                socks5_proxy: None,
+            })
+        }
+        LnNodeCommandType::ClnRest => {
+            DiscoveryBackendImplementation::ClnRest(ClnRestDiscoveryBackendImplementation {
+                url: Url::parse("https://127.0.0.1:3010")?,
+                auth: ClnRestClientAuth::Path(ClnRestClientAuthPath {
+                    ca_cert_path: PathBuf::from("/path/to/ca.pem").into(),
+                    rune_path: PathBuf::from("/path/to/rune"),
+                }),
+                route_hints: None,
             })
         }
         LnNodeCommandType::LndGrpc => {
@@ -185,6 +263,60 @@ pub fn new_backend(
                     macaroon_path: PathBuf::from("/path/to/admin.macaroon"),
                 }),
                 amp_invoice: false,
+                route_hints: None,
+                channels: None,
+                socks5_proxy: None,
+            })
+        }
+        LnNodeCommandType::LndRest => {
+            DiscoveryBackendImplementation::LndRest(LndRestDiscoveryBackendImplementation {
+                url: Url::parse("https://127.0.0.1:8080")?,
+                auth: LndRestClientAuth::Path(LndRestClientAuthPath {
+                    tls_cert_path: PathBuf::from("/path/to/tls.cert").into(),
+                    macaroon_path: PathBuf::from("/path/to/admin.macaroon"),
+                }),
+                amp_invoice: false,
+                route_hints: None,
+            })
+        }
+        LnNodeCommandType::PhoenixdHttp => DiscoveryBackendImplementation::PhoenixdHttp(
+            PhoenixdHttpDiscoveryBackendImplementation {
+                url: Url::parse("http://127.0.0.1:9740")?,
+                auth: PhoenixdHttpClientAuth::Path(PhoenixdHttpClientAuthPath {
+                    ca_cert_path: None,
+                    password_path: PathBuf::from("/path/to/http-password"),
+                }),
+            },
+        ),
+        LnNodeCommandType::EclairHttp => {
+            DiscoveryBackendImplementation::EclairHttp(EclairHttpDiscoveryBackendImplementation {
+                url: Url::parse("http://127.0.0.1:8080")?,
+                auth: EclairHttpClientAuth::Path(EclairHttpClientAuthPath {
+                    ca_cert_path: None,
+                    password_path: PathBuf::from("/path/to/api-password"),
+                }),
+            })
+        }
+        LnNodeCommandType::Nwc => {
+            DiscoveryBackendImplementation::Nwc(NwcDiscoveryBackendImplementation {
+                auth: NwcClientAuth::Path(NwcClientAuthPath {
+                    ca_cert_path: None,
+                    connection_uri_path: PathBuf::from("/path/to/connection-uri"),
+                }),
+            })
+        }
+        LnNodeCommandType::Fake => {
+            DiscoveryBackendImplementation::Fake(FakeDiscoveryBackendImplementation {
+                network: FakeNetwork::Regtest,
+                auth: FakeClientAuth::Path(FakeClientAuthPath {
+                    secret_key_path: PathBuf::from("/path/to/secret-key"),
+                }),
+                latency_ms: 0,
+                latency_jitter_ms: 0,
+                error_rate_percent: 0,
+                failure_modes: vec![],
+                inbound_msat: 1_000_000_000,
+                settle_after_secs: None,
             })
         }
     };
@@ -195,6 +327,7 @@ pub fn new_backend(
             partitions: [partition.to_string()].into(),
             weight: 1,
             enabled: false,
+            region: region.map(String::from),
             implementation: serde_json::to_vec(&implementation)?,
         },
     };
@@ -226,12 +359,17 @@ pub async fn list_backends(
 * location: {}
 * enabled: {}
 * weight: {}
+* region: {}
 "#,
             backend.public_key,
             backend.backend.name.unwrap_or_else(|| "[null]".to_string()),
             backend.public_key,
             backend.backend.enabled,
-            backend.backend.weight
+            backend.backend.weight,
+            backend
+                .backend
+                .region
+                .unwrap_or_else(|| "[null]".to_string())
         );
     }
     Ok(())
@@ -240,6 +378,7 @@ pub async fn list_backends(
 pub async fn get_backend(
     public_key: Option<&str>,
     output: Option<&Path>,
+    show_secrets: bool,
     client_configuration: &DiscoveryBackendManagementClientConfig,
 ) -> anyhow::Result<()> {
     let client = create_backend_client(client_configuration)?;
@@ -247,7 +386,10 @@ pub async fn get_backend(
         let public_key = public_key
             .parse()
             .with_context(|| format!("parsing public key: {public_key}"))?;
-        if let Some(backend) = client.get(&public_key).await? {
+        if let Some(mut backend) = client.get(&public_key).await? {
+            if !show_secrets {
+                backend.backend.redact_secrets();
+            }
             let backend = serde_json::to_string_pretty(&backend)
                 .with_context(|| format!("serializing backend {}", public_key))?;
             cli_write_all(output, backend.as_bytes()).with_context(|| {
@@ -261,7 +403,14 @@ pub async fn get_backend(
             bail!("Backend {public_key} not found");
         }
     } else {
-        let backends = client.get_all(None).await?;
+        let mut backends = client.get_all(None).await?;
+        if !show_secrets {
+            backends
+                .backends
+                .iter_mut()
+                .flatten()
+                .for_each(|backend| backend.backend.redact_secrets());
+        }
         let backends = serde_json::to_string_pretty(&backends.backends)
             .with_context(|| "serializing backends")?;
         cli_write_all(output, backends.as_bytes()).with_context(|| {
@@ -395,6 +544,7 @@ pub async fn enable_backend(
             partitions: None,
             weight: None,
             enabled: Some(enable),
+            region: None,
         },
     };
     if client.patch(backend).await? {
@@ -421,6 +571,69 @@ pub async fn delete_backend(
     Ok(())
 }
 
+pub async fn backend_status(
+    client_configuration: &DiscoveryBackendStatusClientConfig,
+) -> anyhow::Result<()> {
+    let client = create_status_client(client_configuration)?;
+    let statuses = client.status().await?;
+
+    let header = [
+        "PUBLIC KEY",
+        "NAME",
+        "PARTITIONS",
+        "ENABLED",
+        "HEALTHY",
+        "OK",
+        "FAIL",
+        "INBOUND MSAT",
+        "LATENCY MS",
+        "LAST ERROR",
+    ]
+    .map(String::from);
+
+    let rows = statuses
+        .into_iter()
+        .map(|status| {
+            [
+                status.public_key.to_string(),
+                status.name.unwrap_or_else(|| "-".to_string()),
+                status.partitions.into_iter().collect::<Vec<_>>().join(","),
+                status.enabled.to_string(),
+                status.healthy.to_string(),
+                status.consecutive_success.to_string(),
+                status.consecutive_failure.to_string(),
+                status.metrics.map_or_else(
+                    || "-".to_string(),
+                    |m| m.node_effective_inbound_msat.to_string(),
+                ),
+                status
+                    .last_invoice_latency_ms
+                    .map_or_else(|| "-".to_string(), |l| l.to_string()),
+                status.last_error.unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
 fn create_backend_client(
     client_configuration: &DiscoveryBackendManagementClientConfig,
 ) -> anyhow::Result<HttpDiscoveryBackendStore> {
@@ -432,27 +645,68 @@ fn create_backend_client(
         }
         Some(base_url) => base_url.clone(),
     };
-    let authorization_path = match &client_configuration.authorization_path {
+    let authorization = read_authorization(client_configuration.authorization_path.as_deref())?;
+    let trusted_roots = read_trusted_roots(client_configuration.trusted_roots.as_deref())?;
+
+    Ok(HttpDiscoveryBackendStore::create(
+        base_url,
+        Duration::from_secs(1),
+        Duration::from_secs(1),
+        &trusted_roots,
+        authorization,
+        None,
+    )?)
+}
+
+fn create_status_client(
+    client_configuration: &DiscoveryBackendStatusClientConfig,
+) -> anyhow::Result<HttpLnBackendStatusClient> {
+    let base_url = match &client_configuration.base_url {
+        None => {
+            let base_url = env::var("LNURL_SERVICE_HTTP_BASE_URL")
+                .map_err(|_| anyhow!("Missing LNURL_SERVICE_HTTP_BASE_URL"))?;
+            Url::parse(&base_url).with_context(|| format!("parsing {base_url}"))?
+        }
+        Some(base_url) => base_url.clone(),
+    };
+    let authorization = read_authorization(client_configuration.authorization_path.as_deref())?;
+    let trusted_roots = read_trusted_roots(client_configuration.trusted_roots.as_deref())?;
+
+    Ok(HttpLnBackendStatusClient::create(
+        base_url,
+        Duration::from_secs(1),
+        Duration::from_secs(1),
+        &trusted_roots,
+        authorization,
+    )?)
+}
+
+fn read_authorization(authorization_path: Option<&Path>) -> anyhow::Result<String> {
+    let authorization_path = match authorization_path {
         None => {
             let authorization_path = env::var("DISCOVERY_STORE_HTTP_AUTHORIZATION")
                 .map_err(|_| anyhow!("Missing DISCOVERY_STORE_HTTP_AUTHORIZATION"))?;
             PathBuf::from(authorization_path)
         }
-        Some(authorization_path) => authorization_path.clone(),
+        Some(authorization_path) => authorization_path.to_path_buf(),
     };
-    let authorization = fs::read_to_string(&authorization_path).with_context(|| {
+    fs::read_to_string(&authorization_path).with_context(|| {
         format!(
             "reading authorization file: {}",
             authorization_path.to_string_lossy()
         )
-    })?;
+    })
+}
 
-    let trusted_roots_path = match &client_configuration.trusted_roots {
+fn read_trusted_roots(
+    trusted_roots_path: Option<&Path>,
+) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let trusted_roots_path = match trusted_roots_path {
         None => env::var("DISCOVERY_STORE_HTTP_TRUSTED_ROOTS")
             .map_err(|_| anyhow!("Missing DISCOVERY_STORE_HTTP_TRUSTED_ROOTS"))
             .ok()
             .map(PathBuf::from),
-        Some(trusted_roots_path) => Some(trusted_roots_path.clone()),
+        Some(trusted_roots_path) => Some(trusted_roots_path.to_path_buf()),
     };
 
     let trusted_roots = if let Some(trusted_roots_path) = trusted_roots_path {
@@ -466,11 +720,5 @@ fn create_backend_client(
         vec![]
     };
 
-    Ok(HttpDiscoveryBackendStore::create(
-        base_url,
-        Duration::from_secs(1),
-        Duration::from_secs(1),
-        &trusted_roots,
-        authorization,
-    )?)
+    Ok(trusted_roots)
 }
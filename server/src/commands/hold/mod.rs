@@ -0,0 +1,11 @@
+use crate::commands::token::TokenCommands;
+use clap::Subcommand;
+
+pub mod token;
+
+#[derive(Subcommand, Debug)]
+pub enum HoldCommands {
+    /// Manage hold invoice token
+    #[clap(subcommand)]
+    Token(TokenCommands),
+}
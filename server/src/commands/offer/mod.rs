@@ -93,5 +93,6 @@ pub fn create_offer_client(
         Duration::from_secs(1),
         &trusted_roots,
         authorization,
+        None,
     )?)
 }
@@ -95,6 +95,7 @@ pub fn new_offer(partition: &str, metadata_id: &Uuid, output: Option<&Path>) ->
             expires: Some(
                 DateTime::<Utc>::from_timestamp_secs(86_400).expect("unix epoch + 24 hours"),
             ),
+            hold_invoice: false,
         },
     };
 
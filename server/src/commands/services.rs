@@ -55,6 +55,7 @@ pub async fn execute(
         config_injector.clone(),
         enablement_injector.clone(),
         discovery_store_injector.clone(),
+        offer_store_injector.clone(),
     );
 
     let balancer_service_injector = BalancerServiceInjector::new(
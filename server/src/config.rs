@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
@@ -30,11 +30,61 @@ pub struct LnUrlBalancerServiceConfig {
     pub tls: Option<TlsConfig>,
     pub ln_client_timeout_secs: f64,
     pub ln_trusted_roots: Option<PathBuf>,
+    pub ln_socks5_proxy: Option<String>,
     pub selection_capacity_bias: Option<f64>,
     pub comment_allowed: Option<u32>,
     pub bech32_qr_scale: usize,
     pub bech32_qr_light: u8,
     pub bech32_qr_dark: u8,
+    pub status_auth_authority: Option<PathBuf>,
+    pub dynamic_weights: Option<DynamicWeightsConfig>,
+    pub slow_start: Option<SlowStartConfig>,
+    pub outlier_detection: Option<OutlierDetectionConfig>,
+    pub region_preference: Option<RegionPreferenceConfig>,
+    pub hold_invoices: Option<HoldInvoicesConfig>,
+    pub forced_refresh_min_interval_secs: Option<f64>,
+    pub invoice_request_timeout_secs: Option<f64>,
+    pub invoice_request_timeout_header: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DynamicWeightsConfig {
+    pub min_multiplier: f64,
+    pub max_multiplier: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SlowStartConfig {
+    pub window_secs: f64,
+    pub initial_fraction: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OutlierDetectionConfig {
+    pub window_secs: f64,
+    pub minimum_requests: usize,
+    pub minimum_hosts: usize,
+    pub stdev_factor: f64,
+    pub base_ejection_secs: f64,
+    pub max_ejection_secs: f64,
+    pub max_ejection_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RegionPreferenceConfig {
+    pub headers: Vec<String>,
+    pub regions: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HoldInvoicesConfig {
+    pub auth_authority: PathBuf,
+    pub cancel_after_secs: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,6 +149,7 @@ pub enum OfferStoreConfig {
         total_timeout_secs: f64,
         trusted_roots: Option<PathBuf>,
         authorization: PathBuf,
+        socks5_proxy: Option<String>,
     },
 }
 
@@ -118,6 +169,7 @@ pub enum DiscoveryStoreConfig {
         total_timeout_secs: f64,
         trusted_roots: Option<PathBuf>,
         authorization: PathBuf,
+        socks5_proxy: Option<String>,
     },
 }
 
@@ -6,6 +6,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use pingora_load_balancing::selection::{Consistent, Random, RoundRobin};
 use secp256k1::PublicKey;
+use std::time::Instant;
 use switchgear_components::discovery::db::DbDiscoveryBackendStore;
 use switchgear_components::discovery::error::DiscoveryBackendStoreError;
 use switchgear_components::discovery::http::HttpDiscoveryBackendStore;
@@ -23,12 +24,17 @@ use switchgear_pingora::balance::{
 use switchgear_pingora::error::PingoraLnError;
 use switchgear_pingora::pool::DefaultPingoraLnClientPool;
 use switchgear_pingora::PingoraBackoffProvider;
-use switchgear_service_api::balance::{LnBalancer, LnBalancerBackgroundServices};
+use switchgear_service_api::balance::{
+    IssuedInvoice, LnBackendStatus, LnBalancer, LnBalancerBackgroundServices,
+};
 use switchgear_service_api::discovery::{
     DiscoveryBackend, DiscoveryBackendPatch, DiscoveryBackendStore, DiscoveryBackends,
 };
 use switchgear_service_api::offer::Offer;
-use switchgear_service_api::offer::{OfferMetadataStore, OfferStore};
+use switchgear_service_api::offer::{
+    Bolt12OfferRecord, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceStore, OfferMetadataStore,
+    OfferStore,
+};
 use tokio::sync::watch;
 use uuid::Uuid;
 // ===== TYPE ALIASES =====
@@ -39,6 +45,7 @@ type Balancer<T, X> = switchgear_pingora::balance::PingoraLnBalancer<
     DefaultPingoraLnClientPool,
     BackoffProviderDelegate,
     X,
+    OfferStoreDelegate,
 >;
 
 // ===== LN BALANCER DELEGATE =====
@@ -80,14 +87,51 @@ impl LnBalancer for LnBalancerDelegate {
         amount_msat: u64,
         expiry_secs: u64,
         key: &[u8],
-    ) -> Result<String, Self::Error> {
-        delegate_to_ln_balancer_variants!(self, get_invoice, offer, amount_msat, expiry_secs, key)
-            .await
+        preferred_regions: &[String],
+        deadline: Instant,
+    ) -> Result<IssuedInvoice, Self::Error> {
+        delegate_to_ln_balancer_variants!(
+            self,
+            get_invoice,
+            offer,
+            amount_msat,
+            expiry_secs,
+            key,
+            preferred_regions,
+            deadline
+        )
+        .await
+    }
+
+    async fn settle_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> std::result::Result<bool, Self::Error> {
+        delegate_to_ln_balancer_variants!(self, settle_hold_invoice, payment_hash).await
+    }
+
+    async fn cancel_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> std::result::Result<bool, Self::Error> {
+        delegate_to_ln_balancer_variants!(self, cancel_hold_invoice, payment_hash).await
+    }
+
+    async fn get_bolt12_offer(
+        &self,
+        offer: &Offer,
+        bolt12_offers: &[Bolt12OfferRecord],
+    ) -> std::result::Result<Bolt12OfferRecord, Self::Error> {
+        delegate_to_ln_balancer_variants!(self, get_bolt12_offer, offer, bolt12_offers).await
     }
 
     async fn health(&self) -> std::result::Result<(), Self::Error> {
         delegate_to_ln_balancer_variants!(self, health).await
     }
+
+    async fn status(&self) -> std::result::Result<Vec<LnBackendStatus>, Self::Error> {
+        delegate_to_ln_balancer_variants!(self, status).await
+    }
 }
 
 // ===== OFFER STORE DELEGATES =====
@@ -180,6 +224,47 @@ impl OfferMetadataStore for OfferStoreDelegate {
     }
 }
 
+#[async_trait]
+impl Bolt12OfferStore for OfferStoreDelegate {
+    type Error = OfferStoreError;
+
+    async fn get_bolt12_offers(
+        &self,
+        partition: &str,
+        id: &Uuid,
+    ) -> Result<Vec<Bolt12OfferRecord>, Self::Error> {
+        delegate_to_offer_store_variants!(self, get_bolt12_offers, partition, id).await
+    }
+
+    async fn put_bolt12_offer(&self, offer: Bolt12OfferRecord) -> Result<bool, Self::Error> {
+        delegate_to_offer_store_variants!(self, put_bolt12_offer, offer).await
+    }
+}
+
+#[async_trait]
+impl HoldInvoiceStore for OfferStoreDelegate {
+    type Error = OfferStoreError;
+
+    async fn get_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<Option<HoldInvoiceRecord>, Self::Error> {
+        delegate_to_offer_store_variants!(self, get_hold_invoice, payment_hash).await
+    }
+
+    async fn get_hold_invoices(&self) -> Result<Vec<HoldInvoiceRecord>, Self::Error> {
+        delegate_to_offer_store_variants!(self, get_hold_invoices).await
+    }
+
+    async fn put_hold_invoice(&self, hold: HoldInvoiceRecord) -> Result<bool, Self::Error> {
+        delegate_to_offer_store_variants!(self, put_hold_invoice, hold).await
+    }
+
+    async fn delete_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        delegate_to_offer_store_variants!(self, delete_hold_invoice, payment_hash).await
+    }
+}
+
 // ===== DISCOVERY BACKEND STORE DELEGATE =====
 
 #[derive(Clone)]
@@ -1,7 +1,10 @@
-use crate::config::{BackendSelectionConfig, BackoffConfig, LnUrlBalancerServiceConfig};
+use crate::config::{
+    BackendSelectionConfig, BackoffConfig, HoldInvoicesConfig, LnUrlBalancerServiceConfig,
+};
 use crate::di::delegates::{BackoffProviderDelegate, LnBalancerDelegate};
 use crate::di::inject::injectors::config::{ServerConfigInjector, ServiceEnablementInjector};
 use crate::di::inject::injectors::store::discovery::DiscoveryStoreInjector;
+use crate::di::inject::injectors::store::offer::OfferStoreInjector;
 use anyhow::{anyhow, Context};
 use pingora_load_balancing::discovery::ServiceDiscovery;
 use pingora_load_balancing::health_check::HealthCheck;
@@ -12,20 +15,34 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Duration;
-use switchgear_components::pool::LnClientPool;
+use switchgear_components::pool::{LnClientPool, HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA};
 use switchgear_pingora::backoff::{ExponentialBackoffProvider, StopBackoffProvider};
 use switchgear_pingora::balance::{
     ConsistentMaxIterations, PingoraLnBalancer, RandomMaxIterations, RoundRobinMaxIterations,
 };
 use switchgear_pingora::discovery::{LnServiceDiscovery, PingoraDiscoveryBackendStoreProvider};
 use switchgear_pingora::health::PingoraLnHealthCheck;
+use switchgear_pingora::hold::PingoraLnHoldInvoices;
+use switchgear_pingora::outlier::PingoraLnOutlierDetection;
 use switchgear_pingora::pool::DefaultPingoraLnClientPool;
+use switchgear_pingora::refresh::PingoraLnForcedRefresh;
+use switchgear_pingora::reservation::PingoraLnInvoiceReservations;
+use switchgear_pingora::status::PingoraLnBackendStatusRegistry;
+use switchgear_pingora::weight::{PingoraLnDynamicWeights, PingoraLnSlowStart};
+use url::Url;
+
+// unclaimed hold invoices are canceled half way to the earliest CLTV expiry of their payments
+const DEFAULT_HOLD_INVOICE_CANCEL_AFTER_SECS: f64 = 43_200.0;
+
+// failed requests share discovery updates and health sweeps at most once per interval
+const DEFAULT_FORCED_REFRESH_MIN_INTERVAL_SECS: f64 = 1.0;
 
 #[derive(Clone)]
 pub struct BalancerInjector {
     config: ServerConfigInjector,
     enablement: ServiceEnablementInjector,
     discovery: DiscoveryStoreInjector,
+    offer: OfferStoreInjector,
     singleton: Rc<RefCell<Option<Option<LnBalancerDelegate>>>>,
 }
 
@@ -34,11 +51,13 @@ impl BalancerInjector {
         config: ServerConfigInjector,
         enablement: ServiceEnablementInjector,
         discovery: DiscoveryStoreInjector,
+        offer: OfferStoreInjector,
     ) -> Self {
         Self {
             config,
             enablement,
             discovery,
+            offer,
             singleton: Default::default(),
         }
     }
@@ -71,6 +90,21 @@ impl BalancerInjector {
 
         let discovery = PingoraDiscoveryBackendStoreProvider::new(discovery);
 
+        let holds = match &lnurl_config.hold_invoices {
+            Some(hold_invoices) => {
+                let offer = self
+                    .offer
+                    .get()
+                    .await?
+                    .ok_or_else(|| anyhow!("lnurl service enabled but has no offer store"))?;
+                Some(PingoraLnHoldInvoices::new(
+                    offer,
+                    Self::hold_invoice_cancel_after(lnurl_config, hold_invoices)?,
+                ))
+            }
+            None => None,
+        };
+
         let backoff = match lnurl_config.backoff {
             BackoffConfig::Stop => BackoffProviderDelegate::Stop(StopBackoffProvider),
             BackoffConfig::Exponential {
@@ -108,22 +142,53 @@ impl BalancerInjector {
             vec![]
         };
 
+        let socks5_proxy = lnurl_config
+            .ln_socks5_proxy
+            .as_deref()
+            .map(|socks5_proxy| {
+                Url::parse(socks5_proxy)
+                    .with_context(|| format!("parsing ln socks5 proxy: {socks5_proxy}"))
+            })
+            .transpose()?;
+
         let pool = LnClientPool::new(
             Duration::from_secs_f64(lnurl_config.ln_client_timeout_secs),
             trusted_roots,
+            self.config.secrets().clone(),
+            socks5_proxy,
         );
 
         let pool = DefaultPingoraLnClientPool::new(pool);
 
-        let discovery =
-            LnServiceDiscovery::new(discovery, pool.clone(), lnurl_config.partitions.clone());
+        let status = PingoraLnBackendStatusRegistry::new();
+        let reservations = PingoraLnInvoiceReservations::new();
+        let forced_refresh = PingoraLnForcedRefresh::new(Duration::from_secs_f64(
+            lnurl_config
+                .forced_refresh_min_interval_secs
+                .unwrap_or(DEFAULT_FORCED_REFRESH_MIN_INTERVAL_SECS),
+        ));
+
+        let discovery = LnServiceDiscovery::new(
+            discovery,
+            pool.clone(),
+            lnurl_config.partitions.clone(),
+            status.clone(),
+            Self::dynamic_weights(lnurl_config)?,
+            lnurl_config.slow_start.as_ref().map(|s| {
+                PingoraLnSlowStart::new(Duration::from_secs_f64(s.window_secs), s.initial_fraction)
+            }),
+        );
 
         let health = PingoraLnHealthCheck::new(
             pool.clone(),
             lnurl_config.health_check_consecutive_success_to_healthy,
             lnurl_config.health_check_consecutive_failure_to_unhealthy,
+            status.clone(),
+            reservations.clone(),
         );
 
+        let outlier_detection = Self::outlier_detection(lnurl_config)?;
+
         let balancer = match lnurl_config.backend_selection {
             BackendSelectionConfig::RoundRobin => {
                 let balancer = Arc::new(Self::create_pingora_load_balancer(
@@ -139,6 +204,11 @@ impl BalancerInjector {
                     RoundRobinMaxIterations,
                     lnurl_config.parallel_health_check,
                     lnurl_config.selection_capacity_bias,
+                    status,
+                    reservations,
+                    holds,
+                    outlier_detection,
+                    forced_refresh,
                 ))
             }
             BackendSelectionConfig::Random => {
@@ -155,6 +225,11 @@ impl BalancerInjector {
                     RandomMaxIterations,
                     lnurl_config.parallel_health_check,
                     lnurl_config.selection_capacity_bias,
+                    status,
+                    reservations,
+                    holds,
+                    outlier_detection,
+                    forced_refresh,
                 ))
             }
             BackendSelectionConfig::Consistent { max_iterations } => {
@@ -171,6 +246,11 @@ impl BalancerInjector {
                     ConsistentMaxIterations::new(max_iterations),
                     lnurl_config.parallel_health_check,
                     lnurl_config.selection_capacity_bias,
+                    status,
+                    reservations,
+                    holds,
+                    outlier_detection,
+                    forced_refresh,
                 ))
             }
         };
@@ -179,6 +259,76 @@ impl BalancerInjector {
         Ok(Some(balancer))
     }
 
+    // held payments must be resolved before their HTLCs reach CLTV expiry, which can be as soon as
+    // the final CLTV delta after the invoice was created, at ten minutes per block
+    fn hold_invoice_cancel_after(
+        lnurl_config: &LnUrlBalancerServiceConfig,
+        hold_invoices: &HoldInvoicesConfig,
+    ) -> anyhow::Result<Duration> {
+        let cancel_after_secs = hold_invoices
+            .cancel_after_secs
+            .unwrap_or(DEFAULT_HOLD_INVOICE_CANCEL_AFTER_SECS);
+        let cltv_expiry_secs = (HOLD_INVOICE_MIN_FINAL_CLTV_EXPIRY_DELTA * 600) as f64;
+        if cancel_after_secs < lnurl_config.invoice_expiry_secs as f64
+            || cancel_after_secs >= cltv_expiry_secs
+        {
+            return Err(anyhow!(
+                "hold-invoices cancel-after-secs {cancel_after_secs} must be at least \
+                 invoice-expiry-secs {} and less than {cltv_expiry_secs}",
+                lnurl_config.invoice_expiry_secs
+            ));
+        }
+        Ok(Duration::from_secs_f64(cancel_after_secs))
+    }
+
+    fn dynamic_weights(
+        lnurl_config: &LnUrlBalancerServiceConfig,
+    ) -> anyhow::Result<Option<PingoraLnDynamicWeights>> {
+        let Some(dynamic_weights) = &lnurl_config.dynamic_weights else {
+            return Ok(None);
+        };
+        if !PingoraLnDynamicWeights::valid_multipliers(
+            dynamic_weights.min_multiplier,
+            dynamic_weights.max_multiplier,
+        ) {
+            return Err(anyhow!(
+                "dynamic-weights min-multiplier {} and max-multiplier {} must be finite with \
+                 0 < min-multiplier <= max-multiplier",
+                dynamic_weights.min_multiplier,
+                dynamic_weights.max_multiplier
+            ));
+        }
+        Ok(Some(PingoraLnDynamicWeights::new(
+            dynamic_weights.min_multiplier,
+            dynamic_weights.max_multiplier,
+        )))
+    }
+
+    fn outlier_detection(
+        lnurl_config: &LnUrlBalancerServiceConfig,
+    ) -> anyhow::Result<Option<PingoraLnOutlierDetection>> {
+        let Some(o) = &lnurl_config.outlier_detection else {
+            return Ok(None);
+        };
+        if !PingoraLnOutlierDetection::valid_limits(o.minimum_requests, o.max_ejection_percent) {
+            return Err(anyhow!(
+                "outlier-detection minimum-requests {} must be at least 1 and \
+                 max-ejection-percent {} must be between 0 and 100",
+                o.minimum_requests,
+                o.max_ejection_percent
+            ));
+        }
+        Ok(Some(PingoraLnOutlierDetection::new(
+            Duration::from_secs_f64(o.window_secs),
+            o.minimum_requests,
+            o.minimum_hosts,
+            o.stdev_factor,
+            Duration::from_secs_f64(o.base_ejection_secs),
+            Duration::from_secs_f64(o.max_ejection_secs),
+            o.max_ejection_percent,
+        )))
+    }
+
     fn create_pingora_load_balancer<D, S, H>(
         lnurl_config: &LnUrlBalancerServiceConfig,
         discovery: D,
@@ -1,14 +1,19 @@
+use crate::config::BackoffConfig;
 use crate::di::inject::injectors::balance::BalancerInjector;
 use crate::di::inject::injectors::config::{ServerConfigInjector, ServiceEnablementInjector};
 use crate::di::inject::injectors::service::tls::load_server_x509_credentials;
 use crate::di::inject::injectors::store::offer::OfferStoreInjector;
 use anyhow::{anyhow, Context};
+use jsonwebtoken::DecodingKey;
 use log::{info, warn};
 use std::future::Future;
 use std::net::{SocketAddr, TcpListener};
 use std::pin::Pin;
+use std::time::Duration;
 use switchgear_components::axum::middleware::logger::ClfLogger;
 use switchgear_components::offer::provider::StoreOfferProvider;
+use switchgear_service::deadline::{RequestDeadlines, DEFAULT_REQUEST_TIMEOUT};
+use switchgear_service::region::RegionPreferences;
 use switchgear_service::scheme::Scheme;
 use switchgear_service::{LnUrlBalancerService, LnUrlPayState};
 
@@ -89,6 +94,80 @@ impl BalancerServiceInjector {
         let scheme = if acceptor.is_some() { "https" } else { "http" };
         let scheme = Scheme(scheme.to_string());
 
+        let status_auth_authority = match &service_config.status_auth_authority {
+            Some(status_auth_authority) => {
+                let status_auth_authority_pem = std::fs::read(status_auth_authority.as_path())
+                    .with_context(|| {
+                        format!(
+                            "reading status auth authority from: {}",
+                            status_auth_authority.to_string_lossy()
+                        )
+                    })?;
+                let status_auth_authority = DecodingKey::from_ec_pem(&status_auth_authority_pem)
+                    .with_context(|| {
+                        format!(
+                            "decoding status auth authority from: {}",
+                            status_auth_authority.to_string_lossy()
+                        )
+                    })?;
+                Some(status_auth_authority)
+            }
+            None => None,
+        };
+
+        let hold_auth_authority = match &service_config.hold_invoices {
+            Some(hold_invoices) => {
+                let hold_auth_authority = &hold_invoices.auth_authority;
+                let hold_auth_authority_pem = std::fs::read(hold_auth_authority.as_path())
+                    .with_context(|| {
+                        format!(
+                            "reading hold auth authority from: {}",
+                            hold_auth_authority.to_string_lossy()
+                        )
+                    })?;
+                let hold_auth_authority = DecodingKey::from_ec_pem(&hold_auth_authority_pem)
+                    .with_context(|| {
+                        format!(
+                            "decoding hold auth authority from: {}",
+                            hold_auth_authority.to_string_lossy()
+                        )
+                    })?;
+                Some(hold_auth_authority)
+            }
+            None => None,
+        };
+
+        let region_preferences = match &service_config.region_preference {
+            Some(region_preference) => RegionPreferences::new(
+                &region_preference.headers,
+                region_preference.regions.clone(),
+            )
+            .with_context(|| "parsing region preference headers")?,
+            None => RegionPreferences::default(),
+        };
+
+        // without a configured timeout requests get the retry budget of the backoff
+        let request_timeout = match (
+            &service_config.backoff,
+            service_config.invoice_request_timeout_secs,
+        ) {
+            (_, Some(timeout_secs)) => Duration::from_secs_f64(timeout_secs),
+            (
+                BackoffConfig::Exponential {
+                    max_elapsed_time_secs: Some(max_elapsed_time_secs),
+                    ..
+                },
+                None,
+            ) => Duration::from_secs_f64(*max_elapsed_time_secs),
+            _ => DEFAULT_REQUEST_TIMEOUT,
+        };
+
+        let request_deadlines = RequestDeadlines::new(
+            request_timeout,
+            service_config.invoice_request_timeout_header.as_deref(),
+        )
+        .with_context(|| "parsing invoice request timeout header")?;
+
         let router = LnUrlBalancerService::router(LnUrlPayState::new(
             service_config.partitions.clone(),
             offer_store,
@@ -100,6 +179,10 @@ impl BalancerServiceInjector {
             service_config.bech32_qr_scale,
             service_config.bech32_qr_light,
             service_config.bech32_qr_dark,
+            status_auth_authority,
+            hold_auth_authority,
+            region_preferences,
+            request_deadlines,
         ))
         .layer(ClfLogger::new("lnurl"))
         .into_make_service_with_connect_info::<SocketAddr>();
@@ -70,6 +70,7 @@ impl DiscoveryStoreInjector {
                 total_timeout_secs: total_timeout,
                 trusted_roots,
                 authorization,
+                socks5_proxy,
             } => {
                 let trusted_roots = load_server_certificate(trusted_roots.as_deref())
                     .with_context(|| "loading server certificate for http discovery store")?;
@@ -93,6 +94,7 @@ impl DiscoveryStoreInjector {
                         Duration::from_secs_f64(*connect_timeout),
                         &trusted_roots,
                         authorization_token.to_string(),
+                        socks5_proxy.as_deref(),
                     )
                     .with_context(|| "creating http client for discovery store")?,
                 )
@@ -67,6 +67,7 @@ impl OfferStoreInjector {
                 total_timeout_secs: total_timeout,
                 trusted_roots,
                 authorization,
+                socks5_proxy,
             } => {
                 let trusted_roots = load_server_certificate(trusted_roots.as_deref())
                     .with_context(|| "loading server certificates for http offer store")?;
@@ -90,6 +91,7 @@ impl OfferStoreInjector {
                         Duration::from_secs_f64(*connect_timeout),
                         &trusted_roots,
                         authorization_token.to_string(),
+                        socks5_proxy.as_deref(),
                     )
                     .with_context(|| "creating http client for offer store")?,
                 )
@@ -9,6 +9,7 @@ use anyhow::anyhow;
 use clap::{Parser, Subcommand};
 use commands::discovery::backend::DiscoveryBackendManagementCommands;
 use commands::discovery::DiscoveryCommands;
+use commands::hold::HoldCommands;
 use commands::offer::OfferCommands;
 use commands::services::ServiceEnablement;
 use commands::token::TokenCommands;
@@ -47,6 +48,10 @@ enum RootCommands {
     /// Manage discovery
     #[clap(subcommand)]
     Discovery(DiscoveryCommands),
+
+    /// Manage hold invoices
+    #[clap(subcommand)]
+    Hold(HoldCommands),
 }
 
 #[global_allocator]
@@ -259,12 +264,14 @@ async fn _main(args: CliArgs) -> anyhow::Result<()> {
                     public_key,
                     partition,
                     name,
+                    region,
                     output,
                 } => commands::discovery::backend::new_backend(
                     node_type,
                     &public_key,
                     name.as_deref(),
                     &partition,
+                    region.as_deref(),
                     output.as_deref(),
                 ),
                 DiscoveryBackendManagementCommands::List { client } => {
@@ -273,11 +280,13 @@ async fn _main(args: CliArgs) -> anyhow::Result<()> {
                 DiscoveryBackendManagementCommands::Get {
                     public_key: address,
                     output,
+                    show_secrets,
                     client,
                 } => {
                     commands::discovery::backend::get_backend(
                         address.as_deref(),
                         output.as_deref(),
+                        show_secrets,
                         &client,
                     )
                     .await
@@ -313,6 +322,30 @@ async fn _main(args: CliArgs) -> anyhow::Result<()> {
                     public_key: address,
                     client,
                 } => commands::discovery::backend::delete_backend(&address, &client).await,
+                DiscoveryBackendManagementCommands::Status { client } => {
+                    commands::discovery::backend::backend_status(&client).await
+                }
+            },
+        },
+        RootCommands::Hold(hold) => match hold {
+            HoldCommands::Token(token) => match token {
+                TokenCommands::Mint {
+                    key,
+                    expires,
+                    output,
+                } => commands::hold::token::mint(&key, expires, output.as_deref()),
+                TokenCommands::Key { public, private } => commands::token::key(&public, &private),
+                TokenCommands::MintAll {
+                    public,
+                    private,
+                    expires,
+                    output,
+                } => commands::hold::token::mint_all(&public, &private, expires, output.as_deref()),
+                TokenCommands::Verify {
+                    public,
+                    token,
+                    output,
+                } => commands::hold::token::verify(&public, token.as_deref(), output.as_deref()),
             },
         },
     }
@@ -17,9 +17,15 @@ Feature: Discovery CLI management
     And valid backend JSON should be output to stdout
 
     Examples:
-      | node_type |
-      | cln-grpc  |
-      | lnd-grpc  |
+      | node_type     |
+      | cln-grpc      |
+      | cln-rest      |
+      | lnd-grpc      |
+      | lnd-rest      |
+      | phoenixd-http |
+      | eclair-http   |
+      | nwc           |
+      | fake          |
 
   @discovery-new-with-output
   Scenario: Generate backend JSON with output file
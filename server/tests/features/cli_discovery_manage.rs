@@ -30,6 +30,29 @@ async fn test_discovery_new_cln_grpc() {
         .expect("assert");
 }
 
+/// Feature: Discovery CLI management
+/// Scenario Outline: Generate cln-rest backend JSON
+#[tokio::test]
+async fn test_discovery_new_cln_rest() {
+    let mut cli_ctx = CliContext::create().expect("assert");
+
+    // Background
+    step_given_the_swgr_cli_is_available(&mut cli_ctx)
+        .await
+        .expect("assert");
+
+    // Scenario steps
+    step_when_i_run_swgr_discovery_new(&mut cli_ctx, "cln-rest")
+        .await
+        .expect("assert");
+    step_then_the_command_should_succeed(&mut cli_ctx)
+        .await
+        .expect("assert");
+    step_then_valid_backend_json_should_be_output_to_stdout(&mut cli_ctx)
+        .await
+        .expect("assert");
+}
+
 /// Feature: Discovery CLI management
 /// Scenario Outline: Generate lnd-grpc backend JSON
 #[tokio::test]
@@ -53,6 +76,121 @@ async fn test_discovery_new_lnd_grpc() {
         .expect("assert");
 }
 
+/// Feature: Discovery CLI management
+/// Scenario Outline: Generate lnd-rest backend JSON
+#[tokio::test]
+async fn test_discovery_new_lnd_rest() {
+    let mut cli_ctx = CliContext::create().expect("assert");
+
+    // Background
+    step_given_the_swgr_cli_is_available(&mut cli_ctx)
+        .await
+        .expect("assert");
+
+    // Scenario steps
+    step_when_i_run_swgr_discovery_new(&mut cli_ctx, "lnd-rest")
+        .await
+        .expect("assert");
+    step_then_the_command_should_succeed(&mut cli_ctx)
+        .await
+        .expect("assert");
+    step_then_valid_backend_json_should_be_output_to_stdout(&mut cli_ctx)
+        .await
+        .expect("assert");
+}
+
+/// Feature: Discovery CLI management
+/// Scenario Outline: Generate phoenixd-http backend JSON
+#[tokio::test]
+async fn test_discovery_new_phoenixd_http() {
+    let mut cli_ctx = CliContext::create().expect("assert");
+
+    // Background
+    step_given_the_swgr_cli_is_available(&mut cli_ctx)
+        .await
+        .expect("assert");
+
+    // Scenario steps
+    step_when_i_run_swgr_discovery_new(&mut cli_ctx, "phoenixd-http")
+        .await
+        .expect("assert");
+    step_then_the_command_should_succeed(&mut cli_ctx)
+        .await
+        .expect("assert");
+    step_then_valid_backend_json_should_be_output_to_stdout(&mut cli_ctx)
+        .await
+        .expect("assert");
+}
+
+/// Feature: Discovery CLI management
+/// Scenario Outline: Generate eclair-http backend JSON
+#[tokio::test]
+async fn test_discovery_new_eclair_http() {
+    let mut cli_ctx = CliContext::create().expect("assert");
+
+    // Background
+    step_given_the_swgr_cli_is_available(&mut cli_ctx)
+        .await
+        .expect("assert");
+
+    // Scenario steps
+    step_when_i_run_swgr_discovery_new(&mut cli_ctx, "eclair-http")
+        .await
+        .expect("assert");
+    step_then_the_command_should_succeed(&mut cli_ctx)
+        .await
+        .expect("assert");
+    step_then_valid_backend_json_should_be_output_to_stdout(&mut cli_ctx)
+        .await
+        .expect("assert");
+}
+
+/// Feature: Discovery CLI management
+/// Scenario Outline: Generate nwc backend JSON
+#[tokio::test]
+async fn test_discovery_new_nwc() {
+    let mut cli_ctx = CliContext::create().expect("assert");
+
+    // Background
+    step_given_the_swgr_cli_is_available(&mut cli_ctx)
+        .await
+        .expect("assert");
+
+    // Scenario steps
+    step_when_i_run_swgr_discovery_new(&mut cli_ctx, "nwc")
+        .await
+        .expect("assert");
+    step_then_the_command_should_succeed(&mut cli_ctx)
+        .await
+        .expect("assert");
+    step_then_valid_backend_json_should_be_output_to_stdout(&mut cli_ctx)
+        .await
+        .expect("assert");
+}
+
+/// Feature: Discovery CLI management
+/// Scenario Outline: Generate fake backend JSON
+#[tokio::test]
+async fn test_discovery_new_fake() {
+    let mut cli_ctx = CliContext::create().expect("assert");
+
+    // Background
+    step_given_the_swgr_cli_is_available(&mut cli_ctx)
+        .await
+        .expect("assert");
+
+    // Scenario steps
+    step_when_i_run_swgr_discovery_new(&mut cli_ctx, "fake")
+        .await
+        .expect("assert");
+    step_then_the_command_should_succeed(&mut cli_ctx)
+        .await
+        .expect("assert");
+    step_then_valid_backend_json_should_be_output_to_stdout(&mut cli_ctx)
+        .await
+        .expect("assert");
+}
+
 /// Feature: Discovery CLI management
 /// Scenario: Generate backend JSON with output file
 #[tokio::test]
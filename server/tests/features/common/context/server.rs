@@ -298,6 +298,7 @@ impl ServerContext {
                     Duration::from_secs(10),
                     &certs,
                     authorization,
+                    None,
                 )?)
             }
             Protocol::Http => Ok(HttpDiscoveryBackendStore::create(
@@ -306,6 +307,7 @@ impl ServerContext {
                 Duration::from_secs(10),
                 &[],
                 authorization,
+                None,
             )?),
         }
     }
@@ -334,6 +336,7 @@ impl ServerContext {
                     Duration::from_secs(10),
                     &certs,
                     authorization,
+                    None,
                 )?)
             }
             Protocol::Http => Ok(HttpOfferStore::create(
@@ -342,6 +345,7 @@ impl ServerContext {
                 Duration::from_secs(10),
                 &[],
                 authorization,
+                None,
             )?),
         }
     }
@@ -389,6 +389,7 @@ pub async fn step_when_the_payee_creates_an_offer_for_their_lightning_node(
             metadata: None,
             timestamp: now - ChronoDuration::minutes(5),
             expires: Some(now + ChronoDuration::hours(24)),
+            hold_invoice: false,
         },
     };
 
@@ -439,6 +440,9 @@ pub async fn step_when_the_payee_registers_their_lightning_node_as_a_backend(
                     client_key_path: payee.ln_nodes.cln.client_key_path.clone(),
                 }),
                 domain: None,
+                route_hints: None,
+                channels: None,
+                socks5_proxy: None,
             }),
         ),
         "lnd" => (
@@ -455,6 +459,9 @@ pub async fn step_when_the_payee_registers_their_lightning_node_as_a_backend(
                 }),
                 amp_invoice: false,
                 domain: None,
+                route_hints: None,
+                channels: None,
+                socks5_proxy: None,
             }),
         ),
         _ => bail!("Invalid target ln_node"),
@@ -467,6 +474,7 @@ pub async fn step_when_the_payee_registers_their_lightning_node_as_a_backend(
             partitions: ["default".to_string()].into(),
             weight: 100,
             enabled: true,
+            region: None,
             implementation: serde_json::to_vec(&implementation)?,
         },
     };
@@ -707,6 +715,9 @@ pub async fn register_payee_node_as_backend(ctx: &mut GlobalContext, payee_id: &
                     client_key_path: payee.ln_nodes.cln.client_key_path.clone(),
                 }),
                 domain: None,
+                route_hints: None,
+                channels: None,
+                socks5_proxy: None,
             }),
         ),
         "lnd" => (
@@ -719,6 +730,9 @@ pub async fn register_payee_node_as_backend(ctx: &mut GlobalContext, payee_id: &
                 }),
                 amp_invoice: false,
                 domain: None,
+                route_hints: None,
+                channels: None,
+                socks5_proxy: None,
             }),
         ),
         _ => bail!("Invalid target ln_node"),
@@ -732,6 +746,7 @@ pub async fn register_payee_node_as_backend(ctx: &mut GlobalContext, payee_id: &
             weight: 100,
             implementation: serde_json::to_vec(&implementation)?,
             enabled: true,
+            region: None,
         },
     };
 
@@ -837,6 +852,7 @@ pub async fn step_and_the_payee_has_created_an_offer_linked_to_both_lightning_no
             metadata: None,
             timestamp: now - ChronoDuration::minutes(5),
             expires: Some(now + ChronoDuration::hours(24)),
+            hold_invoice: false,
         },
     };
 
@@ -1012,6 +1028,7 @@ async fn enable_disable_backend(
             partitions: None,
             weight: None,
             enabled: Some(enabled),
+            region: None,
         },
     };
 
@@ -3057,6 +3074,7 @@ pub async fn step_and_backend_patch_json_exists(
         partitions: None,
         weight: Some(777),
         enabled: None,
+        region: None,
     };
 
     // Write the patch JSON to file
@@ -1,7 +1,12 @@
-use crate::offer::Offer;
+use crate::offer::{Bolt12OfferRecord, Offer};
 use crate::service::HasServiceErrorSource;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use secp256k1::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::error::Error;
+use std::time::Instant;
 use tokio::sync::watch;
 
 #[async_trait]
@@ -14,12 +19,71 @@ pub trait LnBalancer {
         amount_msat: u64,
         expiry_secs: u64,
         key: &[u8],
-    ) -> Result<String, Self::Error>;
+        preferred_regions: &[String],
+        // retries stop and the request fails once the deadline passes
+        deadline: Instant,
+    ) -> Result<IssuedInvoice, Self::Error>;
+
+    // releases the held payment to the node using the stored preimage, false if no hold invoice
+    // with this payment hash is outstanding
+    async fn settle_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error>;
+
+    // fails the held payment back to the payer, false if no hold invoice with this payment hash is
+    // outstanding
+    async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error>;
+
+    // reuses one of the Bolt12 offers already created for the offer when its backend is still
+    // available, otherwise creates a new one on a selected backend
+    async fn get_bolt12_offer(
+        &self,
+        offer: &Offer,
+        bolt12_offers: &[Bolt12OfferRecord],
+    ) -> Result<Bolt12OfferRecord, Self::Error>;
 
     async fn health(&self) -> Result<(), Self::Error>;
+
+    async fn status(&self) -> Result<Vec<LnBackendStatus>, Self::Error>;
 }
 
 #[async_trait]
 pub trait LnBalancerBackgroundServices {
     async fn start(&self, shutdown_rx: watch::Receiver<bool>);
 }
+
+// a Bolt11 invoice and what is known about it once a node has issued it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuedInvoice {
+    pub invoice: String,
+    pub payment_hash: [u8; 32],
+    // the node that signed the invoice
+    pub public_key: PublicKey,
+    pub expires: DateTime<Utc>,
+    // node requests made for the invoice, including failed ones
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnBackendStatus {
+    pub public_key: PublicKey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub partitions: BTreeSet<String>,
+    pub enabled: bool,
+    pub healthy: bool,
+    pub consecutive_success: usize,
+    pub consecutive_failure: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<LnBackendMetrics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_invoice_latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnBackendMetrics {
+    pub healthy: bool,
+    pub node_effective_inbound_msat: u64,
+}
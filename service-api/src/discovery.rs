@@ -68,10 +68,44 @@ pub struct DiscoveryBackendSparse {
     pub partitions: BTreeSet<String>,
     pub weight: usize,
     pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
     #[serde(with = "json_bytes")]
     pub implementation: Vec<u8>,
 }
 
+pub const REDACTED_SECRET: &str = "<redacted>";
+
+impl DiscoveryBackendSparse {
+    // inline credential material lives in an implementation auth object with type "inline", every
+    // other value of that object is replaced
+    pub fn redact_secrets(&mut self) {
+        let Ok(mut implementation) =
+            serde_json::from_slice::<serde_json::Value>(&self.implementation)
+        else {
+            return;
+        };
+        let Some(auth) = implementation
+            .get_mut("auth")
+            .and_then(serde_json::Value::as_object_mut)
+        else {
+            return;
+        };
+        if auth.get("type").and_then(serde_json::Value::as_str) != Some("inline") {
+            return;
+        }
+
+        for (key, value) in auth.iter_mut() {
+            if key != "type" && !value.is_null() {
+                *value = serde_json::Value::String(REDACTED_SECRET.to_string());
+            }
+        }
+        if let Ok(implementation) = serde_json::to_vec(&implementation) {
+            self.implementation = implementation;
+        }
+    }
+}
+
 mod json_bytes {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
     use serde_json::Value;
@@ -112,4 +146,58 @@ pub struct DiscoveryBackendPatchSparse {
     pub weight: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<Option<String>>,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::discovery::{DiscoveryBackendSparse, REDACTED_SECRET};
+    use serde_json::{json, Value};
+
+    fn backend(implementation: Value) -> DiscoveryBackendSparse {
+        DiscoveryBackendSparse {
+            name: None,
+            partitions: ["default".to_string()].into(),
+            weight: 1,
+            enabled: true,
+            region: None,
+            implementation: serde_json::to_vec(&implementation).unwrap(),
+        }
+    }
+
+    #[test]
+    fn redact_secrets_when_inline_auth_then_values_replaced() {
+        let mut backend = backend(json!({
+            "type": "lndGrpc",
+            "url": "https://127.0.0.1:10009",
+            "auth": {"type": "inline", "tlsCert": null, "macaroon": "0201036c6e64"},
+        }));
+
+        backend.redact_secrets();
+
+        let implementation: Value = serde_json::from_slice(&backend.implementation).unwrap();
+        assert_eq!(
+            implementation,
+            json!({
+                "type": "lndGrpc",
+                "url": "https://127.0.0.1:10009",
+                "auth": {"type": "inline", "tlsCert": null, "macaroon": REDACTED_SECRET},
+            })
+        );
+    }
+
+    #[test]
+    fn redact_secrets_when_path_auth_then_unchanged() {
+        let implementation = json!({
+            "type": "lndGrpc",
+            "url": "https://127.0.0.1:10009",
+            "auth": {"type": "path", "tlsCertPath": null, "macaroonPath": "/macaroon"},
+        });
+        let mut redacted = backend(implementation.clone());
+
+        redacted.redact_secrets();
+
+        assert_eq!(redacted, backend(implementation));
+    }
 }
@@ -1,6 +1,8 @@
+use crate::lnurl::LnUrlOfferMetadata;
 use crate::service::HasServiceErrorSource;
 use async_trait::async_trait;
 use email_address::EmailAddress;
+use secp256k1::PublicKey;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 pub use uuid::Uuid;
@@ -54,6 +56,38 @@ pub trait OfferMetadataStore {
     async fn delete_metadata(&self, partition: &str, id: &Uuid) -> Result<bool, Self::Error>;
 }
 
+#[async_trait]
+pub trait Bolt12OfferStore {
+    type Error: Error + Send + Sync + 'static + HasServiceErrorSource;
+
+    // every Bolt12 offer created for the offer, at most one per backend
+    async fn get_bolt12_offers(
+        &self,
+        partition: &str,
+        id: &Uuid,
+    ) -> Result<Vec<Bolt12OfferRecord>, Self::Error>;
+
+    async fn put_bolt12_offer(&self, offer: Bolt12OfferRecord) -> Result<bool, Self::Error>;
+}
+
+// preimages of hold invoices that are neither settled nor canceled yet, shared by every balancer
+#[async_trait]
+pub trait HoldInvoiceStore {
+    type Error: Error + Send + Sync + 'static + HasServiceErrorSource;
+
+    async fn get_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<Option<HoldInvoiceRecord>, Self::Error>;
+
+    async fn get_hold_invoices(&self) -> Result<Vec<HoldInvoiceRecord>, Self::Error>;
+
+    async fn put_hold_invoice(&self, hold: HoldInvoiceRecord) -> Result<bool, Self::Error>;
+
+    // only one caller deletes the hold, which makes it the one to settle or cancel it
+    async fn delete_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error>;
+}
+
 #[async_trait]
 pub trait HttpOfferClient: OfferStore + OfferMetadataStore {
     async fn health(&self) -> Result<(), <Self as OfferStore>::Error>;
@@ -83,6 +117,8 @@ pub struct Offer {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub hold_invoice: bool,
 }
 
 impl Offer {
@@ -101,6 +137,17 @@ impl Offer {
 
         false
     }
+
+    // Bolt12 offers have a single amount, offers with a range of amounts have none and are not
+    // offered as Bolt12 since the range couldn't be enforced
+    pub fn bolt12_amount_msat(&self) -> Option<u64> {
+        (self.min_sendable == self.max_sendable).then_some(self.min_sendable)
+    }
+
+    pub fn bolt12_description(&self) -> Result<String, serde_json::Error> {
+        let metadata: LnUrlOfferMetadata = serde_json::from_str(&self.metadata_json_string)?;
+        Ok(metadata.0.text)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -114,6 +161,9 @@ pub struct OfferRecordSparse {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires: Option<chrono::DateTime<chrono::Utc>>,
+    // invoices are created as hold invoices, settled or canceled through the admin endpoints
+    #[serde(default)]
+    pub hold_invoice: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -125,6 +175,48 @@ pub struct OfferRecord {
     pub offer: OfferRecordSparse,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bolt12OfferSparse {
+    pub offer_id: String,
+    pub bolt12: String,
+    // what the Bolt12 offer was created with, it is replaced once the offer record changes
+    pub amount_msat: u64,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bolt12OfferRecord {
+    pub partition: String,
+    pub id: Uuid,
+    pub public_key: PublicKey,
+    #[serde(flatten)]
+    pub offer: Bolt12OfferSparse,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldInvoiceSparse {
+    // backends are found again by public key, their address may change between discoveries
+    pub public_key: PublicKey,
+    #[serde(with = "hex_bytes")]
+    pub preimage: [u8; 32],
+    pub invoice: String,
+    pub cancel_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldInvoiceRecord {
+    #[serde(with = "hex_bytes")]
+    pub payment_hash: [u8; 32],
+    #[serde(flatten)]
+    pub hold: HoldInvoiceSparse,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OfferMetadataSparse {
@@ -183,6 +275,27 @@ mod base64_bytes {
     }
 }
 
+mod hex_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(s, &mut bytes).map_err(de::Error::custom)?;
+        Ok(bytes)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::offer::{
@@ -31,6 +31,38 @@ impl ServiceErrorSource {
     }
 }
 
+// how a failed request to a node may be retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceErrorRetry {
+    SameNode,
+    OtherNode,
+    Fatal,
+}
+
+impl fmt::Display for ServiceErrorRetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceErrorRetry::SameNode => write!(f, "SameNode"),
+            ServiceErrorRetry::OtherNode => write!(f, "OtherNode"),
+            ServiceErrorRetry::Fatal => write!(f, "Fatal"),
+        }
+    }
+}
+
+// requests are at fault for downstream errors, anything else may succeed elsewhere
+impl From<ServiceErrorSource> for ServiceErrorRetry {
+    fn from(esource: ServiceErrorSource) -> Self {
+        match esource {
+            ServiceErrorSource::Downstream => ServiceErrorRetry::Fatal,
+            _ => ServiceErrorRetry::OtherNode,
+        }
+    }
+}
+
 pub trait HasServiceErrorSource {
     fn get_service_error_source(&self) -> ServiceErrorSource;
+
+    fn get_service_error_retry(&self) -> ServiceErrorRetry {
+        self.get_service_error_source().into()
+    }
 }
@@ -0,0 +1,112 @@
+use axum::extract::FromRef;
+use axum::http::header::InvalidHeaderName;
+use axum::http::{HeaderMap, HeaderName};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::convert::Infallible;
+use std::time::{Duration, Instant};
+
+// used when neither a timeout nor a backoff max elapsed time is configured, matches the
+// documented retry budget
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+pub struct RequestDeadlines {
+    timeout: Duration,
+    header: Option<HeaderName>,
+}
+
+impl Default for RequestDeadlines {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            header: None,
+        }
+    }
+}
+
+impl RequestDeadlines {
+    pub fn new(timeout: Duration, header: Option<&str>) -> Result<Self, InvalidHeaderName> {
+        let header = header.map(HeaderName::try_from).transpose()?;
+        Ok(Self { timeout, header })
+    }
+
+    // the header holds the seconds the client is willing to wait, it can only shorten the
+    // configured timeout
+    pub fn timeout(&self, headers: &HeaderMap) -> Duration {
+        self.header
+            .as_ref()
+            .and_then(|name| headers.get(name)?.to_str().ok()?.trim().parse::<f64>().ok())
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .map_or(self.timeout, |timeout| timeout.min(self.timeout))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequestDeadline(pub Instant);
+
+impl<S> FromRequestParts<S> for RequestDeadline
+where
+    S: Send + Sync,
+    RequestDeadlines: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let deadlines = RequestDeadlines::from_ref(state);
+        Ok(RequestDeadline(
+            Instant::now() + deadlines.timeout(&parts.headers),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn create_deadlines() -> RequestDeadlines {
+        RequestDeadlines::new(Duration::from_secs(20), Some("X-Request-Timeout")).unwrap()
+    }
+
+    #[test]
+    fn timeout_when_header_shorter_then_header_wins() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-timeout", HeaderValue::from_static("2.5"));
+
+        assert_eq!(
+            create_deadlines().timeout(&headers),
+            Duration::from_millis(2500)
+        );
+    }
+
+    #[test]
+    fn timeout_when_header_longer_then_capped_by_configuration() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-timeout", HeaderValue::from_static("600"));
+
+        assert_eq!(
+            create_deadlines().timeout(&headers),
+            Duration::from_secs(20)
+        );
+    }
+
+    #[test]
+    fn timeout_when_header_missing_or_invalid_then_configuration() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            create_deadlines().timeout(&headers),
+            Duration::from_secs(20)
+        );
+
+        headers.insert("x-request-timeout", HeaderValue::from_static("-1"));
+        assert_eq!(
+            create_deadlines().timeout(&headers),
+            Duration::from_secs(20)
+        );
+    }
+
+    #[test]
+    fn new_when_header_name_invalid_then_error() {
+        assert!(RequestDeadlines::new(Duration::from_secs(1), Some("bad header")).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod deadline;
 pub mod host;
+pub mod region;
 pub mod scheme;
 pub mod uuid;
@@ -0,0 +1,111 @@
+use axum::extract::FromRef;
+use axum::http::header::InvalidHeaderName;
+use axum::http::{HeaderMap, HeaderName};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+#[derive(Debug, Clone, Default)]
+pub struct RegionPreferences {
+    headers: Vec<HeaderName>,
+    regions: HashMap<String, Vec<String>>,
+}
+
+impl RegionPreferences {
+    pub fn new(
+        headers: &[String],
+        regions: HashMap<String, Vec<String>>,
+    ) -> Result<Self, InvalidHeaderName> {
+        let headers = headers
+            .iter()
+            .map(|h| HeaderName::try_from(h.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { headers, regions })
+    }
+
+    // the first configured header present in the request decides the preferred regions
+    pub fn preferred_regions(&self, headers: &HeaderMap) -> Vec<String> {
+        self.headers
+            .iter()
+            .find_map(|name| headers.get(name)?.to_str().ok())
+            .and_then(|value| self.regions.get(value.trim()))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PreferredRegions(pub Vec<String>);
+
+impl<S> FromRequestParts<S> for PreferredRegions
+where
+    S: Send + Sync,
+    RegionPreferences: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let preferences = RegionPreferences::from_ref(state);
+        Ok(PreferredRegions(
+            preferences.preferred_regions(&parts.headers),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn create_preferences() -> RegionPreferences {
+        RegionPreferences::new(
+            &[
+                "CF-IPCountry".to_string(),
+                "CloudFront-Viewer-Country".to_string(),
+            ],
+            HashMap::from([
+                ("US".to_string(), vec!["us-east".to_string()]),
+                (
+                    "DE".to_string(),
+                    vec!["eu-central".to_string(), "eu-west".to_string()],
+                ),
+            ]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn preferred_regions_when_header_mapped_then_returns_regions() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cloudfront-viewer-country", HeaderValue::from_static("DE"));
+
+        let regions = create_preferences().preferred_regions(&headers);
+
+        assert_eq!(regions, vec!["eu-central", "eu-west"]);
+    }
+
+    #[test]
+    fn preferred_regions_when_several_headers_then_first_configured_wins() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cloudfront-viewer-country", HeaderValue::from_static("DE"));
+        headers.insert("cf-ipcountry", HeaderValue::from_static("US"));
+
+        let regions = create_preferences().preferred_regions(&headers);
+
+        assert_eq!(regions, vec!["us-east"]);
+    }
+
+    #[test]
+    fn new_when_header_name_invalid_then_error() {
+        assert!(RegionPreferences::new(&["bad header".to_string()], HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn preferred_regions_when_unmapped_or_missing_then_empty() {
+        let mut headers = HeaderMap::new();
+        assert!(create_preferences().preferred_regions(&headers).is_empty());
+
+        headers.insert("cf-ipcountry", HeaderValue::from_static("JP"));
+        assert!(create_preferences().preferred_regions(&headers).is_empty());
+    }
+}
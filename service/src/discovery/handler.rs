@@ -2,19 +2,26 @@ use crate::axum::crud::error::CrudError;
 use crate::axum::crud::response::JsonCrudResponse;
 use crate::axum::header::no_cache_headers;
 use crate::discovery::state::DiscoveryState;
-use axum::extract::Path;
+use axum::extract::{Path, Query};
 use axum::http::{HeaderMap, HeaderValue};
 use axum::{extract::State, Json};
+use serde::Deserialize;
 use switchgear_service_api::discovery::{
     DiscoveryBackend, DiscoveryBackendPatch, DiscoveryBackendPatchSparse, DiscoveryBackendSparse,
     DiscoveryBackendStore, DiscoveryBackends,
 };
 
+#[derive(Deserialize, Debug)]
+pub struct GetBackendQueryParameters {
+    pub secrets: Option<bool>,
+}
+
 pub struct DiscoveryHandlers;
 
 impl DiscoveryHandlers {
     pub async fn get_backend<S>(
         Path(public_key): Path<String>,
+        Query(params): Query<GetBackendQueryParameters>,
         State(state): State<DiscoveryState<S>>,
     ) -> Result<JsonCrudResponse<DiscoveryBackend>, CrudError>
     where
@@ -22,13 +29,17 @@ impl DiscoveryHandlers {
     {
         let public_key = public_key.parse().map_err(|_| CrudError::bad())?;
 
-        let backend = state
+        let mut backend = state
             .store()
             .get(&public_key)
             .await
             .map_err(|e| crate::crud_error_from_service!(e))?
             .ok_or(CrudError::not_found())?;
 
+        if !params.secrets.unwrap_or(false) {
+            backend.backend.redact_secrets();
+        }
+
         let headers = no_cache_headers();
 
         Ok(JsonCrudResponse::ok(backend, headers))
@@ -36,6 +47,7 @@ impl DiscoveryHandlers {
 
     pub async fn get_backends<S>(
         headers: HeaderMap,
+        Query(params): Query<GetBackendQueryParameters>,
         State(state): State<DiscoveryState<S>>,
     ) -> Result<JsonCrudResponse<Vec<DiscoveryBackend>>, CrudError>
     where
@@ -63,7 +75,14 @@ impl DiscoveryHandlers {
 
         match backends.backends {
             None => Ok(JsonCrudResponse::not_modified(headers)),
-            Some(backends) => Ok(JsonCrudResponse::ok(backends, headers)),
+            Some(mut backends) => {
+                if !params.secrets.unwrap_or(false) {
+                    backends
+                        .iter_mut()
+                        .for_each(|backend| backend.backend.redact_secrets());
+                }
+                Ok(JsonCrudResponse::ok(backends, headers))
+            }
         }
     }
 
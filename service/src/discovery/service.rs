@@ -63,7 +63,7 @@ mod tests {
     use secp256k1::{PublicKey, Secp256k1, SecretKey};
     use std::time::{SystemTime, UNIX_EPOCH};
     use switchgear_service_api::discovery::{
-        DiscoveryBackend, DiscoveryBackendPatchSparse, DiscoveryBackendSparse,
+        DiscoveryBackend, DiscoveryBackendPatchSparse, DiscoveryBackendSparse, REDACTED_SECRET,
     };
 
     fn create_test_backend(partition: &str) -> DiscoveryBackend {
@@ -79,6 +79,7 @@ mod tests {
                 partitions: [partition.to_string()].into(),
                 weight: 100,
                 enabled: true,
+                region: None,
                 implementation: "{}".as_bytes().to_vec(),
             },
         }
@@ -228,6 +229,56 @@ mod tests {
         assert_eq!(retrieved.public_key, backend.public_key);
     }
 
+    #[tokio::test]
+    async fn get_backend_when_inline_auth_then_redacted_unless_secrets_requested() {
+        let server = setup_test_server().await;
+        let mut backend = create_test_backend("default");
+        backend.backend.implementation =
+            br#"{"auth":{"macaroon":"0201036c6e64","type":"inline"},"type":"lndGrpc"}"#.to_vec();
+
+        server
+            .server
+            .post("/discovery")
+            .authorization_bearer(server.authorization.clone())
+            .json(&backend)
+            .await;
+
+        let response = server
+            .server
+            .get(format!("/discovery/{}", backend.public_key).as_str())
+            .authorization_bearer(server.authorization.clone())
+            .await;
+        let retrieved: DiscoveryBackend = response.json();
+        assert_ne!(retrieved, backend);
+        assert!(String::from_utf8(retrieved.backend.implementation)
+            .unwrap()
+            .contains(REDACTED_SECRET));
+
+        let response = server
+            .server
+            .get("/discovery")
+            .authorization_bearer(server.authorization.clone())
+            .await;
+        let retrieved: Vec<DiscoveryBackend> = response.json();
+        assert_ne!(retrieved, vec![backend.clone()]);
+
+        let response = server
+            .server
+            .get(format!("/discovery/{}?secrets=true", backend.public_key).as_str())
+            .authorization_bearer(server.authorization.clone())
+            .await;
+        let retrieved: DiscoveryBackend = response.json();
+        assert_eq!(retrieved, backend);
+
+        let response = server
+            .server
+            .get("/discovery?secrets=true")
+            .authorization_bearer(server.authorization.clone())
+            .await;
+        let retrieved: Vec<DiscoveryBackend> = response.json();
+        assert_eq!(retrieved, vec![backend]);
+    }
+
     #[tokio::test]
     async fn get_backend_when_not_exists_then_returns_not_found() {
         let server = setup_test_server().await;
@@ -314,6 +365,7 @@ mod tests {
             partitions: None,
             weight: Some(200),
             enabled: None,
+            region: None,
         };
         // Update with PATCH
         backend.backend.weight = 200;
@@ -348,6 +400,7 @@ mod tests {
             partitions: None,
             weight: Some(200),
             enabled: None,
+            region: None,
         };
         // Update with PATCH
         backend.backend.weight = 200;
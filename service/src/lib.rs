@@ -5,6 +5,8 @@ pub(crate) mod offer;
 #[cfg(test)]
 mod testing;
 
+pub use axum::extract::deadline;
+pub use axum::extract::region;
 pub use axum::extract::scheme;
 
 pub use crate::discovery::auth::DiscoveryAudience;
@@ -12,6 +14,9 @@ pub use crate::discovery::auth::DiscoveryBearerTokenValidator;
 pub use crate::discovery::auth::DiscoveryClaims;
 pub use crate::discovery::service::DiscoveryService;
 pub use crate::discovery::state::DiscoveryState;
+pub use crate::lnurl::auth::HoldAudience;
+pub use crate::lnurl::auth::HoldBearerTokenValidator;
+pub use crate::lnurl::auth::HoldClaims;
 pub use crate::lnurl::pay::state::LnUrlPayState;
 pub use crate::lnurl::service::LnUrlBalancerService;
 pub use crate::offer::auth::OfferAudience;
@@ -0,0 +1,68 @@
+use crate::axum::auth::BearerTokenValidator;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt::Display;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldClaims {
+    pub aud: HoldAudience,
+    pub exp: usize,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialOrd, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HoldAudience {
+    Hold,
+}
+
+impl Serialize for HoldAudience {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Display for HoldAudience {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HoldAudience::Hold => f.write_str("hold"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HoldBearerTokenValidator {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl HoldBearerTokenValidator {
+    pub fn new(decoding_key: DecodingKey) -> Self {
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_audience(&[HoldAudience::Hold]);
+        Self {
+            decoding_key,
+            validation,
+        }
+    }
+
+    pub fn validate_token(&self, token: &str) -> jsonwebtoken::errors::Result<HoldClaims> {
+        let token = decode::<HoldClaims>(token, &self.decoding_key, &self.validation)?;
+        if token.claims.aud == HoldAudience::Hold {
+            Ok(token.claims)
+        } else {
+            Err(jsonwebtoken::errors::Error::from(
+                jsonwebtoken::errors::ErrorKind::InvalidToken,
+            ))
+        }
+    }
+}
+
+impl BearerTokenValidator for HoldBearerTokenValidator {
+    fn validate(&self, token: &str) -> bool {
+        self.validate_token(token).is_ok()
+    }
+}
@@ -1,10 +1,12 @@
+use crate::axum::extract::deadline::RequestDeadline;
 use crate::axum::extract::host::ValidatedHost;
+use crate::axum::extract::region::PreferredRegions;
 use crate::axum::extract::scheme::Scheme;
 use crate::axum::extract::uuid::UuidParam;
 use crate::axum::header::no_cache_headers;
 use crate::lnurl::pay::error::LnUrlPayServiceError;
 use crate::lnurl::pay::state::LnUrlPayState;
-use axum::extract::Query;
+use axum::extract::{Path, Query};
 use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use axum::response::Response;
 use axum::{extract::State, response::IntoResponse};
@@ -14,9 +16,9 @@ use qrcode::QrCode;
 use serde::Deserialize;
 use sqlx::types::JsonValue;
 use std::io::{self, Cursor};
-use switchgear_service_api::balance::LnBalancer;
+use switchgear_service_api::balance::{LnBackendStatus, LnBalancer};
 use switchgear_service_api::lnurl::{LnUrlInvoice, LnUrlOffer, LnUrlOfferTag};
-use switchgear_service_api::offer::{Offer, OfferProvider};
+use switchgear_service_api::offer::{Bolt12OfferStore, Offer, OfferProvider};
 use url::Url;
 use uuid::Uuid;
 
@@ -59,6 +61,8 @@ impl LnUrlPayHandlers {
 
     pub async fn invoice<O, B>(
         ValidatedHost(hostname): ValidatedHost,
+        PreferredRegions(preferred_regions): PreferredRegions,
+        RequestDeadline(deadline): RequestDeadline,
         UuidParam { partition, id }: UuidParam,
         Query(params): Query<InvoiceParameters>,
         State(state): State<LnUrlPayState<O, B>>,
@@ -102,13 +106,23 @@ impl LnUrlPayHandlers {
             )));
         }
 
-        let pr = state
+        let issued = state
             .balancer()
-            .get_invoice(&offer, params.amount, state.invoice_expiry(), &key)
+            .get_invoice(
+                &offer,
+                params.amount,
+                state.invoice_expiry(),
+                &key,
+                &preferred_regions,
+                deadline,
+            )
             .await
             .map_err(|e| crate::lnurl_pay_error_from_service!(e))?;
 
-        let invoice = LnUrlInvoice { pr, routes: vec![] };
+        let invoice = LnUrlInvoice {
+            pr: issued.invoice,
+            routes: vec![],
+        };
         let headers = no_cache_headers();
         Ok(LnUrlPayResponse::ok(invoice, headers))
     }
@@ -198,6 +212,54 @@ impl LnUrlPayHandlers {
         Ok((headers, png_bytes))
     }
 
+    pub async fn bolt12<O, B>(
+        ValidatedHost(hostname): ValidatedHost,
+        UuidParam { partition, id }: UuidParam,
+        State(state): State<LnUrlPayState<O, B>>,
+    ) -> Result<(HeaderMap, String), LnUrlPayServiceError>
+    where
+        O: OfferProvider + Bolt12OfferStore + Clone,
+        B: LnBalancer,
+    {
+        let offer = Self::get_offer(&hostname, &partition, &id, &state).await?;
+
+        // a Bolt12 offer without an amount would accept any amount outside the offer's range
+        if offer.bolt12_amount_msat().is_none() {
+            return Err(LnUrlPayServiceError::bad_request(format!(
+                "Bolt12 offers require a fixed amount, offer {} has range [{}, {}]",
+                &id, offer.min_sendable, offer.max_sendable
+            )));
+        }
+
+        let bolt12_offers = state
+            .offer_provider()
+            .get_bolt12_offers(&partition, &id)
+            .await
+            .map_err(|e| crate::lnurl_pay_error_from_service!(e))?;
+
+        let bolt12_offer = state
+            .balancer()
+            .get_bolt12_offer(&offer, &bolt12_offers)
+            .await
+            .map_err(|e| crate::lnurl_pay_error_from_service!(e))?;
+
+        if !bolt12_offers.contains(&bolt12_offer) {
+            state
+                .offer_provider()
+                .put_bolt12_offer(bolt12_offer.clone())
+                .await
+                .map_err(|e| crate::lnurl_pay_error_from_service!(e))?;
+        }
+
+        let mut headers = Self::expires_headers(offer.expires)?;
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+
+        Ok((headers, bolt12_offer.offer.bolt12))
+    }
+
     pub async fn health_full<O, B>(
         State(state): State<LnUrlPayState<O, B>>,
     ) -> Result<LnUrlPayResponse<JsonValue>, LnUrlPayServiceError>
@@ -216,6 +278,77 @@ impl LnUrlPayHandlers {
         ))
     }
 
+    pub async fn status<O, B>(
+        State(state): State<LnUrlPayState<O, B>>,
+    ) -> Result<LnUrlPayResponse<Vec<LnBackendStatus>>, LnUrlPayServiceError>
+    where
+        O: OfferProvider + Clone,
+        B: LnBalancer,
+    {
+        let status = state
+            .balancer()
+            .status()
+            .await
+            .map_err(|e| crate::lnurl_pay_error_from_service!(e))?;
+        Ok(LnUrlPayResponse::ok(status, no_cache_headers()))
+    }
+
+    pub async fn settle_hold_invoice<O, B>(
+        Path(payment_hash): Path<String>,
+        State(state): State<LnUrlPayState<O, B>>,
+    ) -> Result<StatusCode, LnUrlPayServiceError>
+    where
+        O: OfferProvider + Clone,
+        B: LnBalancer,
+    {
+        let payment_hash = Self::parse_payment_hash(&payment_hash)?;
+        let settled = state
+            .balancer()
+            .settle_hold_invoice(&payment_hash)
+            .await
+            .map_err(|e| crate::lnurl_pay_error_from_service!(e))?;
+        Self::hold_invoice_status(settled, &payment_hash)
+    }
+
+    pub async fn cancel_hold_invoice<O, B>(
+        Path(payment_hash): Path<String>,
+        State(state): State<LnUrlPayState<O, B>>,
+    ) -> Result<StatusCode, LnUrlPayServiceError>
+    where
+        O: OfferProvider + Clone,
+        B: LnBalancer,
+    {
+        let payment_hash = Self::parse_payment_hash(&payment_hash)?;
+        let canceled = state
+            .balancer()
+            .cancel_hold_invoice(&payment_hash)
+            .await
+            .map_err(|e| crate::lnurl_pay_error_from_service!(e))?;
+        Self::hold_invoice_status(canceled, &payment_hash)
+    }
+
+    fn parse_payment_hash(payment_hash: &str) -> Result<[u8; 32], LnUrlPayServiceError> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(payment_hash, &mut bytes).map_err(|e| {
+            LnUrlPayServiceError::bad_request(format!("invalid payment hash {payment_hash}: {e}"))
+        })?;
+        Ok(bytes)
+    }
+
+    fn hold_invoice_status(
+        resolved: bool,
+        payment_hash: &[u8; 32],
+    ) -> Result<StatusCode, LnUrlPayServiceError> {
+        if resolved {
+            Ok(StatusCode::NO_CONTENT)
+        } else {
+            Err(LnUrlPayServiceError::not_found(format!(
+                "hold invoice not found: {}",
+                hex::encode(payment_hash)
+            )))
+        }
+    }
+
     fn gen_bech32(callback: &str) -> io::Result<String> {
         let callback =
             Url::parse(callback).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
@@ -1,6 +1,9 @@
+use crate::axum::extract::deadline::RequestDeadlines;
 use crate::axum::extract::host::AllowedHosts;
+use crate::axum::extract::region::RegionPreferences;
 use crate::axum::extract::scheme::Scheme;
 use axum::extract::FromRef;
+use jsonwebtoken::DecodingKey;
 use std::collections::HashSet;
 use switchgear_service_api::balance::LnBalancer;
 use switchgear_service_api::offer::OfferProvider;
@@ -17,6 +20,10 @@ pub struct LnUrlPayState<O, B> {
     bech32_qr_scale: usize,
     bech32_qr_light: u8,
     bech32_qr_dark: u8,
+    status_auth_authority: Option<DecodingKey>,
+    hold_auth_authority: Option<DecodingKey>,
+    region_preferences: RegionPreferences,
+    request_deadlines: RequestDeadlines,
 }
 
 impl<O, B> FromRef<LnUrlPayState<O, B>> for Scheme {
@@ -31,6 +38,18 @@ impl<O, B> FromRef<LnUrlPayState<O, B>> for AllowedHosts {
     }
 }
 
+impl<O, B> FromRef<LnUrlPayState<O, B>> for RegionPreferences {
+    fn from_ref(input: &LnUrlPayState<O, B>) -> Self {
+        input.region_preferences.clone()
+    }
+}
+
+impl<O, B> FromRef<LnUrlPayState<O, B>> for RequestDeadlines {
+    fn from_ref(input: &LnUrlPayState<O, B>) -> Self {
+        input.request_deadlines.clone()
+    }
+}
+
 impl<O, B> LnUrlPayState<O, B>
 where
     O: OfferProvider + Clone,
@@ -48,6 +67,10 @@ where
         bech32_qr_scale: usize,
         bech32_qr_light: u8,
         bech32_qr_dark: u8,
+        status_auth_authority: Option<DecodingKey>,
+        hold_auth_authority: Option<DecodingKey>,
+        region_preferences: RegionPreferences,
+        request_deadlines: RequestDeadlines,
     ) -> Self {
         Self {
             partitions,
@@ -60,6 +83,10 @@ where
             bech32_qr_scale,
             bech32_qr_light,
             bech32_qr_dark,
+            status_auth_authority,
+            hold_auth_authority,
+            region_preferences,
+            request_deadlines,
         }
     }
 
@@ -94,4 +121,12 @@ where
     pub fn bech32_qr_dark(&self) -> u8 {
         self.bech32_qr_dark
     }
+
+    pub fn status_auth_authority(&self) -> Option<&DecodingKey> {
+        self.status_auth_authority.as_ref()
+    }
+
+    pub fn hold_auth_authority(&self) -> Option<&DecodingKey> {
+        self.hold_auth_authority.as_ref()
+    }
 }
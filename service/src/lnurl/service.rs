@@ -1,12 +1,15 @@
+use crate::axum::auth::BearerTokenAuthLayer;
 use crate::axum::partitions::PartitionsLayer;
+use crate::discovery::auth::DiscoveryBearerTokenValidator;
+use crate::lnurl::auth::HoldBearerTokenValidator;
 use crate::lnurl::pay::handler::LnUrlPayHandlers;
 use crate::lnurl::pay::state::LnUrlPayState;
 use axum::http::StatusCode;
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::Router;
 use std::sync::Arc;
 use switchgear_service_api::balance::LnBalancer;
-use switchgear_service_api::offer::OfferProvider;
+use switchgear_service_api::offer::{Bolt12OfferStore, OfferProvider};
 
 #[derive(Debug)]
 pub struct LnUrlBalancerService;
@@ -14,10 +17,10 @@ pub struct LnUrlBalancerService;
 impl LnUrlBalancerService {
     pub fn router<O, B>(state: LnUrlPayState<O, B>) -> Router
     where
-        O: OfferProvider + Send + Sync + Clone + 'static,
+        O: OfferProvider + Bolt12OfferStore + Send + Sync + Clone + 'static,
         B: LnBalancer + Send + Sync + Clone + 'static,
     {
-        Router::new()
+        let router = Router::new()
             .route(
                 "/offers/{partition}/{id}/bech32/qr",
                 get(LnUrlPayHandlers::bech32_qr),
@@ -30,11 +33,47 @@ impl LnUrlBalancerService {
                 "/offers/{partition}/{id}/invoice",
                 get(LnUrlPayHandlers::invoice),
             )
+            .route(
+                "/offers/{partition}/{id}/bolt12",
+                get(LnUrlPayHandlers::bolt12),
+            )
             .route("/offers/{partition}/{id}", get(LnUrlPayHandlers::offer))
             .layer(PartitionsLayer::new(Arc::new(state.partitions().clone())))
             .route("/health/full", get(LnUrlPayHandlers::health_full))
-            .route("/health", get(Self::health_check_handler))
-            .with_state(state)
+            .route("/health", get(Self::health_check_handler));
+
+        let router = match state.status_auth_authority() {
+            Some(status_auth_authority) => router.merge(
+                Router::new()
+                    .route("/status", get(LnUrlPayHandlers::status))
+                    .layer(BearerTokenAuthLayer::new(
+                        DiscoveryBearerTokenValidator::new(status_auth_authority.clone()),
+                        "discovery",
+                    )),
+            ),
+            None => router,
+        };
+
+        let router = match state.hold_auth_authority() {
+            Some(hold_auth_authority) => router.merge(
+                Router::new()
+                    .route(
+                        "/holds/{payment_hash}/settle",
+                        post(LnUrlPayHandlers::settle_hold_invoice),
+                    )
+                    .route(
+                        "/holds/{payment_hash}/cancel",
+                        post(LnUrlPayHandlers::cancel_hold_invoice),
+                    )
+                    .layer(BearerTokenAuthLayer::new(
+                        HoldBearerTokenValidator::new(hold_auth_authority.clone()),
+                        "hold",
+                    )),
+            ),
+            None => router,
+        };
+
+        router.with_state(state)
     }
 
     async fn health_check_handler() -> StatusCode {
@@ -44,7 +83,10 @@ impl LnUrlBalancerService {
 
 #[cfg(test)]
 mod tests {
+    use crate::axum::extract::deadline::DEFAULT_REQUEST_TIMEOUT;
     use crate::axum::extract::scheme::Scheme;
+    use crate::discovery::auth::{DiscoveryAudience, DiscoveryClaims};
+    use crate::lnurl::auth::{HoldAudience, HoldClaims};
     use crate::lnurl::pay::state::LnUrlPayState;
     use crate::lnurl::service::LnUrlBalancerService;
     use crate::testing::offer::store::TestOfferStore;
@@ -52,16 +94,23 @@ mod tests {
     use axum::http::StatusCode;
     use axum_test::TestServer;
     use chrono::{Duration, Utc};
+    use jsonwebtoken::{encode, Algorithm, DecodingKey, EncodingKey, Header};
+    use p256::ecdsa::SigningKey;
+    use p256::pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use secp256k1::{PublicKey, Secp256k1, SecretKey};
     use std::collections::HashSet;
-    use switchgear_service_api::balance::LnBalancer;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use switchgear_service_api::balance::{IssuedInvoice, LnBackendStatus, LnBalancer};
     use switchgear_service_api::lnurl::{LnUrlInvoice, LnUrlOffer, LnUrlOfferMetadata};
     use switchgear_service_api::offer::{
-        Offer, OfferMetadata, OfferMetadataSparse, OfferMetadataStore, OfferRecord,
-        OfferRecordSparse, OfferStore,
+        Bolt12OfferRecord, Bolt12OfferSparse, Bolt12OfferStore, Offer, OfferMetadata,
+        OfferMetadataSparse, OfferMetadataStore, OfferRecord, OfferRecordSparse, OfferStore,
     };
     use switchgear_service_api::service::HasServiceErrorSource;
     use uuid::Uuid;
 
+    const HELD_PAYMENT_HASH: [u8; 32] = [1u8; 32];
+
     // Mock LnBalancer implementation
     #[derive(Debug, Clone)]
     pub struct MockLnBalancer {
@@ -69,6 +118,7 @@ mod tests {
         should_fail_upstream: bool,
         invoice_response: String,
         captured_expiry: std::sync::Arc<std::sync::Mutex<Option<u64>>>,
+        captured_deadline: std::sync::Arc<std::sync::Mutex<Option<std::time::Instant>>>,
     }
 
     impl MockLnBalancer {
@@ -78,6 +128,7 @@ mod tests {
                 should_fail_upstream: false,
                 invoice_response: "lnbc1000n1pjdkqs0pp5...".to_string(),
                 captured_expiry: std::sync::Arc::new(std::sync::Mutex::new(None)),
+                captured_deadline: std::sync::Arc::new(std::sync::Mutex::new(None)),
             }
         }
 
@@ -87,6 +138,7 @@ mod tests {
                 should_fail_upstream: false,
                 invoice_response: String::new(),
                 captured_expiry: std::sync::Arc::new(std::sync::Mutex::new(None)),
+                captured_deadline: std::sync::Arc::new(std::sync::Mutex::new(None)),
             }
         }
 
@@ -96,12 +148,17 @@ mod tests {
                 should_fail_upstream: false,
                 invoice_response: invoice.to_string(),
                 captured_expiry: std::sync::Arc::new(std::sync::Mutex::new(None)),
+                captured_deadline: std::sync::Arc::new(std::sync::Mutex::new(None)),
             }
         }
 
         pub fn captured_expiry(&self) -> Option<u64> {
             *self.captured_expiry.lock().unwrap()
         }
+
+        pub fn captured_deadline(&self) -> Option<std::time::Instant> {
+            *self.captured_deadline.lock().unwrap()
+        }
     }
 
     #[derive(Debug, thiserror::Error)]
@@ -135,22 +192,71 @@ mod tests {
             _amount_msat: u64,
             expiry_secs: u64,
             _key: &[u8],
-        ) -> Result<String, Self::Error> {
+            _preferred_regions: &[String],
+            deadline: std::time::Instant,
+        ) -> Result<IssuedInvoice, Self::Error> {
             // Capture the expiry parameter for testing
             *self.captured_expiry.lock().unwrap() = Some(expiry_secs);
+            *self.captured_deadline.lock().unwrap() = Some(deadline);
 
             if self.should_fail_upstream {
                 Err(MockLnBalancerCombinedError::Upstream)
             } else if self.should_fail {
                 Err(MockLnBalancerCombinedError::Internal)
             } else {
-                Ok(self.invoice_response.clone())
+                Ok(IssuedInvoice {
+                    invoice: self.invoice_response.clone(),
+                    payment_hash: [0u8; 32],
+                    public_key: PublicKey::from_secret_key(
+                        &Secp256k1::new(),
+                        &SecretKey::from_byte_array([1u8; 32]).unwrap(),
+                    ),
+                    expires: Utc::now() + Duration::seconds(expiry_secs as i64),
+                    attempts: 1,
+                })
+            }
+        }
+
+        async fn get_bolt12_offer(
+            &self,
+            offer: &Offer,
+            bolt12_offers: &[Bolt12OfferRecord],
+        ) -> Result<Bolt12OfferRecord, Self::Error> {
+            if let Some(existing) = bolt12_offers.first() {
+                return Ok(existing.clone());
             }
+            Ok(Bolt12OfferRecord {
+                partition: offer.partition.clone(),
+                id: offer.id,
+                public_key: PublicKey::from_secret_key(
+                    &Secp256k1::new(),
+                    &SecretKey::from_byte_array([1u8; 32]).unwrap(),
+                ),
+                offer: Bolt12OfferSparse {
+                    offer_id: "offer_id".to_string(),
+                    bolt12: format!("lno1{}", offer.id.simple()),
+                    amount_msat: offer.bolt12_amount_msat().unwrap(),
+                    description: offer.bolt12_description().unwrap(),
+                    expires: offer.expires,
+                },
+            })
+        }
+
+        async fn settle_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+            Ok(payment_hash == &HELD_PAYMENT_HASH)
+        }
+
+        async fn cancel_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+            Ok(payment_hash == &HELD_PAYMENT_HASH)
         }
 
         async fn health(&self) -> Result<(), Self::Error> {
             Ok(())
         }
+
+        async fn status(&self) -> Result<Vec<LnBackendStatus>, Self::Error> {
+            Ok(vec![])
+        }
     }
 
     // Test helper functions
@@ -178,6 +284,7 @@ mod tests {
                 metadata: None,
                 timestamp: Utc::now() - Duration::hours(1),
                 expires: Some(Utc::now() + Duration::hours(1)),
+                hold_invoice: false,
             },
         };
 
@@ -245,6 +352,10 @@ mod tests {
             8,
             255u8,
             0u8,
+            None,
+            None,
+            Default::default(),
+            Default::default(),
         );
 
         let app = LnUrlBalancerService::router(state);
@@ -266,6 +377,10 @@ mod tests {
             8,
             255u8,
             0u8,
+            None,
+            None,
+            Default::default(),
+            Default::default(),
         );
 
         let app = LnUrlBalancerService::router(state);
@@ -302,6 +417,10 @@ mod tests {
             8,
             255u8,
             0u8,
+            None,
+            None,
+            Default::default(),
+            Default::default(),
         );
 
         let app = LnUrlBalancerService::router(state);
@@ -385,6 +504,10 @@ mod tests {
             8,
             255u8,
             0u8,
+            None,
+            None,
+            Default::default(),
+            Default::default(),
         );
 
         let app = LnUrlBalancerService::router(state);
@@ -762,6 +885,10 @@ mod tests {
             8,
             255u8,
             0u8,
+            None,
+            None,
+            Default::default(),
+            Default::default(),
         );
         let app = LnUrlBalancerService::router(state);
         let server = TestServer::new(app).unwrap();
@@ -797,6 +924,26 @@ mod tests {
         assert_eq!(balancer.captured_expiry(), Some(expected_expiry));
     }
 
+    #[tokio::test]
+    async fn get_invoice_when_valid_request_then_passes_request_deadline() {
+        let test_offer = create_test_offer();
+        let offer_id = test_offer.id;
+        let (server, balancer) =
+            create_test_server_with_offer_and_expiry_and_balancer(test_offer, 3600).await;
+
+        let before = std::time::Instant::now();
+        let response = server
+            .get(&format!("/offers/default/{offer_id}/invoice?amount=500000",))
+            .await;
+
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        // the default request timeout applies without a configured header
+        let deadline = balancer.captured_deadline().unwrap();
+        assert!(deadline >= before + DEFAULT_REQUEST_TIMEOUT);
+        assert!(deadline <= std::time::Instant::now() + DEFAULT_REQUEST_TIMEOUT);
+    }
+
     // Bech32 Endpoint Tests
 
     #[tokio::test]
@@ -957,4 +1104,264 @@ mod tests {
 
         assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
     }
+
+    #[tokio::test]
+    async fn get_status_when_no_auth_authority_then_returns_not_found() {
+        let server = create_empty_test_server();
+
+        let response = server.get("/status").await;
+
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    fn create_test_auth_keys() -> (EncodingKey, DecodingKey) {
+        let mut rng = rand::thread_rng();
+        let private_key = SigningKey::random(&mut rng);
+        let public_key = *private_key.verifying_key();
+
+        let private_key = private_key
+            .to_pkcs8_pem(p256::pkcs8::LineEnding::default())
+            .unwrap();
+        let encoding_key = EncodingKey::from_ec_pem(private_key.as_bytes()).unwrap();
+
+        let public_key = public_key
+            .to_public_key_pem(p256::pkcs8::LineEnding::default())
+            .unwrap();
+        let decoding_key = DecodingKey::from_ec_pem(public_key.as_bytes()).unwrap();
+
+        (encoding_key, decoding_key)
+    }
+
+    fn test_token_exp() -> usize {
+        (SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600) as usize
+    }
+
+    fn create_test_server_with_auth(
+        status_auth_authority: Option<DecodingKey>,
+        hold_auth_authority: Option<DecodingKey>,
+    ) -> TestServer {
+        let state = LnUrlPayState::new(
+            HashSet::from(["default".to_string()]),
+            TestOfferStore::default(),
+            MockLnBalancer::new(),
+            3600,
+            Scheme("http".to_string()),
+            Default::default(),
+            Default::default(),
+            8,
+            255u8,
+            0u8,
+            status_auth_authority,
+            hold_auth_authority,
+            Default::default(),
+            Default::default(),
+        );
+        TestServer::new(LnUrlBalancerService::router(state)).unwrap()
+    }
+
+    fn create_test_server_with_status_auth() -> (TestServer, String) {
+        let (encoding_key, decoding_key) = create_test_auth_keys();
+        let server = create_test_server_with_auth(Some(decoding_key), None);
+
+        let claims = DiscoveryClaims {
+            aud: DiscoveryAudience::Discovery,
+            exp: test_token_exp(),
+        };
+        let authorization = encode(&Header::new(Algorithm::ES256), &claims, &encoding_key).unwrap();
+
+        (server, authorization)
+    }
+
+    fn create_test_server_with_hold_auth() -> (TestServer, String) {
+        let (encoding_key, decoding_key) = create_test_auth_keys();
+        let server = create_test_server_with_auth(None, Some(decoding_key));
+
+        let claims = HoldClaims {
+            aud: HoldAudience::Hold,
+            exp: test_token_exp(),
+        };
+        let authorization = encode(&Header::new(Algorithm::ES256), &claims, &encoding_key).unwrap();
+
+        (server, authorization)
+    }
+
+    #[tokio::test]
+    async fn get_status_when_auth_authority_then_requires_authorization() {
+        let (server, authorization) = create_test_server_with_status_auth();
+
+        let response = server.get("/status").await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+        let response = server
+            .get("/status")
+            .authorization_bearer(authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let status: Vec<LnBackendStatus> = response.json();
+        assert!(status.is_empty());
+
+        let response = server.get("/health").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn settle_hold_invoice_when_authorized_then_resolves_by_payment_hash() {
+        let (server, authorization) = create_test_server_with_hold_auth();
+        let held = hex::encode(HELD_PAYMENT_HASH);
+
+        let response = server.post(&format!("/holds/{held}/settle")).await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+        let response = server
+            .post(&format!("/holds/{held}/settle"))
+            .authorization_bearer(&authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+        let response = server
+            .post(&format!("/holds/{held}/cancel"))
+            .authorization_bearer(&authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+        let response = server
+            .post(&format!("/holds/{}/settle", hex::encode([2u8; 32])))
+            .authorization_bearer(&authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+        let response = server
+            .post("/holds/not-a-hash/cancel")
+            .authorization_bearer(&authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn settle_hold_invoice_when_only_status_auth_then_not_mounted() {
+        let (server, authorization) = create_test_server_with_status_auth();
+        let held = hex::encode(HELD_PAYMENT_HASH);
+
+        let response = server
+            .post(&format!("/holds/{held}/settle"))
+            .authorization_bearer(&authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn settle_hold_invoice_when_discovery_token_then_unauthorized() {
+        let (encoding_key, decoding_key) = create_test_auth_keys();
+        let server = create_test_server_with_auth(Some(decoding_key.clone()), Some(decoding_key));
+        let held = hex::encode(HELD_PAYMENT_HASH);
+
+        let claims = DiscoveryClaims {
+            aud: DiscoveryAudience::Discovery,
+            exp: test_token_exp(),
+        };
+        let authorization = encode(&Header::new(Algorithm::ES256), &claims, &encoding_key).unwrap();
+
+        let response = server
+            .post(&format!("/holds/{held}/settle"))
+            .authorization_bearer(&authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+        let response = server
+            .get("/status")
+            .authorization_bearer(&authorization)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+
+    async fn create_test_server_with_bolt12_offer(
+        offer: OfferRecord,
+    ) -> (TestServer, TestOfferStore) {
+        let offer_provider = TestOfferStore::default();
+        offer_provider
+            .put_metadata(OfferMetadata {
+                id: offer.offer.metadata_id,
+                partition: offer.partition.clone(),
+                metadata: OfferMetadataSparse {
+                    text: "Test offer".to_string(),
+                    long_text: None,
+                    image: None,
+                    identifier: None,
+                },
+            })
+            .await
+            .unwrap();
+        offer_provider.put_offer(offer).await.unwrap();
+
+        let state = LnUrlPayState::new(
+            HashSet::from(["default".to_string()]),
+            offer_provider.clone(),
+            MockLnBalancer::new(),
+            3600,
+            Scheme("http".to_string()),
+            Default::default(),
+            Default::default(),
+            8,
+            255u8,
+            0u8,
+            None,
+            None,
+            Default::default(),
+            Default::default(),
+        );
+        let server = TestServer::new(LnUrlBalancerService::router(state)).unwrap();
+        (server, offer_provider)
+    }
+
+    #[tokio::test]
+    async fn get_bolt12_when_offer_exists_then_returns_and_persists_offer() {
+        let mut offer = create_test_offer();
+        offer.offer.min_sendable = 5000;
+        offer.offer.max_sendable = 5000;
+        let offer_id = offer.id;
+        let (server, offer_provider) = create_test_server_with_bolt12_offer(offer).await;
+
+        let response = server
+            .get(&format!("/offers/default/{offer_id}/bolt12"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let expected = format!("lno1{}", offer_id.simple());
+        assert_eq!(response.text(), expected);
+
+        let stored = offer_provider
+            .get_bolt12_offers("default", &offer_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].offer.bolt12, expected);
+        assert_eq!(stored[0].offer.description, "Test offer");
+        assert_eq!(stored[0].offer.amount_msat, 5000);
+
+        let response = server
+            .get(&format!("/offers/default/{}/bolt12", Uuid::new_v4()))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn get_bolt12_when_offer_has_amount_range_then_bad_request() {
+        let offer = create_test_offer();
+        let offer_id = offer.id;
+        let (server, offer_provider) = create_test_server_with_bolt12_offer(offer).await;
+
+        let response = server
+            .get(&format!("/offers/default/{offer_id}/bolt12"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+        let stored = offer_provider
+            .get_bolt12_offers("default", &offer_id)
+            .await
+            .unwrap();
+        assert!(stored.is_empty());
+    }
 }
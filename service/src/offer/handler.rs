@@ -3,14 +3,16 @@ use crate::axum::crud::response::JsonCrudResponse;
 use crate::axum::extract::uuid::UuidParam;
 use crate::axum::header::no_cache_headers;
 use crate::offer::state::OfferState;
-use axum::extract::Query;
+use axum::extract::{Path, Query};
 use axum::http::HeaderValue;
 use axum::{extract::State, Json};
 use serde::Deserialize;
 use switchgear_service_api::offer::{
-    OfferMetadata, OfferMetadataSparse, OfferMetadataStore, OfferRecord, OfferRecordSparse,
-    OfferStore,
+    Bolt12OfferRecord, Bolt12OfferSparse, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceSparse,
+    HoldInvoiceStore, OfferMetadata, OfferMetadataSparse, OfferMetadataStore, OfferRecord,
+    OfferRecordSparse, OfferStore,
 };
+use uuid::Uuid;
 
 #[derive(Deserialize, Debug)]
 pub struct GetAllOffersQueryParameters {
@@ -152,6 +154,146 @@ impl OfferHandlers {
         }
     }
 
+    pub async fn get_bolt12_offers<S, M>(
+        UuidParam { partition, id }: UuidParam,
+        State(state): State<OfferState<S, M>>,
+    ) -> Result<JsonCrudResponse<Vec<Bolt12OfferRecord>>, CrudError>
+    where
+        S: OfferStore + Bolt12OfferStore,
+        M: OfferMetadataStore,
+    {
+        let offers = state
+            .offer_store()
+            .get_bolt12_offers(&partition, &id)
+            .await
+            .map_err(|e| crate::crud_error_from_service!(e))?;
+
+        let headers = no_cache_headers();
+
+        Ok(JsonCrudResponse::ok(offers, headers))
+    }
+
+    pub async fn put_bolt12_offer<S, M>(
+        State(state): State<OfferState<S, M>>,
+        Path((partition, id, public_key)): Path<(String, String, String)>,
+        Json(offer): Json<Bolt12OfferSparse>,
+    ) -> Result<JsonCrudResponse<()>, CrudError>
+    where
+        S: OfferStore + Bolt12OfferStore,
+        M: OfferMetadataStore,
+    {
+        let id = id.parse::<Uuid>().map_err(|_| CrudError::not_found())?;
+        let public_key = public_key.parse().map_err(|_| CrudError::bad())?;
+
+        let offer = Bolt12OfferRecord {
+            partition,
+            id,
+            public_key,
+            offer,
+        };
+
+        let was_created = state
+            .offer_store()
+            .put_bolt12_offer(offer)
+            .await
+            .map_err(|e| crate::crud_error_from_service!(e))?;
+
+        if was_created {
+            Ok(JsonCrudResponse::created())
+        } else {
+            Ok(JsonCrudResponse::no_content())
+        }
+    }
+
+    pub async fn get_hold_invoices<S, M>(
+        State(state): State<OfferState<S, M>>,
+    ) -> Result<JsonCrudResponse<Vec<HoldInvoiceRecord>>, CrudError>
+    where
+        S: OfferStore + HoldInvoiceStore,
+        M: OfferMetadataStore,
+    {
+        let holds = state
+            .offer_store()
+            .get_hold_invoices()
+            .await
+            .map_err(|e| crate::crud_error_from_service!(e))?;
+
+        let headers = no_cache_headers();
+
+        Ok(JsonCrudResponse::ok(holds, headers))
+    }
+
+    pub async fn get_hold_invoice<S, M>(
+        Path(payment_hash): Path<String>,
+        State(state): State<OfferState<S, M>>,
+    ) -> Result<JsonCrudResponse<HoldInvoiceRecord>, CrudError>
+    where
+        S: OfferStore + HoldInvoiceStore,
+        M: OfferMetadataStore,
+    {
+        let payment_hash = Self::parse_payment_hash(&payment_hash)?;
+        let hold = state
+            .offer_store()
+            .get_hold_invoice(&payment_hash)
+            .await
+            .map_err(|e| crate::crud_error_from_service!(e))?
+            .ok_or(CrudError::not_found())?;
+
+        let headers = no_cache_headers();
+
+        Ok(JsonCrudResponse::ok(hold, headers))
+    }
+
+    pub async fn put_hold_invoice<S, M>(
+        Path(payment_hash): Path<String>,
+        State(state): State<OfferState<S, M>>,
+        Json(hold): Json<HoldInvoiceSparse>,
+    ) -> Result<JsonCrudResponse<()>, CrudError>
+    where
+        S: OfferStore + HoldInvoiceStore,
+        M: OfferMetadataStore,
+    {
+        let payment_hash = Self::parse_payment_hash(&payment_hash)?;
+        let was_created = state
+            .offer_store()
+            .put_hold_invoice(HoldInvoiceRecord { payment_hash, hold })
+            .await
+            .map_err(|e| crate::crud_error_from_service!(e))?;
+
+        if was_created {
+            Ok(JsonCrudResponse::created())
+        } else {
+            Ok(JsonCrudResponse::no_content())
+        }
+    }
+
+    pub async fn delete_hold_invoice<S, M>(
+        Path(payment_hash): Path<String>,
+        State(state): State<OfferState<S, M>>,
+    ) -> Result<JsonCrudResponse<()>, CrudError>
+    where
+        S: OfferStore + HoldInvoiceStore,
+        M: OfferMetadataStore,
+    {
+        let payment_hash = Self::parse_payment_hash(&payment_hash)?;
+        if state
+            .offer_store()
+            .delete_hold_invoice(&payment_hash)
+            .await
+            .map_err(|e| crate::crud_error_from_service!(e))?
+        {
+            Ok(JsonCrudResponse::no_content())
+        } else {
+            Err(CrudError::not_found())
+        }
+    }
+
+    fn parse_payment_hash(payment_hash: &str) -> Result<[u8; 32], CrudError> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(payment_hash, &mut bytes).map_err(|_| CrudError::bad())?;
+        Ok(bytes)
+    }
+
     pub async fn get_metadata<S, M>(
         UuidParam { partition, id }: UuidParam,
         State(state): State<OfferState<S, M>>,
@@ -4,7 +4,9 @@ use crate::offer::handler::OfferHandlers;
 use crate::offer::state::OfferState;
 use axum::routing::{delete, get, post, put};
 use axum::Router;
-use switchgear_service_api::offer::{OfferMetadataStore, OfferStore};
+use switchgear_service_api::offer::{
+    Bolt12OfferStore, HoldInvoiceStore, OfferMetadataStore, OfferStore,
+};
 use switchgear_service_api::service::StatusCode;
 
 #[derive(Debug)]
@@ -13,7 +15,7 @@ pub struct OfferService;
 impl OfferService {
     pub fn router<S, M>(state: OfferState<S, M>) -> Router
     where
-        S: OfferStore + Clone + Send + Sync + 'static,
+        S: OfferStore + Bolt12OfferStore + HoldInvoiceStore + Clone + Send + Sync + 'static,
         M: OfferMetadataStore + Clone + Send + Sync + 'static,
     {
         Router::new()
@@ -23,7 +25,28 @@ impl OfferService {
                 "/offers/{partition}/{id}",
                 delete(OfferHandlers::delete_offer),
             )
+            .route(
+                "/offers/{partition}/{id}/bolt12",
+                get(OfferHandlers::get_bolt12_offers),
+            )
+            .route(
+                "/offers/{partition}/{id}/bolt12/{public_key}",
+                put(OfferHandlers::put_bolt12_offer),
+            )
             .route("/offers/{partition}", get(OfferHandlers::get_offers))
+            .route("/holds", get(OfferHandlers::get_hold_invoices))
+            .route(
+                "/holds/{payment_hash}",
+                get(OfferHandlers::get_hold_invoice),
+            )
+            .route(
+                "/holds/{payment_hash}",
+                put(OfferHandlers::put_hold_invoice),
+            )
+            .route(
+                "/holds/{payment_hash}",
+                delete(OfferHandlers::delete_hold_invoice),
+            )
             .route("/offers", post(OfferHandlers::post_offer))
             .route(
                 "/metadata/{partition}/{id}",
@@ -87,6 +110,7 @@ mod tests {
                 metadata: None,
                 timestamp: Utc::now() - Duration::hours(1),
                 expires: Some(Utc::now() + Duration::hours(1)),
+                hold_invoice: false,
             },
         }
     }
@@ -96,6 +96,9 @@ impl DiscoveryBackendStore for TestDiscoveryBackendStore {
         if let Some(name) = backend.backend.name {
             entry.backend.name = name;
         }
+        if let Some(region) = backend.backend.region {
+            entry.backend.region = region;
+        }
         self.etag.fetch_add(1, Ordering::Relaxed);
         Ok(true)
     }
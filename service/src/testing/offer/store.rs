@@ -1,16 +1,20 @@
 use crate::testing::error::TestError;
 use async_trait::async_trait;
 use indexmap::IndexMap;
+use secp256k1::PublicKey;
 use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use switchgear_service_api::lnurl::LnUrlOfferMetadata;
 use switchgear_service_api::offer::{
-    Offer, OfferMetadata, OfferMetadataStore, OfferProvider, OfferRecord, OfferStore,
+    Bolt12OfferRecord, Bolt12OfferStore, HoldInvoiceRecord, HoldInvoiceStore, Offer, OfferMetadata,
+    OfferMetadataStore, OfferProvider, OfferRecord, OfferStore,
 };
 use switchgear_service_api::service::ServiceErrorSource;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+type Bolt12OfferMap = IndexMap<(String, Uuid, PublicKey), Bolt12OfferRecord>;
+
 /// Simplified in-memory offer store for unit tests.
 /// This is a minimal implementation designed to replace MemoryOfferStore
 /// in service crate tests. Uses IndexMap to preserve insertion order.
@@ -18,6 +22,8 @@ use uuid::Uuid;
 pub struct TestOfferStore {
     offer: Arc<Mutex<IndexMap<(String, Uuid), OfferRecord>>>,
     metadata: Arc<Mutex<IndexMap<(String, Uuid), OfferMetadata>>>,
+    bolt12: Arc<Mutex<Bolt12OfferMap>>,
+    hold: Arc<Mutex<IndexMap<[u8; 32], HoldInvoiceRecord>>>,
 }
 
 impl TestOfferStore {
@@ -25,6 +31,8 @@ impl TestOfferStore {
         Self {
             offer: Arc::new(Mutex::new(IndexMap::new())),
             metadata: Arc::new(Mutex::new(IndexMap::new())),
+            bolt12: Arc::new(Mutex::new(IndexMap::new())),
+            hold: Arc::new(Mutex::new(IndexMap::new())),
         }
     }
 }
@@ -209,6 +217,63 @@ impl OfferMetadataStore for TestOfferStore {
     }
 }
 
+#[async_trait]
+impl Bolt12OfferStore for TestOfferStore {
+    type Error = TestError;
+
+    async fn get_bolt12_offers(
+        &self,
+        partition: &str,
+        id: &Uuid,
+    ) -> Result<Vec<Bolt12OfferRecord>, Self::Error> {
+        let store = self.bolt12.lock().await;
+        Ok(store
+            .values()
+            .filter(|offer| offer.partition == partition && offer.id == *id)
+            .cloned()
+            .collect())
+    }
+
+    async fn put_bolt12_offer(&self, offer: Bolt12OfferRecord) -> Result<bool, Self::Error> {
+        let mut store = self.bolt12.lock().await;
+        let was_new = store
+            .insert(
+                (offer.partition.to_string(), offer.id, offer.public_key),
+                offer,
+            )
+            .is_none();
+        Ok(was_new)
+    }
+}
+
+#[async_trait]
+impl HoldInvoiceStore for TestOfferStore {
+    type Error = TestError;
+
+    async fn get_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+    ) -> Result<Option<HoldInvoiceRecord>, Self::Error> {
+        let store = self.hold.lock().await;
+        Ok(store.get(payment_hash).cloned())
+    }
+
+    async fn get_hold_invoices(&self) -> Result<Vec<HoldInvoiceRecord>, Self::Error> {
+        let store = self.hold.lock().await;
+        Ok(store.values().cloned().collect())
+    }
+
+    async fn put_hold_invoice(&self, hold: HoldInvoiceRecord) -> Result<bool, Self::Error> {
+        let mut store = self.hold.lock().await;
+        Ok(store.insert(hold.payment_hash, hold).is_none())
+    }
+
+    async fn delete_hold_invoice(&self, payment_hash: &[u8; 32]) -> Result<bool, Self::Error> {
+        let mut store = self.hold.lock().await;
+        Ok(store.shift_remove(payment_hash).is_some())
+    }
+}
+
 #[async_trait]
 impl OfferProvider for TestOfferStore {
     type Error = TestError;
@@ -254,6 +319,7 @@ impl OfferProvider for TestOfferStore {
                 metadata_json_hash,
                 timestamp: offer.offer.timestamp,
                 expires: offer.offer.expires,
+                hold_invoice: false,
             }))
         } else {
             Ok(None)
@@ -44,7 +44,7 @@ exec lnd \
     --bitcoind.zmqpubrawblock=tcp://bitcoin:28332 \
     --bitcoind.zmqpubrawtx=tcp://bitcoin:28333 \
     --rpclisten=0.0.0.0:${LND_PORT} \
-    --restlisten=0.0.0.0:8080 \
+    --restlisten=0.0.0.0:${LND_REST_PORT} \
     --listen=0.0.0.0:9734 \
     --externalip=lnd:9734 \
     --tlscertpath="$CERT_DIR/tls.cert" \
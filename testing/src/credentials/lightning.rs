@@ -19,6 +19,7 @@ pub struct ClnRegTestLnNode {
 pub struct LndRegTestLnNode {
     pub public_key: PublicKey,
     pub address: String,
+    pub rest_address: String,
     pub tls_cert_path: PathBuf,
     pub macaroon_path: PathBuf,
 }
@@ -110,6 +111,7 @@ impl LnCredentials {
                     lnd = Some(Self::build_lnd_node(
                         public_key,
                         &self.lightning.lnd,
+                        &self.lightning.lnd_rest,
                         &path,
                     )?);
                 }
@@ -172,6 +174,7 @@ impl LnCredentials {
     fn build_lnd_node(
         public_key: PublicKey,
         address: &str,
+        rest_address: &str,
         node_path: &Path,
     ) -> anyhow::Result<LndRegTestLnNode> {
         let tls_cert_path = node_path.join("tls.cert");
@@ -192,6 +195,7 @@ impl LnCredentials {
         Ok(LndRegTestLnNode {
             public_key,
             address: address.to_string(),
+            rest_address: rest_address.to_string(),
             tls_cert_path,
             macaroon_path,
         })
@@ -14,6 +14,7 @@ pub struct IntegrationTestServices {
 pub struct LightningIntegrationTestServices {
     pub cln: String,
     pub lnd: String,
+    pub lnd_rest: String,
 }
 
 impl Default for IntegrationTestServices {
@@ -56,11 +57,17 @@ impl IntegrationTestServices {
             Self::env_or_panic("LND_PORT")
         );
 
+        let lnd_rest = format!(
+            "{}:{}",
+            Self::env_or_panic("LND_HOSTNAME"),
+            Self::env_or_panic("LND_REST_PORT")
+        );
+
         Self {
             credentials,
             postgres,
             mysql,
-            lightning: LightningIntegrationTestServices { cln, lnd },
+            lightning: LightningIntegrationTestServices { cln, lnd, lnd_rest },
         }
     }
 